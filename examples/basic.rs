@@ -8,20 +8,19 @@
 //! - Ctrl+C (twice): Open exit confirmation dialog
 //! - Ctrl+Q: Quit immediately
 //! - Ctrl+N: Focus next pane
+//! - Alt+Shift+Arrows: Resize the focused pane
 //! - Mouse click: Focus pane under cursor
+//! - Mouse drag: Select text and copy it to the clipboard
 //! - All other input goes to the focused pane
 //!
 //! Layout:
 //! - 4 PTY panes on top
 //! - 8 sub-panes below
 
-use std::io::{self, stdout};
+use std::io::{self, stdout, Write};
 use std::time::{Duration, Instant};
 
-use cockpit::{
-    CockpitWidget, ConfirmDialog, DialogState, GitUserPlugin, PaneManager, SpawnConfig,
-    StatusBarWidget, STATUS_BAR_HEIGHT,
-};
+use cockpit::{CockpitFrame, DialogState, Direction, GitUserPlugin, PaneManager, SpawnConfig};
 use crossterm::{
     event::{
         self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyModifiers, MouseEventKind,
@@ -62,6 +61,7 @@ async fn main() -> io::Result<()> {
 /// Time window for detecting double Ctrl+C press (500ms).
 const CTRL_C_WINDOW: Duration = Duration::from_millis(500);
 
+#[allow(clippy::too_many_lines)]
 async fn run_app(terminal: &mut Terminal<CrosstermBackend<io::Stdout>>) -> cockpit::Result<()> {
     // Create pane manager with plugin support
     let cwd = std::env::current_dir().unwrap_or_default();
@@ -72,14 +72,7 @@ async fn run_app(terminal: &mut Terminal<CrosstermBackend<io::Stdout>>) -> cockp
 
     // Get terminal size and set it in the manager
     let term_size = terminal.size()?;
-    let panes_area = Rect {
-        x: 0,
-        y: STATUS_BAR_HEIGHT,
-        width: term_size.width,
-        height: term_size.height.saturating_sub(STATUS_BAR_HEIGHT),
-    };
-
-    manager.set_terminal_size(panes_area);
+    manager.set_frame_size(Rect::new(0, 0, term_size.width, term_size.height));
 
     // Spawn four bash panes
     manager.spawn(SpawnConfig::new_shell())?;
@@ -98,57 +91,25 @@ async fn run_app(terminal: &mut Terminal<CrosstermBackend<io::Stdout>>) -> cockp
     loop {
         // Draw UI
         terminal.draw(|frame| {
-            let area = frame.area();
-
-            // Reserve space for status bar at the top
-            let status_bar_area = Rect {
-                x: area.x,
-                y: area.y,
-                width: area.width,
-                height: STATUS_BAR_HEIGHT,
-            };
-            let panes_area = Rect {
-                x: area.x,
-                y: area.y + STATUS_BAR_HEIGHT,
-                width: area.width,
-                height: area.height.saturating_sub(STATUS_BAR_HEIGHT),
-            };
-
-            // Render status bar with plugin segments
-            let segments = manager.status_bar_segments();
-            let status_bar = StatusBarWidget::new(&segments);
-            frame.render_widget(status_bar, status_bar_area);
-
-            // Get pre-calculated layout areas (automatic!)
-            let areas = manager.get_areas();
-            let areas_vec: Vec<_> = areas.iter().map(|(&id, &rect)| (id, rect)).collect();
-
-            // Get pane handles
-            let pane_ids = manager.pane_ids();
-            let panes: Vec<_> = pane_ids
-                .iter()
-                .filter_map(|id| manager.get_pane(*id).map(|h| (*id, h)))
-                .collect();
-
-            // Build the widget
-            let sub_panes = manager.get_sub_pane_areas();
-            let empty_panes = manager.get_empty_pane_areas();
-            let widget = CockpitWidget::new(&panes, &areas_vec, manager.focused())
-                .sub_panes(sub_panes)
-                .empty_panes(empty_panes);
-
-            frame.render_widget(widget, panes_area);
-
-            // Render exit confirmation dialog if visible
-            if dialog_state.visible {
-                dialog_area = DialogState::calculate_area(area);
-                let dialog =
-                    ConfirmDialog::new(" Exit Cockpit? ", "Are you sure you want to quit?")
-                        .selected(dialog_state.selected);
-                frame.render_widget(dialog, dialog_area);
-            }
+            // Status bar, panes, sub-panes and the dialog in one call
+            dialog_area = CockpitFrame::new(&mut manager)
+                .dialog(
+                    &dialog_state,
+                    " Exit Cockpit? ",
+                    "Are you sure you want to quit?",
+                )
+                .render(frame)
+                .dialog
+                .unwrap_or_default();
         })?;
 
+        // Forward clipboard requests and other host terminal sequences
+        let passthrough = manager.take_passthrough();
+        if !passthrough.is_empty() {
+            terminal.backend_mut().write_all(&passthrough)?;
+            terminal.backend_mut().flush()?;
+        }
+
         // Handle events with a short timeout for responsive updates
         if event::poll(Duration::from_millis(16))? {
             match event::read()? {
@@ -201,18 +162,27 @@ async fn run_app(terminal: &mut Terminal<CrosstermBackend<io::Stdout>>) -> cockp
                         continue;
                     }
 
+                    // Resize the focused pane (Alt+Shift+Arrows)
+                    if key.modifiers == KeyModifiers::ALT | KeyModifiers::SHIFT {
+                        let direction = match key.code {
+                            KeyCode::Left => Some(Direction::Left),
+                            KeyCode::Right => Some(Direction::Right),
+                            KeyCode::Up => Some(Direction::Up),
+                            KeyCode::Down => Some(Direction::Down),
+                            _ => None,
+                        };
+                        if let Some(direction) = direction {
+                            manager.resize_focused(direction, 1);
+                            continue;
+                        }
+                    }
+
                     // Route all other input to focused pane
                     manager.route_key(key).await?;
                 }
                 Event::Resize(width, height) => {
                     // Recalculate layout on terminal resize
-                    let panes_area = Rect {
-                        x: 0,
-                        y: STATUS_BAR_HEIGHT,
-                        width,
-                        height: height.saturating_sub(STATUS_BAR_HEIGHT),
-                    };
-                    manager.set_terminal_size(panes_area);
+                    manager.set_frame_size(Rect::new(0, 0, width, height));
                 }
                 Event::Mouse(mouse) => {
                     // If dialog is visible, handle mouse for dialog
@@ -230,10 +200,8 @@ async fn run_app(terminal: &mut Terminal<CrosstermBackend<io::Stdout>>) -> cockp
                         continue;
                     }
 
-                    // Handle mouse click (arrows + focus) - managed by the lib
-                    if matches!(mouse.kind, MouseEventKind::Down(_)) {
-                        manager.handle_click(mouse.column, mouse.row);
-                    }
+                    // Handle clicks (arrows + focus) and selection - managed by the lib
+                    manager.handle_mouse(mouse);
                 }
                 _ => {}
             }
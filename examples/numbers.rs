@@ -15,10 +15,7 @@
 use std::io::{self, stdout};
 use std::time::{Duration, Instant};
 
-use cockpit::{
-    CockpitWidget, ConfirmDialog, DialogState, GitUserPlugin, PaneManager, StatusBarWidget,
-    STATUS_BAR_HEIGHT,
-};
+use cockpit::{CockpitFrame, DialogState, GitUserPlugin, PaneManager, Theme};
 use crossterm::{
     event::{
         self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyModifiers, MouseEventKind,
@@ -59,24 +56,22 @@ async fn main() -> io::Result<()> {
 /// Time window for detecting double Ctrl+C press (500ms).
 const CTRL_C_WINDOW: Duration = Duration::from_millis(500);
 
+#[allow(clippy::too_many_lines)]
 async fn run_app(terminal: &mut Terminal<CrosstermBackend<io::Stdout>>) -> cockpit::Result<()> {
     // Create pane manager with plugin support
     let cwd = std::env::current_dir().unwrap_or_default();
     let mut manager = PaneManager::new().with_plugins(cwd);
+    manager.set_theme(Theme {
+        show_numbers: true,
+        ..Theme::default()
+    });
 
     // Register the git user plugin for the status bar
     let _ = manager.register_plugin(Box::new(GitUserPlugin::new()));
 
     // Get terminal size and set it in the manager
     let term_size = terminal.size()?;
-    let panes_area = Rect {
-        x: 0,
-        y: STATUS_BAR_HEIGHT,
-        width: term_size.width,
-        height: term_size.height.saturating_sub(STATUS_BAR_HEIGHT),
-    };
-
-    manager.set_terminal_size(panes_area);
+    manager.set_frame_size(Rect::new(0, 0, term_size.width, term_size.height));
 
     // No panes spawned - all 12 panes will be empty with numbers displayed
     // To spawn terminal panes, uncomment below:
@@ -96,55 +91,16 @@ async fn run_app(terminal: &mut Terminal<CrosstermBackend<io::Stdout>>) -> cockp
     loop {
         // Draw UI
         terminal.draw(|frame| {
-            let area = frame.area();
-
-            // Reserve space for status bar at the top
-            let status_bar_area = Rect {
-                x: area.x,
-                y: area.y,
-                width: area.width,
-                height: STATUS_BAR_HEIGHT,
-            };
-            let panes_area = Rect {
-                x: area.x,
-                y: area.y + STATUS_BAR_HEIGHT,
-                width: area.width,
-                height: area.height.saturating_sub(STATUS_BAR_HEIGHT),
-            };
-
-            // Render status bar with plugin segments
-            let segments = manager.status_bar_segments();
-            let status_bar = StatusBarWidget::new(&segments);
-            frame.render_widget(status_bar, status_bar_area);
-
-            // Get pre-calculated layout areas (automatic!)
-            let areas = manager.get_areas();
-            let areas_vec: Vec<_> = areas.iter().map(|(&id, &rect)| (id, rect)).collect();
-
-            // Get pane handles
-            let pane_ids = manager.pane_ids();
-            let panes: Vec<_> = pane_ids
-                .iter()
-                .filter_map(|id| manager.get_pane(*id).map(|h| (*id, h)))
-                .collect();
-
-            // Render the cockpit widget with sub-panes and empty pane placeholders
-            let sub_panes = manager.get_sub_pane_areas();
-            let empty_panes = manager.get_empty_pane_areas();
-            let widget = CockpitWidget::new(&panes, &areas_vec, manager.focused())
-                .sub_panes(sub_panes)
-                .empty_panes(empty_panes)
-                .show_numbers(true);
-            frame.render_widget(widget, panes_area);
-
-            // Render exit confirmation dialog if visible
-            if dialog_state.visible {
-                dialog_area = DialogState::calculate_area(area);
-                let dialog =
-                    ConfirmDialog::new(" Exit Cockpit? ", "Are you sure you want to quit?")
-                        .selected(dialog_state.selected);
-                frame.render_widget(dialog, dialog_area);
-            }
+            // Status bar, panes, sub-panes and the dialog in one call
+            dialog_area = CockpitFrame::new(&mut manager)
+                .dialog(
+                    &dialog_state,
+                    " Exit Cockpit? ",
+                    "Are you sure you want to quit?",
+                )
+                .render(frame)
+                .dialog
+                .unwrap_or_default();
         })?;
 
         // Handle events with a short timeout for responsive updates
@@ -204,13 +160,7 @@ async fn run_app(terminal: &mut Terminal<CrosstermBackend<io::Stdout>>) -> cockp
                 }
                 Event::Resize(width, height) => {
                     // Recalculate layout on terminal resize
-                    let panes_area = Rect {
-                        x: 0,
-                        y: STATUS_BAR_HEIGHT,
-                        width,
-                        height: height.saturating_sub(STATUS_BAR_HEIGHT),
-                    };
-                    manager.set_terminal_size(panes_area);
+                    manager.set_frame_size(Rect::new(0, 0, width, height));
                 }
                 Event::Mouse(mouse) => {
                     // If dialog is visible, handle mouse for dialog
@@ -0,0 +1,285 @@
+//! Actions that keybindings trigger, and the keymap that binds them.
+
+use std::time::{Duration, Instant};
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+/// Key repeats closer together than this belong to one held key.
+const REPEAT_WINDOW: Duration = Duration::from_millis(150);
+
+/// Repeats of a held key before each doubling of its step.
+const REPEATS_PER_DOUBLING: u32 = 5;
+
+/// Largest factor a held key's step is multiplied by.
+const MAX_ACCELERATION: u16 = 8;
+
+/// A direction on screen.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Direction {
+    /// Towards the left edge.
+    Left,
+    /// Towards the right edge.
+    Right,
+    /// Towards the top edge.
+    Up,
+    /// Towards the bottom edge.
+    Down,
+}
+
+/// Groups of actions, in the order the help overlay lists them.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ActionCategory {
+    /// Quitting, help and the command palette.
+    General,
+    /// Moving focus between panes.
+    Focus,
+    /// Changing the layout.
+    Layout,
+    /// Starting and restarting panes.
+    Panes,
+}
+
+impl ActionCategory {
+    /// Heading of the category.
+    #[must_use]
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::General => "General",
+            Self::Focus => "Focus",
+            Self::Layout => "Layout",
+            Self::Panes => "Panes",
+        }
+    }
+}
+
+/// Something a keybinding, or the command palette, does.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Action {
+    /// Leave the event loop.
+    Quit,
+    /// Focus the next pane.
+    FocusNext,
+    /// Focus the previous pane.
+    FocusPrev,
+    /// Number the panes and focus the one whose number is pressed next;
+    /// see [`PaneManager::display_panes`](crate::PaneManager::display_panes).
+    DisplayPanes,
+    /// Move a border of the focused pane by a number of cells; see
+    /// [`PaneManager::resize_focused`](crate::PaneManager::resize_focused).
+    /// Holding the key moves it faster.
+    Resize(Direction, u16),
+    /// Open the command palette.
+    CommandPalette,
+    /// Show the keybindings.
+    Help,
+    /// Restart the focused pane if its process has exited or crashed; see
+    /// [`PaneManager::restart_pane`](crate::PaneManager::restart_pane).
+    RestartPane,
+    /// Spawn a pane from a registered profile; see
+    /// [`PaneManager::spawn_profile`](crate::PaneManager::spawn_profile).
+    SpawnProfile(String),
+}
+
+impl Action {
+    /// What the action does, as listed in the command palette.
+    #[must_use]
+    pub fn description(&self) -> String {
+        match self {
+            Self::Quit => "Quit".to_string(),
+            Self::FocusNext => "Focus next pane".to_string(),
+            Self::FocusPrev => "Focus previous pane".to_string(),
+            Self::DisplayPanes => "Show pane numbers".to_string(),
+            Self::Resize(direction, cells) => {
+                let direction = match direction {
+                    Direction::Left => "left",
+                    Direction::Right => "right",
+                    Direction::Up => "up",
+                    Direction::Down => "down",
+                };
+                format!("Resize pane {direction} by {cells}")
+            }
+            Self::CommandPalette => "Open command palette".to_string(),
+            Self::Help => "Show keybindings".to_string(),
+            Self::RestartPane => "Restart exited pane".to_string(),
+            Self::SpawnProfile(name) => format!("Spawn {name}"),
+        }
+    }
+
+    /// Group the action is listed under.
+    #[must_use]
+    pub fn category(&self) -> ActionCategory {
+        match self {
+            Self::Quit | Self::CommandPalette | Self::Help => ActionCategory::General,
+            Self::FocusNext | Self::FocusPrev | Self::DisplayPanes => ActionCategory::Focus,
+            Self::Resize(..) => ActionCategory::Layout,
+            Self::RestartPane | Self::SpawnProfile(_) => ActionCategory::Panes,
+        }
+    }
+}
+
+/// Keys bound to actions.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct KeyMap {
+    bindings: Vec<(KeyEvent, Action)>,
+}
+
+impl KeyMap {
+    /// Keymap without bindings.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Bind `key` to `action`, replacing its previous binding.
+    pub fn bind(&mut self, key: KeyEvent, action: Action) {
+        self.unbind(key);
+        self.bindings.push((key, action));
+    }
+
+    /// Remove the binding of `key`, returning its action.
+    pub fn unbind(&mut self, key: KeyEvent) -> Option<Action> {
+        let index = self
+            .bindings
+            .iter()
+            .position(|(bound, _)| same_key(*bound, key))?;
+        Some(self.bindings.remove(index).1)
+    }
+
+    /// Remove every binding of `action`.
+    pub fn unbind_action(&mut self, action: &Action) {
+        self.bindings.retain(|(_, bound)| bound != action);
+    }
+
+    /// Action bound to `key`, ignoring the event's kind and state.
+    #[must_use]
+    pub fn action(&self, key: KeyEvent) -> Option<&Action> {
+        self.bindings
+            .iter()
+            .find(|(bound, _)| same_key(*bound, key))
+            .map(|(_, action)| action)
+    }
+
+    /// First key bound to `action`.
+    #[must_use]
+    pub fn key_for(&self, action: &Action) -> Option<KeyEvent> {
+        self.bindings
+            .iter()
+            .find(|(_, bound)| bound == action)
+            .map(|(key, _)| *key)
+    }
+
+    /// All bindings, in the order they were made.
+    pub fn bindings(&self) -> impl Iterator<Item = (KeyEvent, &Action)> + '_ {
+        self.bindings.iter().map(|(key, action)| (*key, action))
+    }
+
+    /// Alt+Shift+arrow keys move the focused pane's borders by one cell.
+    #[must_use]
+    pub fn with_resize_bindings(mut self) -> Self {
+        let modifiers = KeyModifiers::ALT | KeyModifiers::SHIFT;
+        for (code, direction) in [
+            (KeyCode::Left, Direction::Left),
+            (KeyCode::Right, Direction::Right),
+            (KeyCode::Up, Direction::Up),
+            (KeyCode::Down, Direction::Down),
+        ] {
+            self.bind(KeyEvent::new(code, modifiers), Action::Resize(direction, 1));
+        }
+        self
+    }
+}
+
+fn same_key(a: KeyEvent, b: KeyEvent) -> bool {
+    a.code == b.code && a.modifiers == b.modifiers
+}
+
+/// Speeds up actions while their key is held.
+#[derive(Debug, Default)]
+pub(crate) struct RepeatAccelerator {
+    last: Option<(Action, Instant)>,
+    repeats: u32,
+}
+
+impl RepeatAccelerator {
+    /// Scale `action` for a key press at `now`. Presses of the same action
+    /// in quick succession, or reported as repeats by the terminal, count
+    /// as a held key; resize steps double every few repeats.
+    pub fn accelerate(&mut self, action: Action, repeat: bool, now: Instant) -> Action {
+        let held = self.last.as_ref().is_some_and(|(last, at)| {
+            *last == action && (repeat || now.saturating_duration_since(*at) < REPEAT_WINDOW)
+        });
+        self.repeats = if held {
+            self.repeats.saturating_add(1)
+        } else {
+            0
+        };
+        self.last = Some((action.clone(), now));
+
+        let doublings = (self.repeats / REPEATS_PER_DOUBLING).min(MAX_ACCELERATION.ilog2());
+        match action {
+            Action::Resize(direction, cells) => {
+                Action::Resize(direction, cells.saturating_mul(1 << doublings))
+            }
+            other => other,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_keymap() {
+        let key = KeyEvent::new(KeyCode::Char('n'), KeyModifiers::CONTROL);
+        let mut keymap = KeyMap::new().with_resize_bindings();
+        keymap.bind(key, Action::FocusNext);
+        keymap.bind(key, Action::FocusPrev);
+        assert_eq!(keymap.action(key), Some(&Action::FocusPrev));
+        assert_eq!(keymap.key_for(&Action::FocusPrev), Some(key));
+        assert_eq!(keymap.bindings().count(), 5);
+
+        keymap.unbind_action(&Action::FocusPrev);
+        assert_eq!(keymap.action(key), None);
+        let left = KeyEvent::new(KeyCode::Left, KeyModifiers::ALT | KeyModifiers::SHIFT);
+        assert_eq!(
+            keymap.unbind(left),
+            Some(Action::Resize(Direction::Left, 1))
+        );
+    }
+
+    #[test]
+    fn test_repeat_acceleration() {
+        let mut accelerator = RepeatAccelerator::default();
+        let action = Action::Resize(Direction::Right, 2);
+        let start = Instant::now();
+        let steps: Vec<_> = (0..25)
+            .map(|i| {
+                let now = start + Duration::from_millis(30) * i;
+                match accelerator.accelerate(action.clone(), false, now) {
+                    Action::Resize(_, cells) => cells,
+                    _ => unreachable!(),
+                }
+            })
+            .collect();
+        assert_eq!(steps[..5], [2; 5]);
+        assert_eq!(steps[5], 4);
+        assert_eq!(steps[10], 8);
+        assert_eq!(steps[24], 16);
+
+        // A pause, or another action, starts over
+        let later = start + Duration::from_secs(5);
+        assert_eq!(accelerator.accelerate(action.clone(), false, later), action);
+        assert_eq!(
+            accelerator.accelerate(action.clone(), true, later + Duration::from_secs(1)),
+            action
+        );
+        assert_eq!(
+            accelerator.accelerate(Action::FocusNext, false, later),
+            Action::FocusNext
+        );
+    }
+}
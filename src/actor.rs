@@ -0,0 +1,283 @@
+//! Cloneable handle to a pane manager running in its own task.
+//!
+//! Most [`PaneManager`] methods take `&mut self`, so sharing a manager
+//! between, say, a render task and an input task means wrapping it in a
+//! lock. [`PaneManager::into_handle`] instead moves the manager into a
+//! task that owns it and applies commands sent through a channel, one at
+//! a time. Every clone of the returned [`PaneManagerHandle`] talks to the
+//! same manager. [`PaneManager::into_actor`] does the same on any executor:
+//! it returns the task's future for the caller to spawn.
+
+use std::collections::HashMap;
+use std::future::Future;
+
+use ratatui::layout::Rect;
+use tokio::sync::{mpsc, oneshot};
+
+use crate::error::{Error, Result};
+use crate::manager::PaneManager;
+use crate::pane::{PaneHandle, PaneId, SpawnConfig};
+use crate::pty::PaneEvent;
+
+/// Commands queued before senders have to wait for the manager task.
+const COMMAND_CAPACITY: usize = 64;
+
+/// Closure run against the manager by [`PaneManagerHandle::with`].
+type ManagerFn = Box<dyn FnOnce(&mut PaneManager) + Send>;
+
+/// A request to the manager task.
+enum Command {
+    Spawn {
+        config: Box<SpawnConfig>,
+        reply: oneshot::Sender<Result<PaneHandle>>,
+    },
+    Close {
+        pane_id: PaneId,
+        reply: oneshot::Sender<()>,
+    },
+    Focus {
+        pane_id: PaneId,
+        reply: oneshot::Sender<()>,
+    },
+    Focused {
+        reply: oneshot::Sender<Option<PaneId>>,
+    },
+    SendInput {
+        data: Vec<u8>,
+        reply: oneshot::Sender<Result<()>>,
+    },
+    Areas {
+        reply: oneshot::Sender<HashMap<PaneId, Rect>>,
+    },
+    SetTerminalSize {
+        size: Rect,
+        reply: oneshot::Sender<()>,
+    },
+    PollEvents {
+        reply: oneshot::Sender<Vec<PaneEvent>>,
+    },
+    With(ManagerFn),
+}
+
+/// Cloneable handle to a [`PaneManager`] running in its own task.
+///
+/// Created with [`PaneManager::into_handle`] or
+/// [`PaneManager::into_actor`]. The manager task stops, and
+/// closes its panes, once every handle has been dropped.
+#[derive(Clone, Debug)]
+pub struct PaneManagerHandle {
+    commands: mpsc::Sender<Command>,
+}
+
+impl PaneManagerHandle {
+    /// Create a handle to `manager` and the future that serves it.
+    pub(crate) fn new(manager: PaneManager) -> (Self, impl Future<Output = ()> + Send + 'static) {
+        let (commands, rx) = mpsc::channel(COMMAND_CAPACITY);
+        (Self { commands }, run(manager, rx))
+    }
+
+    /// Send a command built around a reply channel and wait for the reply.
+    async fn request<T>(&self, command: impl FnOnce(oneshot::Sender<T>) -> Command) -> Result<T> {
+        let (reply, response) = oneshot::channel();
+        self.commands
+            .send(command(reply))
+            .await
+            .map_err(|_| Error::ManagerClosed)?;
+        response.await.map_err(|_| Error::ManagerClosed)
+    }
+
+    /// Spawn a new pane. See [`PaneManager::spawn`].
+    ///
+    /// # Errors
+    /// Returns an error if the manager task has stopped or spawning fails.
+    pub async fn spawn_pane(&self, config: SpawnConfig) -> Result<PaneHandle> {
+        self.request(|reply| Command::Spawn {
+            config: Box::new(config),
+            reply,
+        })
+        .await?
+    }
+
+    /// Close a pane. See [`PaneManager::close_pane`].
+    ///
+    /// # Errors
+    /// Returns an error if the manager task has stopped.
+    pub async fn close_pane(&self, pane_id: PaneId) -> Result<()> {
+        self.request(|reply| Command::Close { pane_id, reply })
+            .await
+    }
+
+    /// Focus a pane. See [`PaneManager::set_focus`].
+    ///
+    /// # Errors
+    /// Returns an error if the manager task has stopped.
+    pub async fn set_focus(&self, pane_id: PaneId) -> Result<()> {
+        self.request(|reply| Command::Focus { pane_id, reply })
+            .await
+    }
+
+    /// The focused pane.
+    ///
+    /// # Errors
+    /// Returns an error if the manager task has stopped.
+    pub async fn focused(&self) -> Result<Option<PaneId>> {
+        self.request(|reply| Command::Focused { reply }).await
+    }
+
+    /// Send input to the focused pane. See [`PaneManager::send_input`].
+    ///
+    /// # Errors
+    /// Returns an error if the manager task has stopped, no pane is
+    /// focused or input sending fails.
+    pub async fn send_input(&self, data: impl Into<Vec<u8>>) -> Result<()> {
+        let data = data.into();
+        self.request(|reply| Command::SendInput { data, reply })
+            .await?
+    }
+
+    /// Current pane areas. See [`PaneManager::get_areas`].
+    ///
+    /// # Errors
+    /// Returns an error if the manager task has stopped.
+    pub async fn areas(&self) -> Result<HashMap<PaneId, Rect>> {
+        self.request(|reply| Command::Areas { reply }).await
+    }
+
+    /// Set the terminal size. See [`PaneManager::set_terminal_size`].
+    ///
+    /// # Errors
+    /// Returns an error if the manager task has stopped.
+    pub async fn set_terminal_size(&self, size: Rect) -> Result<()> {
+        self.request(|reply| Command::SetTerminalSize { size, reply })
+            .await
+    }
+
+    /// Take pending pane events. See [`PaneManager::poll_events`].
+    ///
+    /// # Errors
+    /// Returns an error if the manager task has stopped.
+    pub async fn poll_events(&self) -> Result<Vec<PaneEvent>> {
+        self.request(|reply| Command::PollEvents { reply }).await
+    }
+
+    /// Run a closure against the manager in its task and return its result.
+    ///
+    /// Covers everything without a dedicated command. The manager is
+    /// unavailable to other handles while `f` runs, so keep it short.
+    ///
+    /// # Errors
+    /// Returns an error if the manager task has stopped.
+    pub async fn with<R, F>(&self, f: F) -> Result<R>
+    where
+        R: Send + 'static,
+        F: FnOnce(&mut PaneManager) -> R + Send + 'static,
+    {
+        self.request(|reply| {
+            Command::With(Box::new(move |manager| {
+                let _ = reply.send(f(manager));
+            }))
+        })
+        .await
+    }
+
+    /// Whether the manager task has stopped.
+    #[must_use]
+    pub fn is_closed(&self) -> bool {
+        self.commands.is_closed()
+    }
+}
+
+/// Apply commands to the manager until every handle is dropped.
+async fn run(mut manager: PaneManager, mut commands: mpsc::Receiver<Command>) {
+    while let Some(command) = commands.recv().await {
+        // Replies are dropped if the requester gave up waiting
+        match command {
+            Command::Spawn { config, reply } => {
+                let _ = reply.send(manager.spawn(*config));
+            }
+            Command::Close { pane_id, reply } => {
+                manager.close_pane(pane_id);
+                let _ = reply.send(());
+            }
+            Command::Focus { pane_id, reply } => {
+                manager.set_focus(pane_id);
+                let _ = reply.send(());
+            }
+            Command::Focused { reply } => {
+                let _ = reply.send(manager.focused());
+            }
+            Command::SendInput { data, reply } => {
+                // Await on a handle clone: the manager itself is not `Sync`
+                let pane = manager.focused().ok_or(Error::PaneClosed).and_then(|id| {
+                    manager
+                        .get_pane(id)
+                        .cloned()
+                        .ok_or(Error::PaneNotFound(id.0))
+                });
+                let result = match pane {
+                    Ok(pane) => pane.send_input(&data).await,
+                    Err(e) => Err(e),
+                };
+                let _ = reply.send(result);
+            }
+            Command::Areas { reply } => {
+                let _ = reply.send(manager.get_areas().clone());
+            }
+            Command::SetTerminalSize { size, reply } => {
+                manager.set_terminal_size(size);
+                let _ = reply.send(());
+            }
+            Command::PollEvents { reply } => {
+                let _ = reply.send(manager.poll_events());
+            }
+            Command::With(f) => f(&mut manager),
+        }
+    }
+    for pane_id in manager.pane_ids() {
+        manager.close_pane(pane_id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::manager::ManagerConfig;
+    use crate::mock::MockBackend;
+
+    #[tokio::test]
+    async fn test_handle_drives_manager() {
+        let backend = MockBackend::new();
+        let mut manager = PaneManager::with_mock_backend(ManagerConfig::default(), backend.clone());
+        manager.set_terminal_size(Rect::new(0, 0, 80, 24));
+        let (handle, task) = manager.into_actor();
+        tokio::spawn(task);
+
+        let first = handle.spawn_pane(SpawnConfig::new_shell()).await.unwrap();
+        let other = handle.clone();
+        let second = other.spawn_pane(SpawnConfig::new_shell()).await.unwrap();
+        assert_eq!(handle.areas().await.unwrap().len(), 2);
+        assert_eq!(handle.focused().await.unwrap(), Some(first.id()));
+
+        other.set_focus(second.id()).await.unwrap();
+        handle.send_input(b"ls\r".as_slice()).await.unwrap();
+        let count = handle.with(|manager| manager.pane_count()).await.unwrap();
+        assert_eq!(count, 2);
+
+        handle.close_pane(first.id()).await.unwrap();
+        let events = handle.poll_events().await.unwrap();
+        assert!(events
+            .iter()
+            .any(|e| matches!(e, PaneEvent::PaneClosed { pane_id } if *pane_id == first.id())));
+        assert_eq!(handle.focused().await.unwrap(), Some(second.id()));
+
+        let pty = backend.pane(second.id()).unwrap();
+        tokio::time::timeout(std::time::Duration::from_secs(5), async {
+            while pty.input().is_empty() {
+                tokio::time::sleep(std::time::Duration::from_millis(1)).await;
+            }
+        })
+        .await
+        .unwrap();
+        assert_eq!(pty.input(), b"ls\r");
+    }
+}
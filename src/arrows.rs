@@ -1,15 +1,14 @@
 //! Arrow definitions for pane navigation.
 //!
-//! This module contains the visual representations and dimensions
-//! of navigation arrows used for expanding/collapsing panes.
+//! This module contains the visual representations of the navigation
+//! arrows used for expanding/collapsing panes, and the clickable regions
+//! they occupy.
 
+use ratatui::buffer::Buffer;
 use ratatui::layout::Rect;
+use ratatui::style::{Color, Style};
 
-use crate::pane::PaneId;
-
-/// Arrow dimensions (width x height in terminal cells).
-pub const ARROW_WIDTH: u16 = 5;
-pub const ARROW_HEIGHT: u16 = 3;
+use crate::layout::SubPaneArrangement;
 
 /// Down arrow ASCII art (5 wide x 3 tall).
 /// Used on sub-panes to indicate "click to expand".
@@ -19,7 +18,7 @@ pub const ARROW_HEIGHT: u16 = 3;
 ///  ╲ ╱
 ///   V
 /// ```
-pub const DOWN_ARROW: [[char; 5]; 3] = [
+const DOWN_ARROW: [[char; 5]; 3] = [
     ['╲', ' ', ' ', ' ', '╱'],
     [' ', '╲', ' ', '╱', ' '],
     [' ', ' ', 'V', ' ', ' '],
@@ -33,40 +32,308 @@ pub const DOWN_ARROW: [[char; 5]; 3] = [
 ///  ╱ ╲
 /// ╱   ╲
 /// ```
-pub const UP_ARROW: [[char; 5]; 3] = [
+const UP_ARROW: [[char; 5]; 3] = [
     [' ', ' ', '^', ' ', ' '],
     [' ', '╱', ' ', '╲', ' '],
     ['╱', ' ', ' ', ' ', '╲'],
 ];
 
 /// Right arrow ASCII art (5 wide x 3 tall).
-/// Used on inner-left sub-panes (112, 212) for horizontal navigation.
+/// Used on the innermost sub-pane of left positions (112, 212 by default)
+/// for horizontal navigation.
 ///
 /// ```text
 ///  ╲
 ///   >
 ///  ╱
 /// ```
-pub const RIGHT_ARROW: [[char; 5]; 3] = [
+const RIGHT_ARROW: [[char; 5]; 3] = [
     [' ', '╲', ' ', ' ', ' '],
     [' ', ' ', '>', ' ', ' '],
     [' ', '╱', ' ', ' ', ' '],
 ];
 
 /// Left arrow ASCII art (5 wide x 3 tall).
-/// Used on inner-right sub-panes (121, 221) for horizontal navigation.
+/// Used on the innermost sub-pane of right positions (121, 221 by default)
+/// for horizontal navigation.
 ///
 /// ```text
 ///    ╱
 ///   <
 ///    ╲
 /// ```
-pub const LEFT_ARROW: [[char; 5]; 3] = [
+const LEFT_ARROW: [[char; 5]; 3] = [
     [' ', ' ', ' ', '╱', ' '],
     [' ', ' ', '<', ' ', ' '],
     [' ', ' ', ' ', '╲', ' '],
 ];
 
+/// Direction a navigation arrow points in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ArrowDirection {
+    /// Collapse an expanded pane.
+    Up,
+    /// Expand a pane over its sub-panes.
+    Down,
+    /// Expand or collapse towards the left.
+    Left,
+    /// Expand or collapse towards the right.
+    Right,
+}
+
+/// Drawing of a navigation arrow, one string per row.
+///
+/// Spaces are transparent, so the pane content shows through them.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Glyph {
+    rows: Vec<String>,
+}
+
+impl Glyph {
+    /// Create a glyph from its rows, e.g. `Glyph::new(["[+]"])`.
+    pub fn new<S: Into<String>>(rows: impl IntoIterator<Item = S>) -> Self {
+        Self {
+            rows: rows.into_iter().map(Into::into).collect(),
+        }
+    }
+
+    /// Rows of the glyph, top to bottom.
+    #[must_use]
+    pub fn rows(&self) -> &[String] {
+        &self.rows
+    }
+
+    /// Width in cells of the widest row.
+    #[must_use]
+    pub fn width(&self) -> u16 {
+        let width = self.rows.iter().map(|row| row.chars().count()).max();
+        u16::try_from(width.unwrap_or(0)).unwrap_or(u16::MAX)
+    }
+
+    /// Height in rows.
+    #[must_use]
+    pub fn height(&self) -> u16 {
+        u16::try_from(self.rows.len()).unwrap_or(u16::MAX)
+    }
+
+    fn from_art(art: &[[char; 5]; 3]) -> Self {
+        Self::new(art.iter().map(|row| row.iter().collect::<String>()))
+    }
+}
+
+/// Where arrows are drawn in their pane or sub-pane.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ArrowPlacement {
+    /// In the bottom corner, just inside the border.
+    #[default]
+    Inside,
+    /// On the bottom border, like a button in the frame.
+    Border,
+}
+
+/// Look of the navigation arrows.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
+pub struct ArrowTheme {
+    /// Drawn on expanded panes, to collapse them.
+    pub up: Glyph,
+    /// Drawn on sub-panes, to expand the pane above.
+    pub down: Glyph,
+    /// Drawn on inner sub-panes, to expand or collapse to the left.
+    pub left: Glyph,
+    /// Drawn on inner sub-panes, to expand or collapse to the right.
+    pub right: Glyph,
+    /// Style of the arrow characters.
+    pub style: Style,
+    /// Where arrows are drawn.
+    pub placement: ArrowPlacement,
+}
+
+impl Default for ArrowTheme {
+    /// The 5x3 line-art arrows.
+    fn default() -> Self {
+        Self {
+            up: Glyph::from_art(&UP_ARROW),
+            down: Glyph::from_art(&DOWN_ARROW),
+            left: Glyph::from_art(&LEFT_ARROW),
+            right: Glyph::from_art(&RIGHT_ARROW),
+            style: Style::default().fg(Color::White),
+            placement: ArrowPlacement::Inside,
+        }
+    }
+}
+
+impl ArrowTheme {
+    /// Compact `[+]`/`[−]` buttons on the bottom borders.
+    #[must_use]
+    pub fn buttons() -> Self {
+        Self {
+            up: Glyph::new(["[−]"]),
+            down: Glyph::new(["[+]"]),
+            left: Glyph::new(["[<]"]),
+            right: Glyph::new(["[>]"]),
+            placement: ArrowPlacement::Border,
+            ..Self::default()
+        }
+    }
+
+    /// The glyph drawn for an arrow direction.
+    #[must_use]
+    pub fn glyph(&self, direction: ArrowDirection) -> &Glyph {
+        match direction {
+            ArrowDirection::Up => &self.up,
+            ArrowDirection::Down => &self.down,
+            ArrowDirection::Left => &self.left,
+            ArrowDirection::Right => &self.right,
+        }
+    }
+
+    /// Area of a glyph in the bottom-left or bottom-right corner of `area`,
+    /// clipped to it. `None` if `area` is empty.
+    fn place(&self, area: Rect, direction: ArrowDirection, left: bool) -> Option<Rect> {
+        if area.is_empty() {
+            return None;
+        }
+        let glyph = self.glyph(direction);
+        let bottom = area.y + area.height - 1;
+        let y = match self.placement {
+            ArrowPlacement::Inside => bottom.saturating_sub(glyph.height()),
+            ArrowPlacement::Border => bottom,
+        };
+        let x = if left {
+            area.x + 1
+        } else {
+            (area.x + area.width).saturating_sub(1 + glyph.width())
+        };
+        let region = Rect {
+            x,
+            y: y.max(area.y),
+            width: glyph.width(),
+            height: glyph.height(),
+        };
+        Some(region.intersection(area))
+    }
+}
+
+/// What clicking a navigation region does.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NavigationTarget {
+    /// Collapse the expanded pane at a position (0-3).
+    Collapse(usize),
+    /// Expand a pane over its sub-panes.
+    Expand(ArrowPosition),
+    /// Expand or collapse a row horizontally.
+    Horizontal(HorizontalArrowPosition),
+}
+
+/// A clickable navigation arrow.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NavigationRegion {
+    /// Cells the arrow covers.
+    pub area: Rect,
+    /// Which glyph is drawn.
+    pub direction: ArrowDirection,
+    /// What clicking it does.
+    pub target: NavigationTarget,
+}
+
+/// Layout state the navigation regions are derived from.
+pub(crate) struct NavigationLayout<'a> {
+    /// Areas of the pane positions with a pane, left to right.
+    pub pane_areas: &'a [Rect],
+    pub expanded_positions: [bool; 4],
+    pub horizontal_expanded: [Option<bool>; 2],
+    pub sub_pane_areas: &'a [Rect],
+    pub arrangement: &'a SubPaneArrangement,
+}
+
+impl NavigationLayout<'_> {
+    /// Clickable arrows in hit-test order: up arrows on expanded panes,
+    /// then down arrows, then horizontal arrows.
+    pub fn regions(&self, theme: &ArrowTheme) -> Vec<NavigationRegion> {
+        let mut regions = Vec::new();
+        for (position, area) in self.pane_areas.iter().enumerate().take(4) {
+            if !self.expanded_positions[position] {
+                continue;
+            }
+            let left = is_left_arrow_position(position);
+            if let Some(area) = theme.place(*area, ArrowDirection::Up, left) {
+                regions.push(NavigationRegion {
+                    area,
+                    direction: ArrowDirection::Up,
+                    target: NavigationTarget::Collapse(position),
+                });
+            }
+        }
+
+        // Down arrows sit in the outermost sub-pane of each position:
+        // bottom-left for left positions, bottom-right for right ones
+        for position in 0..4 {
+            let left = is_left_arrow_position(position);
+            let area = down_arrow_sub_pane(self.arrangement, position)
+                .and_then(|idx| self.sub_pane_areas.get(idx))
+                .and_then(|area| theme.place(*area, ArrowDirection::Down, left));
+            if let (Some(area), Some(arrow)) = (area, ArrowPosition::from_position(position)) {
+                regions.push(NavigationRegion {
+                    area,
+                    direction: ArrowDirection::Down,
+                    target: NavigationTarget::Expand(arrow),
+                });
+            }
+        }
+
+        // Horizontal arrows sit in the innermost sub-pane, facing the other
+        // pane of the row. They normally point towards it, and back once
+        // their own pane is expanded.
+        for position in 0..4 {
+            let left = is_left_arrow_position(position);
+            let expanded = self.horizontal_expanded[position / 2] == Some(left);
+            let direction = if left == expanded {
+                ArrowDirection::Left
+            } else {
+                ArrowDirection::Right
+            };
+            let area = horizontal_arrow_sub_pane(self.arrangement, position)
+                .and_then(|idx| self.sub_pane_areas.get(idx))
+                .and_then(|area| theme.place(*area, direction, !left));
+            if let (Some(area), Some(arrow)) =
+                (area, HorizontalArrowPosition::from_source(position))
+            {
+                regions.push(NavigationRegion {
+                    area,
+                    direction,
+                    target: NavigationTarget::Horizontal(arrow),
+                });
+            }
+        }
+        regions
+    }
+}
+
+/// Draw the glyphs of navigation regions.
+pub(crate) fn render_regions(regions: &[NavigationRegion], theme: &ArrowTheme, buf: &mut Buffer) {
+    for region in regions {
+        let glyph = theme.glyph(region.direction);
+        for (y, row) in (region.area.y..region.area.bottom()).zip(glyph.rows()) {
+            for (x, ch) in (region.area.x..region.area.right()).zip(row.chars()) {
+                if ch == ' ' || !buf.area.contains((x, y).into()) {
+                    continue;
+                }
+                let cell = &mut buf[(x, y)];
+                cell.set_char(ch);
+                cell.set_style(theme.style);
+            }
+        }
+    }
+}
+
 /// Vertical arrow positions for expand/collapse navigation.
 /// These correspond to clickable down arrows in corner sub-panes.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -96,7 +363,15 @@ pub enum HorizontalArrowPosition {
 }
 
 impl HorizontalArrowPosition {
+    /// The arrow located under a pane position (0-3).
+    pub(crate) fn from_source(position: usize) -> Option<Self> {
+        [Self::Pane112, Self::Pane121, Self::Pane212, Self::Pane221]
+            .get(position)
+            .copied()
+    }
+
     /// Get the target pane position index (0-3) that this arrow points to.
+    #[must_use]
     pub fn target_position(self) -> usize {
         match self {
             Self::Pane112 => 1, // Points to pane 120 (position 1)
@@ -107,6 +382,7 @@ impl HorizontalArrowPosition {
     }
 
     /// Get the source pane position index (0-3) where this arrow is located.
+    #[must_use]
     pub fn source_position(self) -> usize {
         match self {
             Self::Pane112 => 0, // Located under pane 110 (position 0)
@@ -118,7 +394,15 @@ impl HorizontalArrowPosition {
 }
 
 impl ArrowPosition {
+    /// The arrow that controls a pane position (0-3).
+    pub(crate) fn from_position(position: usize) -> Option<Self> {
+        [Self::Pane111, Self::Pane122, Self::Pane211, Self::Pane222]
+            .get(position)
+            .copied()
+    }
+
     /// Get the pane position index (0-3) that this arrow controls.
+    #[must_use]
     pub fn pane_position(self) -> usize {
         match self {
             Self::Pane111 => 0, // Controls pane 110 (position 0)
@@ -129,110 +413,28 @@ impl ArrowPosition {
     }
 }
 
-/// Check if a click at (x, y) hits any up arrow on expanded panes.
-/// Returns the pane position (0-3) if an up arrow was clicked, None otherwise.
-pub fn up_arrow_at_position(
-    x: u16,
-    y: u16,
-    pane_areas: &[(PaneId, Rect)],
-    expanded_positions: &[bool; 4],
-) -> Option<usize> {
-    for (idx, (_, pane_area)) in pane_areas.iter().enumerate() {
-        if idx >= 4 || !expanded_positions[idx] {
-            continue;
-        }
-
-        let base_y = pane_area.y + pane_area.height.saturating_sub(1 + ARROW_HEIGHT);
-
-        // Position 0 and 2: bottom-left, Position 1 and 3: bottom-right
-        let is_left = idx == 0 || idx == 2;
-        let base_x = if is_left {
-            pane_area.x + 1
-        } else {
-            pane_area.x + pane_area.width.saturating_sub(1 + ARROW_WIDTH)
-        };
-
-        // Check if click is within arrow bounds
-        if x >= base_x && x < base_x + ARROW_WIDTH && y >= base_y && y < base_y + ARROW_HEIGHT {
-            return Some(idx);
-        }
-    }
-
-    None
+/// Sub-pane holding the down arrow of a position: its outermost sub-pane,
+/// i.e. the first one of a left position and the last one of a right one.
+fn down_arrow_sub_pane(arrangement: &SubPaneArrangement, position: usize) -> Option<usize> {
+    let count = arrangement.count(position);
+    let local = if is_left_arrow_position(position) {
+        0
+    } else {
+        count.checked_sub(1)?
+    };
+    arrangement.index(position, local)
 }
 
-/// Check if a click at (x, y) hits any of the navigation arrows (down arrows on sub-panes).
-/// Returns the arrow position if clicked, None otherwise.
-pub fn down_arrow_at_position(x: u16, y: u16, sub_pane_areas: &[Rect]) -> Option<ArrowPosition> {
-    // Arrow indices: 111=0, 122=3, 211=4, 222=7
-    let arrow_configs: [(usize, ArrowPosition, bool); 4] = [
-        (0, ArrowPosition::Pane111, true),  // idx 0, left-aligned
-        (3, ArrowPosition::Pane122, false), // idx 3, right-aligned
-        (4, ArrowPosition::Pane211, true),  // idx 4, left-aligned
-        (7, ArrowPosition::Pane222, false), // idx 7, right-aligned
-    ];
-
-    for (idx, position, is_left) in arrow_configs {
-        if let Some(sub_area) = sub_pane_areas.get(idx) {
-            // Skip empty sub-panes (expanded positions)
-            if sub_area.width == 0 || sub_area.height == 0 {
-                continue;
-            }
-
-            let base_y = sub_area.y + sub_area.height.saturating_sub(1 + ARROW_HEIGHT);
-            let base_x = if is_left {
-                sub_area.x + 1
-            } else {
-                sub_area.x + sub_area.width.saturating_sub(1 + ARROW_WIDTH)
-            };
-
-            // Check if click is within arrow bounds
-            if x >= base_x && x < base_x + ARROW_WIDTH && y >= base_y && y < base_y + ARROW_HEIGHT {
-                return Some(position);
-            }
-        }
-    }
-
-    None
-}
-
-/// Check if a click at (x, y) hits any horizontal navigation arrow.
-/// Returns the arrow position if clicked, None otherwise.
-pub fn horizontal_arrow_at_position(
-    x: u16,
-    y: u16,
-    sub_pane_areas: &[Rect],
-) -> Option<HorizontalArrowPosition> {
-    // Sub-pane indices: 112=1 (right arrow), 121=2 (left arrow), 212=5 (right arrow), 221=6 (left arrow)
-    let arrow_configs: [(usize, HorizontalArrowPosition, bool); 4] = [
-        (1, HorizontalArrowPosition::Pane112, false), // idx 1, right-aligned (right arrow)
-        (2, HorizontalArrowPosition::Pane121, true),  // idx 2, left-aligned (left arrow)
-        (5, HorizontalArrowPosition::Pane212, false), // idx 5, right-aligned (right arrow)
-        (6, HorizontalArrowPosition::Pane221, true),  // idx 6, left-aligned (left arrow)
-    ];
-
-    for (idx, position, is_left) in arrow_configs {
-        if let Some(sub_area) = sub_pane_areas.get(idx) {
-            // Skip empty sub-panes (expanded positions)
-            if sub_area.width == 0 || sub_area.height == 0 {
-                continue;
-            }
-
-            let base_y = sub_area.y + sub_area.height.saturating_sub(1 + ARROW_HEIGHT);
-            let base_x = if is_left {
-                sub_area.x + 1
-            } else {
-                sub_area.x + sub_area.width.saturating_sub(1 + ARROW_WIDTH)
-            };
-
-            // Check if click is within arrow bounds
-            if x >= base_x && x < base_x + ARROW_WIDTH && y >= base_y && y < base_y + ARROW_HEIGHT {
-                return Some(position);
-            }
-        }
-    }
-
-    None
+/// Sub-pane holding the horizontal arrow of a position: its innermost
+/// sub-pane, facing the other pane of the row.
+fn horizontal_arrow_sub_pane(arrangement: &SubPaneArrangement, position: usize) -> Option<usize> {
+    let count = arrangement.count(position);
+    let local = if is_left_arrow_position(position) {
+        count.checked_sub(1)?
+    } else {
+        0
+    };
+    arrangement.index(position, local)
 }
 
 /// Returns whether a position should have its arrow on the left side.
@@ -0,0 +1,102 @@
+//! Border styling shared by panes, sub-panes, empty slots and dialogs.
+//!
+//! A [`BorderTheme`] holds everything about how a border looks: the line
+//! set, the focused and unfocused colors, the style of titles drawn in it
+//! and the padding inside it. Widgets build their blocks from it with
+//! [`BorderTheme::block`], so one theme styles the whole screen.
+
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::widgets::{Block, BorderType, Borders, Padding};
+
+/// Line set borders are drawn with.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum BorderSet {
+    /// `┌─┐` thin lines with square corners.
+    #[default]
+    Plain,
+    /// `╭─╮` thin lines with rounded corners.
+    Rounded,
+    /// `╔═╗` double lines.
+    Double,
+    /// `┏━┓` thick lines.
+    Thick,
+}
+
+impl From<BorderSet> for BorderType {
+    fn from(set: BorderSet) -> Self {
+        match set {
+            BorderSet::Plain => Self::Plain,
+            BorderSet::Rounded => Self::Rounded,
+            BorderSet::Double => Self::Double,
+            BorderSet::Thick => Self::Thick,
+        }
+    }
+}
+
+/// How pane and dialog borders are drawn.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
+pub struct BorderTheme {
+    /// Line set of all borders.
+    pub set: BorderSet,
+    /// Border style of the focused pane.
+    pub focus_style: Style,
+    /// Border style of the other panes, sub-panes and empty slots.
+    pub unfocus_style: Style,
+    /// Style patched over the border style for titles.
+    pub title_style: Style,
+    /// Space between a pane's border and its terminal content.
+    #[cfg_attr(feature = "serde", serde(with = "PaddingDef"))]
+    pub padding: Padding,
+}
+
+/// Serde mirror of ratatui's [`Padding`], which has no serde support.
+#[cfg(feature = "serde")]
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(remote = "Padding")]
+struct PaddingDef {
+    left: u16,
+    right: u16,
+    top: u16,
+    bottom: u16,
+}
+
+impl Default for BorderTheme {
+    fn default() -> Self {
+        Self {
+            set: BorderSet::Plain,
+            focus_style: Style::default().fg(Color::Cyan),
+            unfocus_style: Style::default().fg(Color::DarkGray),
+            title_style: Style::default().add_modifier(Modifier::BOLD),
+            padding: Padding::ZERO,
+        }
+    }
+}
+
+impl BorderTheme {
+    /// Border style for a pane, depending on whether it is focused.
+    #[must_use]
+    pub fn style(&self, focused: bool) -> Style {
+        if focused {
+            self.focus_style
+        } else {
+            self.unfocus_style
+        }
+    }
+
+    /// A block with all borders, styled for a pane.
+    #[must_use]
+    pub fn block(&self, focused: bool) -> Block<'static> {
+        Block::default()
+            .borders(Borders::ALL)
+            .border_type(self.set.into())
+            .border_style(self.style(focused))
+            .title_style(self.title_style)
+            .padding(self.padding)
+    }
+}
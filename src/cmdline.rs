@@ -0,0 +1,102 @@
+//! Splitting command lines into words the way a POSIX shell would.
+
+use crate::error::{Error, Result};
+
+/// Split `line` into words, honoring quotes and backslash escapes.
+///
+/// Follows `sh` rules: single quotes keep everything literally, double
+/// quotes allow `\"`, `\\`, `\$` and `` \` `` escapes, and a backslash
+/// outside quotes escapes the next character. No expansion is done.
+///
+/// # Errors
+/// Returns [`Error::CommandLine`] for an unterminated quote or a trailing
+/// backslash.
+pub(crate) fn split(line: &str) -> Result<Vec<String>> {
+    let mut words = Vec::new();
+    let mut word = String::new();
+    // Distinguishes `''` (an empty word) from no word at all
+    let mut in_word = false;
+    let mut chars = line.chars();
+
+    while let Some(c) = chars.next() {
+        match c {
+            c if c.is_whitespace() => {
+                if in_word {
+                    words.push(std::mem::take(&mut word));
+                    in_word = false;
+                }
+            }
+            '\'' => {
+                in_word = true;
+                loop {
+                    match chars.next() {
+                        Some('\'') => break,
+                        Some(c) => word.push(c),
+                        None => return Err(unterminated("'")),
+                    }
+                }
+            }
+            '"' => {
+                in_word = true;
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some('\\') => match chars.next() {
+                            Some(c @ ('"' | '\\' | '$' | '`')) => word.push(c),
+                            Some('\n') => {}
+                            Some(c) => {
+                                word.push('\\');
+                                word.push(c);
+                            }
+                            None => return Err(unterminated("\"")),
+                        },
+                        Some(c) => word.push(c),
+                        None => return Err(unterminated("\"")),
+                    }
+                }
+            }
+            '\\' => match chars.next() {
+                // Line continuation
+                Some('\n') => {}
+                Some(c) => {
+                    in_word = true;
+                    word.push(c);
+                }
+                None => return Err(Error::CommandLine("trailing backslash".into())),
+            },
+            c => {
+                in_word = true;
+                word.push(c);
+            }
+        }
+    }
+    if in_word {
+        words.push(word);
+    }
+    Ok(words)
+}
+
+fn unterminated(quote: &str) -> Error {
+    Error::CommandLine(format!("unterminated {quote} quote"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_quoting() {
+        assert_eq!(
+            split("npm run dev -- --port 3000").unwrap(),
+            ["npm", "run", "dev", "--", "--port", "3000"]
+        );
+        assert_eq!(
+            split(r#"echo 'it''s' "a \"b\" \n" c\ d ''"#).unwrap(),
+            ["echo", "its", r#"a "b" \n"#, "c d", ""]
+        );
+        assert!(split("").unwrap().is_empty());
+        assert!(split("echo 'open").is_err());
+        assert!(split("echo \"open").is_err());
+        assert!(split("echo \\").is_err());
+    }
+}
@@ -0,0 +1,262 @@
+//! Color depth handling for host terminals without truecolor support.
+
+use ratatui::style::Color;
+
+/// Color depth supported by the host terminal.
+///
+/// Pane programs may emit 24-bit RGB colors; terminals that ignore RGB
+/// SGR sequences need them quantized to the nearest indexed color.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ColorMode {
+    /// 24-bit RGB colors are passed through unchanged.
+    #[default]
+    TrueColor,
+    /// RGB colors are mapped to the xterm 256-color palette.
+    Indexed256,
+    /// All colors are mapped to the 16 standard ANSI colors.
+    Ansi16,
+}
+
+/// RGB values of the 16 standard ANSI colors (xterm defaults).
+const ANSI_PALETTE: [(u8, u8, u8); 16] = [
+    (0, 0, 0),
+    (205, 0, 0),
+    (0, 205, 0),
+    (205, 205, 0),
+    (0, 0, 238),
+    (205, 0, 205),
+    (0, 205, 205),
+    (229, 229, 229),
+    (127, 127, 127),
+    (255, 0, 0),
+    (0, 255, 0),
+    (255, 255, 0),
+    (92, 92, 255),
+    (255, 0, 255),
+    (0, 255, 255),
+    (255, 255, 255),
+];
+
+/// Channel levels of the 6x6x6 color cube (indices 16-231).
+const CUBE_LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+impl ColorMode {
+    /// Guess the host terminal's color depth from `COLORTERM` and `TERM`.
+    ///
+    /// Terminals that declare neither truecolor nor 256 colors, including
+    /// `dumb` and unset `TERM`, get the 16 ANSI colors.
+    #[must_use]
+    pub fn detect() -> Self {
+        let colorterm = std::env::var("COLORTERM").unwrap_or_default();
+        let term = std::env::var("TERM").unwrap_or_default();
+        Self::from_env(&colorterm, &term)
+    }
+
+    /// Color depth for the given `COLORTERM` and `TERM` values.
+    fn from_env(colorterm: &str, term: &str) -> Self {
+        if colorterm == "truecolor" || colorterm == "24bit" || term.contains("direct") {
+            Self::TrueColor
+        } else if term.contains("256color") {
+            Self::Indexed256
+        } else if cfg!(windows) && term.is_empty() {
+            // Windows consoles set no TERM, and have rendered RGB colors
+            // since Windows 10
+            Self::TrueColor
+        } else {
+            Self::Ansi16
+        }
+    }
+
+    /// Downgrade a color so the host terminal can display it.
+    #[must_use]
+    pub fn apply(self, color: Color) -> Color {
+        match (self, color) {
+            (Self::Indexed256, Color::Rgb(r, g, b)) => Color::Indexed(nearest_256((r, g, b))),
+            (Self::Ansi16, Color::Rgb(r, g, b)) => ansi_color(nearest_16((r, g, b))),
+            (Self::Ansi16, Color::Indexed(idx)) => ansi_color(nearest_16(indexed_rgb(idx))),
+            _ => color,
+        }
+    }
+}
+
+/// Gray of the same brightness as `color`. The terminal's default colors
+/// are kept, as their brightness is unknown.
+pub(crate) fn grayscale(color: Color) -> Color {
+    let (r, g, b) = match color {
+        Color::Rgb(r, g, b) => (r, g, b),
+        Color::Indexed(idx) => indexed_rgb(idx),
+        Color::Reset => return color,
+        named => match ansi_index(named) {
+            Some(idx) => ANSI_PALETTE[usize::from(idx)],
+            None => return color,
+        },
+    };
+    // Rec. 601 luma
+    let luma = (299 * u32::from(r) + 587 * u32::from(g) + 114 * u32::from(b)) / 1000;
+    let gray = u8::try_from(luma).unwrap_or(u8::MAX);
+    Color::Rgb(gray, gray, gray)
+}
+
+/// Squared euclidean distance between two RGB colors.
+fn distance(a: (u8, u8, u8), b: (u8, u8, u8)) -> u32 {
+    let d = |x: u8, y: u8| u32::from(x.abs_diff(y)).pow(2);
+    d(a.0, b.0) + d(a.1, b.1) + d(a.2, b.2)
+}
+
+/// Index of the closest cube level for a single channel.
+#[allow(clippy::cast_possible_truncation)]
+fn nearest_level(value: u8) -> u8 {
+    CUBE_LEVELS
+        .iter()
+        .enumerate()
+        .min_by_key(|(_, level)| level.abs_diff(value))
+        .map_or(0, |(i, _)| i as u8)
+}
+
+/// Find the closest color in the 256-color palette (cube or grayscale ramp).
+fn nearest_256(rgb: (u8, u8, u8)) -> u8 {
+    let (r, g, b) = (
+        nearest_level(rgb.0),
+        nearest_level(rgb.1),
+        nearest_level(rgb.2),
+    );
+    let cube_idx = 16 + 36 * r + 6 * g + b;
+    let cube_rgb = (
+        CUBE_LEVELS[r as usize],
+        CUBE_LEVELS[g as usize],
+        CUBE_LEVELS[b as usize],
+    );
+
+    // Grayscale ramp: 232-255 = 8, 18, ..., 238
+    let avg = (u16::from(rgb.0) + u16::from(rgb.1) + u16::from(rgb.2)) / 3;
+    let step = u8::try_from(avg.saturating_sub(3) / 10)
+        .unwrap_or(23)
+        .min(23);
+    let gray = 8 + 10 * step;
+    let gray_idx = 232 + step;
+
+    if distance(rgb, (gray, gray, gray)) < distance(rgb, cube_rgb) {
+        gray_idx
+    } else {
+        cube_idx
+    }
+}
+
+/// Find the closest of the 16 standard ANSI colors.
+#[allow(clippy::cast_possible_truncation)]
+fn nearest_16(rgb: (u8, u8, u8)) -> u8 {
+    ANSI_PALETTE
+        .iter()
+        .enumerate()
+        .min_by_key(|(_, &c)| distance(rgb, c))
+        .map_or(0, |(i, _)| i as u8)
+}
+
+/// RGB value of an entry in the xterm 256-color palette.
+fn indexed_rgb(idx: u8) -> (u8, u8, u8) {
+    match idx {
+        0..=15 => ANSI_PALETTE[idx as usize],
+        16..=231 => {
+            let i = idx - 16;
+            (
+                CUBE_LEVELS[(i / 36) as usize],
+                CUBE_LEVELS[(i / 6 % 6) as usize],
+                CUBE_LEVELS[(i % 6) as usize],
+            )
+        }
+        _ => {
+            let gray = 8 + 10 * (idx - 232);
+            (gray, gray, gray)
+        }
+    }
+}
+
+/// Map an ANSI color index (0-15) to a named ratatui color.
+fn ansi_color(idx: u8) -> Color {
+    match idx {
+        0 => Color::Black,
+        1 => Color::Red,
+        2 => Color::Green,
+        3 => Color::Yellow,
+        4 => Color::Blue,
+        5 => Color::Magenta,
+        6 => Color::Cyan,
+        7 => Color::Gray,
+        8 => Color::DarkGray,
+        9 => Color::LightRed,
+        10 => Color::LightGreen,
+        11 => Color::LightYellow,
+        12 => Color::LightBlue,
+        13 => Color::LightMagenta,
+        14 => Color::LightCyan,
+        _ => Color::White,
+    }
+}
+
+/// ANSI color index (0-15) of a named ratatui color.
+fn ansi_index(color: Color) -> Option<u8> {
+    (0..16).find(|&idx| ansi_color(idx) == color)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect() {
+        std::env::set_var("COLORTERM", "truecolor");
+        std::env::set_var("TERM", "xterm-256color");
+        assert_eq!(ColorMode::detect(), ColorMode::TrueColor);
+        std::env::remove_var("COLORTERM");
+        assert_eq!(ColorMode::detect(), ColorMode::Indexed256);
+        std::env::set_var("TERM", "dumb");
+        assert_eq!(ColorMode::detect(), ColorMode::Ansi16);
+
+        assert_eq!(
+            ColorMode::from_env("", "xterm-direct"),
+            ColorMode::TrueColor
+        );
+        assert_eq!(ColorMode::from_env("", "linux"), ColorMode::Ansi16);
+        let unset = if cfg!(windows) {
+            ColorMode::TrueColor
+        } else {
+            ColorMode::Ansi16
+        };
+        assert_eq!(ColorMode::from_env("", ""), unset);
+    }
+
+    #[test]
+    fn test_truecolor_passthrough() {
+        let color = Color::Rgb(12, 34, 56);
+        assert_eq!(ColorMode::TrueColor.apply(color), color);
+    }
+
+    #[test]
+    fn test_quantize_256() {
+        let mode = ColorMode::Indexed256;
+        assert_eq!(mode.apply(Color::Rgb(255, 0, 0)), Color::Indexed(196));
+        assert_eq!(mode.apply(Color::Rgb(0, 0, 0)), Color::Indexed(16));
+        assert_eq!(mode.apply(Color::Rgb(128, 128, 128)), Color::Indexed(244));
+        // Indexed colors are already displayable
+        assert_eq!(mode.apply(Color::Indexed(42)), Color::Indexed(42));
+        assert_eq!(mode.apply(Color::Reset), Color::Reset);
+    }
+
+    #[test]
+    fn test_quantize_16() {
+        let mode = ColorMode::Ansi16;
+        assert_eq!(mode.apply(Color::Rgb(250, 10, 10)), Color::LightRed);
+        assert_eq!(mode.apply(Color::Rgb(10, 10, 10)), Color::Black);
+        assert_eq!(mode.apply(Color::Indexed(1)), Color::Red);
+        assert_eq!(mode.apply(Color::Indexed(231)), Color::White);
+    }
+
+    #[test]
+    fn test_grayscale() {
+        assert_eq!(grayscale(Color::Rgb(255, 0, 0)), Color::Rgb(76, 76, 76));
+        assert_eq!(grayscale(Color::White), Color::Rgb(255, 255, 255));
+        assert_eq!(grayscale(Color::Indexed(46)), Color::Rgb(149, 149, 149));
+        assert_eq!(grayscale(Color::Reset), Color::Reset);
+    }
+}
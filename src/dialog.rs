@@ -0,0 +1,962 @@
+//! Dialogs that collect input or a choice, or show progress, over the
+//! panes like [`ConfirmDialog`](crate::ConfirmDialog).
+
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers, MouseButton, MouseEvent, MouseEventKind};
+use ratatui::buffer::Buffer;
+use ratatui::layout::{Alignment, Rect};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, Clear, Gauge, Paragraph, StatefulWidget, Widget};
+
+use crate::border::{BorderSet, BorderTheme};
+use crate::palette::fuzzy_score;
+
+/// Entries kept in an input dialog's history.
+const HISTORY_LIMIT: usize = 100;
+
+/// Frames of the spinner shown while progress has no known fraction.
+pub(crate) const SPINNER_FRAMES: [char; 10] = ['⠋', '⠙', '⠹', '⠸', '⠼', '⠴', '⠦', '⠧', '⠇', '⠏'];
+
+/// Time each spinner frame is shown.
+pub(crate) const SPINNER_INTERVAL: Duration = Duration::from_millis(100);
+
+/// How an input dialog was closed.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum InputResult {
+    /// Enter was pressed; holds the entered text.
+    Submit(String),
+    /// Esc was pressed.
+    Cancel,
+}
+
+/// State for the input dialog: an editable line with a cursor and a
+/// history of submitted entries.
+#[derive(Clone, Debug, Default)]
+pub struct InputDialogState {
+    /// Whether the dialog is visible.
+    pub visible: bool,
+    value: String,
+    /// Cursor position, in characters.
+    cursor: usize,
+    history: Vec<String>,
+    /// Entry of `history` being shown while browsing it.
+    history_index: Option<usize>,
+    /// What was typed before browsing the history.
+    draft: String,
+}
+
+impl InputDialogState {
+    /// Create a new hidden dialog state.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Show the dialog with `value` filled in and the cursor at its end.
+    /// The history is kept.
+    pub fn show(&mut self, value: impl Into<String>) {
+        self.visible = true;
+        self.set_value(value);
+    }
+
+    /// Hide the dialog.
+    pub fn hide(&mut self) {
+        self.visible = false;
+    }
+
+    /// The text entered so far.
+    #[must_use]
+    pub fn value(&self) -> &str {
+        &self.value
+    }
+
+    /// Replace the text, moving the cursor to its end.
+    pub fn set_value(&mut self, value: impl Into<String>) {
+        self.value = value.into();
+        self.cursor = self.value.chars().count();
+        self.history_index = None;
+    }
+
+    /// Cursor position, in characters from the start of the text.
+    #[must_use]
+    pub fn cursor(&self) -> usize {
+        self.cursor
+    }
+
+    /// Submitted entries, oldest first.
+    #[must_use]
+    pub fn history(&self) -> &[String] {
+        &self.history
+    }
+
+    /// Handle a key press while the dialog is shown.
+    ///
+    /// Typing edits the text at the cursor. Left/Right, Home/End and
+    /// Ctrl+A/Ctrl+E move the cursor, Backspace and Delete remove
+    /// characters, Ctrl+U clears up to the cursor and Up/Down browse the
+    /// history. Enter and Esc close the dialog and return the result.
+    pub fn handle_key(&mut self, key: KeyEvent) -> Option<InputResult> {
+        if !self.visible {
+            return None;
+        }
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+        match key.code {
+            KeyCode::Enter => {
+                let value = std::mem::take(&mut self.value);
+                self.remember(&value);
+                self.set_value(String::new());
+                self.hide();
+                return Some(InputResult::Submit(value));
+            }
+            KeyCode::Esc => {
+                self.hide();
+                return Some(InputResult::Cancel);
+            }
+            KeyCode::Left => self.cursor = self.cursor.saturating_sub(1),
+            KeyCode::Right => self.cursor = (self.cursor + 1).min(self.len()),
+            KeyCode::Home => self.cursor = 0,
+            KeyCode::Char('a') if ctrl => self.cursor = 0,
+            KeyCode::End => self.cursor = self.len(),
+            KeyCode::Char('e') if ctrl => self.cursor = self.len(),
+            KeyCode::Backspace if self.cursor > 0 => {
+                self.cursor -= 1;
+                self.value.remove(self.byte_index(self.cursor));
+            }
+            KeyCode::Delete if self.cursor < self.len() => {
+                self.value.remove(self.byte_index(self.cursor));
+            }
+            KeyCode::Char('u') if ctrl => {
+                self.value.drain(..self.byte_index(self.cursor));
+                self.cursor = 0;
+            }
+            KeyCode::Up => self.browse_history(-1),
+            KeyCode::Down => self.browse_history(1),
+            KeyCode::Char(c) if !ctrl => {
+                self.value.insert(self.byte_index(self.cursor), c);
+                self.cursor += 1;
+            }
+            _ => {}
+        }
+        None
+    }
+
+    fn len(&self) -> usize {
+        self.value.chars().count()
+    }
+
+    fn byte_index(&self, cursor: usize) -> usize {
+        self.value
+            .char_indices()
+            .nth(cursor)
+            .map_or(self.value.len(), |(index, _)| index)
+    }
+
+    /// Add a submitted entry to the history, unless it is empty or repeats
+    /// the last one.
+    fn remember(&mut self, value: &str) {
+        if value.is_empty() || self.history.last().is_some_and(|last| last == value) {
+            return;
+        }
+        if self.history.len() == HISTORY_LIMIT {
+            self.history.remove(0);
+        }
+        self.history.push(value.to_string());
+    }
+
+    /// Step through the history: back towards older entries, or forward
+    /// until the text typed before browsing comes back.
+    fn browse_history(&mut self, delta: isize) {
+        if self.history.is_empty() {
+            return;
+        }
+        let index = match self.history_index {
+            None if delta < 0 => {
+                self.draft = self.value.clone();
+                Some(self.history.len() - 1)
+            }
+            None => return,
+            Some(index) => index
+                .checked_add_signed(delta)
+                .or(Some(0))
+                .filter(|index| *index < self.history.len()),
+        };
+        let value = match index {
+            Some(index) => self.history[index].clone(),
+            None => std::mem::take(&mut self.draft),
+        };
+        self.set_value(value);
+        self.history_index = index;
+    }
+
+    /// Calculate the dialog area for a given terminal size.
+    #[must_use]
+    pub fn calculate_area(terminal_area: Rect) -> Rect {
+        let width = 50.min(terminal_area.width.saturating_sub(4));
+        let height = 6.min(terminal_area.height.saturating_sub(2));
+        let x = terminal_area.x + (terminal_area.width.saturating_sub(width)) / 2;
+        let y = terminal_area.y + (terminal_area.height.saturating_sub(height)) / 2;
+        Rect::new(x, y, width, height)
+    }
+}
+
+/// A dialog asking for a line of text.
+pub struct InputDialog<'a> {
+    /// State holding the text and cursor.
+    state: &'a InputDialogState,
+    /// Title of the dialog.
+    title: &'a str,
+    /// Prompt shown above the text.
+    prompt: &'a str,
+    /// Style for the dialog border.
+    border_style: Style,
+    /// Line set of the border.
+    border_set: BorderSet,
+    /// Style patched over the border style for the title.
+    title_style: Style,
+    /// Style for the text being edited.
+    input_style: Style,
+}
+
+impl<'a> InputDialog<'a> {
+    /// Create a new input dialog.
+    #[must_use]
+    pub fn new(state: &'a InputDialogState, title: &'a str, prompt: &'a str) -> Self {
+        Self {
+            state,
+            title,
+            prompt,
+            border_style: Style::default().fg(Color::Yellow),
+            border_set: BorderSet::Plain,
+            title_style: Style::default(),
+            input_style: Style::default().bg(Color::DarkGray).fg(Color::White),
+        }
+    }
+
+    /// Set the border style.
+    #[must_use]
+    pub fn border_style(mut self, style: Style) -> Self {
+        self.border_style = style;
+        self
+    }
+
+    /// Draw the border with the line set and title style of `theme`; the
+    /// border keeps its own color.
+    #[must_use]
+    pub fn border_theme(mut self, theme: &BorderTheme) -> Self {
+        self.border_set = theme.set;
+        self.title_style = theme.title_style;
+        self
+    }
+
+    /// Set the style of the text being edited.
+    #[must_use]
+    pub fn input_style(mut self, style: Style) -> Self {
+        self.input_style = style;
+        self
+    }
+}
+
+impl Widget for InputDialog<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        Clear.render(area, buf);
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .border_type(self.border_set.into())
+            .border_style(self.border_style)
+            .title(self.title)
+            .title_style(self.title_style);
+        let inner = block.inner(area);
+        block.render(area, buf);
+        if inner.height < 2 || inner.width < 3 {
+            return;
+        }
+
+        buf.set_stringn(
+            inner.x + 1,
+            inner.y,
+            self.prompt,
+            usize::from(inner.width - 1),
+            Style::default(),
+        );
+
+        // Scroll horizontally so the cursor stays in view
+        let field = Rect::new(inner.x + 1, inner.y + 1, inner.width - 2, 1);
+        let width = usize::from(field.width);
+        let first = (self.state.cursor() + 1).saturating_sub(width);
+        buf.set_style(field, self.input_style);
+        for (x, c) in (field.x..field.right()).zip(self.state.value().chars().skip(first)) {
+            buf[(x, field.y)].set_char(c);
+        }
+        let cursor_x = field.x + u16::try_from(self.state.cursor() - first).unwrap_or(0);
+        buf[(cursor_x, field.y)].set_style(Style::default().add_modifier(Modifier::REVERSED));
+
+        let hint = Paragraph::new("Enter • Esc • ↑↓ history")
+            .alignment(Alignment::Center)
+            .style(Style::default().fg(Color::DarkGray));
+        hint.render(Rect::new(inner.x, inner.bottom() - 1, inner.width, 1), buf);
+    }
+}
+
+/// How a select dialog was closed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SelectResult {
+    /// An option was chosen; holds its index in the options shown.
+    Select(usize),
+    /// Esc was pressed.
+    Cancel,
+}
+
+/// State for the select dialog: a list of options filtered by a query,
+/// with one of the matches selected.
+#[derive(Clone, Debug, Default)]
+pub struct SelectDialogState {
+    /// Whether the dialog is visible.
+    pub visible: bool,
+    options: Vec<String>,
+    query: String,
+    /// Selected entry of `matches`.
+    selected: usize,
+    /// Rows of the list in the last drawn dialog, for paging.
+    page: usize,
+}
+
+impl SelectDialogState {
+    /// Create a new hidden dialog state.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Show the dialog offering `options`, with an empty query and the
+    /// first option selected.
+    pub fn show(&mut self, options: impl IntoIterator<Item = impl Into<String>>) {
+        self.options = options.into_iter().map(Into::into).collect();
+        self.query.clear();
+        self.selected = 0;
+        self.visible = true;
+    }
+
+    /// Hide the dialog.
+    pub fn hide(&mut self) {
+        self.visible = false;
+    }
+
+    /// The options shown.
+    #[must_use]
+    pub fn options(&self) -> &[String] {
+        &self.options
+    }
+
+    /// The query typed so far.
+    #[must_use]
+    pub fn query(&self) -> &str {
+        &self.query
+    }
+
+    /// Indices of the options matching the query, best match first.
+    /// Options are matched like the command palette's actions.
+    #[must_use]
+    pub fn matches(&self) -> Vec<usize> {
+        let mut scored: Vec<_> = self
+            .options
+            .iter()
+            .enumerate()
+            .filter_map(|(index, option)| {
+                fuzzy_score(&self.query, option).map(|score| (score, index))
+            })
+            .collect();
+        // Stable, so equal scores keep their listed order
+        scored.sort_by_key(|(score, _)| std::cmp::Reverse(*score));
+        scored.into_iter().map(|(_, index)| index).collect()
+    }
+
+    /// Index of the selected option, if any option matches.
+    #[must_use]
+    pub fn selected(&self) -> Option<usize> {
+        self.matches().get(self.selected).copied()
+    }
+
+    /// Handle a key press while the dialog is shown.
+    ///
+    /// Typing filters the options, Up/Down (or Ctrl+P/Ctrl+N), Page
+    /// Up/Down and Home/End move the selection. Enter closes the dialog
+    /// and returns the selected option; Esc closes it without one.
+    pub fn handle_key(&mut self, key: KeyEvent) -> Option<SelectResult> {
+        if !self.visible {
+            return None;
+        }
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+        let page = isize::try_from(self.page.max(1)).unwrap_or(isize::MAX);
+        match key.code {
+            KeyCode::Esc => {
+                self.hide();
+                return Some(SelectResult::Cancel);
+            }
+            KeyCode::Enter => {
+                let selected = self.selected()?;
+                self.hide();
+                return Some(SelectResult::Select(selected));
+            }
+            KeyCode::Up => self.move_selection(-1),
+            KeyCode::Down => self.move_selection(1),
+            KeyCode::Char('p') if ctrl => self.move_selection(-1),
+            KeyCode::Char('n') if ctrl => self.move_selection(1),
+            KeyCode::PageUp => self.move_selection(-page),
+            KeyCode::PageDown => self.move_selection(page),
+            KeyCode::Home => self.selected = 0,
+            KeyCode::End => self.move_selection(isize::MAX),
+            KeyCode::Backspace => {
+                self.query.pop();
+                self.selected = 0;
+            }
+            KeyCode::Char(c) if !ctrl => {
+                self.query.push(c);
+                self.selected = 0;
+            }
+            _ => {}
+        }
+        None
+    }
+
+    /// Handle a mouse event over the dialog drawn in `dialog_area`.
+    ///
+    /// Clicking an option closes the dialog and returns it; the wheel
+    /// moves the selection.
+    pub fn handle_mouse(&mut self, mouse: MouseEvent, dialog_area: Rect) -> Option<SelectResult> {
+        if !self.visible {
+            return None;
+        }
+        match mouse.kind {
+            MouseEventKind::ScrollUp => self.move_selection(-1),
+            MouseEventKind::ScrollDown => self.move_selection(1),
+            MouseEventKind::Down(MouseButton::Left) => {
+                let list = Self::list_area(dialog_area);
+                if !list.contains((mouse.column, mouse.row).into()) {
+                    return None;
+                }
+                let row = usize::from(mouse.row - list.y);
+                let index = self.first_visible(usize::from(list.height)) + row;
+                let selected = *self.matches().get(index)?;
+                self.hide();
+                return Some(SelectResult::Select(selected));
+            }
+            _ => {}
+        }
+        None
+    }
+
+    fn move_selection(&mut self, delta: isize) {
+        let count = self.matches().len();
+        if count > 0 {
+            self.selected = self.selected.saturating_add_signed(delta).min(count - 1);
+        }
+    }
+
+    /// First entry of the list shown in `rows` rows, so that the selected
+    /// entry stays in view.
+    fn first_visible(&self, rows: usize) -> usize {
+        (self.selected + 1).saturating_sub(rows)
+    }
+
+    /// Where the list is drawn: inside the borders, below the query.
+    fn list_area(dialog_area: Rect) -> Rect {
+        let inner = Block::default().borders(Borders::ALL).inner(dialog_area);
+        Rect {
+            y: inner.y + 1,
+            height: inner.height.saturating_sub(1),
+            ..inner
+        }
+    }
+
+    /// Calculate the dialog area for a given terminal size.
+    #[must_use]
+    pub fn calculate_area(terminal_area: Rect) -> Rect {
+        let width = 50.min(terminal_area.width.saturating_sub(4));
+        let height = 16.min(terminal_area.height.saturating_sub(2));
+        let x = terminal_area.x + (terminal_area.width.saturating_sub(width)) / 2;
+        let y = terminal_area.y + (terminal_area.height.saturating_sub(height)) / 2;
+        Rect::new(x, y, width, height)
+    }
+}
+
+/// A dialog for picking one of a list of options, such as panes, profiles
+/// or sessions.
+///
+/// Rendered with [`StatefulWidget`](ratatui::widgets::StatefulWidget) so
+/// that page keys move by the number of rows actually shown.
+pub struct SelectDialog<'a> {
+    /// Title of the dialog.
+    title: &'a str,
+    /// Style for the dialog border.
+    border_style: Style,
+    /// Line set of the border.
+    border_set: BorderSet,
+    /// Style patched over the border style for the title.
+    title_style: Style,
+    /// Style for the selected option.
+    selected_style: Style,
+}
+
+impl<'a> SelectDialog<'a> {
+    /// Create a new select dialog.
+    #[must_use]
+    pub fn new(title: &'a str) -> Self {
+        Self {
+            title,
+            border_style: Style::default().fg(Color::Yellow),
+            border_set: BorderSet::Plain,
+            title_style: Style::default(),
+            selected_style: Style::default()
+                .fg(Color::Black)
+                .bg(Color::White)
+                .add_modifier(Modifier::BOLD),
+        }
+    }
+
+    /// Set the border style.
+    #[must_use]
+    pub fn border_style(mut self, style: Style) -> Self {
+        self.border_style = style;
+        self
+    }
+
+    /// Draw the border with the line set and title style of `theme`; the
+    /// border keeps its own color.
+    #[must_use]
+    pub fn border_theme(mut self, theme: &BorderTheme) -> Self {
+        self.border_set = theme.set;
+        self.title_style = theme.title_style;
+        self
+    }
+
+    /// Set the style of the selected option.
+    #[must_use]
+    pub fn selected_style(mut self, style: Style) -> Self {
+        self.selected_style = style;
+        self
+    }
+}
+
+impl StatefulWidget for SelectDialog<'_> {
+    type State = SelectDialogState;
+
+    fn render(self, area: Rect, buf: &mut Buffer, state: &mut SelectDialogState) {
+        Clear.render(area, buf);
+        let matches = state.matches();
+        let count = format!(" {}/{} ", matches.len(), state.options.len());
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .border_type(self.border_set.into())
+            .border_style(self.border_style)
+            .title(self.title)
+            .title_style(self.title_style)
+            .title_bottom(Line::from(count).right_aligned());
+        let inner = block.inner(area);
+        block.render(area, buf);
+        if inner.height == 0 {
+            return;
+        }
+
+        Line::from(vec![Span::raw("> "), Span::raw(state.query())])
+            .render(Rect { height: 1, ..inner }, buf);
+
+        let list = SelectDialogState::list_area(area);
+        let rows = usize::from(list.height);
+        state.page = rows;
+        let first = state.first_visible(rows);
+        for (y, (index, option)) in (list.y..list.bottom()).zip(
+            matches
+                .iter()
+                .map(|&index| &state.options[index])
+                .enumerate()
+                .skip(first),
+        ) {
+            let line_area = Rect {
+                y,
+                height: 1,
+                ..list
+            };
+            if index == state.selected {
+                buf.set_style(line_area, self.selected_style);
+            }
+            buf.set_stringn(
+                line_area.x + 1,
+                y,
+                option,
+                usize::from(line_area.width.saturating_sub(1)),
+                Style::default(),
+            );
+        }
+    }
+}
+
+/// Progress of a long operation, as reported through a
+/// [`ProgressHandle`].
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Progress {
+    /// What is being done.
+    pub message: String,
+    /// How much is done, from 0 to 1. `None` while unknown; a spinner is
+    /// shown instead.
+    pub fraction: Option<f64>,
+    /// Whether the operation has finished.
+    pub finished: bool,
+}
+
+/// Shared handle through which an application reports progress, e.g.
+/// from a background task, for a [`ProgressDialog`] to draw.
+///
+/// Clones update the same progress.
+#[derive(Clone, Debug)]
+pub struct ProgressHandle {
+    progress: Arc<Mutex<Progress>>,
+    started: Instant,
+}
+
+impl ProgressHandle {
+    /// Progress of an operation doing `message`, with no fraction yet.
+    #[must_use]
+    pub fn new(message: impl Into<String>) -> Self {
+        Self {
+            progress: Arc::new(Mutex::new(Progress {
+                message: message.into(),
+                ..Progress::default()
+            })),
+            started: Instant::now(),
+        }
+    }
+
+    /// The current progress.
+    #[must_use]
+    pub fn get(&self) -> Progress {
+        self.lock().clone()
+    }
+
+    /// Change what is being done.
+    pub fn set_message(&self, message: impl Into<String>) {
+        self.lock().message = message.into();
+    }
+
+    /// Set how much is done, from 0 to 1. Values outside are clamped.
+    pub fn set_fraction(&self, fraction: f64) {
+        self.lock().fraction = Some(fraction.clamp(0.0, 1.0));
+    }
+
+    /// Set how much is done as `done` of `total` steps.
+    pub fn set_steps(&self, done: u64, total: u64) {
+        // Precision loss only matters beyond 2^52 steps
+        #[allow(clippy::cast_precision_loss)]
+        let fraction = if total == 0 {
+            1.0
+        } else {
+            done as f64 / total as f64
+        };
+        self.set_fraction(fraction);
+    }
+
+    /// Forget the fraction and show a spinner.
+    pub fn set_indeterminate(&self) {
+        self.lock().fraction = None;
+    }
+
+    /// Mark the operation as finished.
+    pub fn finish(&self) {
+        self.lock().finished = true;
+    }
+
+    /// Whether [`finish`](Self::finish) was called.
+    #[must_use]
+    pub fn is_finished(&self) -> bool {
+        self.lock().finished
+    }
+
+    /// Spinner frame for the time elapsed since the handle was created.
+    fn spinner(&self) -> char {
+        let frame = self.started.elapsed().as_millis() / SPINNER_INTERVAL.as_millis();
+        SPINNER_FRAMES[usize::try_from(frame).unwrap_or(0) % SPINNER_FRAMES.len()]
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Progress> {
+        self.progress.lock().expect("progress lock poisoned")
+    }
+}
+
+/// A dialog showing the progress reported through a [`ProgressHandle`]:
+/// a bar with the percentage done, or a spinner while the fraction is
+/// unknown.
+pub struct ProgressDialog<'a> {
+    /// Handle the progress is read from.
+    handle: &'a ProgressHandle,
+    /// Title of the dialog.
+    title: &'a str,
+    /// Style for the dialog border.
+    border_style: Style,
+    /// Line set of the border.
+    border_set: BorderSet,
+    /// Style patched over the border style for the title.
+    title_style: Style,
+    /// Style for the progress bar.
+    bar_style: Style,
+}
+
+impl<'a> ProgressDialog<'a> {
+    /// Create a new progress dialog.
+    #[must_use]
+    pub fn new(handle: &'a ProgressHandle, title: &'a str) -> Self {
+        Self {
+            handle,
+            title,
+            border_style: Style::default().fg(Color::Yellow),
+            border_set: BorderSet::Plain,
+            title_style: Style::default(),
+            bar_style: Style::default().fg(Color::Cyan).bg(Color::DarkGray),
+        }
+    }
+
+    /// Set the border style.
+    #[must_use]
+    pub fn border_style(mut self, style: Style) -> Self {
+        self.border_style = style;
+        self
+    }
+
+    /// Draw the border with the line set and title style of `theme`; the
+    /// border keeps its own color.
+    #[must_use]
+    pub fn border_theme(mut self, theme: &BorderTheme) -> Self {
+        self.border_set = theme.set;
+        self.title_style = theme.title_style;
+        self
+    }
+
+    /// Set the style of the progress bar.
+    #[must_use]
+    pub fn bar_style(mut self, style: Style) -> Self {
+        self.bar_style = style;
+        self
+    }
+
+    /// Calculate the dialog area for a given terminal size.
+    #[must_use]
+    pub fn calculate_area(terminal_area: Rect) -> Rect {
+        let width = 50.min(terminal_area.width.saturating_sub(4));
+        let height = 6.min(terminal_area.height.saturating_sub(2));
+        let x = terminal_area.x + (terminal_area.width.saturating_sub(width)) / 2;
+        let y = terminal_area.y + (terminal_area.height.saturating_sub(height)) / 2;
+        Rect::new(x, y, width, height)
+    }
+}
+
+impl Widget for ProgressDialog<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        Clear.render(area, buf);
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .border_type(self.border_set.into())
+            .border_style(self.border_style)
+            .title(self.title)
+            .title_style(self.title_style);
+        let inner = block.inner(area);
+        block.render(area, buf);
+        if inner.height < 3 || inner.width < 3 {
+            return;
+        }
+
+        let progress = self.handle.get();
+        let message = match progress.fraction {
+            Some(_) => progress.message,
+            None => format!("{} {}", self.handle.spinner(), progress.message),
+        };
+        Paragraph::new(message)
+            .alignment(Alignment::Center)
+            .render(Rect::new(inner.x, inner.y + 1, inner.width, 1), buf);
+
+        if let Some(fraction) = progress.fraction {
+            // Truncation is fine; 100% only shows once done
+            #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+            let percent = (fraction * 100.0) as u16;
+            Gauge::default()
+                .gauge_style(self.bar_style)
+                .ratio(fraction)
+                .label(format!("{percent}%"))
+                .render(Rect::new(inner.x + 1, inner.y + 2, inner.width - 2, 1), buf);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn press(state: &mut InputDialogState, code: KeyCode) -> Option<InputResult> {
+        state.handle_key(KeyEvent::new(code, KeyModifiers::NONE))
+    }
+
+    fn type_text(state: &mut InputDialogState, text: &str) {
+        for c in text.chars() {
+            press(state, KeyCode::Char(c));
+        }
+    }
+
+    #[test]
+    fn test_input_editing() {
+        let mut state = InputDialogState::new();
+        state.show("cargo");
+        type_text(&mut state, " tst");
+        press(&mut state, KeyCode::Left);
+        press(&mut state, KeyCode::Left);
+        type_text(&mut state, "e");
+        assert_eq!(state.value(), "cargo test");
+        assert_eq!(state.cursor(), 8);
+
+        press(&mut state, KeyCode::Home);
+        press(&mut state, KeyCode::Delete);
+        press(&mut state, KeyCode::End);
+        press(&mut state, KeyCode::Backspace);
+        assert_eq!(state.value(), "argo tes");
+        state.handle_key(KeyEvent::new(KeyCode::Char('u'), KeyModifiers::CONTROL));
+        assert_eq!((state.value(), state.cursor()), ("", 0));
+
+        type_text(&mut state, "é!");
+        assert_eq!(
+            press(&mut state, KeyCode::Enter),
+            Some(InputResult::Submit("é!".to_string()))
+        );
+        assert!(!state.visible);
+        assert_eq!(press(&mut state, KeyCode::Enter), None);
+    }
+
+    #[test]
+    fn test_input_history() {
+        let mut state = InputDialogState::new();
+        for entry in ["one", "two", "two"] {
+            state.show("");
+            type_text(&mut state, entry);
+            press(&mut state, KeyCode::Enter);
+        }
+        assert_eq!(state.history(), ["one", "two"]);
+
+        state.show("");
+        type_text(&mut state, "dra");
+        press(&mut state, KeyCode::Up);
+        assert_eq!(state.value(), "two");
+        press(&mut state, KeyCode::Up);
+        press(&mut state, KeyCode::Up);
+        assert_eq!(state.value(), "one");
+        press(&mut state, KeyCode::Down);
+        assert_eq!(state.value(), "two");
+        press(&mut state, KeyCode::Down);
+        assert_eq!(state.value(), "dra");
+        assert_eq!(press(&mut state, KeyCode::Esc), Some(InputResult::Cancel));
+    }
+
+    #[test]
+    fn test_select_dialog() {
+        let mut state = SelectDialogState::new();
+        state.show(["shell", "logs", "server", "build"]);
+        assert_eq!(state.selected(), Some(0));
+        type_select(&mut state, "s");
+        assert_eq!(state.matches(), [0, 2, 1]);
+        type_select(&mut state, "e");
+        assert_eq!(state.matches(), [2, 0]);
+        state.handle_key(KeyEvent::new(KeyCode::End, KeyModifiers::NONE));
+        assert_eq!(state.selected(), Some(0));
+        assert_eq!(
+            state.handle_key(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE)),
+            Some(SelectResult::Select(0))
+        );
+        assert!(!state.visible);
+
+        // Nothing to select when no option matches
+        state.show(["shell"]);
+        type_select(&mut state, "x");
+        assert_eq!(
+            state.handle_key(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE)),
+            None
+        );
+        assert!(state.visible);
+    }
+
+    fn type_select(state: &mut SelectDialogState, text: &str) {
+        for c in text.chars() {
+            state.handle_key(KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE));
+        }
+    }
+
+    #[test]
+    fn test_render_select_dialog() {
+        let mut state = SelectDialogState::new();
+        state.show((1..=6).map(|i| format!("pane {i}")));
+        let area = Rect::new(0, 0, 16, 6);
+        let mut buf = Buffer::empty(area);
+        // Three rows of list; paging moves by three
+        SelectDialog::new(" Panes ").render(area, &mut buf, &mut state);
+        state.handle_key(KeyEvent::new(KeyCode::PageDown, KeyModifiers::NONE));
+        state.handle_key(KeyEvent::new(KeyCode::Down, KeyModifiers::NONE));
+        assert_eq!(state.selected(), Some(4));
+
+        SelectDialog::new(" Panes ").render(area, &mut buf, &mut state);
+        let row = |y| -> String { (0..16).map(|x| buf[(x, y)].symbol()).collect() };
+        assert_eq!(row(1), "│>             │");
+        assert_eq!(row(2), "│ pane 3       │");
+        assert_eq!(row(4), "│ pane 5       │");
+        assert_eq!(row(5), "└───────── 6/6 ┘");
+        assert_eq!(buf[(3, 4)].bg, Color::White);
+
+        let click = |row| MouseEvent {
+            kind: MouseEventKind::Down(MouseButton::Left),
+            column: 5,
+            row,
+            modifiers: KeyModifiers::NONE,
+        };
+        assert_eq!(state.handle_mouse(click(1), area), None);
+        assert_eq!(
+            state.handle_mouse(click(3), area),
+            Some(SelectResult::Select(3))
+        );
+    }
+
+    #[test]
+    fn test_progress_dialog() {
+        let handle = ProgressHandle::new("Restarting services");
+        let area = Rect::new(0, 0, 30, 6);
+        let mut buf = Buffer::empty(area);
+        ProgressDialog::new(&handle, " Progress ").render(area, &mut buf);
+        let row = |buf: &Buffer, y| -> String { (0..30).map(|x| buf[(x, y)].symbol()).collect() };
+        let message = row(&buf, 2);
+        assert!(SPINNER_FRAMES.iter().any(|frame| message.contains(*frame)));
+        assert!(message.contains("Restarting services"));
+
+        // Clones report to the same dialog
+        let worker = handle.clone();
+        worker.set_steps(3, 8);
+        worker.set_message("Restarting db");
+        assert_eq!(handle.get().fraction, Some(0.375));
+        ProgressDialog::new(&handle, " Progress ").render(area, &mut buf);
+        assert_eq!(row(&buf, 2), "│        Restarting db       │");
+        assert!(row(&buf, 3).contains("37%"));
+        assert_eq!(buf[(2, 3)].fg, Color::Cyan);
+
+        worker.set_fraction(1.5);
+        worker.finish();
+        assert_eq!(handle.get().fraction, Some(1.0));
+        assert!(handle.is_finished());
+    }
+
+    #[test]
+    fn test_render_input_dialog() {
+        let mut state = InputDialogState::new();
+        state.show("a long command line");
+        let area = Rect::new(0, 0, 14, 5);
+        let mut buf = Buffer::empty(area);
+        InputDialog::new(&state, " Run ", "Command:").render(area, &mut buf);
+        let row = |y| -> String { (0..14).map(|x| buf[(x, y)].symbol()).collect() };
+        assert_eq!(row(1), "│ Command:   │");
+        // Scrolled so the cursor, after the text, is in view
+        assert_eq!(row(2), "│ mand line  │");
+        assert!(buf[(11, 2)].modifier.contains(Modifier::REVERSED));
+    }
+}
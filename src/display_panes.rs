@@ -0,0 +1,104 @@
+//! Large pane numbers drawn by the "display panes" mode.
+//!
+//! [`PaneManager::display_panes`](crate::PaneManager::display_panes) starts
+//! the mode; [`PaneNumbers`] draws a big digit in the middle of every
+//! numbered pane so it can be picked with a number key, like tmux's
+//! `display-panes`.
+
+use ratatui::buffer::Buffer;
+use ratatui::layout::Rect;
+use ratatui::style::{Color, Style};
+use ratatui::widgets::Widget;
+
+use crate::pane::PaneId;
+
+/// Digits 1-9 drawn with block characters, 3 cells wide and 5 rows tall.
+const DIGITS: [[&str; 5]; 9] = [
+    [" █ ", "██ ", " █ ", " █ ", "███"],
+    ["███", "  █", "███", "█  ", "███"],
+    ["███", "  █", "███", "  █", "███"],
+    ["█ █", "█ █", "███", "  █", "  █"],
+    ["███", "█  ", "███", "  █", "███"],
+    ["███", "█  ", "███", "█ █", "███"],
+    ["███", "  █", "  █", "  █", "  █"],
+    ["███", "█ █", "███", "█ █", "███"],
+    ["███", "█ █", "███", "  █", "███"],
+];
+
+/// Width of a big digit.
+const DIGIT_WIDTH: u16 = 3;
+
+/// Height of a big digit.
+const DIGIT_HEIGHT: u16 = 5;
+
+/// Overlay numbering panes, as listed by
+/// [`PaneManager::numbered_panes`](crate::PaneManager::numbered_panes).
+///
+/// Panes too small for a big digit get a plain one; panes after the ninth
+/// are not numbered.
+pub struct PaneNumbers<'a> {
+    panes: &'a [(PaneId, Rect)],
+    focused: Option<PaneId>,
+    style: Style,
+    focused_style: Style,
+}
+
+impl<'a> PaneNumbers<'a> {
+    /// Number `panes` in order, starting at 1.
+    #[must_use]
+    pub fn new(panes: &'a [(PaneId, Rect)], focused: Option<PaneId>) -> Self {
+        Self {
+            panes,
+            focused,
+            style: Style::default().fg(Color::Blue),
+            focused_style: Style::default().fg(Color::Red),
+        }
+    }
+
+    /// Set the style of the numbers.
+    #[must_use]
+    pub fn style(mut self, style: Style) -> Self {
+        self.style = style;
+        self
+    }
+
+    /// Set the style of the focused pane's number.
+    #[must_use]
+    pub fn focused_style(mut self, style: Style) -> Self {
+        self.focused_style = style;
+        self
+    }
+}
+
+impl Widget for PaneNumbers<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        for (index, (pane_id, pane_area)) in self.panes.iter().take(DIGITS.len()).enumerate() {
+            let pane_area = pane_area.intersection(area);
+            if pane_area.is_empty() {
+                continue;
+            }
+            let style = if self.focused == Some(*pane_id) {
+                self.focused_style
+            } else {
+                self.style
+            };
+            // Panes with no room for a big digit inside their borders
+            if pane_area.width < DIGIT_WIDTH + 2 || pane_area.height < DIGIT_HEIGHT + 2 {
+                let x = pane_area.x + pane_area.width / 2;
+                let y = pane_area.y + pane_area.height / 2;
+                buf.set_string(x, y, (index + 1).to_string(), style);
+                continue;
+            }
+            let x = pane_area.x + (pane_area.width - DIGIT_WIDTH) / 2;
+            let y = pane_area.y + (pane_area.height - DIGIT_HEIGHT) / 2;
+            for (row, line) in (y..).zip(DIGITS[index]) {
+                // Only the blocks, so the pane shows through the gaps
+                for (column, c) in (x..).zip(line.chars()) {
+                    if c != ' ' {
+                        buf[(column, row)].set_char(c).set_style(style);
+                    }
+                }
+            }
+        }
+    }
+}
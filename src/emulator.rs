@@ -0,0 +1,361 @@
+//! Terminal emulation, extending vt100 with the cell attributes it drops.
+//!
+//! vt100 parses SGR dim, blink, strikethrough, underline styles and
+//! underline colors, but keeps none of them. An [`Emulator`] therefore
+//! runs a second vt100 parser, the shadow, over the same output. SGR
+//! sequences are rewritten on the way in so the attributes vt100 drops are
+//! stored as ones it keeps: dim as bold, blink as italic, strikethrough as
+//! underline, the underline style as the background and the underline
+//! color as the foreground. Everything else reaches the shadow unchanged,
+//! so its cells move, scroll and clear exactly like the real screen's.
+//! The main parser gets the output unchanged too, except for underline
+//! colors, which vt100 would misread as other attributes.
+//!
+//! The shadow costs a second parse of all output and doubles the memory
+//! held by the screen and its scrollback.
+
+use crate::pane::UnderlineStyle;
+
+/// Upper bound for a CSI sequence the filter holds back; longer ones are
+/// passed through unchanged.
+const MAX_CSI_LEN: usize = 64;
+
+/// The attributes of a cell the vt100 emulator does not keep.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub(crate) struct ExtraAttributes {
+    /// Faint text (SGR 2).
+    pub dim: bool,
+    /// Blinking text (SGR 5 or 6).
+    pub blink: bool,
+    /// Crossed-out text (SGR 9).
+    pub strikethrough: bool,
+    /// Underline style set by SGR 4 with a subparameter, or SGR 21.
+    pub underline_style: UnderlineStyle,
+    /// Underline color (SGR 58).
+    pub underline_color: vt100::Color,
+}
+
+/// A vt100 parser that also tracks the attributes vt100 drops.
+pub(crate) struct Emulator {
+    parser: vt100::Parser,
+    /// Parser holding the dropped attributes; see the module docs.
+    shadow: vt100::Parser,
+    filter: SgrFilter,
+}
+
+impl Emulator {
+    /// Create an emulator with a `rows` x `cols` screen keeping
+    /// `scrollback` lines of history.
+    pub fn new(rows: u16, cols: u16, scrollback: usize) -> Self {
+        Self {
+            parser: vt100::Parser::new(rows, cols, scrollback),
+            shadow: vt100::Parser::new(rows, cols, scrollback),
+            filter: SgrFilter::default(),
+        }
+    }
+
+    /// The emulated screen.
+    pub fn screen(&self) -> &vt100::Screen {
+        self.parser.screen()
+    }
+
+    /// The vt100 parser, for callers that only need what vt100 tracks.
+    ///
+    /// Changes to its scrollback offset must be undone before the screen
+    /// is drawn, or the extra attributes end up on the wrong rows.
+    pub fn parser_mut(&mut self) -> &mut vt100::Parser {
+        &mut self.parser
+    }
+
+    /// Process output of the pane's program.
+    pub fn process(&mut self, data: &[u8]) {
+        let (main, shadow) = self.filter.filter(data);
+        self.parser.process(&main);
+        self.shadow.process(&shadow);
+    }
+
+    /// Resize the screen.
+    pub fn set_size(&mut self, rows: u16, cols: u16) {
+        self.parser.set_size(rows, cols);
+        self.shadow.set_size(rows, cols);
+    }
+
+    /// Scroll the view back `rows` lines into scrollback.
+    pub fn set_scrollback(&mut self, rows: usize) {
+        self.parser.set_scrollback(rows);
+        self.shadow.set_scrollback(rows);
+    }
+
+    /// Replace the emulator with one keeping `scrollback` lines of history,
+    /// holding only the current visible screen.
+    pub fn rebuild(&mut self, scrollback: usize) {
+        let rebuild = |parser: &vt100::Parser| {
+            let screen = parser.screen();
+            let (rows, cols) = screen.size();
+            let mut rebuilt = vt100::Parser::new(rows, cols, scrollback);
+            if screen.alternate_screen() {
+                rebuilt.process(b"\x1b[?1049h");
+            }
+            rebuilt.process(&screen.state_formatted());
+            rebuilt
+        };
+        self.parser = rebuild(&self.parser);
+        // The shadow's own attributes are replayed as they are
+        self.shadow = rebuild(&self.shadow);
+    }
+
+    /// Attributes vt100 drops of the cell at `row`, `col` of the view.
+    pub fn extra_attributes(&self, row: u16, col: u16) -> ExtraAttributes {
+        let Some(cell) = self.shadow.screen().cell(row, col) else {
+            return ExtraAttributes::default();
+        };
+        // Erasing copies the current attributes into the blanked cells,
+        // where a terminal would only keep the background
+        if !cell.has_contents() {
+            return ExtraAttributes::default();
+        }
+        let underline_style = match cell.bgcolor() {
+            vt100::Color::Idx(1) => UnderlineStyle::Single,
+            vt100::Color::Idx(2) => UnderlineStyle::Double,
+            vt100::Color::Idx(3) => UnderlineStyle::Curly,
+            vt100::Color::Idx(4) => UnderlineStyle::Dotted,
+            vt100::Color::Idx(5) => UnderlineStyle::Dashed,
+            _ => UnderlineStyle::None,
+        };
+        ExtraAttributes {
+            dim: cell.bold(),
+            blink: cell.italic(),
+            strikethrough: cell.underline(),
+            underline_style,
+            underline_color: cell.fgcolor(),
+        }
+    }
+}
+
+/// Rewrites output for the two parsers.
+///
+/// Bytes pass through unchanged, except for SGR sequences, which are held
+/// back until complete: the shadow gets [`shadow_sgr`] instead, and the
+/// main parser [`main_sgr`].
+#[derive(Debug, Default)]
+struct SgrFilter {
+    /// An unfinished `ESC` or CSI sequence.
+    pending: Vec<u8>,
+}
+
+impl SgrFilter {
+    /// Output for the main parser and for the shadow.
+    fn filter(&mut self, data: &[u8]) -> (Vec<u8>, Vec<u8>) {
+        let mut main = Vec::with_capacity(data.len() + self.pending.len());
+        let mut shadow = Vec::with_capacity(data.len() + self.pending.len());
+        for &byte in data {
+            if self.pending.is_empty() {
+                if byte == 0x1b {
+                    self.pending.push(byte);
+                } else {
+                    main.push(byte);
+                    shadow.push(byte);
+                }
+                continue;
+            }
+            let in_csi = self.pending.len() >= 2;
+            match byte {
+                b'[' if !in_csi => self.pending.push(byte),
+                0x40..=0x7e if in_csi => {
+                    self.pending.push(byte);
+                    let sequence = std::mem::take(&mut self.pending);
+                    let body = &sequence[2..sequence.len() - 1];
+                    let private = body.first().is_some_and(|b| (0x3c..=0x3f).contains(b));
+                    if byte == b'm' && !private {
+                        main.extend(main_sgr(body).unwrap_or_else(|| sequence.clone()));
+                        shadow.extend(shadow_sgr(body));
+                    } else {
+                        main.extend_from_slice(&sequence);
+                        shadow.extend(sequence);
+                    }
+                }
+                0x20..=0x3f if in_csi && self.pending.len() < MAX_CSI_LEN => {
+                    self.pending.push(byte);
+                }
+                // Not an SGR sequence after all: leave it to the parsers
+                0x1b => {
+                    main.extend_from_slice(&self.pending);
+                    shadow.append(&mut self.pending);
+                    self.pending.push(byte);
+                }
+                _ => {
+                    main.extend_from_slice(&self.pending);
+                    shadow.append(&mut self.pending);
+                    main.push(byte);
+                    shadow.push(byte);
+                }
+            }
+        }
+        (main, shadow)
+    }
+}
+
+/// The SGR sequence for the main parser replacing one with parameters
+/// `body`, or `None` to keep it.
+///
+/// vt100 takes the parameters of an underline color given as separate
+/// parameters (`58;5;n` or `58;2;r;g;b`) for attributes of their own, so
+/// they are removed.
+fn main_sgr(body: &[u8]) -> Option<Vec<u8>> {
+    let params: Vec<&[u8]> = body.split(|&b| b == b';').collect();
+    if !params.contains(&&b"58"[..]) {
+        return None;
+    }
+    let mut kept = Vec::new();
+    let mut rest = params.iter();
+    while let Some(&param) = rest.next() {
+        if param != b"58" {
+            kept.push(param);
+            continue;
+        }
+        let components = match rest.next().copied() {
+            Some(b"5") => 1,
+            Some(b"2") => 3,
+            _ => 0,
+        };
+        rest.by_ref().take(components).for_each(drop);
+    }
+    // An empty SGR would reset every attribute
+    if kept.is_empty() {
+        return Some(Vec::new());
+    }
+    Some([&b"\x1b["[..], &kept.join(&b';'), b"m"].concat())
+}
+
+/// The SGR sequence for the shadow parser replacing one with parameters
+/// `body`, or nothing if it sets none of the attributes vt100 drops.
+fn shadow_sgr(body: &[u8]) -> Vec<u8> {
+    // Parameters, each with its colon-separated subparameters; empty or
+    // out of range ones read as 0
+    let params: Vec<Vec<u8>> = body
+        .split(|&b| b == b';')
+        .map(|param| {
+            param
+                .split(|&b| b == b':')
+                .map(|sub| {
+                    std::str::from_utf8(sub)
+                        .ok()
+                        .and_then(|s| s.parse().ok())
+                        .unwrap_or(0)
+                })
+                .collect()
+        })
+        .collect();
+
+    let mut shadow = Vec::new();
+    let mut rest = params.iter();
+    while let Some(param) = rest.next() {
+        match param.as_slice() {
+            [0] => shadow.push("0".to_string()),
+            [2] => shadow.push("1".to_string()),
+            [22] => shadow.push("22".to_string()),
+            [5 | 6] => shadow.push("3".to_string()),
+            [25] => shadow.push("23".to_string()),
+            [9] => shadow.push("4".to_string()),
+            [29] => shadow.push("24".to_string()),
+            [4] => shadow.push("48;5;1".to_string()),
+            [4, style] if *style <= 5 => shadow.push(if *style == 0 {
+                "49".to_string()
+            } else {
+                format!("48;5;{style}")
+            }),
+            [21] => shadow.push("48;5;2".to_string()),
+            [24] => shadow.push("49".to_string()),
+            [59] => shadow.push("39".to_string()),
+            // Colors given with subparameters; the RGB form may carry a
+            // color space before the components
+            [58, 5, index] => shadow.push(format!("38;5;{index}")),
+            [58, 2, .., r, g, b] => shadow.push(format!("38;2;{r};{g};{b}")),
+            // Colors given as separate parameters, which must be skipped
+            // for the others
+            [38 | 48 | 58] => {
+                let color: Vec<u8> = match rest.next().map(Vec::as_slice) {
+                    Some([5]) => rest.next().map(|p| vec![5, p[0]]).unwrap_or_default(),
+                    Some([2]) => {
+                        let rgb: Vec<u8> = rest.by_ref().take(3).map(|p| p[0]).collect();
+                        [vec![2], rgb].concat()
+                    }
+                    _ => Vec::new(),
+                };
+                if param[0] == 58 && matches!(color.len(), 2 | 4) {
+                    let color: Vec<_> = color.iter().map(u8::to_string).collect();
+                    shadow.push(format!("38;{}", color.join(";")));
+                }
+            }
+            _ => {}
+        }
+    }
+    if shadow.is_empty() {
+        return Vec::new();
+    }
+    format!("\x1b[{}m", shadow.join(";")).into_bytes()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_shadow_sgr() {
+        assert_eq!(shadow_sgr(b""), b"\x1b[0m");
+        assert_eq!(shadow_sgr(b"1;31"), b"");
+        assert_eq!(shadow_sgr(b"2;9;4:3"), b"\x1b[1;4;48;5;3m");
+        assert_eq!(shadow_sgr(b"38;2;1;2;3;5;58;5;196"), b"\x1b[3;38;5;196m");
+        assert_eq!(shadow_sgr(b"58:2::10:20:30;24"), b"\x1b[38;2;10;20;30;49m");
+    }
+
+    #[test]
+    fn test_main_sgr() {
+        assert_eq!(main_sgr(b"1;31"), None);
+        assert_eq!(main_sgr(b"58;5;1"), Some(Vec::new()));
+        assert_eq!(main_sgr(b"1;58;2;255;0;0;4"), Some(b"\x1b[1;4m".to_vec()));
+
+        // Neither the bold nor the reset hidden in the colors apply
+        let mut emulator = Emulator::new(1, 10, 0);
+        emulator.process(b"\x1b[3m\x1b[58;5;1mx\x1b[58;2;0;0;0my");
+        let cell = emulator.screen().cell(0, 1).unwrap();
+        assert!(cell.italic() && !cell.bold());
+        assert_eq!(
+            emulator.extra_attributes(0, 1).underline_color,
+            vt100::Color::Rgb(0, 0, 0)
+        );
+    }
+
+    #[test]
+    fn test_extra_attributes() {
+        let mut emulator = Emulator::new(2, 20, 10);
+        // SGR split across reads, mixed with attributes vt100 keeps
+        emulator.process(b"\x1b[1;2mdim\x1b[22m \x1b[9");
+        emulator.process(b";4:3;58;5;1mx\x1b[0m\x1b[5my\x1b[K");
+
+        assert!(emulator.screen().cell(0, 0).unwrap().bold());
+        assert!(emulator.extra_attributes(0, 0).dim);
+        assert_eq!(emulator.extra_attributes(0, 3), ExtraAttributes::default());
+        assert_eq!(
+            emulator.extra_attributes(0, 4),
+            ExtraAttributes {
+                strikethrough: true,
+                underline_style: UnderlineStyle::Curly,
+                underline_color: vt100::Color::Idx(1),
+                ..ExtraAttributes::default()
+            }
+        );
+        assert!(emulator.extra_attributes(0, 5).blink);
+        // Erased cells carry no attributes
+        assert_eq!(emulator.extra_attributes(0, 6), ExtraAttributes::default());
+
+        emulator.rebuild(10);
+        assert!(emulator.extra_attributes(0, 5).blink);
+
+        // Both parsers scroll together
+        emulator.process(b"\r\n\r\nnext");
+        assert!(!emulator.extra_attributes(0, 0).dim);
+        emulator.set_scrollback(1);
+        assert!(emulator.extra_attributes(0, 0).dim);
+    }
+}
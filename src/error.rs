@@ -28,6 +28,26 @@ pub enum Error {
     #[error("pane not found: {0}")]
     PaneNotFound(u64),
 
+    /// No spawn profile registered under the given name.
+    #[error("profile not found: {0}")]
+    ProfileNotFound(String),
+
+    /// No workspace with the given name.
+    #[error("workspace not found: {0}")]
+    WorkspaceNotFound(String),
+
+    /// A workspace with the given name already exists.
+    #[error("workspace exists: {0}")]
+    WorkspaceExists(String),
+
+    /// Invalid manager configuration.
+    #[error("invalid configuration: {0}")]
+    Config(String),
+
+    /// A command line could not be split into words.
+    #[error("invalid command line: {0}")]
+    CommandLine(String),
+
     /// Layout error.
     #[error("layout error: {0}")]
     Layout(String),
@@ -40,6 +60,30 @@ pub enum Error {
     #[error("failed to send input to pane")]
     InputSend,
 
+    /// Unknown key name.
+    #[error("invalid key: {0}")]
+    InvalidKey(String),
+
+    /// Invalid output pattern.
+    #[error("invalid pattern: {0}")]
+    Pattern(#[from] regex::Error),
+
+    /// Setting up or drawing to the host terminal failed.
+    #[error("terminal error: {0}")]
+    Terminal(#[source] std::io::Error),
+
+    /// The task running the pane manager has stopped.
+    #[error("pane manager has shut down")]
+    ManagerClosed,
+
+    /// Waiting for a pane timed out.
+    #[error("timed out waiting for pane")]
+    Timeout,
+
+    /// Sending a signal to a pane's process failed.
+    #[error("failed to signal process: {0}")]
+    Signal(#[source] std::io::Error),
+
     /// Process monitoring error.
     #[error("process monitor error: {0}")]
     ProcessMonitor(String),
@@ -0,0 +1,224 @@
+//! Bounded pane event queue with a configurable overflow policy.
+
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Condvar, Mutex};
+
+use crate::pty::PaneEvent;
+
+/// What happens when a pane event is sent while the event queue is full.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum OverflowPolicy {
+    /// The sending task waits until the app polls events. Nothing is lost,
+    /// but a pane's reader stalls while the queue is full.
+    #[default]
+    Block,
+    /// The oldest queued event is discarded.
+    DropOldest,
+    /// Output notifications are discarded to make room. Lifecycle events
+    /// (exits, crashes, ...) are always queued, even beyond the capacity.
+    DropOutput,
+}
+
+/// Queue state shared between senders and the receiver.
+struct Shared {
+    state: Mutex<QueueState>,
+    not_full: Condvar,
+    capacity: usize,
+    policy: OverflowPolicy,
+    dropped: AtomicU64,
+}
+
+struct QueueState {
+    events: VecDeque<PaneEvent>,
+    /// Set when the receiver is dropped; sends become no-ops.
+    closed: bool,
+}
+
+/// Create a bounded event queue.
+pub(crate) fn event_queue(capacity: usize, policy: OverflowPolicy) -> (EventSender, EventReceiver) {
+    let capacity = capacity.max(1);
+    let shared = Arc::new(Shared {
+        state: Mutex::new(QueueState {
+            events: VecDeque::with_capacity(capacity),
+            closed: false,
+        }),
+        not_full: Condvar::new(),
+        capacity,
+        policy,
+        dropped: AtomicU64::new(0),
+    });
+    (EventSender(shared.clone()), EventReceiver(shared))
+}
+
+/// Sending half of the event queue, used by pane tasks.
+#[derive(Clone)]
+pub(crate) struct EventSender(Arc<Shared>);
+
+impl EventSender {
+    /// Queue an event, applying the overflow policy if the queue is full.
+    ///
+    /// With [`OverflowPolicy::Block`] this blocks the calling thread, so it
+    /// must only be called from blocking tasks.
+    pub fn send(&self, event: PaneEvent) {
+        let shared = &self.0;
+        let mut state = shared.state.lock().expect("event queue lock poisoned");
+        loop {
+            if state.closed {
+                return;
+            }
+            if state.events.len() < shared.capacity {
+                break;
+            }
+            match shared.policy {
+                OverflowPolicy::Block => {
+                    state = shared
+                        .not_full
+                        .wait(state)
+                        .expect("event queue lock poisoned");
+                }
+                OverflowPolicy::DropOldest => {
+                    state.events.pop_front();
+                    shared.dropped.fetch_add(1, Ordering::Relaxed);
+                    break;
+                }
+                OverflowPolicy::DropOutput => {
+                    if matches!(event, PaneEvent::Output { .. }) {
+                        shared.dropped.fetch_add(1, Ordering::Relaxed);
+                        return;
+                    }
+                    let output = state
+                        .events
+                        .iter()
+                        .position(|e| matches!(e, PaneEvent::Output { .. }));
+                    if let Some(pos) = output {
+                        state.events.remove(pos);
+                        shared.dropped.fetch_add(1, Ordering::Relaxed);
+                    }
+                    break;
+                }
+            }
+        }
+        state.events.push_back(event);
+    }
+
+    /// Whether the receiver has been dropped.
+    pub fn is_closed(&self) -> bool {
+        self.0
+            .state
+            .lock()
+            .expect("event queue lock poisoned")
+            .closed
+    }
+
+    /// Queue an event without waiting, even if the queue is full.
+    ///
+    /// For events raised on the manager's own thread, which would deadlock
+    /// waiting for itself to drain the queue.
+    pub fn push(&self, event: PaneEvent) {
+        let mut state = self.0.state.lock().expect("event queue lock poisoned");
+        if !state.closed {
+            state.events.push_back(event);
+        }
+    }
+}
+
+/// Receiving half of the event queue, owned by the manager.
+pub(crate) struct EventReceiver(Arc<Shared>);
+
+impl EventReceiver {
+    /// Take all queued events, waking any blocked senders.
+    pub fn drain(&self) -> Vec<PaneEvent> {
+        let events: Vec<_> = self
+            .0
+            .state
+            .lock()
+            .expect("event queue lock poisoned")
+            .events
+            .drain(..)
+            .collect();
+        self.0.not_full.notify_all();
+        events
+    }
+
+    /// Number of events discarded by the overflow policy.
+    pub fn dropped(&self) -> u64 {
+        self.0.dropped.load(Ordering::Relaxed)
+    }
+}
+
+impl Drop for EventReceiver {
+    fn drop(&mut self) {
+        if let Ok(mut state) = self.0.state.lock() {
+            state.closed = true;
+        }
+        self.0.not_full.notify_all();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pane::PaneId;
+
+    fn output(id: u64) -> PaneEvent {
+        PaneEvent::Output {
+            pane_id: PaneId(id),
+            size: 1,
+        }
+    }
+
+    fn exited(id: u64) -> PaneEvent {
+        PaneEvent::Exited {
+            pane_id: PaneId(id),
+            code: 0,
+        }
+    }
+
+    #[test]
+    fn test_drop_oldest() {
+        let (tx, rx) = event_queue(2, OverflowPolicy::DropOldest);
+        tx.send(output(1));
+        tx.send(output(2));
+        tx.send(exited(3));
+        let events = rx.drain();
+        assert_eq!(events.len(), 2);
+        assert!(matches!(
+            events[0],
+            PaneEvent::Output {
+                pane_id: PaneId(2),
+                ..
+            }
+        ));
+        assert!(matches!(events[1], PaneEvent::Exited { .. }));
+        assert_eq!(rx.dropped(), 1);
+    }
+
+    #[test]
+    fn test_drop_output_keeps_lifecycle_events() {
+        let (tx, rx) = event_queue(2, OverflowPolicy::DropOutput);
+        tx.send(exited(1));
+        tx.send(output(2));
+        tx.send(exited(3));
+        tx.send(exited(4));
+        tx.send(output(5));
+        let events = rx.drain();
+        assert_eq!(events.len(), 3);
+        assert!(events.iter().all(|e| matches!(e, PaneEvent::Exited { .. })));
+        assert_eq!(rx.dropped(), 2);
+    }
+
+    #[test]
+    fn test_block_waits_for_drain() {
+        let (tx, rx) = event_queue(1, OverflowPolicy::Block);
+        tx.send(output(1));
+        let sender = std::thread::spawn(move || tx.send(exited(2)));
+        while rx.drain().is_empty() {
+            std::thread::yield_now();
+        }
+        sender.join().unwrap();
+        assert_eq!(rx.drain().len(), 1);
+        assert_eq!(rx.dropped(), 0);
+    }
+}
@@ -0,0 +1,427 @@
+//! Drawing a whole cockpit frame in one call.
+//!
+//! [`CockpitFrame`] gathers everything the individual widgets need from
+//! the [`PaneManager`] (pane handles, layout areas, sub-panes, empty slots,
+//! status bar segments, workspace tabs) and draws them, plus an optional
+//! confirmation dialog, styled by a [`Theme`].
+
+use ratatui::layout::Rect;
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::Frame;
+
+use crate::arrows::ArrowTheme;
+use crate::border::BorderTheme;
+use crate::display_panes::PaneNumbers;
+use crate::manager::PaneManager;
+use crate::status_bar::{StatusBarConfig, StatusBarWidget};
+use crate::tab_bar::{TabBarConfig, TabBarWidget, TAB_BAR_HEIGHT};
+use crate::widget::{
+    ActivityIndicator, CockpitWidget, ConfirmDialog, DialogState, DimMode, ExitOverlay,
+    PaneScrollbar, PaneTitle, SearchHighlight,
+};
+
+/// Styles and options for drawing a cockpit frame.
+#[derive(Clone, Debug)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
+pub struct Theme {
+    /// Borders of panes, sub-panes, empty slots and dialogs.
+    pub borders: BorderTheme,
+    /// Show pane labels and PIDs in borders.
+    pub show_numbers: bool,
+    /// Look of the expand/collapse arrows. Clicks are matched against the
+    /// arrows of the manager's theme.
+    pub arrows: ArrowTheme,
+    /// Status bar look. `None` hides the status bar; it is also hidden
+    /// when plugins are not enabled.
+    pub status_bar: Option<StatusBarConfig>,
+    /// Tab bar look. `None` hides the tab bar; it is also hidden while
+    /// there is only one workspace. Clicks are matched against the tabs
+    /// of the manager's theme.
+    pub tab_bar: Option<TabBarConfig>,
+    /// Border style of dialogs.
+    pub dialog_border_style: Style,
+    /// Style of the selected dialog button.
+    pub dialog_selected_style: Style,
+    /// Style of the other dialog buttons.
+    pub dialog_unselected_style: Style,
+    /// Style patched over destructive dialog buttons.
+    pub dialog_danger_style: Style,
+    /// How panes are drawn once their process has exited. `None` shows
+    /// their last output unchanged.
+    pub exit_overlay: Option<ExitOverlay>,
+    /// Shown in the top border of panes that are printing output. `None`
+    /// shows nothing.
+    pub activity_indicator: Option<ActivityIndicator>,
+    /// How pane names and titles are drawn in borders. `None` leaves
+    /// borders plain.
+    pub pane_titles: Option<PaneTitle>,
+    /// How the content of unfocused panes is toned down. `None` draws it
+    /// unchanged.
+    pub dim_unfocused: Option<DimMode>,
+    /// Drawn over the right border of panes scrolled back into their
+    /// scrollback. `None` draws none, and scrollbar clicks select text.
+    pub scrollbar: Option<PaneScrollbar>,
+    /// How matches of the panes' active searches are drawn.
+    pub search_highlight: SearchHighlight,
+    /// Style patched over text selected in panes. `None` reverses it.
+    pub selection_style: Option<Style>,
+    /// Style of the numbers shown by
+    /// [`display_panes`](PaneManager::display_panes).
+    pub pane_number_style: Style,
+    /// Style of the focused pane's number.
+    pub pane_number_focused_style: Style,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self {
+            borders: BorderTheme::default(),
+            show_numbers: false,
+            arrows: ArrowTheme::default(),
+            status_bar: Some(StatusBarConfig::default()),
+            tab_bar: Some(TabBarConfig::default()),
+            dialog_border_style: Style::default().fg(Color::Yellow),
+            dialog_selected_style: Style::default()
+                .fg(Color::Black)
+                .bg(Color::White)
+                .add_modifier(Modifier::BOLD),
+            dialog_unselected_style: Style::default().fg(Color::White),
+            dialog_danger_style: Style::default().fg(Color::Red),
+            exit_overlay: Some(ExitOverlay::default()),
+            activity_indicator: Some(ActivityIndicator::default()),
+            pane_titles: Some(PaneTitle::default()),
+            dim_unfocused: None,
+            scrollbar: Some(PaneScrollbar::default()),
+            search_highlight: SearchHighlight::default(),
+            selection_style: None,
+            pane_number_style: Style::default().fg(Color::Blue),
+            pane_number_focused_style: Style::default().fg(Color::Red),
+        }
+    }
+}
+
+/// Where the parts of a frame were drawn.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct FrameAreas {
+    /// Status bar above the panes, if shown.
+    pub status_bar: Option<Rect>,
+    /// Status bar below the panes, if shown; see
+    /// [`StatusBarPlacement`](crate::StatusBarPlacement).
+    pub bottom_status_bar: Option<Rect>,
+    /// Tab bar, if shown.
+    pub tab_bar: Option<Rect>,
+    /// Area shared by the panes.
+    pub panes: Rect,
+    /// Dialog, if shown. Pass it to [`DialogState::handle_mouse`].
+    pub dialog: Option<Rect>,
+}
+
+/// Confirmation dialog drawn on top of the panes.
+struct Dialog<'a> {
+    state: &'a DialogState,
+    title: &'a str,
+    message: &'a str,
+}
+
+/// Draws the panes, status bar and dialogs of a [`PaneManager`].
+///
+/// ```no_run
+/// # fn draw(manager: &mut cockpit::PaneManager, dialog: &cockpit::DialogState, frame: &mut ratatui::Frame) {
+/// use cockpit::CockpitFrame;
+///
+/// let areas = CockpitFrame::new(manager)
+///     .dialog(dialog, " Exit? ", "Are you sure you want to quit?")
+///     .render(frame);
+/// # }
+/// ```
+pub struct CockpitFrame<'a> {
+    manager: &'a mut PaneManager,
+    theme: Option<&'a Theme>,
+    dialog: Option<Dialog<'a>>,
+}
+
+impl<'a> CockpitFrame<'a> {
+    /// Draw `manager` with the theme from its configuration.
+    #[must_use]
+    pub fn new(manager: &'a mut PaneManager) -> Self {
+        Self {
+            manager,
+            theme: None,
+            dialog: None,
+        }
+    }
+
+    /// Use a different theme.
+    #[must_use]
+    pub fn theme(mut self, theme: &'a Theme) -> Self {
+        self.theme = Some(theme);
+        self
+    }
+
+    /// Draw a confirmation dialog while `state` is visible.
+    #[must_use]
+    pub fn dialog(mut self, state: &'a DialogState, title: &'a str, message: &'a str) -> Self {
+        self.dialog = Some(Dialog {
+            state,
+            title,
+            message,
+        });
+        self
+    }
+
+    /// Draw the frame.
+    ///
+    /// The manager's terminal size is updated to the area left for panes,
+    /// so terminal resizes need no separate handling.
+    pub fn render(self, frame: &mut Frame<'_>) -> FrameAreas {
+        let Self {
+            manager,
+            theme,
+            dialog,
+        } = self;
+        let theme = theme.unwrap_or(manager.theme()).clone();
+        let area = frame.area();
+        let areas = frame_areas(manager, &theme, area);
+        let panes_area = areas.panes;
+        manager.set_terminal_size(panes_area);
+
+        if let Some(config) = &theme.status_bar {
+            let segments = manager.status_bar_segments();
+            let (top_rows, _) = config.split_rows();
+            let widget = || StatusBarWidget::new(&segments).config(config.clone());
+            if let Some(bar) = areas.status_bar {
+                frame.render_widget(widget(), bar);
+            }
+            if let Some(bar) = areas.bottom_status_bar {
+                frame.render_widget(widget().first_row(top_rows), bar);
+            }
+        }
+        manager.set_tab_bar_area(areas.tab_bar);
+        if let Some((bar, config)) = areas.tab_bar.zip(theme.tab_bar.as_ref()) {
+            let tabs = manager.workspace_tabs();
+            frame.render_widget(TabBarWidget::new(&tabs).config(config.clone()), bar);
+        }
+
+        frame.render_stateful_widget(
+            CockpitWidget::default().theme(&theme),
+            panes_area,
+            &mut manager.cockpit_state(),
+        );
+        if manager.displaying_panes() {
+            let numbered = manager.numbered_panes();
+            let numbers = PaneNumbers::new(&numbered, manager.focused())
+                .style(theme.pane_number_style)
+                .focused_style(theme.pane_number_focused_style);
+            frame.render_widget(numbers, panes_area);
+        }
+        manager.mark_rendered();
+
+        let dialog = dialog.filter(|d| d.state.visible).map(|d| {
+            let dialog_area = DialogState::calculate_area(area);
+            let widget = ConfirmDialog::new(d.title, d.message)
+                .buttons(d.state.buttons())
+                .selected(d.state.selected)
+                .border_style(theme.dialog_border_style)
+                .border_theme(&theme.borders)
+                .selected_style(theme.dialog_selected_style)
+                .unselected_style(theme.dialog_unselected_style)
+                .danger_style(theme.dialog_danger_style);
+            frame.render_widget(widget, dialog_area);
+            dialog_area
+        });
+
+        FrameAreas { dialog, ..areas }
+    }
+}
+
+/// Where the bars of `theme` are drawn in a frame of `area`, and the area
+/// left for the panes of `manager`. No dialog is placed.
+pub(crate) fn frame_areas(manager: &PaneManager, theme: &Theme, area: Rect) -> FrameAreas {
+    let (top_rows, bottom_rows) = theme
+        .status_bar
+        .as_ref()
+        .filter(|_| manager.plugins_enabled())
+        .map_or((0, 0), StatusBarConfig::split_rows);
+    let (status_bar, rest) = split_top(area, top_rows);
+    let (rest, bottom_status_bar) = split_bottom(rest, bottom_rows);
+    let tab_bar_rows = if theme.tab_bar.is_some() && manager.workspaces().len() > 1 {
+        TAB_BAR_HEIGHT
+    } else {
+        0
+    };
+    let (tab_bar, panes) = split_top(rest, tab_bar_rows);
+    FrameAreas {
+        status_bar,
+        bottom_status_bar,
+        tab_bar,
+        panes,
+        dialog: None,
+    }
+}
+
+/// Take a bar of `height` rows off the top of `area`, returning the bar,
+/// if any rows were asked for, and the rest of the area.
+fn split_top(area: Rect, height: u16) -> (Option<Rect>, Rect) {
+    if height == 0 {
+        return (None, area);
+    }
+    let height = height.min(area.height);
+    let bar = Rect { height, ..area };
+    let rest = Rect {
+        y: area.y + height,
+        height: area.height - height,
+        ..area
+    };
+    (Some(bar), rest)
+}
+
+/// Take a bar of `height` rows off the bottom of `area`, returning the
+/// rest of the area and the bar, if any rows were asked for.
+fn split_bottom(area: Rect, height: u16) -> (Rect, Option<Rect>) {
+    if height == 0 {
+        return (area, None);
+    }
+    let height = height.min(area.height);
+    let rest = Rect {
+        height: area.height - height,
+        ..area
+    };
+    let bar = Rect {
+        y: rest.bottom(),
+        height,
+        ..area
+    };
+    (rest, Some(bar))
+}
+
+#[cfg(test)]
+mod tests {
+    use ratatui::backend::TestBackend;
+    use ratatui::Terminal;
+
+    use super::*;
+    use crate::manager::ManagerConfig;
+    use crate::mock::MockBackend;
+    use crate::pane::SpawnConfig;
+    use crate::status_bar::StatusBarPlacement;
+
+    #[tokio::test]
+    async fn test_render_frame() {
+        let mut manager =
+            PaneManager::with_mock_backend(ManagerConfig::default(), MockBackend::new())
+                .with_plugins(std::env::temp_dir());
+        manager.spawn(SpawnConfig::new_shell()).unwrap();
+        let mut dialog = DialogState::new();
+        dialog.show();
+
+        let mut terminal = Terminal::new(TestBackend::new(40, 12)).unwrap();
+        let mut areas = FrameAreas::default();
+        terminal
+            .draw(|frame| {
+                areas = CockpitFrame::new(&mut manager)
+                    .dialog(&dialog, " Quit? ", "Sure?")
+                    .render(frame);
+            })
+            .unwrap();
+
+        assert_eq!(areas.status_bar, Some(Rect::new(0, 0, 40, 1)));
+        assert_eq!(areas.panes, Rect::new(0, 1, 40, 11));
+        assert!(areas.dialog.is_some());
+        assert!(!manager.needs_render());
+
+        // The layout follows the frame without a separate resize call
+        terminal.backend_mut().resize(60, 20);
+        terminal.autoresize().unwrap();
+        terminal
+            .draw(|frame| areas = manager.render(frame))
+            .unwrap();
+        assert_eq!(areas.panes, Rect::new(0, 1, 60, 19));
+        assert_eq!(areas.dialog, None);
+        assert!(manager.get_areas().values().all(|area| area.y >= 1));
+    }
+
+    #[tokio::test]
+    async fn test_tab_bar_below_status_bar() {
+        let mut manager =
+            PaneManager::with_mock_backend(ManagerConfig::default(), MockBackend::new())
+                .with_plugins(std::env::temp_dir());
+        manager.create_workspace("logs").unwrap();
+
+        let mut terminal = Terminal::new(TestBackend::new(40, 12)).unwrap();
+        let mut areas = FrameAreas::default();
+        terminal
+            .draw(|frame| areas = manager.render(frame))
+            .unwrap();
+        assert_eq!(areas.tab_bar, Some(Rect::new(0, 1, 40, 1)));
+        assert_eq!(areas.panes, Rect::new(0, 2, 40, 10));
+        let tabs: String = (0..15)
+            .map(|x| terminal.backend().buffer()[(x, 1)].symbol())
+            .collect();
+        assert_eq!(tabs, " default  logs ");
+
+        assert!(manager.handle_click(11, 1));
+        assert_eq!(manager.active_workspace(), "logs");
+    }
+
+    #[tokio::test]
+    async fn test_status_bar_placement() {
+        let mut manager =
+            PaneManager::with_mock_backend(ManagerConfig::default(), MockBackend::new())
+                .with_plugins(std::env::temp_dir());
+        manager.create_workspace("logs").unwrap();
+        manager.set_theme(Theme {
+            status_bar: Some(StatusBarConfig::default().placement(StatusBarPlacement::Bottom)),
+            ..Theme::default()
+        });
+        let area = Rect::new(0, 0, 40, 12);
+        let areas = manager.frame_areas(area);
+        assert_eq!(areas.status_bar, None);
+        assert_eq!(areas.bottom_status_bar, Some(Rect::new(0, 11, 40, 1)));
+        assert_eq!(areas.tab_bar, Some(Rect::new(0, 0, 40, 1)));
+        assert_eq!(areas.panes, Rect::new(0, 1, 40, 10));
+
+        // A split bar puts its first row on top and the rest below
+        manager.set_theme(Theme {
+            status_bar: Some(
+                StatusBarConfig::default()
+                    .rows(3)
+                    .placement(StatusBarPlacement::Both),
+            ),
+            ..Theme::default()
+        });
+        manager.set_frame_size(area);
+        let mut terminal = Terminal::new(TestBackend::new(40, 12)).unwrap();
+        let mut areas = FrameAreas::default();
+        terminal
+            .draw(|frame| areas = manager.render(frame))
+            .unwrap();
+        assert_eq!(areas.status_bar, Some(Rect::new(0, 0, 40, 1)));
+        assert_eq!(areas.bottom_status_bar, Some(Rect::new(0, 10, 40, 2)));
+        assert_eq!(areas.panes, Rect::new(0, 2, 40, 8));
+        assert!(manager.get_areas().values().all(|a| a.bottom() <= 10));
+    }
+
+    #[tokio::test]
+    async fn test_status_bar_rows() {
+        let mut manager =
+            PaneManager::with_mock_backend(ManagerConfig::default(), MockBackend::new())
+                .with_plugins(std::env::temp_dir());
+        manager.set_theme(Theme {
+            status_bar: Some(StatusBarConfig::default().rows(3)),
+            ..Theme::default()
+        });
+        assert_eq!(manager.status_bar_height(), 3);
+
+        let mut terminal = Terminal::new(TestBackend::new(40, 12)).unwrap();
+        let mut areas = FrameAreas::default();
+        terminal
+            .draw(|frame| areas = manager.render(frame))
+            .unwrap();
+        assert_eq!(areas.status_bar, Some(Rect::new(0, 0, 40, 3)));
+        assert_eq!(areas.panes, Rect::new(0, 3, 40, 9));
+    }
+}
@@ -0,0 +1,131 @@
+//! Inline image protocols (sixel, kitty graphics, iTerm2).
+//!
+//! The vt100 emulator drops image sequences. The reader task detects them
+//! so they can either be forwarded to the host terminal at the pane's
+//! on-screen position or replaced by a placeholder in the pane.
+
+use crate::sequences::{Sequence, SequenceKind};
+
+/// Text written into a pane in place of an image that is not forwarded.
+pub(crate) const IMAGE_PLACEHOLDER: &str = "[image]";
+
+/// Inline image protocol used by a graphics sequence.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GraphicsProtocol {
+    /// DEC sixel graphics (`DCS ... q <data> ST`).
+    Sixel,
+    /// Kitty graphics protocol (`APC G <control> ; <data> ST`).
+    Kitty,
+    /// iTerm2 inline images (`OSC 1337 ; File=... ST`).
+    Iterm2,
+}
+
+/// An inline image emitted by a pane's program.
+#[derive(Clone, Debug)]
+pub struct InlineImage {
+    /// Protocol the image was sent with.
+    pub protocol: GraphicsProtocol,
+    /// Cursor row inside the pane when the image was emitted.
+    pub row: u16,
+    /// Cursor column inside the pane when the image was emitted.
+    pub col: u16,
+    /// The raw escape sequence, ready to be written to a host terminal.
+    pub data: Vec<u8>,
+}
+
+impl InlineImage {
+    /// Encode the image for the host terminal, placed relative to `origin`
+    /// (the top-left cell of the pane's content area, zero-based).
+    ///
+    /// The host cursor is saved and restored around the image.
+    #[must_use]
+    pub fn to_host_bytes(&self, origin: (u16, u16)) -> Vec<u8> {
+        let row = origin.1.saturating_add(self.row).saturating_add(1);
+        let col = origin.0.saturating_add(self.col).saturating_add(1);
+        let mut out = format!("\x1b7\x1b[{row};{col}H").into_bytes();
+        out.extend_from_slice(&self.data);
+        out.extend_from_slice(b"\x1b8");
+        out
+    }
+}
+
+/// Detect which image protocol a sequence belongs to, if any.
+pub(crate) fn graphics_protocol(seq: &Sequence) -> Option<GraphicsProtocol> {
+    let body = seq.body();
+    match seq.kind {
+        SequenceKind::Dcs => {
+            // Sixel: optional numeric parameters followed by 'q'
+            let params_end = body
+                .iter()
+                .position(|b| !(b.is_ascii_digit() || *b == b';'))?;
+            (body[params_end] == b'q').then_some(GraphicsProtocol::Sixel)
+        }
+        SequenceKind::Apc => body.starts_with(b"G").then_some(GraphicsProtocol::Kitty),
+        SequenceKind::Osc => body
+            .starts_with(b"1337;File=")
+            .then_some(GraphicsProtocol::Iterm2),
+        SequenceKind::Csi => None,
+    }
+}
+
+/// Whether an image sequence puts something on screen (as opposed to
+/// uploading data, continuing a chunked transfer or querying support).
+pub(crate) fn is_displayed(protocol: GraphicsProtocol, seq: &Sequence) -> bool {
+    match protocol {
+        GraphicsProtocol::Sixel => true,
+        GraphicsProtocol::Kitty => {
+            let body = seq.body();
+            let control = body[1..].split(|&b| b == b';').next().unwrap_or_default();
+            control
+                .split(|&b| b == b',')
+                .any(|kv| kv == b"a=T" || kv == b"a=p")
+        }
+        GraphicsProtocol::Iterm2 => seq
+            .body()
+            .split(|&b| b == b';' || b == b':')
+            .any(|kv| kv.strip_prefix(b"File=").unwrap_or(kv) == b"inline=1"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sequences::SequenceScanner;
+
+    #[test]
+    fn test_detects_image_protocols() {
+        let mut scanner = SequenceScanner::new();
+        let seqs = scanner.feed(
+            b"\x1bP0;1;0q#0;2;0;0;0~~\x1b\\\
+              \x1b_Ga=T,f=100;AAAA\x1b\\\
+              \x1b_Gm=0;AAAA\x1b\\\
+              \x1b]1337;File=inline=1:AAAA\x07\
+              \x1b]0;title\x07",
+        );
+        let detected: Vec<_> = seqs.iter().map(graphics_protocol).collect();
+        assert_eq!(
+            detected,
+            vec![
+                Some(GraphicsProtocol::Sixel),
+                Some(GraphicsProtocol::Kitty),
+                Some(GraphicsProtocol::Kitty),
+                Some(GraphicsProtocol::Iterm2),
+                None,
+            ]
+        );
+        assert!(is_displayed(GraphicsProtocol::Kitty, &seqs[1]));
+        assert!(!is_displayed(GraphicsProtocol::Kitty, &seqs[2]));
+        assert!(is_displayed(GraphicsProtocol::Iterm2, &seqs[3]));
+    }
+
+    #[test]
+    fn test_host_bytes_positioning() {
+        let image = InlineImage {
+            protocol: GraphicsProtocol::Sixel,
+            row: 2,
+            col: 3,
+            data: b"DATA".to_vec(),
+        };
+        assert_eq!(image.to_host_bytes((10, 5)), b"\x1b7\x1b[8;14HDATA\x1b8");
+    }
+}
@@ -0,0 +1,210 @@
+//! Help overlay listing the keybindings of a [`KeyMap`].
+//!
+//! The overlay is generated from the keymap itself, so it always shows the
+//! bindings in effect. Bindings are grouped by
+//! [`ActionCategory`](crate::ActionCategory), in the order the categories
+//! are declared, and keep their binding order within a group.
+
+use std::collections::BTreeMap;
+
+use ratatui::buffer::Buffer;
+use ratatui::layout::Rect;
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::Span;
+use ratatui::widgets::{Block, Borders, Clear, Widget};
+
+use crate::actions::{ActionCategory, KeyMap};
+use crate::keys::key_name;
+
+/// Gap between the key column and the descriptions.
+const KEY_GAP: usize = 2;
+
+/// One row of the overlay.
+enum Row {
+    Heading(ActionCategory),
+    Binding(String, String),
+    Blank,
+}
+
+/// Overlay listing the keybindings of a keymap, grouped by category.
+pub struct HelpOverlay<'a> {
+    keymap: &'a KeyMap,
+    border_style: Style,
+    heading_style: Style,
+    key_style: Style,
+}
+
+impl<'a> HelpOverlay<'a> {
+    /// List the bindings of `keymap`.
+    #[must_use]
+    pub fn new(keymap: &'a KeyMap) -> Self {
+        Self {
+            keymap,
+            border_style: Style::default().fg(Color::Yellow),
+            heading_style: Style::default().add_modifier(Modifier::BOLD),
+            key_style: Style::default().fg(Color::Cyan),
+        }
+    }
+
+    /// Set the border style.
+    #[must_use]
+    pub fn border_style(mut self, style: Style) -> Self {
+        self.border_style = style;
+        self
+    }
+
+    /// Set the style of category headings.
+    #[must_use]
+    pub fn heading_style(mut self, style: Style) -> Self {
+        self.heading_style = style;
+        self
+    }
+
+    /// Set the style of key names.
+    #[must_use]
+    pub fn key_style(mut self, style: Style) -> Self {
+        self.key_style = style;
+        self
+    }
+
+    /// Area of the overlay: just large enough for the bindings, centered
+    /// and clamped to `terminal_area`.
+    #[must_use]
+    pub fn calculate_area(&self, terminal_area: Rect) -> Rect {
+        let rows = self.rows();
+        let key_width = key_width(&rows);
+        let content_width = rows
+            .iter()
+            .map(|row| match row {
+                Row::Heading(category) => category.name().len(),
+                Row::Binding(_, description) => {
+                    key_width + KEY_GAP + Span::raw(description).width()
+                }
+                Row::Blank => 0,
+            })
+            .max()
+            .unwrap_or(0);
+        // Borders, plus a space of padding on each side
+        let width = u16::try_from(content_width + 4)
+            .unwrap_or(u16::MAX)
+            .min(terminal_area.width);
+        let height = u16::try_from(rows.len() + 2)
+            .unwrap_or(u16::MAX)
+            .min(terminal_area.height);
+        Rect {
+            x: terminal_area.x + (terminal_area.width - width) / 2,
+            y: terminal_area.y + (terminal_area.height - height) / 2,
+            width,
+            height,
+        }
+    }
+
+    /// Rows of the overlay, with a blank row between categories.
+    fn rows(&self) -> Vec<Row> {
+        let mut groups: BTreeMap<ActionCategory, Vec<Row>> = BTreeMap::new();
+        for (key, action) in self.keymap.bindings() {
+            groups
+                .entry(action.category())
+                .or_default()
+                .push(Row::Binding(key_name(key), action.description()));
+        }
+        let mut rows = Vec::new();
+        for (category, bindings) in groups {
+            if !rows.is_empty() {
+                rows.push(Row::Blank);
+            }
+            rows.push(Row::Heading(category));
+            rows.extend(bindings);
+        }
+        rows
+    }
+}
+
+fn key_width(rows: &[Row]) -> usize {
+    rows.iter()
+        .map(|row| match row {
+            Row::Binding(key, _) => Span::raw(key).width(),
+            _ => 0,
+        })
+        .max()
+        .unwrap_or(0)
+}
+
+impl Widget for HelpOverlay<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        Clear.render(area, buf);
+        let block = Block::default()
+            .title(" Keybindings ")
+            .title_bottom(" Press any key to close ")
+            .borders(Borders::ALL)
+            .border_style(self.border_style);
+        let inner = block.inner(area);
+        block.render(area, buf);
+
+        let rows = self.rows();
+        let key_width = key_width(&rows);
+        let x = inner.x.saturating_add(1).min(inner.right());
+        let width = usize::from(inner.right() - x);
+        for (y, row) in (inner.y..inner.bottom()).zip(&rows) {
+            match row {
+                Row::Heading(category) => {
+                    buf.set_stringn(x, y, category.name(), width, self.heading_style);
+                }
+                Row::Binding(key, description) => {
+                    buf.set_stringn(x, y, key, width, self.key_style);
+                    let offset = key_width + KEY_GAP;
+                    if offset < width {
+                        let column = x + u16::try_from(offset).unwrap_or(u16::MAX);
+                        buf.set_stringn(column, y, description, width - offset, Style::default());
+                    }
+                }
+                Row::Blank => {}
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::actions::Action;
+    use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+    #[test]
+    fn test_help_overlay() {
+        let mut keymap = KeyMap::new();
+        keymap.bind(
+            KeyEvent::new(KeyCode::Char('n'), KeyModifiers::CONTROL),
+            Action::FocusNext,
+        );
+        keymap.bind(
+            KeyEvent::new(KeyCode::Char('q'), KeyModifiers::CONTROL),
+            Action::Quit,
+        );
+        keymap.bind(
+            KeyEvent::new(KeyCode::F(1), KeyModifiers::NONE),
+            Action::Help,
+        );
+
+        let overlay = HelpOverlay::new(&keymap);
+        let area = overlay.calculate_area(Rect::new(0, 0, 40, 20));
+        assert_eq!((area.width, area.height), (25, 8));
+        let mut buf = Buffer::empty(area);
+        overlay.render(area, &mut buf);
+        let row = |y| -> String {
+            (area.x..area.right())
+                .map(|x| buf[(x, y)].symbol())
+                .collect()
+        };
+
+        // General comes before Focus, whatever the binding order
+        let y = area.y;
+        assert_eq!(row(y + 1), "│ General               │");
+        assert_eq!(row(y + 2), "│ C-q  Quit             │");
+        assert_eq!(row(y + 3), "│ F1   Show keybindings │");
+        assert_eq!(row(y + 4), "│                       │");
+        assert_eq!(row(y + 5), "│ Focus                 │");
+        assert_eq!(row(y + 6), "│ C-n  Focus next pane  │");
+        assert_eq!(buf[(area.x + 2, y + 2)].fg, Color::Cyan);
+    }
+}
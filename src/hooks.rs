@@ -0,0 +1,179 @@
+//! Callbacks attached to pane lifecycle events.
+//!
+//! Hooks registered with `PaneManager::on` run on the manager's thread
+//! while it handles events (`spawn`, `poll_events`). Instead of touching
+//! the manager directly, a hook returns a [`HookAction`] that the manager
+//! applies once all hooks for the event have run.
+
+use std::collections::HashMap;
+
+use crate::pane::{PaneHandle, PaneId};
+use crate::transcript::Pattern;
+
+/// Unique identifier for a registered hook.
+#[derive(Clone, Copy, Debug, Hash, Eq, PartialEq)]
+pub struct HookId(pub u64);
+
+impl std::fmt::Display for HookId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// When a hook runs.
+#[derive(Clone, Debug)]
+pub enum HookKind {
+    /// A pane was spawned.
+    OnSpawn,
+    /// A pane's process exited.
+    OnExit,
+    /// A pane's process crashed or could not be monitored.
+    OnCrash,
+    /// A pane printed text matching the pattern. Matches are looked for
+    /// within lines of output, once per occurrence.
+    OnOutputMatch(Pattern),
+}
+
+/// The event a hook is called for.
+#[derive(Clone, Copy, Debug)]
+pub enum HookEvent<'a> {
+    /// The pane was spawned.
+    Spawned,
+    /// The pane's process exited.
+    Exited { code: i32 },
+    /// The pane's process crashed.
+    Crashed { signal: Option<i32>, error: &'a str },
+    /// The pane printed matching text.
+    OutputMatched { text: &'a str },
+}
+
+/// What the manager should do after a hook ran.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum HookAction {
+    /// Nothing.
+    #[default]
+    None,
+    /// Close the pane.
+    Close,
+    /// Close the pane and spawn a new one with the same configuration.
+    /// The new pane gets a new ID and is placed last in the layout.
+    Respawn,
+}
+
+/// Hook callback: receives the pane's handle and the triggering event.
+pub type HookFn = Box<dyn FnMut(&PaneHandle, &HookEvent<'_>) -> HookAction + Send>;
+
+struct Hook {
+    id: HookId,
+    kind: HookKind,
+    callback: HookFn,
+    /// Output already searched, per pane (output-match hooks only).
+    positions: HashMap<PaneId, u64>,
+}
+
+/// Registered hooks, in registration order.
+#[derive(Default)]
+pub(crate) struct HookRegistry {
+    hooks: Vec<Hook>,
+    next_id: u64,
+}
+
+impl HookRegistry {
+    /// Register a hook.
+    pub fn add(&mut self, kind: HookKind, callback: HookFn) -> HookId {
+        self.next_id += 1;
+        let id = HookId(self.next_id);
+        self.hooks.push(Hook {
+            id,
+            kind,
+            callback,
+            positions: HashMap::new(),
+        });
+        id
+    }
+
+    /// Remove a hook. Returns `false` if it was not registered.
+    pub fn remove(&mut self, id: HookId) -> bool {
+        let len = self.hooks.len();
+        self.hooks.retain(|hook| hook.id != id);
+        self.hooks.len() != len
+    }
+
+    /// Whether any output-match hooks are registered.
+    pub fn watches_output(&self) -> bool {
+        self.hooks
+            .iter()
+            .any(|hook| matches!(hook.kind, HookKind::OnOutputMatch(_)))
+    }
+
+    /// Run the lifecycle hooks matching `event`.
+    ///
+    /// Returns the strongest action requested (`Respawn` over `Close`).
+    pub fn dispatch(&mut self, handle: &PaneHandle, event: &HookEvent<'_>) -> HookAction {
+        let mut action = HookAction::None;
+        for hook in &mut self.hooks {
+            let applies = matches!(
+                (&hook.kind, event),
+                (HookKind::OnSpawn, HookEvent::Spawned)
+                    | (HookKind::OnExit, HookEvent::Exited { .. })
+                    | (HookKind::OnCrash, HookEvent::Crashed { .. })
+            );
+            if applies {
+                action = strongest(action, (hook.callback)(handle, event));
+            }
+        }
+        action
+    }
+
+    /// Run output-match hooks against the pane's output since they last
+    /// looked at it.
+    pub fn dispatch_output(&mut self, handle: &PaneHandle) -> HookAction {
+        let mut action = HookAction::None;
+        for hook in &mut self.hooks {
+            let HookKind::OnOutputMatch(pattern) = &hook.kind else {
+                continue;
+            };
+            let position = hook.positions.entry(handle.id()).or_insert(0);
+            let (start, text) = handle.output_since(*position);
+            let mut searched = 0;
+            while searched < text.len() {
+                let Some(range) = pattern.find_range(&text[searched..]) else {
+                    break;
+                };
+                let (match_start, match_end) = (searched + range.start, searched + range.end);
+                let event = HookEvent::OutputMatched {
+                    text: &text[match_start..match_end],
+                };
+                action = strongest(action, (hook.callback)(handle, &event));
+                // Step past empty matches
+                searched = if match_end > searched {
+                    match_end
+                } else {
+                    match_end + text[match_end..].chars().next().map_or(1, char::len_utf8)
+                };
+            }
+            // Keep the unfinished last line for the next search
+            let searched = searched.min(text.len());
+            let line_start = text[searched..]
+                .rfind('\n')
+                .map_or(searched, |i| searched + i + 1);
+            *position = start + line_start as u64;
+        }
+        action
+    }
+
+    /// Forget per-pane state of a closed pane.
+    pub fn forget(&mut self, pane_id: PaneId) {
+        for hook in &mut self.hooks {
+            hook.positions.remove(&pane_id);
+        }
+    }
+}
+
+fn strongest(a: HookAction, b: HookAction) -> HookAction {
+    match (a, b) {
+        (HookAction::Respawn, _) | (_, HookAction::Respawn) => HookAction::Respawn,
+        (HookAction::Close, _) | (_, HookAction::Close) => HookAction::Close,
+        _ => HookAction::None,
+    }
+}
@@ -0,0 +1,466 @@
+//! Key encoding - translates crossterm key events into PTY input bytes.
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+/// Terminal input modes requested by the program running in a pane.
+///
+/// These are tracked by the pane's terminal emulator and change how
+/// keys must be encoded before they are written to the PTY.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct InputModes {
+    /// Application cursor keys (DECCKM). When set, arrow keys and
+    /// Home/End are sent as SS3 sequences (`ESC O A`) instead of CSI.
+    pub application_cursor: bool,
+    /// Active kitty keyboard protocol flags pushed by the program (0 = none).
+    pub kitty_flags: u8,
+    /// xterm `modifyOtherKeys` level requested by the program (0 = off).
+    pub modify_other_keys: u8,
+}
+
+/// Extended keyboard protocol used when encoding modified keys.
+///
+/// Extended protocols are opt-in: a pane only receives extended encodings
+/// after its program has negotiated them. The focused pane's negotiated
+/// state is mirrored on the host terminal (see
+/// `PaneManager::take_passthrough`).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum KeyboardProtocol {
+    /// Traditional xterm-style encoding only.
+    #[default]
+    Legacy,
+    /// Kitty keyboard protocol (`CSI code ; modifiers u`).
+    Kitty,
+    /// xterm `modifyOtherKeys` (`CSI 27 ; modifiers ; code ~`).
+    ModifyOtherKeys,
+}
+
+/// Convert a key event to bytes to send to the PTY.
+pub(crate) fn key_to_bytes(
+    key: KeyEvent,
+    modes: InputModes,
+    protocol: KeyboardProtocol,
+) -> Vec<u8> {
+    if let Some(bytes) = extended_key(key, modes, protocol) {
+        return bytes;
+    }
+
+    let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+    let alt = key.modifiers.contains(KeyModifiers::ALT);
+    let modifiers = modifier_param(key.modifiers);
+
+    match key.code {
+        KeyCode::Char(c) => {
+            let mut bytes = if ctrl {
+                // Control characters (Ctrl+A = 0x01, etc.)
+                let code = c.to_ascii_lowercase();
+                if code.is_ascii_lowercase() {
+                    vec![code as u8 - b'a' + 1]
+                } else {
+                    return vec![];
+                }
+            } else {
+                c.to_string().into_bytes()
+            };
+            if alt {
+                // Alt sends ESC prefix
+                bytes.insert(0, 0x1b);
+            }
+            bytes
+        }
+        KeyCode::Enter => vec![b'\r'],
+        KeyCode::Tab => vec![b'\t'],
+        // ConPTY reads BS as Ctrl+Backspace (delete word) and DEL as Backspace
+        KeyCode::Backspace if ctrl && cfg!(windows) => vec![0x08],
+        KeyCode::Backspace => vec![0x7f],
+        KeyCode::Esc => vec![0x1b],
+        KeyCode::BackTab => b"\x1b[Z".to_vec(),
+        KeyCode::Up => cursor_key(b'A', modifiers, modes),
+        KeyCode::Down => cursor_key(b'B', modifiers, modes),
+        KeyCode::Right => cursor_key(b'C', modifiers, modes),
+        KeyCode::Left => cursor_key(b'D', modifiers, modes),
+        KeyCode::Home => cursor_key(b'H', modifiers, modes),
+        KeyCode::End => cursor_key(b'F', modifiers, modes),
+        KeyCode::PageUp => tilde_key(5, modifiers),
+        KeyCode::PageDown => tilde_key(6, modifiers),
+        KeyCode::Delete => tilde_key(3, modifiers),
+        KeyCode::Insert => tilde_key(2, modifiers),
+        KeyCode::F(n) => function_key(n, modifiers),
+        _ => vec![],
+    }
+}
+
+/// Parse a tmux-style key name such as `C-c`, `M-x`, `S-Tab`, `Enter`
+/// or `F5`.
+///
+/// Modifier prefixes are `C-` (Ctrl), `M-` (Alt) and `S-` (Shift); `^x`
+/// is short for `C-x`. Any single character stands for itself.
+pub(crate) fn parse_key(name: &str) -> Option<KeyEvent> {
+    let mut modifiers = KeyModifiers::NONE;
+    let mut rest = name;
+    loop {
+        let modifier = match rest.get(..2) {
+            Some("C-") => KeyModifiers::CONTROL,
+            Some("M-") => KeyModifiers::ALT,
+            Some("S-") => KeyModifiers::SHIFT,
+            _ => break,
+        };
+        if rest.len() == 2 {
+            // Nothing left to modify
+            break;
+        }
+        modifiers |= modifier;
+        rest = &rest[2..];
+    }
+    if let Some(c) = rest.strip_prefix('^').filter(|c| c.chars().count() == 1) {
+        modifiers |= KeyModifiers::CONTROL;
+        rest = c;
+    }
+
+    let mut chars = rest.chars();
+    let code = match (chars.next(), chars.next()) {
+        (Some(c), None) => KeyCode::Char(c),
+        _ => match rest.to_ascii_lowercase().as_str() {
+            "enter" => KeyCode::Enter,
+            "tab" if modifiers.contains(KeyModifiers::SHIFT) => {
+                modifiers -= KeyModifiers::SHIFT;
+                KeyCode::BackTab
+            }
+            "tab" => KeyCode::Tab,
+            "btab" => KeyCode::BackTab,
+            "escape" | "esc" => KeyCode::Esc,
+            "bspace" | "backspace" => KeyCode::Backspace,
+            "space" => KeyCode::Char(' '),
+            "up" => KeyCode::Up,
+            "down" => KeyCode::Down,
+            "left" => KeyCode::Left,
+            "right" => KeyCode::Right,
+            "home" => KeyCode::Home,
+            "end" => KeyCode::End,
+            "pageup" | "pgup" | "ppage" => KeyCode::PageUp,
+            "pagedown" | "pgdn" | "npage" => KeyCode::PageDown,
+            "insert" | "ic" => KeyCode::Insert,
+            "delete" | "dc" => KeyCode::Delete,
+            other => {
+                let n = other.strip_prefix('f')?.parse().ok()?;
+                if !(1..=12).contains(&n) {
+                    return None;
+                }
+                KeyCode::F(n)
+            }
+        },
+    };
+    Some(KeyEvent::new(code, modifiers))
+}
+
+/// Tmux-style name of a key, such as `C-q` or `M-S-Left`; the inverse of
+/// [`parse_key`] for the keys it knows.
+pub(crate) fn key_name(key: KeyEvent) -> String {
+    let mut prefix = String::new();
+    for (modifier, name) in [
+        (KeyModifiers::CONTROL, "C-"),
+        (KeyModifiers::ALT, "M-"),
+        (KeyModifiers::SHIFT, "S-"),
+    ] {
+        if key.modifiers.contains(modifier) {
+            prefix.push_str(name);
+        }
+    }
+    let code = match key.code {
+        KeyCode::Char(' ') => "Space".to_string(),
+        KeyCode::Char(c) => c.to_string(),
+        KeyCode::F(n) => format!("F{n}"),
+        KeyCode::Enter => "Enter".to_string(),
+        KeyCode::Tab => "Tab".to_string(),
+        KeyCode::BackTab => "BTab".to_string(),
+        KeyCode::Esc => "Escape".to_string(),
+        KeyCode::Backspace => "BSpace".to_string(),
+        KeyCode::Up => "Up".to_string(),
+        KeyCode::Down => "Down".to_string(),
+        KeyCode::Left => "Left".to_string(),
+        KeyCode::Right => "Right".to_string(),
+        KeyCode::Home => "Home".to_string(),
+        KeyCode::End => "End".to_string(),
+        KeyCode::PageUp => "PageUp".to_string(),
+        KeyCode::PageDown => "PageDown".to_string(),
+        KeyCode::Insert => "Insert".to_string(),
+        KeyCode::Delete => "Delete".to_string(),
+        other => format!("{other:?}"),
+    };
+    prefix + &code
+}
+
+/// Encode a key using the negotiated extended protocol, if any applies.
+///
+/// Only keys that carry modifiers beyond Shift (or keys that legacy
+/// encoding cannot distinguish, such as Esc under kitty) are affected.
+fn extended_key(key: KeyEvent, modes: InputModes, protocol: KeyboardProtocol) -> Option<Vec<u8>> {
+    let code = match key.code {
+        KeyCode::Char(c) => u32::from(c.to_ascii_lowercase()),
+        KeyCode::Enter => 13,
+        KeyCode::Tab | KeyCode::BackTab => 9,
+        KeyCode::Backspace => 127,
+        KeyCode::Esc => 27,
+        _ => return None,
+    };
+    let modifiers = modifier_param(key.modifiers);
+    // Shift alone is already expressed by the character itself
+    let has_modifiers = if matches!(key.code, KeyCode::Char(_)) {
+        !(key.modifiers - KeyModifiers::SHIFT).is_empty()
+    } else {
+        !key.modifiers.is_empty()
+    };
+
+    match protocol {
+        KeyboardProtocol::Legacy => None,
+        KeyboardProtocol::Kitty => {
+            if modes.kitty_flags == 0 {
+                return None;
+            }
+            if has_modifiers {
+                Some(format!("\x1b[{code};{modifiers}u").into_bytes())
+            } else if key.code == KeyCode::Esc {
+                // "Disambiguate" mode reports Esc unambiguously
+                Some(b"\x1b[27u".to_vec())
+            } else {
+                None
+            }
+        }
+        KeyboardProtocol::ModifyOtherKeys => {
+            // Level 1 only covers keys without a well-known legacy encoding
+            let ambiguous = key.modifiers.contains(KeyModifiers::SUPER)
+                || (key.modifiers.contains(KeyModifiers::CONTROL)
+                    && !matches!(key.code, KeyCode::Char(c) if c.is_ascii_lowercase()));
+            let applies = match modes.modify_other_keys {
+                0 => false,
+                1 => has_modifiers && ambiguous,
+                _ => has_modifiers,
+            };
+            applies.then(|| format!("\x1b[27;{modifiers};{code}~").into_bytes())
+        }
+    }
+}
+
+/// Compute the xterm/kitty modifier parameter (1 + modifier bits).
+fn modifier_param(modifiers: KeyModifiers) -> u8 {
+    let mut bits = 0;
+    if modifiers.contains(KeyModifiers::SHIFT) {
+        bits |= 1;
+    }
+    if modifiers.contains(KeyModifiers::ALT) {
+        bits |= 2;
+    }
+    if modifiers.contains(KeyModifiers::CONTROL) {
+        bits |= 4;
+    }
+    if modifiers.contains(KeyModifiers::SUPER) {
+        bits |= 8;
+    }
+    if modifiers.contains(KeyModifiers::HYPER) {
+        bits |= 16;
+    }
+    if modifiers.contains(KeyModifiers::META) {
+        bits |= 32;
+    }
+    1 + bits
+}
+
+/// Encode a cursor key (arrows, Home, End) honoring DECCKM.
+///
+/// Modified keys always use the `CSI 1 ; <mod> X` form, as xterm does.
+fn cursor_key(final_byte: u8, modifiers: u8, modes: InputModes) -> Vec<u8> {
+    if modifiers > 1 {
+        return format!("\x1b[1;{modifiers}{}", char::from(final_byte)).into_bytes();
+    }
+    let introducer = if modes.application_cursor { b'O' } else { b'[' };
+    vec![0x1b, introducer, final_byte]
+}
+
+/// Encode a `CSI <code> ~` key (Page Up, Delete, F5+, ...) with modifiers.
+fn tilde_key(code: u8, modifiers: u8) -> Vec<u8> {
+    if modifiers > 1 {
+        format!("\x1b[{code};{modifiers}~").into_bytes()
+    } else {
+        format!("\x1b[{code}~").into_bytes()
+    }
+}
+
+/// Encode a function key with modifiers.
+///
+/// F1-F4 use SS3 (`ESC O P`) unmodified and `CSI 1 ; <mod> P` when modified.
+fn function_key(n: u8, modifiers: u8) -> Vec<u8> {
+    let code = match n {
+        1..=4 => {
+            let final_byte = char::from(b'P' + (n - 1));
+            return if modifiers > 1 {
+                format!("\x1b[1;{modifiers}{final_byte}").into_bytes()
+            } else {
+                format!("\x1bO{final_byte}").into_bytes()
+            };
+        }
+        5 => 15,
+        6 => 17,
+        7 => 18,
+        8 => 19,
+        9 => 20,
+        10 => 21,
+        11 => 23,
+        12 => 24,
+        _ => return vec![],
+    };
+    tilde_key(code, modifiers)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(code: KeyCode) -> KeyEvent {
+        KeyEvent::new(code, KeyModifiers::NONE)
+    }
+
+    #[test]
+    fn test_parse_key_names() {
+        let parse = |name| parse_key(name).map(|k| (k.code, k.modifiers));
+        assert_eq!(
+            parse("C-c"),
+            Some((KeyCode::Char('c'), KeyModifiers::CONTROL))
+        );
+        assert_eq!(
+            parse("C-M-x"),
+            Some((
+                KeyCode::Char('x'),
+                KeyModifiers::CONTROL | KeyModifiers::ALT
+            ))
+        );
+        assert_eq!(
+            parse("^d"),
+            Some((KeyCode::Char('d'), KeyModifiers::CONTROL))
+        );
+        assert_eq!(parse("S-Tab"), Some((KeyCode::BackTab, KeyModifiers::NONE)));
+        assert_eq!(parse("enter"), Some((KeyCode::Enter, KeyModifiers::NONE)));
+        assert_eq!(parse("F12"), Some((KeyCode::F(12), KeyModifiers::NONE)));
+        assert_eq!(parse("é"), Some((KeyCode::Char('é'), KeyModifiers::NONE)));
+        assert_eq!(parse("F13"), None);
+        assert_eq!(parse("hello"), None);
+    }
+
+    #[test]
+    fn test_key_names() {
+        for name in ["C-q", "M-S-Left", "F5", "BTab", "C-Space", "é"] {
+            assert_eq!(key_name(parse_key(name).unwrap()), name);
+        }
+    }
+
+    #[test]
+    fn test_alt_non_ascii() {
+        let key = KeyEvent::new(KeyCode::Char('é'), KeyModifiers::ALT);
+        let modes = InputModes::default();
+        assert_eq!(
+            key_to_bytes(key, modes, KeyboardProtocol::Legacy),
+            "\x1bé".as_bytes()
+        );
+    }
+
+    #[test]
+    fn test_arrows_normal_mode() {
+        let modes = InputModes::default();
+        assert_eq!(
+            key_to_bytes(key(KeyCode::Up), modes, KeyboardProtocol::Legacy),
+            b"\x1b[A"
+        );
+        assert_eq!(
+            key_to_bytes(key(KeyCode::Left), modes, KeyboardProtocol::Legacy),
+            b"\x1b[D"
+        );
+        assert_eq!(
+            key_to_bytes(key(KeyCode::Home), modes, KeyboardProtocol::Legacy),
+            b"\x1b[H"
+        );
+    }
+
+    #[test]
+    fn test_arrows_application_cursor_mode() {
+        let modes = InputModes {
+            application_cursor: true,
+            ..InputModes::default()
+        };
+        assert_eq!(
+            key_to_bytes(key(KeyCode::Up), modes, KeyboardProtocol::Legacy),
+            b"\x1bOA"
+        );
+        assert_eq!(
+            key_to_bytes(key(KeyCode::Right), modes, KeyboardProtocol::Legacy),
+            b"\x1bOC"
+        );
+        assert_eq!(
+            key_to_bytes(key(KeyCode::End), modes, KeyboardProtocol::Legacy),
+            b"\x1bOF"
+        );
+        // Keys outside the cursor set are unaffected
+        assert_eq!(
+            key_to_bytes(key(KeyCode::PageUp), modes, KeyboardProtocol::Legacy),
+            b"\x1b[5~"
+        );
+    }
+
+    #[test]
+    fn test_kitty_encoding_requires_negotiation() {
+        let ctrl_shift_a = KeyEvent::new(
+            KeyCode::Char('A'),
+            KeyModifiers::CONTROL | KeyModifiers::SHIFT,
+        );
+        let legacy = key_to_bytes(ctrl_shift_a, InputModes::default(), KeyboardProtocol::Kitty);
+        assert_eq!(legacy, vec![0x01]);
+
+        let modes = InputModes {
+            kitty_flags: 1,
+            ..InputModes::default()
+        };
+        let kitty = key_to_bytes(ctrl_shift_a, modes, KeyboardProtocol::Kitty);
+        assert_eq!(kitty, b"\x1b[97;6u");
+        // Unmodified text is still sent as-is
+        assert_eq!(
+            key_to_bytes(key(KeyCode::Char('x')), modes, KeyboardProtocol::Kitty),
+            b"x"
+        );
+    }
+
+    #[test]
+    fn test_modify_other_keys_encoding() {
+        let modes = InputModes {
+            modify_other_keys: 1,
+            ..InputModes::default()
+        };
+        let ctrl_a = KeyEvent::new(KeyCode::Char('a'), KeyModifiers::CONTROL);
+        let ctrl_1 = KeyEvent::new(KeyCode::Char('1'), KeyModifiers::CONTROL);
+        let protocol = KeyboardProtocol::ModifyOtherKeys;
+        // Level 1 keeps well-known control characters
+        assert_eq!(key_to_bytes(ctrl_a, modes, protocol), vec![0x01]);
+        assert_eq!(key_to_bytes(ctrl_1, modes, protocol), b"\x1b[27;5;49~");
+    }
+
+    #[test]
+    fn test_modified_navigation_keys() {
+        let modes = InputModes {
+            application_cursor: true,
+            ..InputModes::default()
+        };
+        let encode = |code, modifiers| {
+            key_to_bytes(
+                KeyEvent::new(code, modifiers),
+                modes,
+                KeyboardProtocol::Legacy,
+            )
+        };
+        assert_eq!(encode(KeyCode::Right, KeyModifiers::CONTROL), b"\x1b[1;5C");
+        assert_eq!(encode(KeyCode::Up, KeyModifiers::SHIFT), b"\x1b[1;2A");
+        assert_eq!(encode(KeyCode::PageDown, KeyModifiers::ALT), b"\x1b[6;3~");
+        assert_eq!(encode(KeyCode::F(1), KeyModifiers::NONE), b"\x1bOP");
+        assert_eq!(encode(KeyCode::F(2), KeyModifiers::CONTROL), b"\x1b[1;5Q");
+        assert_eq!(
+            encode(KeyCode::F(5), KeyModifiers::CONTROL | KeyModifiers::SHIFT),
+            b"\x1b[15;6~"
+        );
+    }
+}
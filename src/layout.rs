@@ -4,14 +4,19 @@ use std::collections::HashMap;
 
 use ratatui::layout::Rect;
 
+use crate::error::{Error, Result};
 use crate::pane::PaneId;
 
+/// Label prefixes of the pane positions (110, 120, 210, 220).
+const POSITION_PREFIXES: [u16; 4] = [11, 12, 21, 22];
+
 /// Split direction for layouts.
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum Direction {
     /// Split horizontally (top and bottom).
     Horizontal,
     /// Split vertically (left and right).
+    #[allow(dead_code)]
     Vertical,
 }
 
@@ -54,6 +59,7 @@ impl Layout {
 
     /// Create a vertical split (left and right).
     #[must_use]
+    #[allow(dead_code)]
     pub fn vsplit(ratio: f32, first: Layout, second: Layout) -> Self {
         Self::Split {
             direction: Direction::Vertical,
@@ -71,18 +77,21 @@ impl Layout {
 
     /// Create an equal vertical split.
     #[must_use]
+    #[allow(dead_code)]
     pub fn vsplit_equal(first: Layout, second: Layout) -> Self {
         Self::vsplit(0.5, first, second)
     }
 
     /// Get all pane IDs in this layout.
     #[must_use]
+    #[allow(dead_code)]
     pub fn pane_ids(&self) -> Vec<PaneId> {
         let mut ids = Vec::new();
         self.collect_pane_ids(&mut ids);
         ids
     }
 
+    #[allow(dead_code)]
     fn collect_pane_ids(&self, ids: &mut Vec<PaneId>) {
         match self {
             Self::Single(id) => ids.push(*id),
@@ -95,6 +104,7 @@ impl Layout {
 
     /// Check if a pane ID is in this layout.
     #[must_use]
+    #[allow(dead_code)]
     pub fn contains(&self, pane_id: PaneId) -> bool {
         match self {
             Self::Single(id) => *id == pane_id,
@@ -135,6 +145,7 @@ impl LayoutCalculator {
         }
     }
 
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
     fn split_area(area: Rect, direction: Direction, ratio: f32) -> (Rect, Rect) {
         match direction {
             Direction::Horizontal => {
@@ -179,6 +190,139 @@ impl LayoutCalculator {
     }
 }
 
+/// How the area below each of the four pane positions is divided into
+/// sub-panes.
+///
+/// Each position gets a list of relative widths, one per sub-pane, from
+/// left to right. Sub-panes are labelled after their position: the
+/// sub-panes of pane 120 are 121, 122, and so on. The default gives every
+/// position two equal sub-panes.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SubPaneArrangement {
+    positions: [Vec<u16>; 4],
+}
+
+impl Default for SubPaneArrangement {
+    fn default() -> Self {
+        Self::uniform(2)
+    }
+}
+
+impl SubPaneArrangement {
+    /// `count` equal sub-panes below every position.
+    #[must_use]
+    pub fn uniform(count: usize) -> Self {
+        Self {
+            positions: std::array::from_fn(|_| vec![1; count]),
+        }
+    }
+
+    /// Divide one position (0-3, left to right) by relative widths, e.g.
+    /// `vec![2, 1]` for a wide and a narrow sub-pane, or `vec![]` for none.
+    #[must_use]
+    pub fn position(mut self, position: usize, widths: Vec<u16>) -> Self {
+        if let Some(slot) = self.positions.get_mut(position) {
+            *slot = widths;
+        }
+        self
+    }
+
+    /// Number of sub-panes below a position.
+    #[must_use]
+    pub fn count(&self, position: usize) -> usize {
+        self.positions.get(position).map_or(0, Vec::len)
+    }
+
+    /// Total number of sub-panes.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.positions.iter().map(Vec::len).sum()
+    }
+
+    /// Whether no position has sub-panes.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Label of the sub-pane at `index` in layout order, e.g. 112.
+    #[must_use]
+    pub fn label(&self, index: usize) -> Option<u16> {
+        let (position, local) = self.locate(index)?;
+        Some(POSITION_PREFIXES[position] * 10 + u16::try_from(local).ok()? + 1)
+    }
+
+    /// Check that every sub-pane has a width and a one-digit label.
+    pub(crate) fn validate(&self) -> Result<()> {
+        for (position, widths) in self.positions.iter().enumerate() {
+            if widths.len() > 9 {
+                return Err(Error::Config(format!(
+                    "position {position} has {} sub-panes, at most 9 are allowed",
+                    widths.len()
+                )));
+            }
+            if widths.contains(&0) {
+                return Err(Error::Config(format!(
+                    "sub-pane widths of position {position} must not be zero"
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// Index in layout order of sub-pane `local` of a position.
+    pub(crate) fn index(&self, position: usize, local: usize) -> Option<usize> {
+        (local < self.count(position))
+            .then(|| (0..position).map(|p| self.count(p)).sum::<usize>() + local)
+    }
+
+    /// Position and index within it of the sub-pane at `index`.
+    pub(crate) fn locate(&self, mut index: usize) -> Option<(usize, usize)> {
+        for (position, widths) in self.positions.iter().enumerate() {
+            if index < widths.len() {
+                return Some((position, index));
+            }
+            index -= widths.len();
+        }
+        None
+    }
+
+    /// Index in layout order of the sub-pane with the given label.
+    pub(crate) fn index_of_label(&self, label: u16) -> Option<usize> {
+        let position = POSITION_PREFIXES
+            .iter()
+            .position(|prefix| *prefix == label / 10)?;
+        let local = usize::from(label % 10).checked_sub(1)?;
+        self.index(position, local)
+    }
+
+    /// Split the column below a position into its sub-panes.
+    #[allow(clippy::cast_possible_truncation)]
+    pub(crate) fn split(&self, position: usize, column: Rect) -> Vec<Rect> {
+        let widths = &self.positions[position];
+        let total: u32 = widths.iter().map(|w| u32::from(*w)).sum();
+        let mut x = column.x;
+        let mut used = 0;
+        widths
+            .iter()
+            .enumerate()
+            .map(|(i, weight)| {
+                // The last sub-pane takes up the rounding remainder
+                let width = if i + 1 == widths.len() {
+                    column.width - used
+                } else {
+                    (u32::from(column.width) * u32::from(*weight) / total) as u16
+                };
+                let area = Rect { x, width, ..column };
+                x += width;
+                used += width;
+                area
+            })
+            .collect()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -254,4 +398,32 @@ mod tests {
         assert!(layout.contains(pane2));
         assert!(!layout.contains(pane3));
     }
+
+    #[test]
+    fn test_sub_pane_arrangement() {
+        let arrangement = SubPaneArrangement::default()
+            .position(1, vec![])
+            .position(3, vec![2, 1, 1]);
+        assert_eq!(arrangement.len(), 7);
+        assert_eq!(arrangement.label(0), Some(111));
+        assert_eq!(arrangement.label(2), Some(211));
+        assert_eq!(arrangement.label(6), Some(223));
+        assert_eq!(arrangement.label(7), None);
+        assert_eq!(arrangement.index_of_label(212), Some(3));
+        assert_eq!(arrangement.index_of_label(121), None);
+        assert_eq!(arrangement.index_of_label(110), None);
+        assert_eq!(arrangement.locate(5), Some((3, 1)));
+
+        let areas = arrangement.split(3, Rect::new(10, 5, 21, 4));
+        assert_eq!(areas[0], Rect::new(10, 5, 10, 4));
+        assert_eq!(areas[1], Rect::new(20, 5, 5, 4));
+        assert_eq!(areas[2], Rect::new(25, 5, 6, 4));
+
+        assert!(arrangement.validate().is_ok());
+        assert!(SubPaneArrangement::uniform(10).validate().is_err());
+        assert!(SubPaneArrangement::default()
+            .position(0, vec![1, 0])
+            .validate()
+            .is_err());
+    }
 }
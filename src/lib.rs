@@ -44,29 +44,104 @@
 #![warn(clippy::pedantic)]
 #![allow(clippy::module_name_repetitions)]
 
+mod actions;
+mod actor;
 mod arrows;
+mod border;
+mod cmdline;
+mod color;
+mod dialog;
+mod display_panes;
+mod emulator;
 mod error;
+mod events;
+mod frame;
+mod graphics;
+mod help;
+mod hooks;
+mod keys;
 mod layout;
 mod manager;
+#[cfg(any(test, feature = "mock-pty"))]
+mod mock;
+mod output;
+mod palette;
 mod pane;
 mod plugins;
+mod profiles;
 mod pty;
+#[cfg(feature = "resource-usage")]
+mod resources;
+pub mod runtime;
+mod search;
+mod selection;
+mod sequences;
+mod stats;
 mod status_bar;
+mod tab_bar;
+mod task;
+#[cfg(any(test, feature = "testing"))]
+pub mod testing;
+mod transcript;
 mod widget;
 
 // Re-export public API
-pub use arrows::{ArrowPosition, HorizontalArrowPosition};
+pub use actions::{Action, ActionCategory, Direction, KeyMap};
+pub use actor::PaneManagerHandle;
+pub use arrows::{
+    ArrowDirection, ArrowPlacement, ArrowPosition, ArrowTheme, Glyph, HorizontalArrowPosition,
+    NavigationRegion, NavigationTarget,
+};
+pub use border::{BorderSet, BorderTheme};
+pub use color::ColorMode;
+pub use dialog::{
+    InputDialog, InputDialogState, InputResult, Progress, ProgressDialog, ProgressHandle,
+    SelectDialog, SelectDialogState, SelectResult,
+};
+pub use display_panes::PaneNumbers;
 pub use error::{Error, Result};
-pub use manager::{ManagerConfig, PaneManager};
+pub use events::OverflowPolicy;
+pub use frame::{CockpitFrame, FrameAreas, Theme};
+pub use graphics::{GraphicsProtocol, InlineImage};
+pub use help::HelpOverlay;
+pub use hooks::{HookAction, HookEvent, HookFn, HookId, HookKind};
+pub use keys::{InputModes, KeyboardProtocol};
+pub use layout::SubPaneArrangement;
+pub use manager::{ManagerConfig, ManagerConfigBuilder, PaneManager, SpawnFocus};
+#[cfg(any(test, feature = "mock-pty"))]
+pub use mock::{MockBackend, MockPty};
+pub use palette::{CommandPalette, CommandPaletteState};
 pub use pane::{
-    PaneHandle, PaneId, PaneSize, PaneState, ScreenCell, ScreenColor, ScreenSnapshot, SpawnConfig,
+    CursorConfig, CursorShape, CursorStyle, ExitBehavior, PaneColors, PaneHandle, PaneId, PaneSize,
+    PaneState, ScreenCell, ScreenColor, ScreenSnapshot, Signal, Slot, SpawnConfig, UnderlineStyle,
+    UnfocusedCursor,
 };
+#[cfg(feature = "battery")]
+pub use plugins::BatteryPlugin;
+#[cfg(feature = "clock")]
+pub use plugins::ClockPlugin;
 pub use plugins::{
-    GitUserPlugin, Plugin, PluginConfig, PluginContext, PluginError, PluginId, PluginRegistry,
-    PluginResult,
+    CommandPlugin, DockerPlugin, GitBranchPlugin, GitUserPlugin, HostPlugin, InputMode,
+    KeyHintPlugin, PaneView, Plugin, PluginAction, PluginConfig, PluginContext, PluginError,
+    PluginId, PluginRegistry, PluginResult,
 };
+#[cfg(feature = "resource-usage")]
+pub use plugins::{StatThresholds, SystemStatsPlugin};
+pub use profiles::ProfileRegistry;
 pub use pty::PaneEvent;
-pub use status_bar::{StatusBarConfig, StatusBarSegment, StatusBarWidget, STATUS_BAR_HEIGHT};
+#[cfg(feature = "resource-usage")]
+pub use resources::ResourceUsage;
+pub use search::{Search, SearchMatch};
+pub use selection::{Selection, SelectionMode};
+pub use stats::{ManagerStats, PaneStats};
+pub use status_bar::{
+    PowerlineSeparators, StatusBarConfig, StatusBarPlacement, StatusBarSegment, StatusBarWidget,
+    STATUS_BAR_HEIGHT,
+};
+pub use tab_bar::{TabBarConfig, TabBarWidget, WorkspaceTab, TAB_BAR_HEIGHT};
+pub use transcript::Pattern;
 pub use widget::{
-    CockpitWidget, ConfirmDialog, DialogButton, DialogState, PaneWidget, SubPaneWidget,
+    ActivityIndicator, CockpitState, CockpitWidget, ConfirmDialog, DialogButton, DialogChoice,
+    DialogState, DimMode, ExitOverlay, PaneScrollbar, PaneTitle, PaneWidget, SearchHighlight,
+    SubPaneWidget,
 };
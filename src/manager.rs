@@ -1,30 +1,122 @@
 //! Pane manager - central orchestrator for all panes.
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::future::Future;
 use std::path::PathBuf;
 use std::sync::atomic::{AtomicU64, Ordering};
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 
-use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use crossterm::event::{KeyCode, KeyEvent, KeyEventKind, MouseButton, MouseEvent, MouseEventKind};
 use ratatui::layout::Rect;
-use tokio::sync::{mpsc, RwLock};
-use tokio::task::JoinHandle;
-
-use crate::arrows::{down_arrow_at_position, horizontal_arrow_at_position, up_arrow_at_position};
+use ratatui::widgets::Padding;
+use ratatui::Frame;
+use tokio::sync::RwLock;
+
+use crate::actions::Direction;
+use crate::actor::PaneManagerHandle;
+use crate::arrows::{NavigationLayout, NavigationRegion, NavigationTarget};
+use crate::color::ColorMode;
 use crate::error::{Error, Result};
-use crate::layout::{Layout, LayoutCalculator};
-use crate::pane::{PaneHandle, PaneId, PaneSize, SpawnConfig};
-use crate::plugins::{Plugin, PluginId, PluginRegistry, PluginResult};
-use crate::pty::{self, PaneEvent, SpawnedPty};
-use crate::status_bar::StatusBarSegment;
+use crate::events::{event_queue, EventReceiver, EventSender, OverflowPolicy};
+use crate::frame::{self, CockpitFrame, FrameAreas, Theme};
+use crate::hooks::{HookAction, HookEvent, HookId, HookKind, HookRegistry};
+use crate::keys::{key_to_bytes, KeyboardProtocol};
+use crate::layout::{Layout, LayoutCalculator, SubPaneArrangement};
+#[cfg(any(test, feature = "mock-pty"))]
+use crate::mock::MockBackend;
+use crate::output::OutputProcessor;
+use crate::pane::{ExitBehavior, PaneHandle, PaneId, PaneSize, PaneState, Slot, SpawnConfig};
+use crate::plugins::{
+    InputMode, PaneView, Plugin, PluginAction, PluginId, PluginRegistry, PluginResult,
+};
+use crate::profiles::ProfileRegistry;
+use crate::pty::{self, PaneEvent, PtyBackend, PtyMaster, SpawnedPty};
+#[cfg(feature = "resource-usage")]
+use crate::resources::ResourceMonitor;
+use crate::selection::{osc52, selection_row, ClickCounter, Selection, SelectionMode};
+use crate::sequences::{KeyboardState, TrackingOptions};
+use crate::stats::ManagerStats;
+use crate::status_bar::{StatusBarConfig, StatusBarSegment};
+use crate::tab_bar::WorkspaceTab;
+use crate::task::{self, TaskHandle};
+use crate::widget::{scrollbar_offset, CockpitState};
+
+/// Smallest width or height, borders included, that resizing leaves a pane.
+const MIN_PANE_SIZE: u16 = 3;
+
+/// How long a blinking cursor is shown, then hidden.
+const CURSOR_BLINK_INTERVAL: Duration = Duration::from_millis(500);
 
 /// Configuration for the pane manager.
 #[derive(Clone, Debug)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
 pub struct ManagerConfig {
     /// Maximum number of panes.
     pub max_panes: usize,
     /// Default scrollback buffer size.
     pub scrollback_lines: usize,
+    /// Extended keyboard protocol panes may negotiate (opt-in).
+    pub keyboard_protocol: KeyboardProtocol,
+    /// Forward the focused pane's cursor shape (DECSCUSR) to the host terminal.
+    pub forward_cursor_shape: bool,
+    /// Color depth of the host terminal, used when rendering panes.
+    pub color_mode: ColorMode,
+    /// Forward inline images (sixel, kitty, iTerm2) to the host terminal.
+    /// When disabled, images are shown as a placeholder in the pane.
+    pub graphics_passthrough: bool,
+    /// Maximum number of pane events queued between `poll_events` calls.
+    pub event_capacity: usize,
+    /// What to do when the event queue is full.
+    pub overflow_policy: OverflowPolicy,
+    /// Styles used by [`render`](PaneManager::render).
+    pub theme: Theme,
+    /// Share of the height given to panes; sub-panes get the rest.
+    pub sub_pane_ratio: f32,
+    /// How many sub-panes each pane position gets, and their widths.
+    pub sub_panes: SubPaneArrangement,
+    /// Shell for panes spawned without a command. `None` uses `$SHELL`,
+    /// falling back to `/bin/sh`; on Windows it prefers PowerShell 7, then
+    /// `%ComSpec%`.
+    pub default_shell: Option<String>,
+    /// Whether newly spawned panes take focus.
+    pub spawn_focus: SpawnFocus,
+    /// `TERM` for spawned panes, describing cockpit's emulator rather than
+    /// the host terminal. `None` inherits the host's value. A `TERM` set
+    /// on the [`SpawnConfig`] takes precedence.
+    pub term: Option<String>,
+    /// `COLORTERM` for spawned panes. `None` inherits the host's value. A
+    /// `COLORTERM` set on the [`SpawnConfig`] takes precedence.
+    pub colorterm: Option<String>,
+    /// Copy text selected with the mouse to the host terminal's clipboard
+    /// (OSC 52).
+    pub copy_on_select: bool,
+    /// How often [`PaneHandle::resource_usage`] re-reads the process table.
+    #[cfg(feature = "resource-usage")]
+    pub resource_interval: Duration,
+    /// What to do when a pane's process exits, unless its [`SpawnConfig`]
+    /// says otherwise. Kept panes show their final output.
+    pub on_exit: ExitBehavior,
+    /// How long [`display_panes`](PaneManager::display_panes) shows pane
+    /// numbers.
+    pub display_panes_time: Duration,
+}
+
+/// Whether a newly spawned pane takes focus.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum SpawnFocus {
+    /// Only when no pane is focused, e.g. for the first pane.
+    #[default]
+    IfNone,
+    /// Always.
+    Always,
+    /// Never; focus only moves on request.
+    Never,
 }
 
 impl Default for ManagerConfig {
@@ -32,7 +124,225 @@ impl Default for ManagerConfig {
         Self {
             max_panes: 4,
             scrollback_lines: 10_000,
+            keyboard_protocol: KeyboardProtocol::Legacy,
+            forward_cursor_shape: false,
+            color_mode: ColorMode::TrueColor,
+            graphics_passthrough: false,
+            event_capacity: 256,
+            overflow_policy: OverflowPolicy::Block,
+            theme: Theme::default(),
+            sub_pane_ratio: 0.7,
+            sub_panes: SubPaneArrangement::default(),
+            default_shell: None,
+            spawn_focus: SpawnFocus::IfNone,
+            term: Some("xterm-256color".to_string()),
+            colorterm: Some("truecolor".to_string()),
+            copy_on_select: true,
+            #[cfg(feature = "resource-usage")]
+            resource_interval: Duration::from_secs(2),
+            on_exit: ExitBehavior::KeepPane,
+            display_panes_time: Duration::from_secs(2),
+        }
+    }
+}
+
+impl ManagerConfig {
+    /// Start building a configuration from the defaults.
+    #[must_use]
+    pub fn builder() -> ManagerConfigBuilder {
+        ManagerConfigBuilder::default()
+    }
+
+    /// Check that the configuration is usable.
+    ///
+    /// # Errors
+    /// Returns [`Error::Config`] describing the first invalid setting.
+    pub fn validate(&self) -> Result<()> {
+        if !(1..=4).contains(&self.max_panes) {
+            return Err(Error::Config(format!(
+                "max_panes must be between 1 and 4, got {}",
+                self.max_panes
+            )));
+        }
+        if self.event_capacity == 0 {
+            return Err(Error::Config("event_capacity must be at least 1".into()));
         }
+        if !(self.sub_pane_ratio > 0.0 && self.sub_pane_ratio <= 1.0) {
+            return Err(Error::Config(format!(
+                "sub_pane_ratio must be in (0, 1], got {}",
+                self.sub_pane_ratio
+            )));
+        }
+        self.sub_panes.validate()?;
+        if self.default_shell.as_deref().is_some_and(str::is_empty) {
+            return Err(Error::Config("default_shell must not be empty".into()));
+        }
+        #[cfg(feature = "resource-usage")]
+        if self.resource_interval.is_zero() {
+            return Err(Error::Config("resource_interval must not be zero".into()));
+        }
+        Ok(())
+    }
+}
+
+/// Builder for a validated [`ManagerConfig`].
+#[derive(Clone, Debug, Default)]
+pub struct ManagerConfigBuilder {
+    config: ManagerConfig,
+}
+
+impl ManagerConfigBuilder {
+    /// Maximum number of panes, from 1 to 4.
+    #[must_use]
+    pub fn max_panes(mut self, max: usize) -> Self {
+        self.config.max_panes = max;
+        self
+    }
+
+    /// Default scrollback buffer size in lines.
+    #[must_use]
+    pub fn scrollback_lines(mut self, lines: usize) -> Self {
+        self.config.scrollback_lines = lines;
+        self
+    }
+
+    /// Extended keyboard protocol panes may negotiate.
+    #[must_use]
+    pub fn keyboard_protocol(mut self, protocol: KeyboardProtocol) -> Self {
+        self.config.keyboard_protocol = protocol;
+        self
+    }
+
+    /// Forward the focused pane's cursor shape to the host terminal.
+    #[must_use]
+    pub fn forward_cursor_shape(mut self, forward: bool) -> Self {
+        self.config.forward_cursor_shape = forward;
+        self
+    }
+
+    /// Color depth of the host terminal.
+    #[must_use]
+    pub fn color_mode(mut self, mode: ColorMode) -> Self {
+        self.config.color_mode = mode;
+        self
+    }
+
+    /// Forward inline images to the host terminal.
+    #[must_use]
+    pub fn graphics_passthrough(mut self, passthrough: bool) -> Self {
+        self.config.graphics_passthrough = passthrough;
+        self
+    }
+
+    /// Maximum number of pane events queued between polls (at least 1).
+    #[must_use]
+    pub fn event_capacity(mut self, capacity: usize) -> Self {
+        self.config.event_capacity = capacity;
+        self
+    }
+
+    /// What to do when the event queue is full.
+    #[must_use]
+    pub fn overflow_policy(mut self, policy: OverflowPolicy) -> Self {
+        self.config.overflow_policy = policy;
+        self
+    }
+
+    /// Styles used when rendering.
+    #[must_use]
+    pub fn theme(mut self, theme: Theme) -> Self {
+        self.config.theme = theme;
+        self
+    }
+
+    /// Share of the height given to panes, in (0, 1].
+    #[must_use]
+    pub fn sub_pane_ratio(mut self, ratio: f32) -> Self {
+        self.config.sub_pane_ratio = ratio;
+        self
+    }
+
+    /// Sub-panes below each pane position.
+    #[must_use]
+    pub fn sub_panes(mut self, arrangement: SubPaneArrangement) -> Self {
+        self.config.sub_panes = arrangement;
+        self
+    }
+
+    /// Shell for panes spawned without a command.
+    #[must_use]
+    pub fn default_shell(mut self, shell: impl Into<String>) -> Self {
+        self.config.default_shell = Some(shell.into());
+        self
+    }
+
+    /// Whether newly spawned panes take focus.
+    #[must_use]
+    pub fn spawn_focus(mut self, focus: SpawnFocus) -> Self {
+        self.config.spawn_focus = focus;
+        self
+    }
+
+    /// Space between a pane's border and its terminal content; sets the
+    /// theme's [`BorderTheme::padding`](crate::BorderTheme::padding).
+    #[must_use]
+    pub fn pane_padding(mut self, padding: Padding) -> Self {
+        self.config.theme.borders.padding = padding;
+        self
+    }
+
+    /// `TERM` for spawned panes; `None` inherits the host's value.
+    #[must_use]
+    pub fn term(mut self, term: Option<String>) -> Self {
+        self.config.term = term;
+        self
+    }
+
+    /// `COLORTERM` for spawned panes; `None` inherits the host's value.
+    #[must_use]
+    pub fn colorterm(mut self, colorterm: Option<String>) -> Self {
+        self.config.colorterm = colorterm;
+        self
+    }
+
+    /// Copy text selected with the mouse to the host terminal's clipboard.
+    #[must_use]
+    pub fn copy_on_select(mut self, copy: bool) -> Self {
+        self.config.copy_on_select = copy;
+        self
+    }
+
+    /// How often pane resource usage is re-sampled; must not be zero.
+    #[cfg(feature = "resource-usage")]
+    #[must_use]
+    pub fn resource_interval(mut self, interval: Duration) -> Self {
+        self.config.resource_interval = interval;
+        self
+    }
+
+    /// What to do when a pane's process exits, for panes that do not set
+    /// it themselves.
+    #[must_use]
+    pub fn on_exit(mut self, behavior: ExitBehavior) -> Self {
+        self.config.on_exit = behavior;
+        self
+    }
+
+    /// How long pane numbers stay up after
+    /// [`display_panes`](PaneManager::display_panes).
+    #[must_use]
+    pub fn display_panes_time(mut self, time: Duration) -> Self {
+        self.config.display_panes_time = time;
+        self
+    }
+
+    /// Finish the configuration.
+    ///
+    /// # Errors
+    /// Returns [`Error::Config`] if a setting is out of range.
+    pub fn build(self) -> Result<ManagerConfig> {
+        self.config.validate()?;
+        Ok(self.config)
     }
 }
 
@@ -40,17 +350,103 @@ impl Default for ManagerConfig {
 struct ManagedPane {
     /// The public handle.
     handle: PaneHandle,
+    /// Configuration the pane was spawned with, for respawning.
+    config: SpawnConfig,
     /// PTY master for resize operations.
-    pty_master: Box<dyn portable_pty::MasterPty + Send>,
+    pty_master: Arc<Mutex<PtyMaster>>,
     /// Reader task handle.
     #[allow(dead_code)]
-    reader_handle: JoinHandle<()>,
+    reader_handle: TaskHandle,
     /// Writer task handle.
     #[allow(dead_code)]
-    writer_handle: JoinHandle<()>,
+    writer_handle: TaskHandle,
     /// Monitor task handle.
     #[allow(dead_code)]
-    monitor_handle: JoinHandle<()>,
+    monitor_handle: TaskHandle,
+    /// Output processing state shared with the reader task.
+    output: Arc<OutputProcessor>,
+    /// How often in a row the pane was restarted by its exit behavior.
+    restarts: u32,
+    /// When the pane was spawned.
+    started: task::Instant,
+}
+
+/// Where a spawned pane goes.
+#[derive(Clone, Copy, Debug)]
+enum Placement {
+    /// The next free pane position.
+    Pane,
+    /// A sub-pane slot by index.
+    SubPane(usize),
+    /// A floating popup with content of the given size.
+    Popup(PaneSize),
+}
+
+/// A pane floating above the layout.
+#[derive(Debug)]
+struct Popup {
+    pane_id: PaneId,
+    /// Requested content size, shrunk to fit the terminal.
+    size: PaneSize,
+    /// Pane to focus once the popup closes.
+    return_focus: Option<PaneId>,
+}
+
+/// Name of the workspace a manager starts with.
+const DEFAULT_WORKSPACE: &str = "default";
+
+/// A set of panes with its own layout and focus.
+///
+/// The active workspace's state lives in the manager's fields; its entry
+/// here only keeps the name.
+#[derive(Debug)]
+struct Workspace {
+    name: String,
+    focused: Option<PaneId>,
+    pane_order: Vec<PaneId>,
+    pinned: HashMap<PaneId, usize>,
+    sub_pane_slots: Vec<Option<PaneId>>,
+    expanded_positions: [bool; 4],
+    column_offsets: [i32; 3],
+    row_offset: i32,
+    horizontal_expanded: [Option<bool>; 2],
+    /// Output generation of each pane when the workspace was last shown.
+    seen: HashMap<PaneId, u64>,
+}
+
+impl Workspace {
+    fn new(name: String, sub_panes: usize) -> Self {
+        Self {
+            name,
+            focused: None,
+            pane_order: Vec::with_capacity(4),
+            pinned: HashMap::new(),
+            sub_pane_slots: vec![None; sub_panes],
+            expanded_positions: [false; 4],
+            column_offsets: [0; 3],
+            row_offset: 0,
+            horizontal_expanded: [None; 2],
+            seen: HashMap::new(),
+        }
+    }
+
+    fn contains(&self, pane_id: PaneId) -> bool {
+        self.pane_order.contains(&pane_id) || self.sub_pane_slots.contains(&Some(pane_id))
+    }
+
+    /// Forget a closed pane, focusing the first remaining one if needed.
+    fn remove(&mut self, pane_id: PaneId) {
+        self.pane_order.retain(|&id| id != pane_id);
+        self.pinned.remove(&pane_id);
+        for slot in &mut self.sub_pane_slots {
+            if *slot == Some(pane_id) {
+                *slot = None;
+            }
+        }
+        if self.focused == Some(pane_id) {
+            self.focused = self.pane_order.first().copied();
+        }
+    }
 }
 
 /// Central manager for all panes.
@@ -64,31 +460,87 @@ pub struct PaneManager {
     /// Currently focused pane.
     focused: Option<PaneId>,
     /// Event sender for pane events.
-    event_tx: mpsc::Sender<PaneEvent>,
+    event_tx: EventSender,
     /// Event receiver for pane events.
-    event_rx: mpsc::Receiver<PaneEvent>,
+    event_rx: EventReceiver,
     /// Next pane ID.
     next_id: AtomicU64,
     /// Plugin registry for status bar plugins.
     plugin_registry: Option<PluginRegistry>,
+    /// Events polled since the last plugin tick, for plugins that take
+    /// them.
+    plugin_events: Vec<PaneEvent>,
     /// Current terminal size for automatic layout calculations.
     terminal_size: Option<Rect>,
     /// Pre-calculated pane areas (updated on spawn/close/resize).
     cached_areas: HashMap<PaneId, Rect>,
     /// Order of panes for consistent layout (first = left, second = right).
     pane_order: Vec<PaneId>,
-    /// Sub-pane areas (non-PTY decorative panes).
+    /// Pinned panes and the position (0-3) they are pinned to.
+    pinned: HashMap<PaneId, usize>,
+    /// Sub-pane areas, decorative unless a pane is spawned into them.
     sub_pane_areas: Vec<Rect>,
-    /// Ratio of space for panes vs sub-panes (0.7 = 70% panes, 30% sub-panes).
-    sub_pane_ratio: f32,
-    /// Empty pane areas for slots without active PTYs (pane_number, Rect).
+    /// Panes hosted in sub-pane slots, in layout order.
+    sub_pane_slots: Vec<Option<PaneId>>,
+    /// Empty pane areas for slots without active PTYs (`pane_number`, `Rect`).
     empty_pane_areas: Vec<(usize, Rect)>,
     /// Which pane positions (0-3) are expanded (hiding their sub-panes).
     expanded_positions: [bool; 4],
+    /// All workspaces, in creation order.
+    workspaces: Vec<Workspace>,
+    /// Index of the active workspace.
+    active_workspace: usize,
+    /// Pane floating above the layout, if one is open.
+    popup: Option<Popup>,
+    /// How far each column border was moved by resizes, in cells, from
+    /// left to right.
+    column_offsets: [i32; 3],
+    /// How far the border between panes and sub-panes was moved by resizes.
+    row_offset: i32,
     /// Horizontal expansion state per row.
     /// Index 0 = top row (110/120), Index 1 = bottom row (210/220).
     /// None = no expansion, Some(true) = left expanded, Some(false) = right expanded.
     horizontal_expanded: [Option<bool>; 2],
+    /// Pane that was focused at the last passthrough drain.
+    passthrough_focus: Option<PaneId>,
+    /// Keyboard protocol state last sent to the host terminal.
+    host_keyboard: KeyboardState,
+    /// Clipboard requests waiting for the next passthrough drain.
+    clipboard: Vec<u8>,
+    /// Repeated clicks, for word and line selection.
+    clicks: ClickCounter,
+    /// Pane and anchor cell of the selection being dragged.
+    drag: Option<(PaneId, (i32, u16))>,
+    /// Pane whose scrollbar is being dragged.
+    scrollbar_drag: Option<PaneId>,
+    /// Layout, focus or status bar changed since the last render.
+    dirty: bool,
+    /// Output generation of each pane at the last render, `None` if it
+    /// was drawn from a placeholder frame.
+    rendered_generations: HashMap<PaneId, Option<u64>>,
+    /// Activity of each workspace at the last render.
+    rendered_activity: Vec<bool>,
+    /// Shown panes drawn with an activity indicator at the last render.
+    rendered_busy: HashSet<PaneId>,
+    /// When the pane numbers shown by `display_panes` go away.
+    display_panes_until: Option<Instant>,
+    /// Start of the cursor blink cycle.
+    blink_epoch: Instant,
+    /// Blink phase at the last render.
+    rendered_blink_on: bool,
+    /// Where the tab bar was drawn, for clicks on tabs.
+    tab_bar_area: Option<Rect>,
+    /// Where new panes get their PTYs from.
+    backend: PtyBackend,
+    /// Callbacks for pane lifecycle events.
+    hooks: HookRegistry,
+    /// Named spawn templates.
+    profiles: ProfileRegistry,
+    /// Exited panes waiting out their restart backoff.
+    scheduled_restarts: Vec<(PaneId, task::Instant)>,
+    /// Process table sampled by pane handles.
+    #[cfg(feature = "resource-usage")]
+    resources: Arc<Mutex<ResourceMonitor>>,
 }
 
 impl PaneManager {
@@ -99,15 +551,30 @@ impl PaneManager {
     }
 
     /// Create a new pane manager with custom configuration.
+    ///
+    /// # Panics
+    /// Panics if the configuration is invalid; see
+    /// [`try_with_config`](Self::try_with_config).
     #[must_use]
     pub fn with_config(config: ManagerConfig) -> Self {
-        let (event_tx, event_rx) = mpsc::channel(256);
-        // Enforce max_panes = 4
-        let config = ManagerConfig {
-            max_panes: config.max_panes.min(4),
-            ..config
-        };
-        Self {
+        match Self::try_with_config(config) {
+            Ok(manager) => manager,
+            Err(e) => panic!("{e}"),
+        }
+    }
+
+    /// Create a new pane manager with custom configuration.
+    ///
+    /// # Errors
+    /// Returns [`Error::Config`] if the configuration is invalid.
+    pub fn try_with_config(config: ManagerConfig) -> Result<Self> {
+        config.validate()?;
+        let (event_tx, event_rx) = event_queue(config.event_capacity, config.overflow_policy);
+        let sub_pane_slots = vec![None; config.sub_panes.len()];
+        let workspace = Workspace::new(DEFAULT_WORKSPACE.to_string(), config.sub_panes.len());
+        #[cfg(feature = "resource-usage")]
+        let resources = Arc::new(Mutex::new(ResourceMonitor::new(config.resource_interval)));
+        Ok(Self {
             config,
             panes: HashMap::new(),
             layout: None,
@@ -116,42 +583,235 @@ impl PaneManager {
             event_rx,
             next_id: AtomicU64::new(1),
             plugin_registry: None,
+            plugin_events: Vec::new(),
             terminal_size: None,
             cached_areas: HashMap::new(),
             pane_order: Vec::with_capacity(4),
+            pinned: HashMap::new(),
             sub_pane_areas: Vec::new(),
-            sub_pane_ratio: 0.7,
+            sub_pane_slots,
             empty_pane_areas: Vec::new(),
             expanded_positions: [false; 4],
+            workspaces: vec![workspace],
+            active_workspace: 0,
+            popup: None,
+            column_offsets: [0; 3],
+            row_offset: 0,
             horizontal_expanded: [None; 2],
+            passthrough_focus: None,
+            host_keyboard: KeyboardState::default(),
+            clipboard: Vec::new(),
+            clicks: ClickCounter::default(),
+            drag: None,
+            scrollbar_drag: None,
+            dirty: true,
+            rendered_generations: HashMap::new(),
+            rendered_busy: HashSet::new(),
+            display_panes_until: None,
+            blink_epoch: Instant::now(),
+            rendered_blink_on: true,
+            rendered_activity: vec![false],
+            tab_bar_area: None,
+            backend: PtyBackend::Native,
+            hooks: HookRegistry::default(),
+            profiles: ProfileRegistry::default(),
+            scheduled_restarts: Vec::new(),
+            #[cfg(feature = "resource-usage")]
+            resources,
+        })
+    }
+
+    /// Create a pane manager whose panes run on scripted mock PTYs
+    /// instead of real processes.
+    #[cfg(any(test, feature = "mock-pty"))]
+    #[must_use]
+    pub fn with_mock_backend(config: ManagerConfig, backend: MockBackend) -> Self {
+        Self {
+            backend: PtyBackend::Mock(backend),
+            ..Self::with_config(config)
         }
     }
 
+    /// Spawn a new pane in the focused pane's working directory, like tmux's
+    /// `split-window -c '#{pane_current_path}'`.
+    ///
+    /// A `cwd` already set on `config` wins. If no pane is focused or its
+    /// directory is unknown, this behaves like [`spawn`](Self::spawn).
+    ///
+    /// # Errors
+    /// Returns an error if pane spawning fails or max panes is reached.
+    pub fn spawn_here(&mut self, mut config: SpawnConfig) -> Result<PaneHandle> {
+        if config.cwd.is_none() {
+            config.cwd = self
+                .focused
+                .and_then(|id| self.get_pane(id))
+                .and_then(PaneHandle::cwd);
+        }
+        self.spawn(config)
+    }
+
+    /// Spawn another pane like `pane_id`: same command, arguments and
+    /// environment, started in its current working directory (or the one
+    /// it was spawned in, if that is unknown).
+    ///
+    /// # Errors
+    /// Returns [`Error::PaneNotFound`] if the pane does not exist, or an
+    /// error if spawning fails or max panes is reached.
+    pub fn duplicate_pane(&mut self, pane_id: PaneId) -> Result<PaneHandle> {
+        let managed = self
+            .panes
+            .get(&pane_id)
+            .ok_or(Error::PaneNotFound(pane_id.0))?;
+        let mut config = managed.config.clone();
+        if let Some(cwd) = managed.handle.cwd() {
+            config.cwd = Some(cwd);
+        }
+        // The original keeps its sub-pane slot
+        config.slot = Slot::Pane;
+        self.spawn(config)
+    }
+
+    /// Spawn a pane from a registered profile.
+    ///
+    /// # Errors
+    /// Returns [`Error::ProfileNotFound`] if no profile has that name, or
+    /// an error if spawning fails or max panes is reached.
+    pub fn spawn_profile(&mut self, name: &str) -> Result<PaneHandle> {
+        self.spawn_profile_with(name, |config| config)
+    }
+
+    /// Spawn a pane from a registered profile after adjusting it, e.g.
+    /// `|config| config.cwd(dir).env("PORT", "3001")`.
+    ///
+    /// # Errors
+    /// Returns [`Error::ProfileNotFound`] if no profile has that name, or
+    /// an error if spawning fails or max panes is reached.
+    pub fn spawn_profile_with(
+        &mut self,
+        name: &str,
+        customize: impl FnOnce(SpawnConfig) -> SpawnConfig,
+    ) -> Result<PaneHandle> {
+        let config = self
+            .profiles
+            .get(name)
+            .cloned()
+            .ok_or_else(|| Error::ProfileNotFound(name.to_string()))?;
+        self.spawn(customize(config))
+    }
+
     /// Spawn a new pane with the given configuration.
     ///
     /// The pane size is calculated automatically based on the current terminal
     /// size and number of panes. Layout is updated automatically.
     ///
+    /// Panes take the next free pane position unless the config names a
+    /// sub-pane [`Slot`]; sub-panes do not count towards `max_panes`.
+    ///
     /// # Errors
-    /// Returns an error if pane spawning fails or max panes (2) is reached.
+    /// Returns an error if pane spawning fails, max panes is reached, or
+    /// the requested sub-pane slot is unknown or occupied.
     pub fn spawn(&mut self, config: SpawnConfig) -> Result<PaneHandle> {
-        if self.panes.len() >= self.config.max_panes {
-            return Err(Error::Layout(format!(
-                "Maximum panes ({}) reached",
-                self.config.max_panes
-            )));
+        let placement = match config.slot {
+            Slot::Pane => {
+                if self.pane_order.len() >= self.config.max_panes {
+                    return Err(Error::Layout(format!(
+                        "Maximum panes ({}) reached",
+                        self.config.max_panes
+                    )));
+                }
+                Placement::Pane
+            }
+            Slot::SubPane(label) => {
+                let index = self
+                    .config
+                    .sub_panes
+                    .index_of_label(label)
+                    .ok_or_else(|| Error::Layout(format!("No sub-pane {label}")))?;
+                if self.sub_pane_slots[index].is_some() {
+                    return Err(Error::Layout(format!("Sub-pane {label} is occupied")));
+                }
+                Placement::SubPane(index)
+            }
+        };
+        self.spawn_at(config, placement)
+    }
+
+    /// Run a pane in a floating popup centered above the layout, like
+    /// tmux's `display-popup`. `size` is the size of its content; the
+    /// popup shrinks to fit the terminal.
+    ///
+    /// The popup takes focus and keeps it while open: clicks and focus
+    /// cycling leave it alone. It closes when its process exits, whatever
+    /// the config's exit behavior, and focus returns to the pane that had
+    /// it before.
+    ///
+    /// # Errors
+    /// Returns an error if pane spawning fails or a popup is already open.
+    pub fn spawn_popup(&mut self, config: SpawnConfig, size: PaneSize) -> Result<PaneHandle> {
+        if self.popup.is_some() {
+            return Err(Error::Layout("A popup is already open".to_string()));
         }
+        self.spawn_at(config, Placement::Popup(size))
+    }
 
+    /// The open popup and the area it is drawn in, borders included.
+    #[must_use]
+    pub fn popup(&self) -> Option<(PaneId, Rect)> {
+        let popup = self.popup.as_ref()?;
+        let full_area = self.terminal_size.unwrap_or_default();
+        let width = popup.size.cols.saturating_add(2).min(full_area.width);
+        let height = popup.size.rows.saturating_add(2).min(full_area.height);
+        let area = Rect {
+            x: full_area.x + (full_area.width - width) / 2,
+            y: full_area.y + (full_area.height - height) / 2,
+            width,
+            height,
+        };
+        Some((popup.pane_id, area))
+    }
+
+    /// Close the open popup and its process.
+    ///
+    /// Returns `false` if no popup is open.
+    pub fn close_popup(&mut self) -> bool {
+        let Some(pane_id) = self.popup.as_ref().map(|popup| popup.pane_id) else {
+            return false;
+        };
+        self.close_pane(pane_id);
+        true
+    }
+
+    fn spawn_at(&mut self, config: SpawnConfig, placement: Placement) -> Result<PaneHandle> {
         let pane_id = PaneId(self.next_id.fetch_add(1, Ordering::SeqCst));
 
         // Calculate initial size from terminal size
-        let initial_size = self.calculate_initial_pane_size();
+        let initial_size = match placement {
+            Placement::Popup(size) => size,
+            Placement::Pane | Placement::SubPane(_) => self.calculate_initial_pane_size(),
+        };
 
         let mut spawn_config = config;
         spawn_config.size = initial_size; // Override with calculated size
         if spawn_config.scrollback == 0 {
             spawn_config.scrollback = self.config.scrollback_lines;
         }
+        if spawn_config.command.is_none() {
+            spawn_config.command.clone_from(&self.config.default_shell);
+        }
+        for (key, value) in [
+            ("TERM", &self.config.term),
+            ("COLORTERM", &self.config.colorterm),
+        ] {
+            let Some(value) = value else { continue };
+            // Removing the variable opts the pane out of the default
+            if spawn_config.env_remove.iter().any(|k| k == key) {
+                continue;
+            }
+            spawn_config
+                .env
+                .entry(key.to_string())
+                .or_insert_with(|| value.clone());
+        }
 
         let SpawnedPty {
             handle,
@@ -159,22 +819,56 @@ impl PaneManager {
             reader_handle,
             writer_handle,
             monitor_handle,
-        } = pty::spawn_pty(pane_id, &spawn_config, self.event_tx.clone())?;
+            output,
+        } = pty::spawn_pty(
+            pane_id,
+            &spawn_config,
+            &self.backend,
+            TrackingOptions {
+                keyboard_protocol: self.config.keyboard_protocol,
+                forward_cursor_shape: self.config.forward_cursor_shape,
+                graphics_passthrough: self.config.graphics_passthrough,
+            },
+            self.event_tx.clone(),
+        )?;
+        #[cfg(feature = "resource-usage")]
+        let handle = handle.with_resources(self.resources.clone());
 
         let managed = ManagedPane {
             handle: handle.clone(),
+            config: spawn_config,
             pty_master,
             reader_handle,
             writer_handle,
             monitor_handle,
+            output,
+            restarts: 0,
+            started: task::Instant::now(),
         };
 
         self.panes.insert(pane_id, managed);
-        self.pane_order.push(pane_id);
+        match placement {
+            Placement::Pane => self.pane_order.push(pane_id),
+            Placement::SubPane(index) => self.sub_pane_slots[index] = Some(pane_id),
+            Placement::Popup(size) => {
+                self.popup = Some(Popup {
+                    pane_id,
+                    size,
+                    return_focus: self.focused,
+                });
+            }
+        }
 
-        // Auto-focus first pane
-        if self.focused.is_none() {
-            self.focused = Some(pane_id);
+        self.event_tx.push(PaneEvent::PaneSpawned { pane_id });
+
+        let take_focus = matches!(placement, Placement::Popup(_))
+            || match self.config.spawn_focus {
+                SpawnFocus::IfNone => self.focused.is_none(),
+                SpawnFocus::Always => true,
+                SpawnFocus::Never => false,
+            };
+        if take_focus {
+            self.change_focus(Some(pane_id));
         }
 
         // Recalculate layout for new pane count
@@ -183,20 +877,270 @@ impl PaneManager {
         // Resize all panes to their new areas (ignore errors during spawn)
         let _ = self.resize_all_panes();
 
+        let action = self.hooks.dispatch(&handle, &HookEvent::Spawned);
+        self.apply_hook_action(pane_id, action);
+
         Ok(handle)
     }
 
+    /// Attach a callback to pane lifecycle events.
+    ///
+    /// Hooks run on the calling thread from [`spawn`](Self::spawn) and
+    /// [`poll_events`](Self::poll_events); the action they return is
+    /// applied right after.
+    pub fn on(
+        &mut self,
+        kind: HookKind,
+        callback: impl FnMut(&PaneHandle, &HookEvent<'_>) -> HookAction + Send + 'static,
+    ) -> HookId {
+        self.hooks.add(kind, Box::new(callback))
+    }
+
+    /// Remove a hook. Returns `false` if it was not registered.
+    pub fn off(&mut self, hook: HookId) -> bool {
+        self.hooks.remove(hook)
+    }
+
+    /// Carry out what a hook asked for.
+    fn apply_hook_action(&mut self, pane_id: PaneId, action: HookAction) {
+        match action {
+            HookAction::None => {}
+            HookAction::Close => self.close_pane(pane_id),
+            HookAction::Respawn => {
+                let Some(config) = self.panes.get(&pane_id).map(|m| m.config.clone()) else {
+                    return;
+                };
+                self.in_workspace_of(pane_id, |manager| {
+                    manager.close_pane(pane_id);
+                    if let Err(e) = manager.spawn(config) {
+                        tracing::warn!("Failed to respawn pane {}: {}", pane_id, e);
+                    }
+                });
+            }
+        }
+    }
+
     /// Get the currently focused pane ID.
     #[must_use]
     pub fn focused(&self) -> Option<PaneId> {
         self.focused
     }
 
-    /// Set focus to a specific pane.
+    /// Get the configured host color depth, for use with `color_mode` on widgets.
+    #[must_use]
+    pub fn color_mode(&self) -> ColorMode {
+        self.config.color_mode
+    }
+
+    /// Get the configured pane padding, for use with `padding` on widgets.
+    #[must_use]
+    pub fn pane_padding(&self) -> Padding {
+        self.config.theme.borders.padding
+    }
+
+    /// Get the theme used by [`render`](Self::render).
+    #[must_use]
+    pub fn theme(&self) -> &Theme {
+        &self.config.theme
+    }
+
+    /// Change the theme used by [`render`](Self::render). Panes are
+    /// resized if the border padding changed.
+    pub fn set_theme(&mut self, theme: Theme) {
+        let padding_changed = theme.borders.padding != self.pane_padding();
+        self.config.theme = theme;
+        self.dirty = true;
+        if padding_changed {
+            let _ = self.resize_all_panes();
+        }
+    }
+
+    /// Where [`render`](Self::render) draws the status and tab bars in a
+    /// frame of `area`, with the manager's theme, and the area left for
+    /// the panes.
+    #[must_use]
+    pub fn frame_areas(&self, area: Rect) -> FrameAreas {
+        frame::frame_areas(self, self.theme(), area)
+    }
+
+    /// Set the terminal size from a whole frame of `area`, leaving out the
+    /// rows of the status and tab bars; see [`frame_areas`](Self::frame_areas).
+    /// Use in place of [`set_terminal_size`](Self::set_terminal_size) when
+    /// the panes share the terminal with the bars.
+    pub fn set_frame_size(&mut self, area: Rect) {
+        let panes = self.frame_areas(area).panes;
+        self.set_terminal_size(panes);
+    }
+
+    /// Draw the panes and status bar into `frame`.
+    ///
+    /// Shorthand for [`CockpitFrame::new`]; use that to add a dialog or
+    /// override the theme.
+    pub fn render(&mut self, frame: &mut Frame<'_>) -> FrameAreas {
+        CockpitFrame::new(self).render(frame)
+    }
+
+    /// Set focus to a specific pane, switching to its workspace if needed.
     pub fn set_focus(&mut self, pane_id: PaneId) {
-        if self.panes.contains_key(&pane_id) {
-            self.focused = Some(pane_id);
+        let Some(index) = self.workspace_index_of(pane_id) else {
+            return;
+        };
+        let from = self.focused;
+        self.enter_workspace(index);
+        // Report the move from the pane focused before the switch
+        self.focused = from;
+        self.change_focus(Some(pane_id));
+    }
+
+    /// Show a number on every visible pane for
+    /// [`display_panes_time`](ManagerConfig::display_panes_time), like
+    /// tmux's `display-panes`. Pressing a number key meanwhile focuses
+    /// that pane; see [`display_panes_key`](Self::display_panes_key).
+    pub fn display_panes(&mut self) {
+        self.display_panes_until = Some(Instant::now() + self.config.display_panes_time);
+        self.dirty = true;
+    }
+
+    /// Whether pane numbers are shown.
+    #[must_use]
+    pub fn displaying_panes(&self) -> bool {
+        self.display_panes_until
+            .is_some_and(|until| Instant::now() < until)
+    }
+
+    /// Visible panes in the order they are numbered, left to right and
+    /// top to bottom, with their areas. Pane `n` is at index `n - 1`;
+    /// only the first nine get a number key.
+    #[must_use]
+    pub fn numbered_panes(&self) -> Vec<(PaneId, Rect)> {
+        let mut panes: Vec<_> = self
+            .cached_areas
+            .iter()
+            .filter(|(id, area)| {
+                !area.is_empty() && self.panes.contains_key(id) && !self.is_popup(**id)
+            })
+            .map(|(id, area)| (*id, *area))
+            .chain(self.popup())
+            .collect();
+        panes.sort_by_key(|(id, area)| (self.is_popup(*id), area.x, area.y));
+        panes
+    }
+
+    /// Handle a key while pane numbers are shown: a number key focuses
+    /// that pane, and any key hides the numbers.
+    ///
+    /// Returns `true` if the key was used, `false` if no numbers are shown
+    /// and the key should be handled as usual.
+    pub fn display_panes_key(&mut self, key: KeyEvent) -> bool {
+        if !self.displaying_panes() {
+            return false;
+        }
+        if key.kind == KeyEventKind::Release {
+            return true;
+        }
+        let target = match key.code {
+            KeyCode::Char(c @ '1'..='9') => {
+                let index = usize::from(c as u8 - b'1');
+                self.numbered_panes().get(index).map(|(id, _)| *id)
+            }
+            _ => None,
+        };
+        if let Some(pane_id) = target {
+            self.change_focus(Some(pane_id));
         }
+        self.display_panes_until = None;
+        self.dirty = true;
+        true
+    }
+
+    /// Move focus, emitting [`PaneEvent::FocusChanged`] if it changed.
+    ///
+    /// Returns `true` if focus changed.
+    fn change_focus(&mut self, to: Option<PaneId>) -> bool {
+        let from = self.focused;
+        if from == to {
+            return false;
+        }
+        self.focused = to;
+        self.dirty = true;
+        self.event_tx.push(PaneEvent::FocusChanged { from, to });
+        true
+    }
+
+    /// Check whether anything visible changed since the last
+    /// [`mark_rendered`](Self::mark_rendered) call.
+    ///
+    /// This covers pane output, layout, focus and status bar changes, so
+    /// apps can redraw only when needed instead of at a fixed rate. While
+    /// a shown pane is busy, its activity indicator keeps this true.
+    #[must_use]
+    pub fn needs_render(&self) -> bool {
+        let busy = self.busy_panes();
+        // Output in background workspaces is not shown
+        self.dirty
+            || self.display_panes_until.is_some()
+            || !busy.is_empty()
+            || busy != self.rendered_busy
+            || self.panes.len() != self.rendered_generations.len()
+            || self.panes.iter().any(|(id, managed)| {
+                (self.cached_areas.contains_key(id) || self.is_popup(*id))
+                    && self.rendered_generations.get(id) != Some(&Some(managed.handle.generation()))
+            })
+            || self.workspace_activity() != self.rendered_activity
+            || (self.focused_cursor_blinks() && self.cursor_blink_on() != self.rendered_blink_on)
+    }
+
+    /// Record that the current state has been drawn.
+    pub fn mark_rendered(&mut self) {
+        self.dirty = false;
+        self.rendered_generations = self
+            .panes
+            .iter()
+            .map(|(id, managed)| (*id, managed.handle.rendered_generation()))
+            .collect();
+        self.rendered_activity = self.workspace_activity();
+        self.rendered_busy = self.busy_panes();
+        self.rendered_blink_on = self.cursor_blink_on();
+        // Keep rendering until a frame without the numbers was drawn
+        if !self.displaying_panes() {
+            self.display_panes_until = None;
+        }
+    }
+
+    /// Whether blinking cursors are in their visible phase now. Pass it
+    /// to [`CockpitWidget::cursor_blink_on`](crate::CockpitWidget::cursor_blink_on);
+    /// [`needs_render`](Self::needs_render) turns true each time it flips
+    /// while the focused pane's cursor blinks.
+    #[must_use]
+    pub fn cursor_blink_on(&self) -> bool {
+        let phases = self.blink_epoch.elapsed().as_millis() / CURSOR_BLINK_INTERVAL.as_millis();
+        phases.is_multiple_of(2)
+    }
+
+    /// Whether the focused pane is shown with a blinking cursor.
+    fn focused_cursor_blinks(&self) -> bool {
+        self.focused.is_some_and(|id| {
+            (self.cached_areas.contains_key(&id) || self.is_popup(id))
+                && self.get_pane(id).is_some_and(|handle| {
+                    handle.cursor_config().blink && handle.cursor_style().blinking
+                })
+        })
+    }
+
+    /// Shown panes that get an activity indicator.
+    fn busy_panes(&self) -> HashSet<PaneId> {
+        if self.config.theme.activity_indicator.is_none() {
+            return HashSet::new();
+        }
+        let now = Instant::now();
+        self.panes
+            .iter()
+            .filter(|(id, managed)| {
+                (self.cached_areas.contains_key(id) || self.is_popup(**id))
+                    && managed.handle.stats().is_busy(now)
+            })
+            .map(|(id, _)| *id)
+            .collect()
     }
 
     /// Get a pane handle by ID.
@@ -205,18 +1149,252 @@ impl PaneManager {
         self.panes.get(&pane_id).map(|p| &p.handle)
     }
 
-    /// Get all pane IDs.
+    /// Get all pane IDs, in every workspace.
     #[must_use]
     pub fn pane_ids(&self) -> Vec<PaneId> {
         self.panes.keys().copied().collect()
     }
 
-    /// Get the number of panes.
+    /// Get the number of panes, in every workspace.
     #[must_use]
     pub fn pane_count(&self) -> usize {
         self.panes.len()
     }
 
+    /// Add an empty workspace after the existing ones. Use
+    /// [`switch_workspace`](Self::switch_workspace) to show it.
+    ///
+    /// Each workspace has its own panes, layout and focus; panes in
+    /// background workspaces keep running. The manager starts with one
+    /// workspace, named `default`.
+    ///
+    /// # Errors
+    /// Returns [`Error::WorkspaceExists`] if a workspace with that name
+    /// exists.
+    pub fn create_workspace(&mut self, name: impl Into<String>) -> Result<()> {
+        let name = name.into();
+        if self.workspace_index(&name).is_ok() {
+            return Err(Error::WorkspaceExists(name));
+        }
+        self.workspaces
+            .push(Workspace::new(name, self.config.sub_panes.len()));
+        self.dirty = true;
+        Ok(())
+    }
+
+    /// Show a workspace. New panes are spawned into it, and its focused
+    /// pane gets focus.
+    ///
+    /// # Errors
+    /// Returns [`Error::WorkspaceNotFound`] if no workspace has that name.
+    pub fn switch_workspace(&mut self, name: &str) -> Result<()> {
+        let index = self.workspace_index(name)?;
+        let from = self.focused;
+        self.enter_workspace(index);
+        if self.focused != from {
+            self.event_tx.push(PaneEvent::FocusChanged {
+                from,
+                to: self.focused,
+            });
+        }
+        Ok(())
+    }
+
+    /// Close a workspace and all its panes. Closing the active workspace
+    /// shows the one before it.
+    ///
+    /// # Errors
+    /// Returns [`Error::WorkspaceNotFound`] if no workspace has that name,
+    /// or [`Error::Layout`] if it is the only one.
+    pub fn close_workspace(&mut self, name: &str) -> Result<()> {
+        let index = self.workspace_index(name)?;
+        if self.workspaces.len() == 1 {
+            return Err(Error::Layout("Cannot close the last workspace".to_string()));
+        }
+        if index == self.active_workspace {
+            let next = if index == 0 { 1 } else { index - 1 };
+            let next = self.workspaces[next].name.clone();
+            self.switch_workspace(&next)?;
+        }
+        let workspace = &self.workspaces[index];
+        let pane_ids: Vec<_> = workspace
+            .pane_order
+            .iter()
+            .chain(workspace.sub_pane_slots.iter().flatten())
+            .copied()
+            .collect();
+        for pane_id in pane_ids {
+            self.close_pane(pane_id);
+        }
+        self.workspaces.remove(index);
+        if index < self.active_workspace {
+            self.active_workspace -= 1;
+        }
+        self.dirty = true;
+        Ok(())
+    }
+
+    /// Names of all workspaces, in creation order.
+    #[must_use]
+    pub fn workspaces(&self) -> Vec<&str> {
+        self.workspaces.iter().map(|w| w.name.as_str()).collect()
+    }
+
+    /// Name of the active workspace.
+    #[must_use]
+    pub fn active_workspace(&self) -> &str {
+        &self.workspaces[self.active_workspace].name
+    }
+
+    /// Name of the workspace a pane belongs to.
+    #[must_use]
+    pub fn workspace_of(&self, pane_id: PaneId) -> Option<&str> {
+        self.workspace_index_of(pane_id)
+            .map(|index| self.workspaces[index].name.as_str())
+    }
+
+    /// Workspaces as shown by [`TabBarWidget`](crate::TabBarWidget).
+    #[must_use]
+    pub fn workspace_tabs(&self) -> Vec<WorkspaceTab> {
+        self.workspaces
+            .iter()
+            .zip(self.workspace_activity())
+            .enumerate()
+            .map(|(index, (workspace, activity))| WorkspaceTab {
+                name: workspace.name.clone(),
+                active: index == self.active_workspace,
+                activity,
+            })
+            .collect()
+    }
+
+    /// Tell the manager where the tab bar is drawn, so
+    /// [`handle_click`](Self::handle_click) can switch workspaces when a
+    /// tab is clicked. Tabs are laid out with the manager's theme.
+    /// [`CockpitFrame`] does this itself.
+    pub fn set_tab_bar_area(&mut self, area: Option<Rect>) {
+        self.tab_bar_area = area;
+    }
+
+    /// Whether each workspace has panes with output not shown yet. The
+    /// active workspace never has.
+    fn workspace_activity(&self) -> Vec<bool> {
+        self.workspaces
+            .iter()
+            .enumerate()
+            .map(|(index, workspace)| {
+                index != self.active_workspace
+                    && workspace.seen.iter().any(|(id, generation)| {
+                        self.panes
+                            .get(id)
+                            .is_some_and(|managed| managed.handle.generation() != *generation)
+                    })
+            })
+            .collect()
+    }
+
+    fn is_popup(&self, pane_id: PaneId) -> bool {
+        self.popup
+            .as_ref()
+            .is_some_and(|popup| popup.pane_id == pane_id)
+    }
+
+    fn workspace_index(&self, name: &str) -> Result<usize> {
+        self.workspaces
+            .iter()
+            .position(|w| w.name == name)
+            .ok_or_else(|| Error::WorkspaceNotFound(name.to_string()))
+    }
+
+    fn workspace_index_of(&self, pane_id: PaneId) -> Option<usize> {
+        if self.in_active_workspace(pane_id) {
+            return Some(self.active_workspace);
+        }
+        self.workspaces.iter().position(|w| w.contains(pane_id))
+    }
+
+    fn in_active_workspace(&self, pane_id: PaneId) -> bool {
+        self.pane_order.contains(&pane_id) || self.sub_pane_slots.contains(&Some(pane_id))
+    }
+
+    /// Make workspace `index` active, without reporting the focus change.
+    fn enter_workspace(&mut self, index: usize) {
+        if index == self.active_workspace {
+            return;
+        }
+        let seen = self
+            .panes
+            .iter()
+            .filter(|(id, _)| self.in_active_workspace(**id))
+            .map(|(id, managed)| (*id, managed.handle.generation()))
+            .collect();
+        self.workspaces[self.active_workspace].seen = seen;
+        self.swap_workspace(self.active_workspace);
+        self.swap_workspace(index);
+        self.active_workspace = index;
+        self.drag = None;
+        self.scrollbar_drag = None;
+        self.recalculate_layout();
+        let _ = self.resize_all_panes();
+    }
+
+    /// Exchange the manager's layout state with a stored workspace's.
+    fn swap_workspace(&mut self, index: usize) {
+        use std::mem::swap;
+
+        let workspace = &mut self.workspaces[index];
+        swap(&mut self.focused, &mut workspace.focused);
+        swap(&mut self.pane_order, &mut workspace.pane_order);
+        swap(&mut self.pinned, &mut workspace.pinned);
+        swap(&mut self.sub_pane_slots, &mut workspace.sub_pane_slots);
+        swap(
+            &mut self.expanded_positions,
+            &mut workspace.expanded_positions,
+        );
+        swap(&mut self.column_offsets, &mut workspace.column_offsets);
+        swap(&mut self.row_offset, &mut workspace.row_offset);
+        swap(
+            &mut self.horizontal_expanded,
+            &mut workspace.horizontal_expanded,
+        );
+    }
+
+    /// Run `f` with the workspace of `pane_id` active, so panes it spawns
+    /// land there, then return to the active workspace.
+    ///
+    /// The switch is never shown: the workspace's output is not marked as
+    /// seen, and the active workspace's layout is only recalculated on the
+    /// way back.
+    fn in_workspace_of<T>(&mut self, pane_id: PaneId, f: impl FnOnce(&mut Self) -> T) -> T {
+        let active = self.active_workspace;
+        let Some(index) = self
+            .workspace_index_of(pane_id)
+            .filter(|&index| index != active)
+        else {
+            return f(self);
+        };
+        self.swap_workspace(active);
+        self.swap_workspace(index);
+        self.active_workspace = index;
+        let result = f(self);
+        // Panes spawned there start out seen
+        let spawned: Vec<_> = self
+            .panes
+            .iter()
+            .filter(|(id, _)| {
+                self.in_active_workspace(**id) && !self.workspaces[index].seen.contains_key(id)
+            })
+            .map(|(id, managed)| (*id, managed.handle.generation()))
+            .collect();
+        self.workspaces[index].seen.extend(spawned);
+        self.swap_workspace(index);
+        self.swap_workspace(active);
+        self.active_workspace = active;
+        self.recalculate_layout();
+        let _ = self.resize_all_panes();
+        result
+    }
+
     /// Calculate layout areas for the given total area.
     #[must_use]
     pub fn calculate_areas(&self, area: Rect) -> HashMap<PaneId, Rect> {
@@ -229,12 +1407,22 @@ impl PaneManager {
     ///
     /// # Errors
     /// Returns an error if the pane is not found or resize fails.
+    ///
+    /// # Panics
+    /// Panics if the PTY master lock is poisoned.
     pub fn resize_pane(&mut self, pane_id: PaneId, size: PaneSize) -> Result<()> {
         let managed = self
             .panes
             .get(&pane_id)
             .ok_or(Error::PaneNotFound(pane_id.0))?;
-        pty::resize_pty(managed.pty_master.as_ref(), size)
+        pty::resize_pty(
+            &managed.pty_master.lock().expect("pty master lock poisoned"),
+            size,
+        )?;
+        if managed.handle.resize_screen(size) {
+            self.event_tx.push(PaneEvent::Resized { pane_id, size });
+        }
+        Ok(())
     }
 
     /// Set the terminal size and initialize internal layout calculations.
@@ -250,6 +1438,13 @@ impl PaneManager {
         let _ = self.resize_all_panes();
     }
 
+    /// State drawing this manager's panes with
+    /// [`CockpitWidget`](crate::CockpitWidget) as a stateful widget.
+    #[must_use]
+    pub fn cockpit_state(&self) -> CockpitState<'_> {
+        CockpitState::new(self)
+    }
+
     /// Get pre-calculated pane areas.
     ///
     /// These are updated automatically when panes are added/removed or on resize.
@@ -264,6 +1459,19 @@ impl PaneManager {
         &self.sub_pane_areas
     }
 
+    /// Get the panes hosted in sub-pane slots, indexed like
+    /// [`get_sub_pane_areas`](Self::get_sub_pane_areas).
+    #[must_use]
+    pub fn get_sub_pane_slots(&self) -> &[Option<PaneId>] {
+        &self.sub_pane_slots
+    }
+
+    /// How the area below each pane position is divided into sub-panes.
+    #[must_use]
+    pub fn sub_pane_arrangement(&self) -> &SubPaneArrangement {
+        &self.config.sub_panes
+    }
+
     /// Get empty pane areas for rendering (slots without active PTYs).
     #[must_use]
     pub fn get_empty_pane_areas(&self) -> &[(usize, Rect)] {
@@ -278,8 +1486,11 @@ impl PaneManager {
 
     /// Toggle expansion state for a pane position (0-3).
     /// When expanded, the pane takes full height and its sub-panes are hidden.
+    ///
+    /// Positions holding a pinned pane, or a pinned sub-pane, are left as
+    /// they are.
     pub fn toggle_pane_expansion(&mut self, position: usize) {
-        if position < 4 {
+        if position < 4 && !self.is_position_pinned(position) {
             self.expanded_positions[position] = !self.expanded_positions[position];
             self.recalculate_layout();
             let _ = self.resize_all_panes();
@@ -289,9 +1500,12 @@ impl PaneManager {
     /// Toggle horizontal expansion for a row.
     /// - row 0 = top row (panes 110/120)
     /// - row 1 = bottom row (panes 210/220)
-    /// - expand_left = true means left pane expands, false means right pane expands
+    /// - `expand_left` = true means left pane expands, false means right pane expands
+    ///
+    /// Rows holding a pinned pane are left as they are.
+    #[allow(clippy::match_same_arms)]
     pub fn toggle_horizontal_expansion(&mut self, row: usize, expand_left: bool) {
-        if row < 2 {
+        if row < 2 && !self.is_position_pinned(2 * row) && !self.is_position_pinned(2 * row + 1) {
             let current = self.horizontal_expanded[row];
             self.horizontal_expanded[row] = match current {
                 None => Some(expand_left),
@@ -308,15 +1522,97 @@ impl PaneManager {
         &self.horizontal_expanded
     }
 
+    /// Pin a pane to its current position, so it keeps its place and size
+    /// while other panes are spawned, closed or expanded around it.
+    ///
+    /// Panes after it in the layout order skip its position when earlier
+    /// panes close, and expanding its position or row does nothing.
+    /// Resizing with [`resize_focused`](Self::resize_focused) and terminal
+    /// resizes still apply.
+    ///
+    /// Returns `false` if the pane does not exist.
+    pub fn pin_pane(&mut self, pane_id: PaneId) -> bool {
+        let position = if let Some(position) = self.position_of(pane_id) {
+            position
+        } else if let Some((position, _)) = self
+            .sub_pane_slots
+            .iter()
+            .position(|&id| id == Some(pane_id))
+            .and_then(|index| self.config.sub_panes.locate(index))
+        {
+            position
+        } else {
+            return false;
+        };
+        self.pinned.insert(pane_id, position);
+        true
+    }
+
+    /// Let a pinned pane move with the layout again. It may move to an
+    /// earlier position right away.
+    ///
+    /// Returns `false` if the pane was not pinned.
+    pub fn unpin_pane(&mut self, pane_id: PaneId) -> bool {
+        if self.pinned.remove(&pane_id).is_none() {
+            return false;
+        }
+        self.recalculate_layout();
+        let _ = self.resize_all_panes();
+        true
+    }
+
+    /// Check whether a pane is pinned.
+    #[must_use]
+    pub fn is_pinned(&self, pane_id: PaneId) -> bool {
+        self.pinned.contains_key(&pane_id)
+    }
+
+    /// Whether a pinned pane sits at `position` or in its sub-panes.
+    fn is_position_pinned(&self, position: usize) -> bool {
+        self.pinned.values().any(|&pinned| pinned == position)
+    }
+
+    /// Pane at each position (0-3): pinned panes at their positions, the
+    /// others filling the remaining positions in layout order.
+    fn positions(&self) -> [Option<PaneId>; 4] {
+        let mut positions = [None; 4];
+        for (&pane_id, &position) in &self.pinned {
+            if position < 4 && self.pane_order.contains(&pane_id) {
+                positions[position] = Some(pane_id);
+            }
+        }
+        let mut free = positions.iter_mut().filter(|slot| slot.is_none());
+        for &pane_id in &self.pane_order {
+            if self.pinned.contains_key(&pane_id) {
+                continue;
+            }
+            if let Some(slot) = free.next() {
+                *slot = Some(pane_id);
+            }
+        }
+        positions
+    }
+
+    /// Position (0-3) of a pane that is not in a sub-pane slot.
+    fn position_of(&self, pane_id: PaneId) -> Option<usize> {
+        self.positions().iter().position(|&id| id == Some(pane_id))
+    }
+
     /// Recalculate layout based on current panes and terminal size.
     /// Always calculates 4 pane areas (2x2 grid) for consistent 12-pane layout.
+    #[allow(clippy::match_same_arms)]
+    #[cfg_attr(
+        feature = "trace",
+        tracing::instrument(level = "debug", skip_all, fields(panes = self.panes.len()))
+    )]
     fn recalculate_layout(&mut self) {
         let Some(full_area) = self.terminal_size else {
             return;
         };
+        self.dirty = true;
 
         // Split the area into panes (top) and sub-panes (bottom)
-        let panes_height = (f32::from(full_area.height) * self.sub_pane_ratio).round() as u16;
+        let panes_height = self.panes_height(full_area.height);
         let sub_panes_height = full_area.height.saturating_sub(panes_height);
 
         // Calculate sub-pane areas - overlap by 1 row so borders share the same line
@@ -328,89 +1624,42 @@ impl PaneManager {
         };
         self.recalculate_sub_panes(sub_panes_area);
 
-        // Always calculate 4 areas in a horizontal row (side by side)
-        let quarter_width = full_area.width / 4;
-        let half_width = full_area.width / 2;
-
-        // Calculate widths based on horizontal expansion state
-        // Row 0 = positions 0,1 (panes 110,120), Row 1 = positions 2,3 (panes 210,220)
-        let (width_0, width_1) = match self.horizontal_expanded[0] {
-            None => (quarter_width, quarter_width),
-            Some(true) => (half_width, 0),  // Left expanded
-            Some(false) => (0, half_width), // Right expanded
-        };
-        let (width_2, width_3) = match self.horizontal_expanded[1] {
-            None => (quarter_width, full_area.width - quarter_width * 3), // Account for rounding
-            Some(true) => (half_width, 0),                                // Left expanded
-            Some(false) => (0, half_width),                               // Right expanded
-        };
-
-        // Calculate x positions based on widths
-        let x_1 = full_area.x + width_0;
-        let x_2 = full_area.x + half_width;
-        let x_3 = full_area.x + half_width + width_2;
-
-        // Calculate all 4 pane slot areas (positions 0-3, left to right)
-        // Expanded panes get full height, others get panes_height
-        let all_areas: [Rect; 4] = [
-            // Position 0: leftmost (pane 110)
+        // Always calculate 4 areas in a horizontal row (side by side).
+        // Expanded panes and panes without sub-panes get full height,
+        // others get panes_height
+        let columns = self.columns(full_area);
+        let all_areas: [Rect; 4] = std::array::from_fn(|position| {
+            let (x, width) = columns[position];
             Rect {
-                x: full_area.x,
-                y: full_area.y,
-                width: width_0,
-                height: if self.expanded_positions[0] {
-                    full_area.height
-                } else {
-                    panes_height
-                },
-            },
-            // Position 1: second from left (pane 120)
-            Rect {
-                x: x_1,
-                y: full_area.y,
-                width: width_1,
-                height: if self.expanded_positions[1] {
-                    full_area.height
-                } else {
-                    panes_height
-                },
-            },
-            // Position 2: third from left (pane 210)
-            Rect {
-                x: x_2,
-                y: full_area.y,
-                width: width_2,
-                height: if self.expanded_positions[2] {
-                    full_area.height
-                } else {
-                    panes_height
-                },
-            },
-            // Position 3: rightmost (pane 220)
-            Rect {
-                x: x_3,
+                x,
                 y: full_area.y,
-                width: width_3,
-                height: if self.expanded_positions[3] {
+                width,
+                height: if self.expanded_positions[position]
+                    || self.config.sub_panes.count(position) == 0
+                {
                     full_area.height
                 } else {
                     panes_height
                 },
-            },
-        ];
+            }
+        });
 
         // Clear and recalculate
         self.cached_areas.clear();
         self.empty_pane_areas.clear();
 
         // Assign active panes to positions, track empty slots
-        for (i, area) in all_areas.iter().enumerate() {
-            if i < self.pane_order.len() {
-                let pane_id = self.pane_order[i];
-                self.cached_areas.insert(pane_id, *area);
+        for (i, (pane_id, area)) in self.positions().into_iter().zip(all_areas).enumerate() {
+            if let Some(pane_id) = pane_id {
+                self.cached_areas.insert(pane_id, area);
             } else {
                 // Empty slot - store pane number (1-indexed)
-                self.empty_pane_areas.push((i + 1, *area));
+                self.empty_pane_areas.push((i + 1, area));
+            }
+        }
+        for (pane_id, area) in self.sub_pane_slots.iter().zip(&self.sub_pane_areas) {
+            if let Some(pane_id) = pane_id {
+                self.cached_areas.insert(*pane_id, *area);
             }
         }
 
@@ -448,106 +1697,225 @@ impl PaneManager {
 
     /// Recalculate sub-pane areas.
     ///
-    /// Creates sub-panes for non-expanded positions only.
+    /// Each position's column is divided as declared by the sub-pane
+    /// arrangement; sub-panes of expanded or hidden positions are empty.
     fn recalculate_sub_panes(&mut self, area: Rect) {
         self.sub_pane_areas.clear();
 
-        // 8 sub-pane slots total (2 per pane position)
-        // Sub-pane indices: 0-1 for position 0, 2-3 for position 1, 4-5 for position 2, 6-7 for position 3
-        let quarter_width = area.width / 4;
-        let half_width = area.width / 2;
-        let eighth_width = area.width / 8;
-
-        for i in 0..8 {
-            let position = i / 2; // Which pane position (0-3) this sub-pane belongs to
+        let columns = self.columns(area);
+        for (position, (x, width)) in columns.into_iter().enumerate() {
             let row = position / 2; // Row 0 = positions 0,1; Row 1 = positions 2,3
-
-            // Skip sub-panes for vertically expanded positions
-            if self.expanded_positions[position] {
-                self.sub_pane_areas.push(Rect::default());
-                continue;
+            let is_left = position % 2 == 0;
+            let count = self.config.sub_panes.count(position);
+
+            // Only the expanded side of a horizontally expanded row is shown
+            let hidden = self.horizontal_expanded[row].is_some_and(|left| left != is_left);
+            if hidden || self.expanded_positions[position] {
+                self.sub_pane_areas
+                    .extend(std::iter::repeat_n(Rect::default(), count));
+            } else {
+                let column = Rect {
+                    x,
+                    y: area.y,
+                    width,
+                    height: area.height,
+                };
+                self.sub_pane_areas
+                    .extend(self.config.sub_panes.split(position, column));
             }
+        }
+    }
 
-            // Check horizontal expansion for this row
-            let h_expanded = self.horizontal_expanded[row];
+    /// Height of the pane row before any resize: the configured share of
+    /// the full height.
+    #[allow(clippy::cast_possible_truncation)]
+    fn base_panes_height(&self, height: u16) -> i32 {
+        (f32::from(height) * self.config.sub_pane_ratio).round() as i32
+    }
 
-            // Determine if this sub-pane should be hidden due to horizontal expansion
-            let is_hidden = match h_expanded {
-                None => false,
-                Some(true) => position == 1 || position == 3, // Right panes hidden
-                Some(false) => position == 0 || position == 2, // Left panes hidden
-            };
+    /// Height of the pane row, with the border moved by resizes.
+    fn panes_height(&self, height: u16) -> u16 {
+        let base = self.base_panes_height(height);
+        if self.row_offset == 0 {
+            return u16::try_from(base).unwrap_or(height);
+        }
+        let min = i32::from(MIN_PANE_SIZE);
+        let resized = (base + self.row_offset)
+            .min(i32::from(height) - min)
+            .max(min);
+        u16::try_from(resized.clamp(0, i32::from(height))).unwrap_or(height)
+    }
 
-            if is_hidden {
-                self.sub_pane_areas.push(Rect::default());
-                continue;
+    /// Left edges of the four columns and the right edge of the last, with
+    /// the borders moved by resizes.
+    fn column_edges(&self, area: Rect) -> [u16; 5] {
+        let width = i32::from(area.width);
+        let min = i32::from(MIN_PANE_SIZE);
+        let mut edges = base_column_edges(width);
+        if self.column_offsets != [0; 3] {
+            for border in 1..4 {
+                // Leave room for the columns on either side
+                let right = 4 - i32::try_from(border).unwrap_or(0);
+                edges[border] = (edges[border] + self.column_offsets[border - 1])
+                    .min(width - min * right)
+                    .max(edges[border - 1] + min);
             }
+        }
+        edges.map(|edge| area.x + u16::try_from(edge.clamp(0, width)).unwrap_or(0))
+    }
 
-            // Calculate width and x position based on horizontal expansion
-            let (x, width) = match h_expanded {
-                None => {
-                    // Normal layout: 8 sub-panes, each 1/8 width
-                    let x = area.x + (i as u16 * eighth_width);
-                    let width = if i == 7 {
-                        area.width - (7 * eighth_width)
-                    } else {
-                        eighth_width
-                    };
-                    (x, width)
-                }
+    /// X and width of the column below each position. A horizontally
+    /// expanded row gives its whole half to one position; the other gets
+    /// no width.
+    fn columns(&self, area: Rect) -> [(u16, u16); 4] {
+        let edges = self.column_edges(area);
+        let mut columns: [(u16, u16); 4] = std::array::from_fn(|position| {
+            (edges[position], edges[position + 1] - edges[position])
+        });
+        for (row, expanded) in self.horizontal_expanded.iter().enumerate() {
+            let (left, right) = (2 * row, 2 * row + 1);
+            let span = (edges[left], edges[right + 1] - edges[left]);
+            match expanded {
                 Some(true) => {
-                    // Left pane expanded: sub-panes 0-1 or 4-5 take 1/4 each (total 1/2)
-                    let local_idx = i % 2; // 0 or 1 within the pair
-                    let base_x = if row == 0 {
-                        area.x
-                    } else {
-                        area.x + half_width
-                    };
-                    let x = base_x + (local_idx as u16 * quarter_width);
-                    (x, quarter_width)
+                    columns[left] = span;
+                    columns[right] = (edges[right + 1], 0);
                 }
                 Some(false) => {
-                    // Right pane expanded: sub-panes 2-3 or 6-7 take 1/4 each (total 1/2)
-                    let local_idx = i % 2; // 0 or 1 within the pair
-                    let base_x = if row == 0 {
-                        area.x
-                    } else {
-                        area.x + half_width
-                    };
-                    let x = base_x + (local_idx as u16 * quarter_width);
-                    (x, quarter_width)
+                    columns[left] = (edges[left], 0);
+                    columns[right] = span;
                 }
-            };
+                None => {}
+            }
+        }
+        columns
+    }
 
-            self.sub_pane_areas.push(Rect {
-                x,
-                y: area.y,
-                width,
-                height: area.height,
-            });
+    /// Move a border of the focused pane by `cells`, growing or shrinking
+    /// it and its neighbour.
+    ///
+    /// Left and right move the pane's right border, or its left border if
+    /// it is the rightmost pane. Up and down move the border between the
+    /// panes and the sub-panes, which all positions share. Borders stop
+    /// before a pane gets narrower or lower than a few cells.
+    ///
+    /// Returns `true` if the layout changed.
+    pub fn resize_focused(&mut self, direction: Direction, cells: u16) -> bool {
+        let (Some(full_area), Some(focused)) = (self.terminal_size, self.focused) else {
+            return false;
+        };
+        let position = if let Some(position) = self.position_of(focused) {
+            position
+        } else if let Some((position, _)) = self
+            .sub_pane_slots
+            .iter()
+            .position(|&id| id == Some(focused))
+            .and_then(|index| self.config.sub_panes.locate(index))
+        {
+            position
+        } else {
+            return false;
+        };
+
+        let delta = match direction {
+            Direction::Left | Direction::Up => -i32::from(cells),
+            Direction::Right | Direction::Down => i32::from(cells),
+        };
+        let changed = match direction {
+            Direction::Left | Direction::Right => {
+                // An expanded row shows a single pane spanning its half
+                let border = if self.horizontal_expanded[position / 2].is_some() {
+                    2
+                } else {
+                    (position + 1).min(3)
+                };
+                let before = self.column_edges(full_area);
+                self.column_offsets[border - 1] += delta;
+                let after = self.column_edges(full_area);
+                // Forget movement past the limits, so it need not be undone
+                let base = base_column_edges(i32::from(full_area.width));
+                for border in 1..4 {
+                    self.column_offsets[border - 1] =
+                        i32::from(after[border] - full_area.x) - base[border];
+                }
+                before != after
+            }
+            Direction::Up | Direction::Down => {
+                if self.expanded_positions[position] || self.config.sub_panes.count(position) == 0 {
+                    return false;
+                }
+                let before = self.panes_height(full_area.height);
+                self.row_offset += delta;
+                let after = self.panes_height(full_area.height);
+                self.row_offset = i32::from(after) - self.base_panes_height(full_area.height);
+                before != after
+            }
+        };
+        if changed {
+            self.recalculate_layout();
+            let _ = self.resize_all_panes();
         }
+        changed
     }
 
     /// Resize all panes to match their calculated areas.
+    #[cfg_attr(
+        feature = "trace",
+        tracing::instrument(level = "debug", skip_all, fields(panes = self.panes.len()))
+    )]
     fn resize_all_panes(&mut self) -> Result<()> {
+        self.update_visibility();
+        if let Some((pane_id, area)) = self.popup() {
+            let size = PaneSize::new(area.height.saturating_sub(2), area.width.saturating_sub(2));
+            self.resize_pane(pane_id, size)?;
+        }
         for (pane_id, area) in &self.cached_areas {
-            // Subtract 2 for border (1 on each side)
-            let inner_width = area.width.saturating_sub(2);
-            let inner_height = area.height.saturating_sub(2);
+            // Hidden panes keep their size until they are shown again
+            if area.is_empty() {
+                continue;
+            }
+            // Subtract 2 for border (1 on each side), then the padding
+            let padding = self.pane_padding();
+            let inner_width = area.width.saturating_sub(2 + padding.left + padding.right);
+            let inner_height = area.height.saturating_sub(2 + padding.top + padding.bottom);
 
             if let Some(managed) = self.panes.get(pane_id) {
                 let size = PaneSize::new(inner_height, inner_width);
-                pty::resize_pty(managed.pty_master.as_ref(), size)?;
+                pty::resize_pty(
+                    &managed.pty_master.lock().expect("pty master lock poisoned"),
+                    size,
+                )?;
+                if managed.handle.resize_screen(size) {
+                    self.event_tx.push(PaneEvent::Resized {
+                        pane_id: *pane_id,
+                        size,
+                    });
+                }
             }
         }
         Ok(())
     }
 
+    /// Tell each pane whether it is shown, so collapsed panes can defer
+    /// terminal emulation until they are expanded again.
+    fn update_visibility(&self) {
+        for (pane_id, managed) in &self.panes {
+            let visible = self
+                .pane_content_area(*pane_id)
+                .is_none_or(|area| !area.is_empty());
+            managed.output.set_visible(visible);
+        }
+    }
+
     /// Calculate initial pane size for spawning.
+    #[allow(
+        clippy::cast_possible_truncation,
+        clippy::cast_sign_loss,
+        clippy::match_same_arms
+    )]
     fn calculate_initial_pane_size(&self) -> PaneSize {
         if let Some(mut area) = self.terminal_size {
             // Reduce available height for sub-panes
-            area.height = (f32::from(area.height) * self.sub_pane_ratio).round() as u16;
+            area.height = (f32::from(area.height) * self.config.sub_pane_ratio).round() as u16;
 
             // Estimate size based on how many panes will exist
             let future_pane_count = self.panes.len() + 1;
@@ -557,7 +1925,11 @@ impl PaneManager {
                 3 | 4 => (area.width / 4 - 1, area.height.saturating_sub(2)),
                 _ => (area.width / 4 - 1, area.height.saturating_sub(2)),
             };
-            PaneSize::new(height, width)
+            let padding = self.pane_padding();
+            PaneSize::new(
+                height.saturating_sub(padding.top + padding.bottom),
+                width.saturating_sub(padding.left + padding.right),
+            )
         } else {
             // Default fallback size
             PaneSize::new(24, 80)
@@ -577,45 +1949,430 @@ impl PaneManager {
         managed.handle.send_input(data).await
     }
 
+    /// Wait until the processes of all current panes have exited.
+    ///
+    /// The returned future does not borrow the manager, so it can be
+    /// awaited while the manager keeps running (e.g. behind a lock).
+    /// Final states are returned in layout order.
+    pub fn wait_all_exited(
+        &self,
+    ) -> impl Future<Output = Vec<(PaneId, PaneState)>> + Send + 'static {
+        let handles: Vec<_> = self
+            .pane_order
+            .iter()
+            .chain(self.sub_pane_slots.iter().flatten())
+            .filter_map(|id| self.panes.get(id))
+            .map(|managed| managed.handle.clone())
+            .collect();
+        async move {
+            let mut states = Vec::with_capacity(handles.len());
+            for handle in handles {
+                states.push((handle.id(), handle.wait_exit().await));
+            }
+            states
+        }
+    }
+
     /// Route a key event to the focused pane.
     ///
     /// # Errors
     /// Returns an error if input routing fails.
     pub async fn route_key(&self, key: KeyEvent) -> Result<()> {
-        let bytes = key_to_bytes(key);
+        let bytes = self.key_bytes(key);
         if !bytes.is_empty() {
             self.send_input(&bytes).await?;
         }
         Ok(())
     }
 
+    /// Send input to the focused pane, blocking while its input queue is
+    /// full. Must not be called from async code.
+    ///
+    /// # Errors
+    /// Returns an error if no pane is focused or input sending fails.
+    #[cfg(feature = "sync")]
+    pub fn send_input_blocking(&self, data: &[u8]) -> Result<()> {
+        let pane_id = self.focused.ok_or(Error::PaneClosed)?;
+        let managed = self
+            .panes
+            .get(&pane_id)
+            .ok_or(Error::PaneNotFound(pane_id.0))?;
+        managed.handle.send_input_blocking(data)
+    }
+
+    /// Route a key event to the focused pane, blocking while its input
+    /// queue is full. Must not be called from async code.
+    ///
+    /// # Errors
+    /// Returns an error if input routing fails.
+    #[cfg(feature = "sync")]
+    pub fn route_key_blocking(&self, key: KeyEvent) -> Result<()> {
+        let bytes = self.key_bytes(key);
+        if !bytes.is_empty() {
+            self.send_input_blocking(&bytes)?;
+        }
+        Ok(())
+    }
+
+    /// Encode a key for the focused pane's input modes.
+    fn key_bytes(&self, key: KeyEvent) -> Vec<u8> {
+        let modes = self
+            .focused
+            .and_then(|id| self.panes.get(&id))
+            .map(|managed| managed.handle.input_modes())
+            .unwrap_or_default();
+        key_to_bytes(key, modes, self.config.keyboard_protocol)
+    }
+
+    /// Take bytes that should be written verbatim to the host terminal.
+    ///
+    /// Panes may emit sequences meant for the real terminal. Only the
+    /// focused pane's requests are returned; those from other panes are
+    /// discarded. Call this after drawing and write the result to the
+    /// terminal's output.
+    ///
+    /// Keyboard protocol negotiation is tracked per pane rather than
+    /// forwarded: the host's kitty flag stack and `modifyOtherKeys` level
+    /// are brought in line with the focused pane, so they follow focus
+    /// changes and are reset when the focused pane's program exits.
+    ///
+    /// When cursor shape forwarding is enabled and focus moved since the
+    /// last call, the newly focused pane's cursor style is emitted first.
+    ///
+    /// With graphics passthrough enabled, inline images from every visible
+    /// pane are appended, positioned inside the pane's borders. Images
+    /// that fall outside their pane are dropped.
+    ///
+    /// Text copied with [`copy_selection`](Self::copy_selection) comes
+    /// first, as an OSC 52 clipboard request.
+    #[must_use]
+    pub fn take_passthrough(&mut self) -> Vec<u8> {
+        let mut output = std::mem::take(&mut self.clipboard);
+        if self.passthrough_focus != self.focused {
+            self.passthrough_focus = self.focused;
+            if self.config.forward_cursor_shape {
+                if let Some(handle) = self.focused.and_then(|id| self.get_pane(id)) {
+                    output.extend(handle.cursor_style().to_decscusr());
+                }
+            }
+        }
+        let keyboard = self
+            .focused
+            .and_then(|id| self.get_pane(id))
+            .filter(|handle| handle.is_alive())
+            .map(PaneHandle::keyboard_state)
+            .unwrap_or_default();
+        output.extend(self.host_keyboard.transition_to(&keyboard));
+        self.host_keyboard = keyboard;
+        for (pane_id, managed) in &self.panes {
+            let bytes = managed.handle.take_passthrough();
+            if self.focused == Some(*pane_id) {
+                output.extend(bytes);
+            }
+        }
+        for (pane_id, managed) in &self.panes {
+            let images = managed.handle.take_images();
+            let Some(inner) = self.pane_content_area(*pane_id) else {
+                continue;
+            };
+            for image in images {
+                if image.row < inner.height && image.col < inner.width {
+                    output.extend(image.to_host_bytes((inner.x, inner.y)));
+                }
+            }
+        }
+        output
+    }
+
+    /// Content area of a visible pane, inside the borders and padding
+    /// `CockpitWidget` draws it with: only the first pane, and the first
+    /// visible sub-pane, have a left border.
+    pub(crate) fn pane_content_area(&self, pane_id: PaneId) -> Option<Rect> {
+        let area = *self.cached_areas.get(&pane_id)?;
+        let first = match self
+            .sub_pane_slots
+            .iter()
+            .position(|&slot| slot == Some(pane_id))
+        {
+            Some(idx) => self.sub_pane_areas.iter().take(idx).all(|r| r.is_empty()),
+            None => !self.cached_areas.iter().any(|(&id, other)| {
+                !self.sub_pane_slots.contains(&Some(id)) && (other.x, id.0) < (area.x, pane_id.0)
+            }),
+        };
+        Some(crate::widget::pane_inner_area(
+            &self.config.theme.borders,
+            area,
+            first,
+        ))
+    }
+
     /// Poll for pane events without blocking.
+    ///
+    /// Output notifications are coalesced to at most one per pane per call.
+    /// Output that was dropped by the overflow policy is still reported.
     pub fn poll_events(&mut self) -> Vec<PaneEvent> {
         let mut events = Vec::new();
-        while let Ok(event) = self.event_rx.try_recv() {
+        for mut event in self.event_rx.drain() {
+            if let PaneEvent::Output { pane_id, size } = &mut event {
+                let pending = self.panes.get(pane_id).map_or(0, |managed| {
+                    managed.output.pending_output.swap(0, Ordering::AcqRel)
+                });
+                if pending == 0 {
+                    continue;
+                }
+                *size = pending;
+            } else {
+                // Exits change how panes are drawn even without new output
+                self.dirty = true;
+            }
             events.push(event);
         }
+        for (pane_id, managed) in &self.panes {
+            let pending = managed.output.pending_output.swap(0, Ordering::AcqRel);
+            if pending > 0 {
+                events.push(PaneEvent::Output {
+                    pane_id: *pane_id,
+                    size: pending,
+                });
+            }
+            // Readers paused for lagging consumers may continue
+            managed.output.resume();
+        }
+        if self
+            .plugin_registry
+            .as_ref()
+            .is_some_and(PluginRegistry::wants_events)
+        {
+            self.plugin_events.extend_from_slice(&events);
+        }
+        self.run_event_hooks(&events);
+        self.apply_exit_behaviors(&events);
+        self.run_scheduled_restarts();
         events
     }
 
+    /// Apply each exited pane's [`ExitBehavior`].
+    fn apply_exit_behaviors(&mut self, events: &[PaneEvent]) {
+        for event in events {
+            let (PaneEvent::Exited { pane_id, .. } | PaneEvent::Crashed { pane_id, .. }) = event
+            else {
+                continue;
+            };
+            let Some(managed) = self.panes.get(pane_id) else {
+                continue;
+            };
+            if self.is_popup(*pane_id) {
+                self.close_pane(*pane_id);
+                continue;
+            }
+            let behavior = managed
+                .config
+                .on_exit
+                .as_ref()
+                .unwrap_or(&self.config.on_exit);
+            match behavior {
+                ExitBehavior::KeepPane => {}
+                ExitBehavior::ClosePane => self.close_pane(*pane_id),
+                ExitBehavior::Restart { max, backoff } => {
+                    let (max, backoff) = (*max, *backoff);
+                    let now = task::Instant::now();
+                    let restarts = crate::pane::restarts_in_a_row(
+                        backoff,
+                        managed.restarts,
+                        now.duration_since(managed.started),
+                    );
+                    if let Some(managed) = self.panes.get_mut(pane_id) {
+                        managed.restarts = restarts;
+                    }
+                    if max.is_some_and(|max| restarts >= max) {
+                        continue;
+                    }
+                    let delay = crate::pane::restart_delay(backoff, restarts);
+                    self.scheduled_restarts.push((*pane_id, now + delay));
+                }
+                ExitBehavior::RunCommand(config) => {
+                    let config = (**config).clone();
+                    self.replace_pane(*pane_id, config, 0);
+                }
+            }
+        }
+    }
+
+    /// Restart panes whose backoff has elapsed.
+    fn run_scheduled_restarts(&mut self) {
+        let now = task::Instant::now();
+        let (due, waiting) = std::mem::take(&mut self.scheduled_restarts)
+            .into_iter()
+            .partition(|(_, at)| *at <= now);
+        self.scheduled_restarts = waiting;
+        for (pane_id, _) in due {
+            // The pane may have been closed while waiting
+            let Some(managed) = self.panes.get(&pane_id) else {
+                continue;
+            };
+            let (config, restarts) = (managed.config.clone(), managed.restarts + 1);
+            self.replace_pane(pane_id, config, restarts);
+        }
+    }
+
+    /// Close a pane and spawn `config` in its layout slot, keeping focus.
+    fn replace_pane(&mut self, pane_id: PaneId, config: SpawnConfig, restarts: u32) {
+        let result = self.in_workspace_of(pane_id, |manager| {
+            manager.replace_in_workspace(pane_id, config, restarts)
+        });
+        if let Err(e) = result {
+            tracing::warn!("Failed to restart pane {}: {}", pane_id, e);
+        }
+    }
+
+    /// Run a pane's command again in its place, keeping its slot, pin and
+    /// focus; typically once its process has exited or crashed. Returns
+    /// the ID of the new pane.
+    ///
+    /// # Errors
+    /// Returns [`Error::PaneNotFound`] if the pane does not exist, or the
+    /// error spawning the new pane. The old pane is closed either way.
+    pub fn restart_pane(&mut self, pane_id: PaneId) -> Result<PaneId> {
+        let config = self
+            .panes
+            .get(&pane_id)
+            .map(|managed| managed.config.clone())
+            .ok_or(Error::PaneNotFound(pane_id.0))?;
+        self.in_workspace_of(pane_id, |manager| {
+            manager.replace_in_workspace(pane_id, config, 0)
+        })
+    }
+
+    fn replace_in_workspace(
+        &mut self,
+        pane_id: PaneId,
+        mut config: SpawnConfig,
+        restarts: u32,
+    ) -> Result<PaneId> {
+        let slot = self.pane_order.iter().position(|&id| id == pane_id);
+        let pinned = self.pinned.get(&pane_id).copied();
+        if let Some(managed) = self.panes.get(&pane_id) {
+            if managed.config.slot != Slot::Pane {
+                config.slot = managed.config.slot;
+            }
+        }
+        let was_focused = self.focused == Some(pane_id);
+        self.close_pane(pane_id);
+        let new_id = self.spawn(config)?.id();
+        if let Some(managed) = self.panes.get_mut(&new_id) {
+            managed.restarts = restarts;
+        }
+        if let Some(position) = pinned {
+            self.pinned.insert(new_id, position);
+        }
+        if let Some(slot) = slot {
+            self.pane_order.retain(|&id| id != new_id);
+            self.pane_order
+                .insert(slot.min(self.pane_order.len()), new_id);
+            self.recalculate_layout();
+            let _ = self.resize_all_panes();
+        }
+        if was_focused {
+            self.change_focus(Some(new_id));
+        }
+        Ok(new_id)
+    }
+
+    /// Run the hooks for polled events and apply their actions.
+    fn run_event_hooks(&mut self, events: &[PaneEvent]) {
+        let watches_output = self.hooks.watches_output();
+        let mut actions = Vec::new();
+        for event in events {
+            let pane_id = match event {
+                PaneEvent::Exited { pane_id, .. }
+                | PaneEvent::Crashed { pane_id, .. }
+                | PaneEvent::Output { pane_id, .. } => *pane_id,
+                _ => continue,
+            };
+            let Some(managed) = self.panes.get(&pane_id) else {
+                continue;
+            };
+            let action = match event {
+                PaneEvent::Exited { code, .. } => self
+                    .hooks
+                    .dispatch(&managed.handle, &HookEvent::Exited { code: *code }),
+                PaneEvent::Crashed { signal, error, .. } => self.hooks.dispatch(
+                    &managed.handle,
+                    &HookEvent::Crashed {
+                        signal: *signal,
+                        error,
+                    },
+                ),
+                _ if watches_output => self.hooks.dispatch_output(&managed.handle),
+                _ => HookAction::None,
+            };
+            if action != HookAction::None {
+                actions.push((pane_id, action));
+            }
+        }
+        for (pane_id, action) in actions {
+            self.apply_hook_action(pane_id, action);
+        }
+    }
+
+    /// I/O and render statistics of all panes, busiest pane first.
+    #[must_use]
+    pub fn stats(&self) -> ManagerStats {
+        ManagerStats::new(
+            self.panes
+                .iter()
+                .map(|(id, managed)| (*id, managed.handle.stats()))
+                .collect(),
+        )
+    }
+
+    /// Number of pane events discarded because the event queue was full.
+    ///
+    /// Always zero with [`OverflowPolicy::Block`].
+    #[must_use]
+    pub fn dropped_events(&self) -> u64 {
+        self.event_rx.dropped()
+    }
+
     /// Close a pane.
     ///
     /// Layout is automatically recalculated after closing.
     pub fn close_pane(&mut self, pane_id: PaneId) {
+        self.hooks.forget(pane_id);
         if let Some(managed) = self.panes.remove(&pane_id) {
             // Abort tasks
+            managed.output.close();
             managed.reader_handle.abort();
             managed.writer_handle.abort();
             managed.monitor_handle.abort();
+            self.event_tx.push(PaneEvent::PaneClosed { pane_id });
         }
 
-        // Remove from pane_order
+        // Remove from pane_order or its sub-pane slot
         self.pane_order.retain(|&id| id != pane_id);
+        self.pinned.remove(&pane_id);
+        for slot in &mut self.sub_pane_slots {
+            if *slot == Some(pane_id) {
+                *slot = None;
+            }
+        }
+        for workspace in &mut self.workspaces {
+            workspace.remove(pane_id);
+        }
+        let return_focus = if self.is_popup(pane_id) {
+            self.popup
+                .take()
+                .and_then(|popup| popup.return_focus)
+                .filter(|id| self.panes.contains_key(id))
+        } else {
+            None
+        };
 
         // Update focus if needed
         if self.focused == Some(pane_id) {
-            self.focused = self.pane_order.first().copied();
+            self.change_focus(return_focus.or_else(|| self.pane_order.first().copied()));
         }
+        self.dirty = true;
 
         // Recalculate layout
         self.recalculate_layout();
@@ -624,9 +2381,17 @@ impl PaneManager {
         let _ = self.resize_all_panes();
     }
 
-    /// Cycle focus to the next pane.
+    /// Cycle focus to the next pane of the active workspace.
     pub fn focus_next(&mut self) {
-        let ids: Vec<_> = self.panes.keys().copied().collect();
+        if self.popup.is_some() {
+            return;
+        }
+        let ids: Vec<_> = self
+            .panes
+            .keys()
+            .copied()
+            .filter(|&id| self.in_active_workspace(id))
+            .collect();
         if ids.is_empty() {
             return;
         }
@@ -634,12 +2399,20 @@ impl PaneManager {
         let current = self.focused.unwrap_or(ids[0]);
         let pos = ids.iter().position(|&id| id == current).unwrap_or(0);
         let next_pos = (pos + 1) % ids.len();
-        self.focused = Some(ids[next_pos]);
+        self.change_focus(Some(ids[next_pos]));
     }
 
-    /// Cycle focus to the previous pane.
+    /// Cycle focus to the previous pane of the active workspace.
     pub fn focus_prev(&mut self) {
-        let ids: Vec<_> = self.panes.keys().copied().collect();
+        if self.popup.is_some() {
+            return;
+        }
+        let ids: Vec<_> = self
+            .panes
+            .keys()
+            .copied()
+            .filter(|&id| self.in_active_workspace(id))
+            .collect();
         if ids.is_empty() {
             return;
         }
@@ -647,7 +2420,7 @@ impl PaneManager {
         let current = self.focused.unwrap_or(ids[0]);
         let pos = ids.iter().position(|&id| id == current).unwrap_or(0);
         let prev_pos = if pos == 0 { ids.len() - 1 } else { pos - 1 };
-        self.focused = Some(ids[prev_pos]);
+        self.change_focus(Some(ids[prev_pos]));
     }
 
     /// Find which pane contains the given screen coordinates.
@@ -674,63 +2447,319 @@ impl PaneManager {
     /// Returns `true` if focus was changed, `false` if no pane was found
     /// at the position or if the clicked pane was already focused.
     pub fn focus_at_position(&mut self, x: u16, y: u16, areas: &HashMap<PaneId, Rect>) -> bool {
-        if let Some(pane_id) = self.pane_at_position(x, y, areas) {
-            if self.focused != Some(pane_id) {
-                self.focused = Some(pane_id);
-                return true;
-            }
+        self.pane_at_position(x, y, areas)
+            .is_some_and(|pane_id| self.change_focus(Some(pane_id)))
+    }
+
+    /// Clickable navigation arrows as drawn with the manager's theme, in
+    /// the order clicks are tested.
+    #[must_use]
+    pub fn navigation_regions(&self) -> Vec<NavigationRegion> {
+        // Sort by x coordinate to ensure consistent position ordering (0-3 = left to right)
+        let mut pane_areas: Vec<_> = self
+            .pane_order
+            .iter()
+            .filter_map(|id| self.cached_areas.get(id).copied())
+            .collect();
+        pane_areas.sort_by_key(|rect| rect.x);
+        NavigationLayout {
+            pane_areas: &pane_areas,
+            expanded_positions: self.expanded_positions,
+            horizontal_expanded: self.horizontal_expanded,
+            sub_pane_areas: &self.sub_pane_areas,
+            arrangement: &self.config.sub_panes,
         }
-        false
+        .regions(&self.config.theme.arrows)
     }
 
     /// Handle a mouse click at the given screen coordinates.
     ///
     /// This is the unified click handler that:
-    /// 1. First checks if clicking an up arrow on expanded pane → collapses pane
-    /// 2. Then checks if clicking a down arrow on sub-pane → expands pane
+    /// 1. First checks if clicking a workspace tab → switches workspace
+    ///    (see [`set_tab_bar_area`](Self::set_tab_bar_area))
+    /// 2. Then checks if clicking a navigation arrow → toggles expansion
+    ///    (see [`navigation_regions`](Self::navigation_regions))
     /// 3. Otherwise checks if clicking a pane → changes focus
     ///
-    /// Returns `true` if any action was taken (expansion toggled or focus changed).
+    /// Returns `true` if any action was taken (workspace switched, expansion
+    /// toggled or focus changed).
     pub fn handle_click(&mut self, x: u16, y: u16) -> bool {
-        // First check for up arrow clicks on expanded panes (collapse)
-        // Sort by x coordinate to ensure consistent position ordering (0-3 = left to right)
-        let mut areas_vec: Vec<_> = self
-            .cached_areas
-            .iter()
-            .map(|(&id, &rect)| (id, rect))
-            .collect();
-        areas_vec.sort_by_key(|(_, rect)| rect.x);
-        if let Some(position) = up_arrow_at_position(x, y, &areas_vec, &self.expanded_positions) {
-            self.toggle_pane_expansion(position);
-            return true;
+        // The popup keeps focus until it closes
+        if self.popup.is_some() {
+            return false;
+        }
+        if let Some(area) = self
+            .tab_bar_area
+            .filter(|area| area.contains((x, y).into()))
+        {
+            let config = self.config.theme.tab_bar.clone().unwrap_or_default();
+            let tabs = self.workspace_tabs();
+            return config.tab_at(&tabs, area, x, y).is_some_and(|index| {
+                index != self.active_workspace && self.switch_workspace(&tabs[index].name).is_ok()
+            });
+        }
+        let clicked = self
+            .navigation_regions()
+            .into_iter()
+            .find(|region| region.area.contains((x, y).into()));
+        match clicked.map(|region| region.target) {
+            Some(NavigationTarget::Collapse(position)) => {
+                self.toggle_pane_expansion(position);
+                true
+            }
+            Some(NavigationTarget::Expand(arrow)) => {
+                self.toggle_pane_expansion(arrow.pane_position());
+                true
+            }
+            Some(NavigationTarget::Horizontal(arrow)) => {
+                let source_position = arrow.source_position();
+                let row = source_position / 2; // Row 0 = top, Row 1 = bottom
+                let expand_left = source_position % 2 == 0; // Even positions are left panes
+                self.toggle_horizontal_expansion(row, expand_left);
+                true
+            }
+            None => {
+                // Otherwise handle pane focus
+                let areas = self.cached_areas.clone();
+                self.focus_at_position(x, y, &areas)
+            }
         }
+    }
 
-        // Then check for down arrow clicks on sub-panes (expand)
-        if let Some(arrow) = down_arrow_at_position(x, y, &self.sub_pane_areas) {
-            self.toggle_pane_expansion(arrow.pane_position());
-            return true;
+    /// Handle a mouse event from the host terminal.
+    ///
+    /// Presses are passed to [`handle_click`](Self::handle_click). Inside a
+    /// pane, dragging with the left button selects text, scrolling into
+    /// scrollback when dragged past the top or bottom edge; a double click
+    /// selects the word under the pointer and a triple click the whole
+    /// line. Finished selections are reported as [`PaneEvent::Selected`]
+    /// and, with [`copy_on_select`](ManagerConfig::copy_on_select), copied
+    /// to the clipboard. A single click clears the selection. Clicking or
+    /// dragging along the scrollbar of a pane scrolled back scrolls it to
+    /// that point of its history. While a popup is open, mouse events are
+    /// ignored.
+    ///
+    /// Returns `true` if any action was taken.
+    pub fn handle_mouse(&mut self, event: MouseEvent) -> bool {
+        if self.popup.is_some() {
+            return false;
+        }
+        let (x, y) = (event.column, event.row);
+        match event.kind {
+            MouseEventKind::Down(MouseButton::Left) => {
+                self.drag = None;
+                self.scrollbar_drag = self.scrollbar_at(x, y);
+                if let Some(pane_id) = self.scrollbar_drag {
+                    return self.scroll_to_scrollbar(pane_id, y);
+                }
+                let clicks = self.clicks.click(x, y, std::time::Instant::now());
+                if clicks > 1 && self.select_at(x, y, clicks) {
+                    return true;
+                }
+                let cleared = self.clear_selection();
+                if let Some((pane_id, inner)) = self.content_at(x, y) {
+                    let offset = self.get_pane(pane_id).map_or(0, PaneHandle::scroll_offset);
+                    let anchor = (selection_row(offset, y - inner.y), x - inner.x);
+                    self.drag = Some((pane_id, anchor));
+                }
+                self.handle_click(x, y) || cleared
+            }
+            MouseEventKind::Down(_) => self.handle_click(x, y),
+            MouseEventKind::Drag(MouseButton::Left) => match self.scrollbar_drag {
+                Some(pane_id) => self.scroll_to_scrollbar(pane_id, y),
+                None => self.drag_to(x, y),
+            },
+            MouseEventKind::Up(MouseButton::Left) => {
+                self.scrollbar_drag = None;
+                let Some((pane_id, _)) = self.drag.take() else {
+                    return false;
+                };
+                let Some(text) = self.get_pane(pane_id).and_then(PaneHandle::selected_text) else {
+                    return false;
+                };
+                self.finish_selection(pane_id, text);
+                true
+            }
+            _ => false,
         }
+    }
 
-        // Check for horizontal arrow clicks (horizontal pane expansion)
-        if let Some(arrow) = horizontal_arrow_at_position(x, y, &self.sub_pane_areas) {
-            let source_position = arrow.source_position();
-            let row = source_position / 2; // Row 0 = top, Row 1 = bottom
-            let expand_left = source_position % 2 == 0; // Even positions are left panes
-            self.toggle_horizontal_expansion(row, expand_left);
-            return true;
+    /// Visible pane whose content area contains a screen position.
+    fn content_at(&self, x: u16, y: u16) -> Option<(PaneId, Rect)> {
+        self.cached_areas
+            .keys()
+            .filter_map(|&pane_id| Some((pane_id, self.pane_content_area(pane_id)?)))
+            .find(|(_, inner)| inner.contains((x, y).into()))
+    }
+
+    /// Visible pane, scrolled back, whose scrollbar covers a screen
+    /// position. Scrollbars are drawn over the right border of panes.
+    fn scrollbar_at(&self, x: u16, y: u16) -> Option<PaneId> {
+        self.config.theme.scrollbar.as_ref()?;
+        self.cached_areas.iter().find_map(|(&pane_id, area)| {
+            let inner = self.pane_content_area(pane_id)?;
+            let on_scrollbar =
+                area.right().checked_sub(1) == Some(x) && (inner.y..inner.bottom()).contains(&y);
+            (on_scrollbar && self.get_pane(pane_id)?.scroll_offset() > 0).then_some(pane_id)
+        })
+    }
+
+    /// Scroll a pane to the point of its history at row `y` of its
+    /// scrollbar.
+    fn scroll_to_scrollbar(&mut self, pane_id: PaneId, y: u16) -> bool {
+        let (Some(inner), Some(handle)) = (self.pane_content_area(pane_id), self.get_pane(pane_id))
+        else {
+            return false;
+        };
+        if inner.is_empty() {
+            return false;
+        }
+        let row = y.clamp(inner.y, inner.bottom() - 1) - inner.y;
+        handle.set_scroll_offset(scrollbar_offset(
+            handle.max_scroll_offset(),
+            row,
+            inner.height,
+        ));
+        self.dirty = true;
+        true
+    }
+
+    /// Extend the dragged selection to a screen position.
+    fn drag_to(&mut self, x: u16, y: u16) -> bool {
+        let Some((pane_id, anchor)) = self.drag else {
+            return false;
+        };
+        let (Some(inner), Some(handle)) = (
+            self.pane_content_area(pane_id),
+            self.get_pane(pane_id).cloned(),
+        ) else {
+            return false;
+        };
+        if inner.is_empty() {
+            return false;
+        }
+        // Past the top or bottom edge, scroll a line per movement
+        let offset = handle.scroll_offset();
+        if y < inner.y {
+            handle.set_scroll_offset(offset + 1);
+        } else if y >= inner.bottom() && offset > 0 {
+            handle.set_scroll_offset(offset - 1);
+        }
+        let row = y.clamp(inner.y, inner.bottom() - 1) - inner.y;
+        let col = x.clamp(inner.x, inner.right() - 1) - inner.x;
+        let head = (selection_row(handle.scroll_offset(), row), col);
+        handle.set_selection(Selection::new(anchor, head, SelectionMode::Cell));
+        self.dirty = true;
+        true
+    }
+
+    /// Select the word (two clicks) or line (three clicks) at a screen
+    /// position. Returns `false` if the position is not on pane content.
+    fn select_at(&mut self, x: u16, y: u16, clicks: u8) -> bool {
+        let Some((pane_id, inner)) = self.content_at(x, y) else {
+            return false;
+        };
+        let Some(handle) = self.get_pane(pane_id).cloned() else {
+            return false;
+        };
+        let (row, col) = (y - inner.y, x - inner.x);
+        let selection = {
+            let parser = handle.screen().read().expect("screen lock poisoned");
+            let screen = parser.screen();
+            if clicks == 2 {
+                Selection::word_at(screen, row, col)
+            } else {
+                Selection::line_at(screen, row)
+            }
+        };
+        self.clear_selection();
+        handle.set_selection(selection);
+        self.dirty = true;
+        let text = handle.selected_text().unwrap_or_default();
+        self.finish_selection(pane_id, text);
+        true
+    }
+
+    /// Report a finished selection and copy it if configured to.
+    fn finish_selection(&mut self, pane_id: PaneId, text: String) {
+        if self.config.copy_on_select {
+            self.clipboard.extend(osc52(&text));
+        }
+        self.event_tx.push(PaneEvent::Selected { pane_id, text });
+    }
+
+    /// Clear the selection in every pane. Returns `true` if there was one.
+    pub fn clear_selection(&mut self) -> bool {
+        let mut cleared = false;
+        for managed in self.panes.values() {
+            if managed.handle.selection().is_some() {
+                managed.handle.clear_selection();
+                cleared = true;
+            }
         }
+        self.dirty |= cleared;
+        cleared
+    }
 
-        // Otherwise handle pane focus
-        let areas = self.cached_areas.clone();
-        self.focus_at_position(x, y, &areas)
+    /// Copy the selected text of a pane to the host terminal's clipboard.
+    ///
+    /// The request is written by the next
+    /// [`take_passthrough`](Self::take_passthrough). Returns `false` if
+    /// the pane has no selection.
+    pub fn copy_selection(&mut self, pane_id: PaneId) -> bool {
+        let Some(text) = self.get_pane(pane_id).and_then(PaneHandle::selected_text) else {
+            return false;
+        };
+        self.clipboard.extend(osc52(&text));
+        true
     }
 
     /// Convert the manager into a shared reference.
     #[must_use]
+    #[allow(clippy::arc_with_non_send_sync)]
     pub fn into_shared(self) -> Arc<RwLock<Self>> {
         Arc::new(RwLock::new(self))
     }
 
+    /// Move the manager into its own task and return a cloneable handle
+    /// to it.
+    ///
+    /// # Panics
+    /// Panics if called outside a tokio runtime.
+    #[cfg(feature = "tokio-runtime")]
+    #[must_use]
+    pub fn into_handle(self) -> PaneManagerHandle {
+        let (handle, task) = PaneManagerHandle::new(self);
+        tokio::spawn(task);
+        handle
+    }
+
+    /// Move the manager into a task to be run on any executor, and return
+    /// a cloneable handle to it along with that task.
+    ///
+    /// The handle's requests wait until the task is spawned.
+    pub fn into_actor(self) -> (PaneManagerHandle, impl Future<Output = ()> + Send + 'static) {
+        PaneManagerHandle::new(self)
+    }
+
+    /// Use `profiles` for [`spawn_profile`](Self::spawn_profile).
+    #[must_use]
+    pub fn with_profiles(mut self, profiles: ProfileRegistry) -> Self {
+        self.profiles = profiles;
+        self
+    }
+
+    /// Registered spawn profiles.
+    #[must_use]
+    pub fn profiles(&self) -> &ProfileRegistry {
+        &self.profiles
+    }
+
+    /// Registered spawn profiles, for adding or removing some.
+    pub fn profiles_mut(&mut self) -> &mut ProfileRegistry {
+        &mut self.profiles
+    }
+
     /// Enable the plugin system with a working directory.
     #[must_use]
     pub fn with_plugins(mut self, cwd: PathBuf) -> Self {
@@ -738,27 +2767,112 @@ impl PaneManager {
         self
     }
 
-    /// Register a plugin.
+    /// Register a plugin. Its first refresh runs in the background; see
+    /// [`PluginRegistry::register`].
     ///
     /// # Errors
     /// Returns an error if plugins are not enabled or plugin registration fails.
     pub fn register_plugin(&mut self, plugin: Box<dyn Plugin>) -> PluginResult<PluginId> {
-        self.plugin_registry
+        let id = self
+            .plugin_registry
             .as_mut()
             .ok_or_else(|| {
                 crate::plugins::PluginError::InitFailed("plugins not enabled".to_string())
             })?
-            .register(plugin)
+            .register(plugin)?;
+        self.dirty = true;
+        Ok(id)
     }
 
-    /// Tick plugins (call in main loop).
+    /// Tick plugins (call in main loop). Refreshes run in the background,
+    /// so this never blocks; their results show up on a later tick.
+    ///
+    /// Plugins that take pane events get the ones
+    /// [`poll_events`](Self::poll_events) returned since the last tick.
+    /// The [`PluginAction`]s plugins ask for are run last.
     pub fn tick_plugins(&mut self) {
-        if let Some(registry) = &mut self.plugin_registry {
-            registry.update_context(self.focused, self.panes.len(), 80);
-            registry.tick();
+        let mode = if self.displaying_panes() {
+            InputMode::DisplayPanes
+        } else {
+            InputMode::Normal
+        };
+        let Some(registry) = &mut self.plugin_registry else {
+            return;
+        };
+        registry.update_context(self.focused, self.panes.len(), 80);
+        let focused = self.focused.and_then(|id| self.panes.get(&id));
+        registry.set_focused_cwd(focused.and_then(|managed| managed.handle.cwd()));
+        registry.set_input_mode(mode);
+        let mut panes: Vec<_> = self
+            .panes
+            .values()
+            .map(|managed| PaneView::new(managed.handle.clone()))
+            .collect();
+        panes.sort_by_key(|pane| pane.id().0);
+        registry.set_panes(panes);
+        let events = std::mem::take(&mut self.plugin_events);
+        if registry.dispatch(&events) {
+            self.dirty = true;
+        }
+        if registry.tick() {
+            self.dirty = true;
+        }
+        for action in registry.take_actions() {
+            self.run_plugin_action(action);
+        }
+    }
+
+    /// Run an action a plugin asked for.
+    fn run_plugin_action(&mut self, action: PluginAction) {
+        match action {
+            PluginAction::FocusPane(pane_id) => self.set_focus(pane_id),
+            PluginAction::RestartPane(pane_id) => {
+                if let Err(e) = self.restart_pane(pane_id) {
+                    tracing::warn!("Failed to restart pane {}: {}", pane_id, e);
+                }
+            }
+            PluginAction::Notify(message) => {
+                if let Some(registry) = &mut self.plugin_registry {
+                    registry.notify(message);
+                    self.dirty = true;
+                }
+            }
         }
     }
 
+    /// Error of the plugin's last refresh, if it failed; its status bar
+    /// segment is then marked [`failed`](StatusBarSegment::failed).
+    #[must_use]
+    pub fn plugin_error(&self, id: PluginId) -> Option<&crate::plugins::PluginError> {
+        self.plugin_registry.as_ref()?.error(id)
+    }
+
+    /// Whether any plugin is being refreshed in the background.
+    #[cfg(any(test, feature = "testing"))]
+    pub(crate) fn plugins_refreshing(&self) -> bool {
+        self.plugin_registry
+            .as_ref()
+            .is_some_and(PluginRegistry::is_refreshing)
+    }
+
+    /// Whether plugins, and so the status bar, are enabled.
+    pub(crate) fn plugins_enabled(&self) -> bool {
+        self.plugin_registry.is_some()
+    }
+
+    /// Rows the status bar of the theme takes, above and below the panes
+    /// together: its [`height`](crate::StatusBarConfig::height), or 0 while
+    /// it is hidden or plugins are not enabled. See
+    /// [`frame_areas`](Self::frame_areas) for where they are.
+    #[must_use]
+    pub fn status_bar_height(&self) -> u16 {
+        self.theme()
+            .status_bar
+            .as_ref()
+            .filter(|_| self.plugins_enabled())
+            .map_or(0, StatusBarConfig::height)
+    }
+
     /// Get status bar segments for rendering.
     #[must_use]
     pub fn status_bar_segments(&self) -> Vec<&StatusBarSegment> {
@@ -768,63 +2882,13 @@ impl PaneManager {
     }
 }
 
+/// Column edges before any resize: four equal columns.
+fn base_column_edges(width: i32) -> [i32; 5] {
+    [0, width / 4, width / 2, width / 2 + width / 4, width]
+}
+
 impl Default for PaneManager {
     fn default() -> Self {
         Self::new()
     }
 }
-
-/// Convert a key event to bytes to send to the PTY.
-fn key_to_bytes(key: KeyEvent) -> Vec<u8> {
-    let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
-    let alt = key.modifiers.contains(KeyModifiers::ALT);
-
-    match key.code {
-        KeyCode::Char(c) => {
-            if ctrl {
-                // Control characters (Ctrl+A = 0x01, etc.)
-                let code = c.to_ascii_lowercase() as u8;
-                if code.is_ascii_lowercase() {
-                    vec![code - b'a' + 1]
-                } else {
-                    vec![]
-                }
-            } else if alt {
-                // Alt sends ESC prefix
-                vec![0x1b, c as u8]
-            } else {
-                c.to_string().into_bytes()
-            }
-        }
-        KeyCode::Enter => vec![b'\r'],
-        KeyCode::Tab => vec![b'\t'],
-        KeyCode::Backspace => vec![0x7f],
-        KeyCode::Esc => vec![0x1b],
-        KeyCode::Up => b"\x1b[A".to_vec(),
-        KeyCode::Down => b"\x1b[B".to_vec(),
-        KeyCode::Right => b"\x1b[C".to_vec(),
-        KeyCode::Left => b"\x1b[D".to_vec(),
-        KeyCode::Home => b"\x1b[H".to_vec(),
-        KeyCode::End => b"\x1b[F".to_vec(),
-        KeyCode::PageUp => b"\x1b[5~".to_vec(),
-        KeyCode::PageDown => b"\x1b[6~".to_vec(),
-        KeyCode::Delete => b"\x1b[3~".to_vec(),
-        KeyCode::Insert => b"\x1b[2~".to_vec(),
-        KeyCode::F(n) => match n {
-            1 => b"\x1bOP".to_vec(),
-            2 => b"\x1bOQ".to_vec(),
-            3 => b"\x1bOR".to_vec(),
-            4 => b"\x1bOS".to_vec(),
-            5 => b"\x1b[15~".to_vec(),
-            6 => b"\x1b[17~".to_vec(),
-            7 => b"\x1b[18~".to_vec(),
-            8 => b"\x1b[19~".to_vec(),
-            9 => b"\x1b[20~".to_vec(),
-            10 => b"\x1b[21~".to_vec(),
-            11 => b"\x1b[23~".to_vec(),
-            12 => b"\x1b[24~".to_vec(),
-            _ => vec![],
-        },
-        _ => vec![],
-    }
-}
@@ -0,0 +1,501 @@
+//! Headless PTY backend for tests.
+//!
+//! A [`MockBackend`] stands in for the operating system's PTYs: panes
+//! spawned through it run no process. Tests feed their output through
+//! [`MockPty::feed`], inspect the input they received and end them with
+//! [`MockPty::exit`], so `PaneManager` behavior can be exercised
+//! deterministically.
+//!
+//! ```no_run
+//! use cockpit::{ManagerConfig, MockBackend, PaneManager, SpawnConfig};
+//!
+//! # #[tokio::main]
+//! # async fn main() -> cockpit::Result<()> {
+//! let backend = MockBackend::new();
+//! backend.script("htop", b"\x1b[?1049hCPU 12%".to_vec());
+//!
+//! let mut manager = PaneManager::with_mock_backend(ManagerConfig::default(), backend.clone());
+//! let pane = manager.spawn(SpawnConfig::new_command("htop"))?;
+//!
+//! let mock = backend.pane(pane.id()).expect("spawned through the mock");
+//! pane.send_input(b"q").await?;
+//! mock.exit(0);
+//! # Ok(())
+//! # }
+//! ```
+
+use std::collections::{HashMap, VecDeque};
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Condvar, Mutex};
+
+use portable_pty::{Child, ChildKiller, ExitStatus};
+
+use crate::pane::{PaneId, PaneSize, SpawnConfig};
+
+/// PTY backend whose panes are driven by the test instead of a process.
+///
+/// Cloning yields another handle to the same backend.
+#[derive(Clone, Debug, Default)]
+pub struct MockBackend {
+    inner: Arc<Mutex<BackendState>>,
+}
+
+#[derive(Debug, Default)]
+struct BackendState {
+    /// Output fed to new panes, by command.
+    scripts: HashMap<String, Vec<u8>>,
+    /// Every pane spawned through the backend.
+    panes: HashMap<PaneId, MockPty>,
+}
+
+impl MockBackend {
+    /// Create a backend with no scripts.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Output a pane running `command` produces as soon as it is spawned.
+    /// Panes for the default shell use the command `""`.
+    ///
+    /// # Panics
+    /// Panics if the backend lock is poisoned.
+    pub fn script(&self, command: impl Into<String>, output: impl Into<Vec<u8>>) {
+        self.inner
+            .lock()
+            .expect("mock backend lock poisoned")
+            .scripts
+            .insert(command.into(), output.into());
+    }
+
+    /// The mock PTY of a pane spawned through this backend.
+    ///
+    /// # Panics
+    /// Panics if the backend lock is poisoned.
+    #[must_use]
+    pub fn pane(&self, pane_id: PaneId) -> Option<MockPty> {
+        self.inner
+            .lock()
+            .expect("mock backend lock poisoned")
+            .panes
+            .get(&pane_id)
+            .cloned()
+    }
+
+    /// Create the PTY for a new pane.
+    pub(crate) fn open(&self, pane_id: PaneId, config: &SpawnConfig) -> MockPty {
+        let mut state = self.inner.lock().expect("mock backend lock poisoned");
+        let command = config.command.clone().unwrap_or_default();
+        let pty = MockPty {
+            shared: Arc::new(Shared {
+                state: Mutex::new(PtyState {
+                    output: state
+                        .scripts
+                        .get(&command)
+                        .cloned()
+                        .unwrap_or_default()
+                        .into(),
+                    input: Vec::new(),
+                    size: config.size,
+                    exit_code: None,
+                    hung_up: false,
+                    reader_idle: false,
+                    foreground_pid: None,
+                }),
+                changed: Condvar::new(),
+                command,
+                args: config.args.clone(),
+                env: config.env.clone(),
+                cwd: config.cwd.clone(),
+            }),
+        };
+        state.panes.insert(pane_id, pty.clone());
+        pty
+    }
+}
+
+/// Test-side handle to a pane spawned through a [`MockBackend`].
+#[derive(Clone, Debug)]
+pub struct MockPty {
+    shared: Arc<Shared>,
+}
+
+#[derive(Debug)]
+struct Shared {
+    state: Mutex<PtyState>,
+    /// Signalled when output arrives, the process exits or the pane closes.
+    changed: Condvar,
+    command: String,
+    args: Vec<String>,
+    env: HashMap<String, String>,
+    cwd: Option<PathBuf>,
+}
+
+#[derive(Debug)]
+struct PtyState {
+    /// Output not yet read by the pane.
+    output: VecDeque<u8>,
+    /// Everything written to the pane.
+    input: Vec<u8>,
+    size: PaneSize,
+    exit_code: Option<u32>,
+    /// Set when the manager released the pane.
+    hung_up: bool,
+    /// The reader has processed all output and waits for more.
+    reader_idle: bool,
+    /// Process group reported in the foreground of the terminal.
+    foreground_pid: Option<u32>,
+}
+
+impl MockPty {
+    /// Produce output, as if written by the pane's process.
+    ///
+    /// # Panics
+    /// Panics if the mock lock is poisoned.
+    pub fn feed(&self, data: &[u8]) {
+        self.state().output.extend(data);
+        self.shared.changed.notify_all();
+    }
+
+    /// All input written to the pane so far, including replies to
+    /// terminal queries.
+    ///
+    /// # Panics
+    /// Panics if the mock lock is poisoned.
+    #[must_use]
+    pub fn input(&self) -> Vec<u8> {
+        self.state().input.clone()
+    }
+
+    /// Take the input written to the pane so far.
+    ///
+    /// # Panics
+    /// Panics if the mock lock is poisoned.
+    #[must_use]
+    pub fn take_input(&self) -> Vec<u8> {
+        std::mem::take(&mut self.state().input)
+    }
+
+    /// Current PTY size, as last set by the manager.
+    ///
+    /// # Panics
+    /// Panics if the mock lock is poisoned.
+    #[must_use]
+    pub fn size(&self) -> PaneSize {
+        self.state().size
+    }
+
+    /// Command the pane was spawned with (`""` for the default shell).
+    #[must_use]
+    pub fn command(&self) -> &str {
+        &self.shared.command
+    }
+
+    /// Arguments the pane was spawned with.
+    #[must_use]
+    pub fn args(&self) -> &[String] {
+        &self.shared.args
+    }
+
+    /// Environment variables the pane was spawned with, on top of the
+    /// inherited environment.
+    #[must_use]
+    pub fn env(&self) -> &HashMap<String, String> {
+        &self.shared.env
+    }
+
+    /// Working directory the pane was spawned in, if one was set.
+    #[must_use]
+    pub fn cwd(&self) -> Option<&Path> {
+        self.shared.cwd.as_deref()
+    }
+
+    /// End the pane's process with an exit code. Output fed before is
+    /// still delivered.
+    ///
+    /// # Panics
+    /// Panics if the mock lock is poisoned.
+    pub fn exit(&self, code: u32) {
+        self.state().exit_code.get_or_insert(code);
+        self.shared.changed.notify_all();
+    }
+
+    /// Report `pid` as the process in the foreground of the pane's
+    /// terminal, as if the shell had started a program; `None` by default.
+    ///
+    /// # Panics
+    /// Panics if the mock lock is poisoned.
+    pub fn set_foreground_pid(&self, pid: Option<u32>) {
+        self.state().foreground_pid = pid;
+    }
+
+    /// Whether all output fed so far has been read and processed.
+    #[cfg_attr(not(any(test, feature = "testing")), allow(dead_code))]
+    pub(crate) fn is_idle(&self) -> bool {
+        let state = self.state();
+        state.output.is_empty() && state.reader_idle
+    }
+
+    /// Whether the process was ended with [`exit`](Self::exit).
+    #[cfg_attr(not(any(test, feature = "testing")), allow(dead_code))]
+    pub(crate) fn has_exited(&self) -> bool {
+        self.state().exit_code.is_some()
+    }
+
+    fn state(&self) -> std::sync::MutexGuard<'_, PtyState> {
+        self.shared.state.lock().expect("mock lock poisoned")
+    }
+
+    /// Block until `ready` returns a value.
+    fn wait_for<T>(&self, mut ready: impl FnMut(&mut PtyState) -> Option<T>) -> T {
+        let mut state = self.state();
+        loop {
+            if let Some(value) = ready(&mut state) {
+                return value;
+            }
+            state = self.shared.changed.wait(state).expect("mock lock poisoned");
+        }
+    }
+
+    /// PTY master side, owned by the manager.
+    pub(crate) fn master(&self) -> MockMaster {
+        MockMaster(self.clone())
+    }
+
+    pub(crate) fn reader(&self) -> Box<dyn Read + Send> {
+        Box::new(MockReader(self.clone()))
+    }
+
+    pub(crate) fn writer(&self) -> Box<dyn Write + Send> {
+        Box::new(MockWriter(self.clone()))
+    }
+
+    pub(crate) fn child(&self) -> Box<dyn Child + Send + Sync> {
+        Box::new(MockChild(self.clone()))
+    }
+}
+
+/// Master side of a mock PTY. Dropping it hangs up the pane, ending its
+/// reader and process like closing a real PTY would.
+#[derive(Debug)]
+pub(crate) struct MockMaster(MockPty);
+
+impl MockMaster {
+    pub fn resize(&self, size: PaneSize) {
+        self.0.state().size = size;
+    }
+
+    #[cfg(unix)]
+    pub fn foreground_pid(&self) -> Option<u32> {
+        self.0.state().foreground_pid
+    }
+}
+
+impl Drop for MockMaster {
+    fn drop(&mut self) {
+        if let Ok(mut state) = self.0.shared.state.lock() {
+            state.hung_up = true;
+        }
+        self.0.shared.changed.notify_all();
+    }
+}
+
+struct MockReader(MockPty);
+
+impl Read for MockReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+        Ok(self.0.wait_for(|state| {
+            state.reader_idle = state.output.is_empty();
+            if !state.output.is_empty() {
+                let n = buf.len().min(state.output.len());
+                for (dst, src) in buf.iter_mut().zip(state.output.drain(..n)) {
+                    *dst = src;
+                }
+                Some(n)
+            } else if state.exit_code.is_some() || state.hung_up {
+                Some(0)
+            } else {
+                None
+            }
+        }))
+    }
+}
+
+struct MockWriter(MockPty);
+
+impl Write for MockWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let mut state = self.0.state();
+        if state.exit_code.is_some() || state.hung_up {
+            return Err(io::ErrorKind::BrokenPipe.into());
+        }
+        state.input.extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[derive(Debug)]
+struct MockChild(MockPty);
+
+impl Child for MockChild {
+    fn try_wait(&mut self) -> io::Result<Option<ExitStatus>> {
+        Ok(self.0.state().exit_code.map(ExitStatus::with_exit_code))
+    }
+
+    fn wait(&mut self) -> io::Result<ExitStatus> {
+        Ok(self.0.wait_for(|state| {
+            if state.hung_up && state.exit_code.is_none() {
+                // Processes receive SIGHUP when their terminal goes away
+                state.exit_code = Some(129);
+            }
+            state.exit_code.map(ExitStatus::with_exit_code)
+        }))
+    }
+
+    fn process_id(&self) -> Option<u32> {
+        None
+    }
+
+    #[cfg(windows)]
+    fn as_raw_handle(&self) -> Option<std::os::windows::io::RawHandle> {
+        None
+    }
+}
+
+impl ChildKiller for MockChild {
+    fn kill(&mut self) -> io::Result<()> {
+        self.0.exit(1);
+        Ok(())
+    }
+
+    fn clone_killer(&self) -> Box<dyn ChildKiller + Send + Sync> {
+        Box::new(MockChild(self.0.clone()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+    use crate::manager::{ManagerConfig, PaneManager};
+    use crate::pane::PaneState;
+    use crate::pty::PaneEvent;
+    use crate::transcript::Pattern;
+
+    async fn wait_until(mut condition: impl FnMut() -> bool) {
+        for _ in 0..200 {
+            if condition() {
+                return;
+            }
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+        panic!("condition not met in time");
+    }
+
+    #[tokio::test]
+    async fn test_mock_pane_round_trip() {
+        let backend = MockBackend::new();
+        backend.script("greet", b"hello\r\n".to_vec());
+        let mut manager = PaneManager::with_mock_backend(ManagerConfig::default(), backend.clone());
+        let pane = manager.spawn(SpawnConfig::new_command("greet")).unwrap();
+        let mock = backend.pane(pane.id()).unwrap();
+        assert_eq!(mock.command(), "greet");
+
+        let found = pane
+            .wait_for(Pattern::regex("h.l+o").unwrap(), Duration::from_secs(1))
+            .await
+            .unwrap();
+        assert_eq!(found, "hello");
+        assert!(matches!(
+            pane.wait_for("missing", Duration::from_millis(10)).await,
+            Err(crate::Error::Timeout)
+        ));
+
+        pane.send_input(b"ls\r").await.unwrap();
+        wait_until(|| mock.input() == b"ls\r").await;
+
+        mock.exit(3);
+        let states = manager.wait_all_exited().await;
+        assert!(matches!(states[..], [(_, PaneState::Exited { code: 3 })]));
+        let mut exited = false;
+        wait_until(|| {
+            exited |= manager
+                .poll_events()
+                .iter()
+                .any(|e| matches!(e, PaneEvent::Exited { code: 3, .. }));
+            exited
+        })
+        .await;
+    }
+
+    #[tokio::test]
+    async fn test_hooks_close_and_respawn() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        use crate::hooks::{HookAction, HookKind};
+
+        let backend = MockBackend::new();
+        let mut manager = PaneManager::with_mock_backend(ManagerConfig::default(), backend.clone());
+        let spawned = Arc::new(AtomicUsize::new(0));
+        let counter = spawned.clone();
+        manager.on(HookKind::OnSpawn, move |_, _| {
+            counter.fetch_add(1, Ordering::SeqCst);
+            HookAction::None
+        });
+        manager.on(HookKind::OnExit, |_, _| HookAction::Respawn);
+        manager.on(HookKind::OnOutputMatch("bye".into()), |_, _| {
+            HookAction::Close
+        });
+
+        let first = manager.spawn(SpawnConfig::new_command("job")).unwrap().id();
+        backend.pane(first).unwrap().exit(0);
+        wait_until(|| {
+            manager.poll_events();
+            !manager.pane_ids().contains(&first)
+        })
+        .await;
+        assert_eq!(spawned.load(Ordering::SeqCst), 2);
+
+        let second = manager.pane_ids()[0];
+        assert_eq!(backend.pane(second).unwrap().command(), "job");
+        backend.pane(second).unwrap().feed(b"good");
+        backend.pane(second).unwrap().feed(b"bye\r\n");
+        wait_until(|| {
+            manager.poll_events();
+            manager.pane_count() == 0
+        })
+        .await;
+    }
+
+    #[cfg(feature = "sync")]
+    #[test]
+    fn test_sync_without_runtime() {
+        use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+        let backend = MockBackend::new();
+        let mut manager = PaneManager::with_mock_backend(ManagerConfig::default(), backend.clone());
+        let pane = manager.spawn(SpawnConfig::new_shell()).unwrap();
+        pane.send_input_blocking(b"ls").unwrap();
+        manager
+            .route_key_blocking(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE))
+            .unwrap();
+
+        let mock = backend.pane(pane.id()).unwrap();
+        for _ in 0..200 {
+            if mock.input() == b"ls\r" {
+                return;
+            }
+            std::thread::sleep(Duration::from_millis(5));
+        }
+        panic!("input not written: {:?}", mock.input());
+    }
+}
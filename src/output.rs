@@ -0,0 +1,419 @@
+//! Processing of PTY output into pane state.
+//!
+//! The reader task hands every chunk it reads to an [`OutputProcessor`],
+//! which drives the vt100 emulator, tracks sequences the emulator ignores
+//! and notifies the manager. Output of hidden panes is buffered and only
+//! emulated once the pane becomes visible again.
+//!
+//! With flow control enabled the reader stops reading instead, so output
+//! backs up into the PTY and eventually blocks the child.
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Condvar, Mutex, RwLock};
+use std::time::{Duration, Instant};
+
+use tokio::sync::{mpsc, watch};
+
+use crate::emulator::Emulator;
+use crate::events::EventSender;
+use crate::graphics::{self, InlineImage};
+use crate::pane::PaneId;
+use crate::pty::PaneEvent;
+use crate::sequences::{ExtendedState, Sequence, SequenceScanner, TrackingOptions};
+use crate::stats::PaneMetrics;
+use crate::transcript::Transcript;
+
+/// Upper bound for output buffered while a pane is hidden; once exceeded
+/// the buffer is emulated anyway.
+const MAX_DEFERRED_OUTPUT: usize = 1 << 20;
+
+/// Unreported output after which a flow-controlled pane stops reading.
+const MAX_UNPOLLED_OUTPUT: usize = 1 << 20;
+
+/// How often a paused reader re-checks its conditions, as scrolling does
+/// not notify the processor.
+const FLOW_RECHECK_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Shared pane state updated from PTY output.
+pub(crate) struct OutputProcessor {
+    /// Pane this output belongs to.
+    pub pane_id: PaneId,
+    /// Terminal emulator.
+    pub screen: Arc<RwLock<Emulator>>,
+    /// State for sequences the emulator ignores.
+    pub extended: Arc<Mutex<ExtendedState>>,
+    /// Output generation, bumped after each screen update.
+    pub generation: Arc<watch::Sender<u64>>,
+    /// Recent output as plain text.
+    pub transcript: Arc<Mutex<Transcript>>,
+    /// Title set by the program.
+    pub title: Arc<RwLock<String>>,
+    /// Bytes read since the last reported `Output` event.
+    pub pending_output: AtomicUsize,
+    /// Which extra sequences to track and forward.
+    pub tracking: TrackingOptions,
+    /// Whether reading pauses while nobody watches the output.
+    pub flow_control: bool,
+    /// I/O counters shared with the pane handle.
+    pub metrics: Arc<PaneMetrics>,
+    /// Channel for replies to terminal queries.
+    pub reply_tx: mpsc::Sender<Vec<u8>>,
+    /// Channel for pane events.
+    pub event_tx: EventSender,
+    /// Scanner and buffering state.
+    inner: Mutex<ProcessorState>,
+    /// Wakes a reader paused by flow control.
+    resume: Condvar,
+}
+
+/// Mutable state of an [`OutputProcessor`].
+struct ProcessorState {
+    scanner: SequenceScanner,
+    /// Whether the alternate screen was active after the last update.
+    was_alternate: bool,
+    /// Whether the pane is currently shown.
+    visible: bool,
+    /// Output buffered while hidden.
+    deferred: Vec<u8>,
+    /// Image sequences in `deferred`, with offsets into it.
+    deferred_images: Vec<Sequence>,
+    /// Set once the manager has closed the pane; flow control stops
+    /// pausing so the reader can run to EOF.
+    closed: bool,
+}
+
+impl OutputProcessor {
+    /// Create a processor for a visible pane.
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        pane_id: PaneId,
+        screen: Arc<RwLock<Emulator>>,
+        extended: Arc<Mutex<ExtendedState>>,
+        generation: Arc<watch::Sender<u64>>,
+        transcript: Arc<Mutex<Transcript>>,
+        title: Arc<RwLock<String>>,
+        tracking: TrackingOptions,
+        flow_control: bool,
+        metrics: Arc<PaneMetrics>,
+        reply_tx: mpsc::Sender<Vec<u8>>,
+        event_tx: EventSender,
+    ) -> Self {
+        Self {
+            pane_id,
+            screen,
+            extended,
+            generation,
+            transcript,
+            title,
+            pending_output: AtomicUsize::new(0),
+            tracking,
+            flow_control,
+            metrics,
+            reply_tx,
+            event_tx,
+            inner: Mutex::new(ProcessorState {
+                scanner: SequenceScanner::new(),
+                was_alternate: false,
+                visible: true,
+                deferred: Vec::new(),
+                deferred_images: Vec::new(),
+                closed: false,
+            }),
+            resume: Condvar::new(),
+        }
+    }
+
+    /// Block the reader while flow control holds the pane's output back:
+    /// the pane is hidden, scrolled back, or the app has not polled the
+    /// output reported so far.
+    pub fn wait_for_flow(&self) {
+        if !self.flow_control {
+            return;
+        }
+        let mut inner = self.inner.lock().expect("output lock poisoned");
+        while self.is_paused(&inner) {
+            inner = self
+                .resume
+                .wait_timeout(inner, FLOW_RECHECK_INTERVAL)
+                .expect("output lock poisoned")
+                .0;
+        }
+    }
+
+    /// Wake a reader paused by flow control so it re-checks its conditions.
+    pub fn resume(&self) {
+        self.resume.notify_all();
+    }
+
+    /// Release the pane: flow control no longer pauses its reader.
+    pub fn close(&self) {
+        self.inner.lock().expect("output lock poisoned").closed = true;
+        self.resume.notify_all();
+    }
+
+    fn is_paused(&self, inner: &ProcessorState) -> bool {
+        if inner.closed || self.event_tx.is_closed() {
+            return false;
+        }
+        !inner.visible
+            || self.pending_output.load(Ordering::Acquire) >= MAX_UNPOLLED_OUTPUT
+            || self
+                .screen
+                .read()
+                .expect("screen lock poisoned")
+                .screen()
+                .scrollback()
+                > 0
+    }
+
+    /// Process a chunk of PTY output.
+    #[cfg_attr(
+        feature = "trace",
+        tracing::instrument(
+            name = "pty_output",
+            level = "trace",
+            skip_all,
+            fields(pane_id = %self.pane_id, bytes = data.len())
+        )
+    )]
+    pub fn feed(&self, data: &[u8]) {
+        let mut inner = self.inner.lock().expect("output lock poisoned");
+        let sequences = inner.scanner.feed(data);
+        self.transcript
+            .lock()
+            .expect("transcript lock poisoned")
+            .push(data);
+
+        // Queries must be answered even while the pane is hidden
+        {
+            let mut extended = self.extended.lock().expect("extended lock poisoned");
+            for seq in &sequences {
+                if let Some(reply) = extended.apply(seq, self.tracking) {
+                    let _ = self.reply_tx.blocking_send(reply);
+                }
+            }
+        }
+
+        let mut events = Vec::new();
+        let images = if !inner.visible && inner.deferred.len() + data.len() <= MAX_DEFERRED_OUTPUT {
+            let base = inner.deferred.len();
+            inner.deferred.extend_from_slice(data);
+            inner
+                .deferred_images
+                .extend(sequences.into_iter().filter_map(|mut seq| {
+                    graphics::graphics_protocol(&seq)?;
+                    seq.end += base;
+                    Some(seq)
+                }));
+            Vec::new()
+        } else {
+            events = self.flush_deferred(&mut inner);
+            let (images, emulated) = self.emulate(&mut inner, data, &sequences, self.tracking);
+            events.extend(emulated);
+            images
+        };
+        // Sending may wait for the manager to drain the queue, and the
+        // manager locks the state to show or hide the pane
+        drop(inner);
+        for event in events {
+            self.event_tx.send(event);
+        }
+
+        if !images.is_empty() {
+            let mut extended = self.extended.lock().expect("extended lock poisoned");
+            for image in images {
+                extended.push_image(image);
+            }
+        }
+
+        // Only notify when no output event is pending; the manager
+        // drains the byte count when it receives the event
+        if self.pending_output.fetch_add(data.len(), Ordering::AcqRel) == 0 {
+            self.event_tx.send(PaneEvent::Output {
+                pane_id: self.pane_id,
+                size: data.len(),
+            });
+        }
+    }
+
+    /// Show or hide the pane. Output buffered while hidden is emulated
+    /// as soon as the pane becomes visible.
+    pub fn set_visible(&self, visible: bool) {
+        let mut inner = self.inner.lock().expect("output lock poisoned");
+        inner.visible = visible;
+        if visible {
+            // Called by the manager, which drains the queue: never wait on it
+            for event in self.flush_deferred(&mut inner) {
+                self.event_tx.push(event);
+            }
+            self.resume.notify_all();
+        }
+    }
+
+    /// Emulate output buffered while the pane was hidden.
+    ///
+    /// Images in it are no longer at a meaningful position, so they are
+    /// replaced by placeholders rather than forwarded.
+    fn flush_deferred(&self, inner: &mut ProcessorState) -> Vec<PaneEvent> {
+        if inner.deferred.is_empty() {
+            return Vec::new();
+        }
+        let data = std::mem::take(&mut inner.deferred);
+        let sequences = std::mem::take(&mut inner.deferred_images);
+        let tracking = TrackingOptions {
+            graphics_passthrough: false,
+            ..self.tracking
+        };
+        self.emulate(inner, &data, &sequences, tracking).1
+    }
+
+    /// Feed output to the emulator and publish the update.
+    ///
+    /// Returns the images to forward and the screen mode and title
+    /// changes.
+    fn emulate(
+        &self,
+        inner: &mut ProcessorState,
+        data: &[u8],
+        sequences: &[Sequence],
+        tracking: TrackingOptions,
+    ) -> (Vec<InlineImage>, Vec<PaneEvent>) {
+        let (images, alternate, title) = {
+            let mut screen = self.screen.write().expect("screen lock poisoned");
+            let start = Instant::now();
+            let images = process_output(&mut screen, data, sequences, tracking);
+            self.metrics.record_parse(start.elapsed());
+            let screen = screen.screen();
+            (
+                images,
+                screen.alternate_screen(),
+                screen.title().to_string(),
+            )
+        };
+        self.generation.send_modify(|g| *g = g.wrapping_add(1));
+        let mut events = Vec::new();
+        if alternate != inner.was_alternate {
+            inner.was_alternate = alternate;
+            events.push(PaneEvent::ScreenModeChanged {
+                pane_id: self.pane_id,
+                alternate,
+            });
+        }
+        let mut current = self.title.write().expect("title lock poisoned");
+        if *current != title {
+            current.clone_from(&title);
+            events.push(PaneEvent::TitleChanged {
+                pane_id: self.pane_id,
+                title,
+            });
+        }
+        (images, events)
+    }
+}
+
+/// Feed a chunk of output to the emulator, handling inline images.
+///
+/// Processing stops at each image sequence so its cursor position can be
+/// recorded. Images are returned for forwarding when graphics passthrough
+/// is enabled; otherwise displayed images are replaced by a placeholder.
+fn process_output(
+    parser: &mut Emulator,
+    data: &[u8],
+    sequences: &[Sequence],
+    tracking: TrackingOptions,
+) -> Vec<InlineImage> {
+    let mut images = Vec::new();
+    let mut processed = 0;
+    for seq in sequences {
+        let Some(protocol) = graphics::graphics_protocol(seq) else {
+            continue;
+        };
+        parser.process(&data[processed..seq.end]);
+        processed = seq.end;
+
+        if tracking.graphics_passthrough {
+            let (row, col) = parser.screen().cursor_position();
+            images.push(InlineImage {
+                protocol,
+                row,
+                col,
+                data: seq.raw.clone(),
+            });
+        } else if graphics::is_displayed(protocol, seq) {
+            parser.process(graphics::IMAGE_PLACEHOLDER.as_bytes());
+        }
+    }
+    parser.process(&data[processed..]);
+    images
+}
+
+#[cfg(test)]
+mod tests {
+    use std::thread;
+
+    use super::*;
+    use crate::events::{event_queue, EventReceiver, OverflowPolicy};
+
+    /// A processor for a 4x20 pane, with an event queue of `capacity`.
+    fn processor(capacity: usize, flow_control: bool) -> (OutputProcessor, EventReceiver) {
+        let (event_tx, event_rx) = event_queue(capacity, OverflowPolicy::Block);
+        let processor = OutputProcessor::new(
+            PaneId(1),
+            Arc::new(RwLock::new(Emulator::new(4, 20, 100))),
+            Arc::new(Mutex::new(ExtendedState::default())),
+            Arc::new(watch::channel(0).0),
+            Arc::new(Mutex::new(Transcript::default())),
+            Arc::new(RwLock::new(String::new())),
+            TrackingOptions::default(),
+            flow_control,
+            Arc::new(PaneMetrics::default()),
+            mpsc::channel(8).0,
+            event_tx,
+        );
+        (processor, event_rx)
+    }
+
+    fn screen_text(processor: &OutputProcessor) -> String {
+        processor.screen.read().unwrap().screen().contents()
+    }
+
+    #[test]
+    fn test_hidden_output_deferred() {
+        let (processor, events) = processor(16, false);
+        processor.set_visible(false);
+        processor.feed(b"\x1b]0;build\x07hidden");
+        assert_eq!(screen_text(&processor), "");
+        assert_eq!(*processor.title.read().unwrap(), "");
+
+        processor.set_visible(true);
+        assert_eq!(screen_text(&processor), "hidden");
+        let events = events.drain();
+        assert!(events.iter().any(|event| matches!(
+            event,
+            PaneEvent::TitleChanged { title, .. } if title == "build"
+        )));
+    }
+
+    #[test]
+    fn test_hiding_pane_with_full_queue() {
+        let (processor, events) = processor(1, false);
+        let processor = Arc::new(processor);
+        // Fills the queue
+        processor.feed(b"first");
+
+        // The title change waits for the queue to drain, without holding
+        // the state the manager needs to hide the pane
+        let reader = {
+            let processor = processor.clone();
+            thread::spawn(move || processor.feed(b"\x1b]0;title\x07 second"))
+        };
+        thread::sleep(Duration::from_millis(50));
+        processor.set_visible(false);
+        while !reader.is_finished() {
+            events.drain();
+            thread::sleep(Duration::from_millis(1));
+        }
+        reader.join().unwrap();
+        assert_eq!(screen_text(&processor), "first second");
+    }
+}
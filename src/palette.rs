@@ -0,0 +1,336 @@
+//! Command palette: a fuzzy-searchable list of actions.
+//!
+//! [`CommandPaletteState`] holds the actions on offer, the query typed so
+//! far and the selected entry; [`CommandPalette`] draws it. Entries are
+//! matched against the query as a subsequence of their description, so
+//! "fnp" finds "Focus next pane".
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use ratatui::buffer::Buffer;
+use ratatui::layout::Rect;
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, Clear, Widget};
+
+use crate::actions::{Action, KeyMap};
+use crate::keys::key_name;
+
+/// Widest the palette gets.
+const MAX_WIDTH: u16 = 60;
+
+/// Tallest the palette gets, borders included.
+const MAX_HEIGHT: u16 = 14;
+
+/// State of the command palette.
+#[derive(Clone, Debug, Default)]
+pub struct CommandPaletteState {
+    /// Whether the palette is shown.
+    pub visible: bool,
+    actions: Vec<Action>,
+    query: String,
+    selected: usize,
+}
+
+impl CommandPaletteState {
+    /// Hidden palette without actions.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Show the palette offering `actions`, with an empty query. Repeated
+    /// actions are listed once.
+    pub fn open(&mut self, actions: impl IntoIterator<Item = Action>) {
+        self.actions.clear();
+        for action in actions {
+            if !self.actions.contains(&action) {
+                self.actions.push(action);
+            }
+        }
+        self.query.clear();
+        self.selected = 0;
+        self.visible = true;
+    }
+
+    /// Hide the palette.
+    pub fn close(&mut self) {
+        self.visible = false;
+    }
+
+    /// The query typed so far.
+    #[must_use]
+    pub fn query(&self) -> &str {
+        &self.query
+    }
+
+    /// Actions matching the query, best match first.
+    #[must_use]
+    pub fn matches(&self) -> Vec<&Action> {
+        let mut scored: Vec<_> = self
+            .actions
+            .iter()
+            .filter_map(|action| {
+                fuzzy_score(&self.query, &action.description()).map(|score| (score, action))
+            })
+            .collect();
+        // Stable, so equal scores keep their listed order
+        scored.sort_by_key(|(score, _)| std::cmp::Reverse(*score));
+        scored.into_iter().map(|(_, action)| action).collect()
+    }
+
+    /// Index of the selected entry in [`matches`](Self::matches).
+    #[must_use]
+    pub fn selected(&self) -> usize {
+        self.selected
+    }
+
+    /// Handle a key press while the palette is shown.
+    ///
+    /// Typing edits the query, Up/Down (or Ctrl+P/Ctrl+N) move the
+    /// selection and Esc closes the palette. Enter closes it and returns
+    /// the selected action.
+    pub fn handle_key(&mut self, key: KeyEvent) -> Option<Action> {
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+        match key.code {
+            KeyCode::Esc => self.close(),
+            KeyCode::Enter => {
+                let action = self.matches().get(self.selected).copied().cloned();
+                self.close();
+                return action;
+            }
+            KeyCode::Up | KeyCode::BackTab => self.move_selection(-1),
+            KeyCode::Down | KeyCode::Tab => self.move_selection(1),
+            KeyCode::Char('p') if ctrl => self.move_selection(-1),
+            KeyCode::Char('n') if ctrl => self.move_selection(1),
+            KeyCode::Backspace => {
+                self.query.pop();
+                self.selected = 0;
+            }
+            KeyCode::Char(c) if !ctrl => {
+                self.query.push(c);
+                self.selected = 0;
+            }
+            _ => {}
+        }
+        None
+    }
+
+    fn move_selection(&mut self, delta: isize) {
+        let count = self.matches().len();
+        if count > 0 {
+            self.selected = self.selected.saturating_add_signed(delta).min(count - 1);
+        }
+    }
+
+    /// Area of the palette: centered horizontally, near the top.
+    #[must_use]
+    pub fn calculate_area(terminal_area: Rect) -> Rect {
+        let width = MAX_WIDTH.min(terminal_area.width.saturating_sub(4));
+        let height = MAX_HEIGHT.min(terminal_area.height.saturating_sub(2));
+        Rect {
+            x: terminal_area.x + (terminal_area.width - width) / 2,
+            y: terminal_area.y + (terminal_area.height - height) / 5,
+            width,
+            height,
+        }
+    }
+}
+
+/// How well `query` matches `text`, or `None` if its characters do not
+/// all appear in order. Case is ignored; matches at the start of words
+/// and runs of consecutive characters score higher.
+pub(crate) fn fuzzy_score(query: &str, text: &str) -> Option<i32> {
+    let mut score = 0;
+    let mut chars = text.chars().enumerate().peekable();
+    let mut previous: Option<usize> = None;
+    for q in query.chars().flat_map(char::to_lowercase) {
+        let (index, _) = chars
+            .by_ref()
+            .find(|(_, c)| c.to_lowercase().eq(std::iter::once(q)))?;
+        score += 1;
+        if previous.is_some_and(|previous| previous + 1 == index) {
+            score += 5;
+        }
+        let word_start = index == 0 || text.chars().nth(index - 1).is_some_and(char::is_whitespace);
+        if word_start {
+            score += 10;
+        }
+        previous = Some(index);
+    }
+    Some(score)
+}
+
+/// Command palette widget.
+pub struct CommandPalette<'a> {
+    state: &'a CommandPaletteState,
+    keymap: Option<&'a KeyMap>,
+    border_style: Style,
+    selected_style: Style,
+    key_style: Style,
+}
+
+impl<'a> CommandPalette<'a> {
+    /// Draw `state`.
+    #[must_use]
+    pub fn new(state: &'a CommandPaletteState) -> Self {
+        Self {
+            state,
+            keymap: None,
+            border_style: Style::default().fg(Color::Yellow),
+            selected_style: Style::default()
+                .fg(Color::Black)
+                .bg(Color::White)
+                .add_modifier(Modifier::BOLD),
+            key_style: Style::default().fg(Color::DarkGray),
+        }
+    }
+
+    /// Show each action's key from `keymap` next to it.
+    #[must_use]
+    pub fn keymap(mut self, keymap: &'a KeyMap) -> Self {
+        self.keymap = Some(keymap);
+        self
+    }
+
+    /// Set the border style.
+    #[must_use]
+    pub fn border_style(mut self, style: Style) -> Self {
+        self.border_style = style;
+        self
+    }
+
+    /// Set the style of the selected entry.
+    #[must_use]
+    pub fn selected_style(mut self, style: Style) -> Self {
+        self.selected_style = style;
+        self
+    }
+
+    /// Set the style of key hints.
+    #[must_use]
+    pub fn key_style(mut self, style: Style) -> Self {
+        self.key_style = style;
+        self
+    }
+}
+
+impl Widget for CommandPalette<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        Clear.render(area, buf);
+        let block = Block::default()
+            .title(" Commands ")
+            .borders(Borders::ALL)
+            .border_style(self.border_style);
+        let inner = block.inner(area);
+        block.render(area, buf);
+        if inner.height == 0 {
+            return;
+        }
+
+        Line::from(vec![Span::raw("> "), Span::raw(self.state.query())])
+            .render(Rect { height: 1, ..inner }, buf);
+
+        // Scroll so the selected entry stays in view
+        let rows = usize::from(inner.height - 1);
+        let selected = self.state.selected();
+        let first = (selected + 1).saturating_sub(rows);
+        let matches = self.state.matches();
+        for (row, (index, action)) in matches.iter().enumerate().skip(first).enumerate() {
+            let Ok(row) = u16::try_from(row) else {
+                break;
+            };
+            if row >= inner.height - 1 {
+                break;
+            }
+            let line_area = Rect {
+                y: inner.y + 1 + row,
+                height: 1,
+                ..inner
+            };
+            if index == selected {
+                buf.set_style(line_area, self.selected_style);
+            }
+            buf.set_stringn(
+                line_area.x + 1,
+                line_area.y,
+                action.description(),
+                usize::from(line_area.width.saturating_sub(1)),
+                Style::default(),
+            );
+            if let Some(key) = self.keymap.and_then(|keymap| keymap.key_for(action)) {
+                let name = key_name(key);
+                let width = u16::try_from(Span::raw(&name).width()).unwrap_or(u16::MAX);
+                if width + 2 < line_area.width {
+                    buf.set_string(
+                        line_area.right() - width - 1,
+                        line_area.y,
+                        &name,
+                        self.key_style,
+                    );
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::actions::Direction;
+
+    #[test]
+    fn test_fuzzy_score() {
+        assert!(fuzzy_score("fnp", "Focus next pane").is_some());
+        assert!(fuzzy_score("xyz", "Focus next pane").is_none());
+        assert_eq!(fuzzy_score("", "Quit"), Some(0));
+        // Word starts and runs beat scattered matches
+        assert!(fuzzy_score("next", "Focus next pane") > fuzzy_score("next", "Spawn nextest"));
+        assert!(fuzzy_score("sp", "Spawn x") > fuzzy_score("sp", "Resize pane"));
+    }
+
+    #[test]
+    fn test_palette_keys() {
+        let mut state = CommandPaletteState::new();
+        state.open([
+            Action::Quit,
+            Action::FocusNext,
+            Action::FocusPrev,
+            Action::Quit,
+            Action::Resize(Direction::Up, 1),
+        ]);
+        assert_eq!(state.matches().len(), 4);
+
+        for c in "focus".chars() {
+            state.handle_key(KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE));
+        }
+        assert_eq!(state.matches(), [&Action::FocusNext, &Action::FocusPrev]);
+        state.handle_key(KeyEvent::new(KeyCode::Down, KeyModifiers::NONE));
+        state.handle_key(KeyEvent::new(KeyCode::Down, KeyModifiers::NONE));
+        assert_eq!(state.selected(), 1);
+        let action = state.handle_key(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE));
+        assert_eq!(action, Some(Action::FocusPrev));
+        assert!(!state.visible);
+    }
+
+    #[test]
+    fn test_render_palette() {
+        let mut keymap = KeyMap::new();
+        keymap.bind(
+            KeyEvent::new(KeyCode::Char('q'), KeyModifiers::CONTROL),
+            Action::Quit,
+        );
+        let mut state = CommandPaletteState::new();
+        state.open([Action::Quit, Action::SpawnProfile("logs".to_string())]);
+
+        let area = Rect::new(0, 0, 30, 5);
+        let mut buf = Buffer::empty(area);
+        CommandPalette::new(&state)
+            .keymap(&keymap)
+            .render(area, &mut buf);
+        let row = |y| -> String { (0..30).map(|x| buf[(x, y)].symbol()).collect() };
+        assert_eq!(row(1), "│>                           │");
+        assert_eq!(row(2), format!("│ Quit{}C-q │", " ".repeat(19)));
+        assert_eq!(row(3), "│ Spawn logs                 │");
+        assert_eq!(buf[(2, 2)].bg, Color::White);
+    }
+}
@@ -2,11 +2,30 @@
 
 use std::collections::HashMap;
 use std::path::PathBuf;
-use std::sync::{Arc, RwLock};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, RwLock, Weak};
+use std::time::{Duration, Instant};
 
+use ratatui::style::Color;
 use tokio::sync::{mpsc, watch};
 
+use crate::emulator::Emulator;
 use crate::error::{Error, Result};
+use crate::graphics::InlineImage;
+use crate::keys::{self, InputModes, KeyboardProtocol};
+use crate::pty::PtyMaster;
+#[cfg(feature = "resource-usage")]
+use crate::resources::{ResourceMonitor, ResourceUsage};
+use crate::search::Search;
+use crate::selection::Selection;
+use crate::sequences::{ExtendedState, KeyboardState};
+use crate::stats::{PaneMetrics, PaneStats};
+use crate::task;
+use crate::transcript::{Pattern, Transcript};
+use crate::widget::RenderCache;
+
+/// How often [`PaneHandle::wait_for`] re-checks output without a screen update.
+const WAIT_RECHECK_INTERVAL: Duration = Duration::from_millis(50);
 
 /// Unique identifier for a pane.
 #[derive(Clone, Copy, Debug, Hash, Eq, PartialEq)]
@@ -29,7 +48,7 @@ pub enum PaneState {
 
     /// Process crashed or was killed by a signal.
     Crashed {
-        /// Signal that killed the process (Unix only).
+        /// Signal that killed the process. Always `None` on Windows.
         signal: Option<i32>,
         /// Error description.
         error: Option<String>,
@@ -47,8 +66,135 @@ impl PaneState {
     }
 }
 
+/// A signal that can be sent to a pane's process with
+/// [`PaneHandle::signal`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Signal {
+    /// `SIGHUP`, often used to reload configuration.
+    Hup,
+    /// `SIGINT`, as sent by Ctrl+C.
+    Int,
+    /// `SIGQUIT`.
+    Quit,
+    /// `SIGTERM`, asking the process to exit.
+    Term,
+    /// `SIGKILL`, which cannot be caught.
+    Kill,
+    /// `SIGUSR1`.
+    Usr1,
+    /// `SIGUSR2`.
+    Usr2,
+    /// `SIGSTOP`, suspending the process.
+    Stop,
+    /// `SIGCONT`, resuming a stopped process.
+    Cont,
+}
+
+#[cfg(unix)]
+impl From<Signal> for nix::sys::signal::Signal {
+    fn from(signal: Signal) -> Self {
+        match signal {
+            Signal::Hup => Self::SIGHUP,
+            Signal::Int => Self::SIGINT,
+            Signal::Quit => Self::SIGQUIT,
+            Signal::Term => Self::SIGTERM,
+            Signal::Kill => Self::SIGKILL,
+            Signal::Usr1 => Self::SIGUSR1,
+            Signal::Usr2 => Self::SIGUSR2,
+            Signal::Stop => Self::SIGSTOP,
+            Signal::Cont => Self::SIGCONT,
+        }
+    }
+}
+
+/// Cursor shape requested by a pane's program.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum CursorShape {
+    /// Full-cell block cursor.
+    #[default]
+    Block,
+    /// Underline cursor.
+    Underline,
+    /// Vertical bar (I-beam) cursor.
+    Bar,
+}
+
+/// Cursor style set via DECSCUSR (`CSI Ps SP q`).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CursorStyle {
+    /// Cursor shape.
+    pub shape: CursorShape,
+    /// Whether the cursor should blink.
+    pub blinking: bool,
+}
+
+impl Default for CursorStyle {
+    fn default() -> Self {
+        Self {
+            shape: CursorShape::Block,
+            blinking: true,
+        }
+    }
+}
+
+impl CursorStyle {
+    /// Encode this style as a DECSCUSR sequence.
+    #[must_use]
+    pub fn to_decscusr(self) -> Vec<u8> {
+        let param = match self.shape {
+            CursorShape::Block => 1,
+            CursorShape::Underline => 3,
+            CursorShape::Bar => 5,
+        } + u8::from(!self.blinking);
+        format!("\x1b[{param} q").into_bytes()
+    }
+}
+
+/// How the cursor is drawn while a pane is not focused.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum UnfocusedCursor {
+    /// Not at all.
+    #[default]
+    Hide,
+    /// As an outline, so it is clear where typing would go.
+    Hollow,
+}
+
+/// How a pane's cursor is drawn.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
+pub struct CursorConfig {
+    /// Cursor of the pane while it is not focused.
+    pub unfocused: UnfocusedCursor,
+    /// Blink the cursor when the program asks for a blinking one; see
+    /// [`PaneManager::cursor_blink_on`](crate::PaneManager::cursor_blink_on).
+    pub blink: bool,
+    /// Cursor color; by default a block cursor reverses its cell.
+    pub color: Option<Color>,
+    /// Hide the cursor while the program hides it (DECTCEM,
+    /// `CSI ? 25 l`).
+    pub honor_visibility: bool,
+}
+
+impl Default for CursorConfig {
+    fn default() -> Self {
+        Self {
+            unfocused: UnfocusedCursor::Hide,
+            blink: false,
+            color: None,
+            honor_visibility: true,
+        }
+    }
+}
+
 /// Pane dimensions in rows and columns.
-#[derive(Clone, Copy, Debug, Default)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct PaneSize {
     /// Number of rows.
     pub rows: u16,
@@ -64,8 +210,95 @@ impl PaneSize {
     }
 }
 
+/// What the manager does once a pane's process exits or crashes.
+///
+/// Set for all panes with [`ManagerConfig::on_exit`](crate::ManagerConfig::on_exit),
+/// or for one with [`SpawnConfig::on_exit`].
+///
+/// Applied by [`PaneManager::poll_events`](crate::PaneManager::poll_events)
+/// after hooks have run; a pane a hook already closed or respawned is left
+/// alone.
+#[derive(Clone, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ExitBehavior {
+    /// Keep the pane open, showing its final output.
+    #[default]
+    KeepPane,
+    /// Close the pane.
+    ClosePane,
+    /// Run the pane's command again in the same slot, at most `max` times
+    /// in a row (`None` for no limit). The first restart waits `backoff`,
+    /// and each one after that waits twice as long as the previous, up to
+    /// 5 minutes or `backoff`, whichever is longer.
+    ///
+    /// A restarted pane that stays up as long as its next restart would
+    /// wait, and at least a second, counts as having come up: the count
+    /// towards `max` and the backoff start over.
+    Restart { max: Option<u32>, backoff: Duration },
+    /// Replace the pane with a different command in the same slot.
+    RunCommand(Box<SpawnConfig>),
+}
+
+/// Longest wait before a restart, unless the backoff is longer.
+const MAX_RESTART_DELAY: Duration = Duration::from_mins(5);
+
+/// Shortest run after which a restarted pane counts as having come up.
+const MIN_STABLE_RUN: Duration = Duration::from_secs(1);
+
+/// Wait before the next restart of a pane restarted `restarts` times in a
+/// row by [`ExitBehavior::Restart`] with `backoff`.
+pub(crate) fn restart_delay(backoff: Duration, restarts: u32) -> Duration {
+    backoff
+        .saturating_mul(1 << restarts.min(16))
+        .min(MAX_RESTART_DELAY.max(backoff))
+}
+
+/// Restarts in a row of a pane restarted `restarts` times that exited
+/// after `runtime`: none once it stayed up long enough to have come up.
+pub(crate) fn restarts_in_a_row(backoff: Duration, restarts: u32, runtime: Duration) -> u32 {
+    if runtime >= restart_delay(backoff, restarts).max(MIN_STABLE_RUN) {
+        0
+    } else {
+        restarts
+    }
+}
+
+/// Colors that identify a pane in a dense layout, e.g. blue for the
+/// frontend and green for the backend.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
+pub struct PaneColors {
+    /// Border color while the pane is not focused; the focused pane keeps
+    /// the theme's focus style.
+    pub accent: Option<Color>,
+    /// Background of the pane's border, and of cells the program leaves
+    /// at the default background.
+    pub tint: Option<Color>,
+}
+
+/// Where in the cockpit a pane is placed.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Slot {
+    /// The next free pane position (110, 120, 210, 220).
+    #[default]
+    Pane,
+    /// A sub-pane position by its label, e.g. 112 for the second sub-pane
+    /// below pane 110. See [`SubPaneArrangement`](crate::SubPaneArrangement).
+    SubPane(u16),
+}
+
 /// Configuration for spawning a new pane.
 #[derive(Clone, Debug, Default)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
 pub struct SpawnConfig {
     /// Command to run. If None, uses the default shell.
     pub command: Option<String>,
@@ -73,6 +306,11 @@ pub struct SpawnConfig {
     /// Arguments to pass to the command.
     pub args: Vec<String>,
 
+    /// Name the process sees as `argv[0]`, if different from `command`.
+    /// Only a login name (`-bash` for `bash`, without arguments) is
+    /// supported by the PTY backend; spawning fails otherwise.
+    pub argv0: Option<String>,
+
     /// Initial size of the pane.
     pub size: PaneSize,
 
@@ -82,8 +320,41 @@ pub struct SpawnConfig {
     /// Additional environment variables.
     pub env: HashMap<String, String>,
 
+    /// Start from an empty environment instead of inheriting the parent
+    /// process's; only `env` (and the manager's `TERM` defaults) remain.
+    pub clear_env: bool,
+
+    /// Inherited environment variables to leave out.
+    pub env_remove: Vec<String>,
+
     /// Scrollback buffer size in lines.
     pub scrollback: usize,
+
+    /// Pause reading PTY output while the pane is hidden, scrolled back,
+    /// or its events are not being polled. The child blocks on write once
+    /// the kernel buffer fills up.
+    pub flow_control: bool,
+
+    /// Start the shell as a login shell so profile files are read.
+    /// Ignored on Windows, where shells have no login mode.
+    pub login_shell: bool,
+
+    /// What to do when the process exits. `None` follows the manager's
+    /// [`on_exit`](crate::ManagerConfig::on_exit).
+    pub on_exit: Option<ExitBehavior>,
+
+    /// Position the pane is placed in.
+    pub slot: Slot,
+
+    /// Name shown in the pane's border instead of the title the program
+    /// sets.
+    pub name: Option<String>,
+
+    /// Border and background colors of the pane.
+    pub colors: PaneColors,
+
+    /// How the pane's cursor is drawn.
+    pub cursor: CursorConfig,
 }
 
 impl SpawnConfig {
@@ -110,6 +381,43 @@ impl SpawnConfig {
         }
     }
 
+    /// Create a spawn config from a command line such as
+    /// `npm run dev -- --port 3000`.
+    ///
+    /// The line is split into words with `sh` quoting rules, but not run
+    /// through a shell: no globbing, variables or pipes. Use
+    /// [`new_shell_command`](Self::new_shell_command) for those.
+    ///
+    /// # Errors
+    /// Returns [`Error::CommandLine`] if the line is empty or has an
+    /// unterminated quote.
+    pub fn from_command_line(line: &str) -> Result<Self> {
+        let mut words = crate::cmdline::split(line)?.into_iter();
+        let command = words
+            .next()
+            .ok_or_else(|| Error::CommandLine("empty command".into()))?;
+        Ok(Self::new_command(command).args(words.collect()))
+    }
+
+    /// Create a spawn config that runs `line` through `sh -c` (`cmd /C` on
+    /// Windows), so pipes, variables and globs work.
+    #[must_use]
+    pub fn new_shell_command(line: impl Into<String>) -> Self {
+        let (shell, flag) = if cfg!(windows) {
+            ("cmd.exe", "/C")
+        } else {
+            ("/bin/sh", "-c")
+        };
+        Self::new_command(shell).args(vec![flag.to_string(), line.into()])
+    }
+
+    /// Set what happens when the process exits.
+    #[must_use]
+    pub fn on_exit(mut self, behavior: ExitBehavior) -> Self {
+        self.on_exit = Some(behavior);
+        self
+    }
+
     /// Create a new spawn config with specified size.
     ///
     /// Note: The size may be overridden by the `PaneManager`'s automatic
@@ -137,6 +445,25 @@ impl SpawnConfig {
         self
     }
 
+    /// Set the command and its arguments from an argument vector, with no
+    /// splitting or quoting involved. An empty vector selects the default
+    /// shell.
+    #[must_use]
+    pub fn argv(mut self, argv: Vec<String>) -> Self {
+        let mut argv = argv.into_iter();
+        self.command = argv.next();
+        self.args = argv.collect();
+        self
+    }
+
+    /// Set the name the process sees as `argv[0]`, e.g. `-bash` to start a
+    /// login shell. See the `argv0` field for what is supported.
+    #[must_use]
+    pub fn argv0(mut self, name: impl Into<String>) -> Self {
+        self.argv0 = Some(name.into());
+        self
+    }
+
     /// Set the working directory.
     #[must_use]
     pub fn cwd(mut self, path: impl Into<PathBuf>) -> Self {
@@ -151,16 +478,98 @@ impl SpawnConfig {
         self
     }
 
+    /// Start from an empty environment instead of inheriting everything
+    /// from the parent process. Variables added with [`env`](Self::env)
+    /// are still set.
+    #[must_use]
+    pub fn clear_env(mut self) -> Self {
+        self.clear_env = true;
+        self
+    }
+
+    /// Leave an inherited environment variable out, e.g. to strip secrets.
+    #[must_use]
+    pub fn env_remove(mut self, key: impl Into<String>) -> Self {
+        let key = key.into();
+        self.env.remove(&key);
+        self.env_remove.push(key);
+        self
+    }
+
+    /// Set `TERM` for this pane, overriding the manager's default.
+    #[must_use]
+    pub fn term(self, term: impl Into<String>) -> Self {
+        self.env("TERM", term)
+    }
+
+    /// Set `COLORTERM` for this pane, overriding the manager's default.
+    #[must_use]
+    pub fn colorterm(self, colorterm: impl Into<String>) -> Self {
+        self.env("COLORTERM", colorterm)
+    }
+
     /// Set the scrollback buffer size.
     #[must_use]
     pub fn scrollback(mut self, lines: usize) -> Self {
         self.scrollback = lines;
         self
     }
+
+    /// Enable or disable flow control for the pane's output.
+    #[must_use]
+    pub fn flow_control(mut self, enabled: bool) -> Self {
+        self.flow_control = enabled;
+        self
+    }
+
+    /// Start the shell as a login shell (`-bash`, or `-l` when arguments
+    /// are given).
+    #[must_use]
+    pub fn login_shell(mut self, login: bool) -> Self {
+        self.login_shell = login;
+        self
+    }
+
+    /// Place the pane in a specific position, e.g. `Slot::SubPane(112)`.
+    #[must_use]
+    pub fn slot(mut self, slot: Slot) -> Self {
+        self.slot = slot;
+        self
+    }
+
+    /// Name the pane, e.g. "server". The name is shown in its border
+    /// instead of the title the program sets.
+    #[must_use]
+    pub fn name(mut self, name: impl Into<String>) -> Self {
+        self.name = Some(name.into());
+        self
+    }
+
+    /// Color the pane's border while it is not focused.
+    #[must_use]
+    pub fn accent(mut self, color: Color) -> Self {
+        self.colors.accent = Some(color);
+        self
+    }
+
+    /// Tint the pane's background where the program draws none.
+    #[must_use]
+    pub fn tint(mut self, color: Color) -> Self {
+        self.colors.tint = Some(color);
+        self
+    }
+
+    /// Set how the pane's cursor is drawn.
+    #[must_use]
+    pub fn cursor(mut self, cursor: CursorConfig) -> Self {
+        self.cursor = cursor;
+        self
+    }
 }
 
 /// A snapshot of the terminal screen state.
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ScreenSnapshot {
     /// Screen content as a 2D grid of cells.
     cells: Vec<Vec<ScreenCell>>,
@@ -171,10 +580,18 @@ pub struct ScreenSnapshot {
 }
 
 /// A single cell in the terminal screen.
-#[derive(Clone, Debug, Default)]
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[allow(clippy::struct_excessive_bools)]
 pub struct ScreenCell {
-    /// The character in this cell.
+    /// The first character in this cell.
     pub char: char,
+    /// The full grapheme cluster in this cell (base character plus any
+    /// combining characters). Empty for wide-character continuation cells.
+    pub contents: String,
+    /// Display width in columns: 2 for wide characters, 0 for the
+    /// continuation cell that follows them, 1 otherwise.
+    pub width: u8,
     /// Foreground color.
     pub fg: ScreenColor,
     /// Background color.
@@ -183,14 +600,44 @@ pub struct ScreenCell {
     pub bold: bool,
     /// Text is italic.
     pub italic: bool,
-    /// Text is underlined.
+    /// Text is underlined, in any style.
     pub underline: bool,
     /// Text is inverse (swapped fg/bg).
     pub inverse: bool,
+    /// Text is dim (faint).
+    pub dim: bool,
+    /// Text blinks.
+    pub blink: bool,
+    /// Text is crossed out.
+    pub strikethrough: bool,
+    /// How the text is underlined.
+    pub underline_style: UnderlineStyle,
+    /// Color of the underline; `Default` draws it in the text color.
+    pub underline_color: ScreenColor,
+}
+
+/// How text is underlined, as set by SGR 4 and its subparameters.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum UnderlineStyle {
+    /// Not underlined.
+    #[default]
+    None,
+    /// A single straight line.
+    Single,
+    /// Two straight lines.
+    Double,
+    /// A wavy line, as used by editors for diagnostics.
+    Curly,
+    /// A dotted line.
+    Dotted,
+    /// A dashed line.
+    Dashed,
 }
 
 /// Terminal color representation.
-#[derive(Clone, Copy, Debug, Default)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum ScreenColor {
     /// Default terminal color.
     #[default]
@@ -202,9 +649,9 @@ pub enum ScreenColor {
 }
 
 impl ScreenSnapshot {
-    /// Create a snapshot from a vt100 parser.
-    pub(crate) fn from_parser(parser: &vt100::Parser) -> Self {
-        let screen = parser.screen();
+    /// Create a snapshot of an emulator's screen.
+    pub(crate) fn from_emulator(emulator: &Emulator) -> Self {
+        let screen = emulator.screen();
         let size = PaneSize::new(screen.size().0, screen.size().1);
         let (cursor_row, cursor_col) = screen.cursor_position();
 
@@ -212,17 +659,33 @@ impl ScreenSnapshot {
         for row in 0..size.rows {
             let mut row_cells = Vec::with_capacity(size.cols as usize);
             for col in 0..size.cols {
-                let cell = screen
-                    .cell(row, col)
-                    .map_or_else(ScreenCell::default, |c| ScreenCell {
+                let cell = screen.cell(row, col).map_or_else(ScreenCell::default, |c| {
+                    let extra = emulator.extra_attributes(row, col);
+                    let underline_style = match extra.underline_style {
+                        UnderlineStyle::None if c.underline() => UnderlineStyle::Single,
+                        style => style,
+                    };
+                    ScreenCell {
                         char: c.contents().chars().next().unwrap_or(' '),
+                        contents: c.contents(),
+                        width: if c.is_wide() {
+                            2
+                        } else {
+                            u8::from(!c.is_wide_continuation())
+                        },
                         fg: convert_vt100_color(c.fgcolor()),
                         bg: convert_vt100_color(c.bgcolor()),
                         bold: c.bold(),
                         italic: c.italic(),
-                        underline: c.underline(),
+                        underline: underline_style != UnderlineStyle::None,
                         inverse: c.inverse(),
-                    });
+                        dim: extra.dim,
+                        blink: extra.blink,
+                        strikethrough: extra.strikethrough,
+                        underline_style,
+                        underline_color: convert_vt100_color(extra.underline_color),
+                    }
+                });
                 row_cells.push(cell);
             }
             cells.push(row_cells);
@@ -256,9 +719,47 @@ impl ScreenSnapshot {
     }
 
     /// Iterate over all rows.
+    ///
+    /// Each row has one entry per column; continuation cells of wide
+    /// characters have a `width` of 0 and should be skipped when rendering.
     pub fn rows(&self) -> impl Iterator<Item = &[ScreenCell]> {
         self.cells.iter().map(Vec::as_slice)
     }
+
+    /// Get the text of a row, without trailing whitespace.
+    #[must_use]
+    pub fn row_text(&self, row: u16) -> Option<String> {
+        let cells = self.cells.get(row as usize)?;
+        let mut text = String::with_capacity(cells.len());
+        for cell in cells.iter().filter(|c| c.width > 0) {
+            if cell.contents.is_empty() {
+                text.push(' ');
+            } else {
+                text.push_str(&cell.contents);
+            }
+        }
+        text.truncate(text.trim_end().len());
+        Some(text)
+    }
+
+    /// Get the screen content as text, one line per row.
+    #[must_use]
+    pub fn to_text(&self) -> String {
+        (0..self.size.rows)
+            .filter_map(|row| self.row_text(row))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    /// Check whether `needle` appears on any row.
+    ///
+    /// Text wrapped across rows is not matched.
+    #[must_use]
+    pub fn contains(&self, needle: &str) -> bool {
+        (0..self.size.rows)
+            .filter_map(|row| self.row_text(row))
+            .any(|text| text.contains(needle))
+    }
 }
 
 fn convert_vt100_color(color: vt100::Color) -> ScreenColor {
@@ -280,6 +781,9 @@ pub struct PaneHandle {
     /// Child process ID.
     child_pid: Option<u32>,
 
+    /// PTY master, owned by the manager while the pane is open.
+    master: Weak<Mutex<PtyMaster>>,
+
     /// Channel to send input to the pane.
     input_tx: mpsc::Sender<Vec<u8>>,
 
@@ -287,31 +791,100 @@ pub struct PaneHandle {
     state_rx: watch::Receiver<PaneState>,
 
     /// Shared screen state for reading.
-    screen: Arc<RwLock<vt100::Parser>>,
+    screen: Arc<RwLock<Emulator>>,
+
+    /// Scrollback limit the parser was built with, in lines.
+    scrollback_limit: Arc<AtomicUsize>,
+
+    /// Name from the spawn config.
+    name: Option<String>,
 
-    /// Pane title.
+    /// Colors from the spawn config.
+    colors: PaneColors,
+
+    /// Cursor drawing from the spawn config.
+    cursor: CursorConfig,
+
+    /// Title set by the program with an OSC escape sequence.
     title: Arc<RwLock<String>>,
+
+    /// Terminal state tracked outside of the vt100 parser.
+    extended: Arc<Mutex<ExtendedState>>,
+
+    /// Output generation, bumped each time the screen is updated.
+    generation: Arc<watch::Sender<u64>>,
+
+    /// Cells from the last render, reused while the screen is unchanged.
+    render_cache: Arc<Mutex<RenderCache>>,
+
+    /// I/O and render counters.
+    metrics: Arc<PaneMetrics>,
+
+    /// Recent output as plain text, for pattern matching.
+    transcript: Arc<Mutex<Transcript>>,
+
+    /// Text selected with the mouse.
+    selection: Arc<Mutex<Option<Selection>>>,
+
+    /// Matches of the active search.
+    search: Arc<Mutex<Option<Search>>>,
+
+    /// Process table shared with the manager.
+    #[cfg(feature = "resource-usage")]
+    resources: Option<Arc<Mutex<ResourceMonitor>>>,
 }
 
 impl PaneHandle {
     /// Create a new pane handle.
+    #[allow(clippy::too_many_arguments)]
     pub(crate) fn new(
         id: PaneId,
         child_pid: Option<u32>,
+        master: Weak<Mutex<PtyMaster>>,
         input_tx: mpsc::Sender<Vec<u8>>,
         state_rx: watch::Receiver<PaneState>,
-        screen: Arc<RwLock<vt100::Parser>>,
+        screen: Arc<RwLock<Emulator>>,
+        scrollback: usize,
+        name: Option<String>,
+        colors: PaneColors,
+        cursor: CursorConfig,
+        title: Arc<RwLock<String>>,
+        extended: Arc<Mutex<ExtendedState>>,
+        generation: Arc<watch::Sender<u64>>,
+        metrics: Arc<PaneMetrics>,
+        transcript: Arc<Mutex<Transcript>>,
     ) -> Self {
         Self {
             id,
             child_pid,
+            master,
             input_tx,
             state_rx,
             screen,
-            title: Arc::new(RwLock::new(String::new())),
+            scrollback_limit: Arc::new(AtomicUsize::new(scrollback)),
+            name,
+            colors,
+            cursor,
+            title,
+            extended,
+            generation,
+            render_cache: Arc::new(Mutex::new(RenderCache::default())),
+            metrics,
+            transcript,
+            selection: Arc::new(Mutex::new(None)),
+            search: Arc::new(Mutex::new(None)),
+            #[cfg(feature = "resource-usage")]
+            resources: None,
         }
     }
 
+    /// Sample resource usage from the manager's process table.
+    #[cfg(feature = "resource-usage")]
+    pub(crate) fn with_resources(mut self, resources: Arc<Mutex<ResourceMonitor>>) -> Self {
+        self.resources = Some(resources);
+        self
+    }
+
     /// Get the pane ID.
     #[must_use]
     pub fn id(&self) -> PaneId {
@@ -324,6 +897,94 @@ impl PaneHandle {
         self.child_pid
     }
 
+    /// Process ID of the program in the foreground of the pane's terminal,
+    /// e.g. `vim` started from the pane's shell. This is the shell itself
+    /// while it waits at the prompt.
+    ///
+    /// `None` once the pane is closed, or if it cannot be determined.
+    ///
+    /// # Panics
+    /// Panics if the PTY master lock is poisoned.
+    #[cfg(unix)]
+    #[must_use]
+    pub fn foreground_pid(&self) -> Option<u32> {
+        self.master
+            .upgrade()?
+            .lock()
+            .expect("pty master lock poisoned")
+            .foreground_pid()
+    }
+
+    /// Name of the program in the foreground of the pane's terminal, such
+    /// as `vim` or `bash`, for borders and status bars.
+    ///
+    /// Read from `/proc`, so only available on Linux.
+    ///
+    /// # Panics
+    /// Panics if the PTY master lock is poisoned.
+    #[cfg(target_os = "linux")]
+    #[must_use]
+    pub fn foreground_process(&self) -> Option<String> {
+        let pid = self.foreground_pid()?;
+        let comm = std::fs::read_to_string(format!("/proc/{pid}/comm")).ok()?;
+        Some(comm.trim_end().to_string())
+    }
+
+    /// Send a signal to the pane's process, e.g. [`Signal::Hup`] to make a
+    /// server reload its configuration.
+    ///
+    /// Only the process cockpit spawned is signalled, not the programs it
+    /// started in turn.
+    ///
+    /// # Errors
+    /// Returns [`Error::Signal`] if the pane's process has exited, the
+    /// pane has no process ID or the signal could not be delivered.
+    #[cfg(unix)]
+    pub fn signal(&self, signal: Signal) -> Result<()> {
+        // Its process ID may already belong to another process
+        if !self.is_alive() {
+            return Err(Error::Signal(std::io::Error::new(
+                std::io::ErrorKind::NotFound,
+                "pane process has exited",
+            )));
+        }
+        let pid = self.child_pid.ok_or_else(|| {
+            Error::Signal(std::io::Error::new(
+                std::io::ErrorKind::NotFound,
+                "pane has no process ID",
+            ))
+        })?;
+        let pid = i32::try_from(pid).map_err(|_| {
+            Error::Signal(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                "process ID out of range",
+            ))
+        })?;
+        nix::sys::signal::kill(nix::unistd::Pid::from_raw(pid), Some(signal.into()))
+            .map_err(|errno| Error::Signal(errno.into()))
+    }
+
+    /// The pane's current working directory.
+    ///
+    /// Uses the directory last reported by the shell via OSC 7, falling
+    /// back to `/proc/<pid>/cwd` on Linux. `None` if neither is available.
+    ///
+    /// # Panics
+    /// Panics if the extended state lock is poisoned.
+    #[must_use]
+    pub fn cwd(&self) -> Option<PathBuf> {
+        let reported = self
+            .extended
+            .lock()
+            .expect("extended lock poisoned")
+            .cwd
+            .clone();
+        reported.or_else(|| {
+            let pid = self.child_pid?;
+            std::fs::read_link(format!("/proc/{pid}/cwd")).ok()
+        })
+    }
+
     /// Send input bytes to the pane's PTY.
     ///
     /// # Errors
@@ -335,12 +996,88 @@ impl PaneHandle {
             .map_err(|_| Error::PaneClosed)
     }
 
+    /// Send input bytes to the pane's PTY, blocking while its input queue
+    /// is full.
+    ///
+    /// Must not be called from async code; use
+    /// [`send_input`](Self::send_input) there.
+    ///
+    /// # Errors
+    /// Returns an error if the pane has been closed.
+    #[cfg(feature = "sync")]
+    pub fn send_input_blocking(&self, data: &[u8]) -> Result<()> {
+        self.input_tx
+            .blocking_send(data.to_vec())
+            .map_err(|_| Error::PaneClosed)
+    }
+
+    /// Whether input sent to the pane is still queued for the writer task.
+    #[cfg_attr(not(any(test, feature = "testing")), allow(dead_code))]
+    pub(crate) fn input_pending(&self) -> bool {
+        self.input_tx.capacity() < self.input_tx.max_capacity()
+    }
+
+    /// Send text to the pane's PTY, encoded as UTF-8.
+    ///
+    /// # Errors
+    /// Returns an error if the pane has been closed.
+    pub async fn send_text(&self, text: &str) -> Result<()> {
+        self.send_input(text.as_bytes()).await
+    }
+
+    /// Send a line of text followed by Enter (`\r`).
+    ///
+    /// # Errors
+    /// Returns an error if the pane has been closed.
+    pub async fn send_line(&self, line: &str) -> Result<()> {
+        let mut data = Vec::with_capacity(line.len() + 1);
+        data.extend_from_slice(line.as_bytes());
+        data.push(b'\r');
+        self.send_input(&data).await
+    }
+
+    /// Send whitespace-separated tmux-style key names, e.g. `"C-c"`,
+    /// `"Up Up Enter"` or `"M-x S-Tab F5"`.
+    ///
+    /// Keys are encoded for the pane's current input modes (such as
+    /// application cursor keys). Use [`send_text`](Self::send_text) for
+    /// literal text.
+    ///
+    /// # Errors
+    /// Returns an error if a key name is not recognized (nothing is sent
+    /// then) or the pane has been closed.
+    pub async fn send_keys(&self, keys: &str) -> Result<()> {
+        let modes = self.input_modes();
+        let mut data = Vec::new();
+        for name in keys.split_whitespace() {
+            let key = keys::parse_key(name).ok_or_else(|| Error::InvalidKey(name.to_string()))?;
+            data.extend(keys::key_to_bytes(key, modes, KeyboardProtocol::Legacy));
+        }
+        if data.is_empty() {
+            return Ok(());
+        }
+        self.send_input(&data).await
+    }
+
     /// Get the current pane state.
     #[must_use]
     pub fn state(&self) -> PaneState {
         self.state_rx.borrow().clone()
     }
 
+    /// Wait for the pane's process to exit, returning its final state.
+    ///
+    /// If the pane is closed before its process exits, the last known
+    /// state is returned.
+    pub async fn wait_exit(&self) -> PaneState {
+        let mut state_rx = self.state_rx.clone();
+        let exited = state_rx
+            .wait_for(|state| !state.is_alive())
+            .await
+            .map(|state| state.clone());
+        exited.unwrap_or_else(|_| state_rx.borrow().clone())
+    }
+
     /// Check if the pane's process is still alive.
     #[must_use]
     pub fn is_alive(&self) -> bool {
@@ -348,27 +1085,519 @@ impl PaneHandle {
     }
 
     /// Get a snapshot of the terminal screen.
+    ///
+    /// # Panics
+    /// Panics if the screen lock is poisoned.
     #[must_use]
     pub fn screen_snapshot(&self) -> ScreenSnapshot {
         let screen = self.screen.read().expect("screen lock poisoned");
-        ScreenSnapshot::from_parser(&screen)
+        ScreenSnapshot::from_emulator(&screen)
+    }
+
+    /// Clear the visible screen and move the cursor home, like `clear`.
+    ///
+    /// Only cockpit's copy of the screen is cleared; the program is not
+    /// told and may redraw over it.
+    ///
+    /// # Panics
+    /// Panics if the screen lock is poisoned.
+    pub fn clear_screen(&self) {
+        self.screen
+            .write()
+            .expect("screen lock poisoned")
+            .process(b"\x1b[H\x1b[2J");
+        self.generation.send_modify(|g| *g = g.wrapping_add(1));
+    }
+
+    /// Drop the scrollback history, freeing its memory. The visible screen
+    /// is kept.
+    ///
+    /// # Panics
+    /// Panics if the screen lock is poisoned.
+    pub fn clear_scrollback(&self) {
+        self.rebuild_screen(self.scrollback_limit.load(Ordering::Relaxed));
+    }
+
+    /// Change how many lines of scrollback the pane keeps.
+    ///
+    /// The emulator is rebuilt from the visible screen, so existing history
+    /// is dropped. While a full-screen program is running, the screen it
+    /// will return to on exit is lost as well.
+    ///
+    /// # Panics
+    /// Panics if the screen lock is poisoned.
+    pub fn set_scrollback(&self, lines: usize) {
+        self.scrollback_limit.store(lines, Ordering::Relaxed);
+        self.rebuild_screen(lines);
+    }
+
+    /// Maximum number of scrollback lines the pane keeps.
+    #[must_use]
+    pub fn scrollback_limit(&self) -> usize {
+        self.scrollback_limit.load(Ordering::Relaxed)
+    }
+
+    /// Number of lines currently held in scrollback.
+    ///
+    /// # Panics
+    /// Panics if the screen lock is poisoned.
+    #[must_use]
+    pub fn scrollback_len(&self) -> usize {
+        let mut parser = self.screen.write().expect("screen lock poisoned");
+        let offset = parser.screen().scrollback();
+        parser.set_scrollback(usize::MAX);
+        let len = parser.screen().scrollback();
+        parser.set_scrollback(offset);
+        len
+    }
+
+    /// How many lines the view is scrolled back into scrollback; 0 shows
+    /// the live screen.
+    ///
+    /// # Panics
+    /// Panics if the screen lock is poisoned.
+    #[must_use]
+    pub fn scroll_offset(&self) -> usize {
+        self.screen
+            .read()
+            .expect("screen lock poisoned")
+            .screen()
+            .scrollback()
+    }
+
+    /// Furthest the view can be scrolled back: the lines held in
+    /// scrollback, but at most one screen height, as the emulator cannot
+    /// show further back.
+    ///
+    /// # Panics
+    /// Panics if the screen lock is poisoned.
+    #[must_use]
+    pub fn max_scroll_offset(&self) -> usize {
+        let rows = self
+            .screen
+            .read()
+            .expect("screen lock poisoned")
+            .screen()
+            .size()
+            .0;
+        self.scrollback_len().min(usize::from(rows))
+    }
+
+    /// Scroll the view back `lines` into scrollback, limited to
+    /// [`max_scroll_offset`](Self::max_scroll_offset). 0 returns to the
+    /// live screen.
+    ///
+    /// While scrolled back, the view stays on the same lines as output
+    /// arrives.
+    ///
+    /// # Panics
+    /// Panics if the screen lock is poisoned.
+    pub fn set_scroll_offset(&self, lines: usize) {
+        let lines = lines.min(self.max_scroll_offset());
+        self.screen
+            .write()
+            .expect("screen lock poisoned")
+            .set_scrollback(lines);
+        self.generation.send_modify(|g| *g = g.wrapping_add(1));
+    }
+
+    /// Replace the emulator with one holding `scrollback` lines of history
+    /// and the current visible screen.
+    fn rebuild_screen(&self, scrollback: usize) {
+        self.screen
+            .write()
+            .expect("screen lock poisoned")
+            .rebuild(scrollback);
+        self.generation.send_modify(|g| *g = g.wrapping_add(1));
+    }
+
+    /// Check if the pane's program is using the alternate screen.
+    ///
+    /// Full-screen programs (editors, pagers) switch to the alternate
+    /// screen, which has no scrollback; mouse wheel events are usually
+    /// better forwarded to them than used to scroll.
+    ///
+    /// # Panics
+    /// Panics if the screen lock is poisoned.
+    #[must_use]
+    pub fn is_alternate_screen(&self) -> bool {
+        self.screen
+            .read()
+            .expect("screen lock poisoned")
+            .screen()
+            .alternate_screen()
+    }
+
+    /// Get the input modes currently requested by the pane's program.
+    ///
+    /// # Panics
+    /// Panics if the screen or extended state lock is poisoned.
+    #[must_use]
+    pub fn input_modes(&self) -> InputModes {
+        let application_cursor = self
+            .screen
+            .read()
+            .expect("screen lock poisoned")
+            .screen()
+            .application_cursor();
+        let extended = self.extended.lock().expect("extended lock poisoned");
+        InputModes {
+            application_cursor,
+            kitty_flags: extended.active_kitty_flags(),
+            modify_other_keys: extended.modify_other_keys,
+        }
+    }
+
+    /// Get the cursor style requested by the pane's program.
+    ///
+    /// # Panics
+    /// Panics if the extended state lock is poisoned.
+    #[must_use]
+    pub fn cursor_style(&self) -> CursorStyle {
+        self.extended
+            .lock()
+            .expect("extended lock poisoned")
+            .cursor_style
+    }
+
+    /// Get the keyboard protocol state negotiated by the pane's program.
+    ///
+    /// # Panics
+    /// Panics if the extended state lock is poisoned.
+    pub(crate) fn keyboard_state(&self) -> KeyboardState {
+        self.extended
+            .lock()
+            .expect("extended lock poisoned")
+            .keyboard()
+    }
+
+    /// Take the bytes this pane wants forwarded to the host terminal.
+    ///
+    /// # Panics
+    /// Panics if the extended state lock is poisoned.
+    #[must_use]
+    pub fn take_passthrough(&self) -> Vec<u8> {
+        let mut extended = self.extended.lock().expect("extended lock poisoned");
+        std::mem::take(&mut extended.passthrough)
+    }
+
+    /// Take the inline images this pane emitted since the last call.
+    ///
+    /// Only collected when graphics passthrough is enabled in the
+    /// [`ManagerConfig`](crate::ManagerConfig).
+    ///
+    /// # Panics
+    /// Panics if the extended state lock is poisoned.
+    #[must_use]
+    pub fn take_images(&self) -> Vec<InlineImage> {
+        let mut extended = self.extended.lock().expect("extended lock poisoned");
+        std::mem::take(&mut extended.images)
+    }
+
+    /// The current selection, if any.
+    ///
+    /// # Panics
+    /// Panics if the selection lock is poisoned.
+    #[must_use]
+    pub fn selection(&self) -> Option<Selection> {
+        *self.selection.lock().expect("selection lock poisoned")
+    }
+
+    /// Select a range of the pane's screen, replacing any selection.
+    ///
+    /// # Panics
+    /// Panics if the selection lock is poisoned.
+    pub fn set_selection(&self, selection: Selection) {
+        *self.selection.lock().expect("selection lock poisoned") = Some(selection);
+    }
+
+    /// Clear the selection.
+    ///
+    /// # Panics
+    /// Panics if the selection lock is poisoned.
+    pub fn clear_selection(&self) {
+        *self.selection.lock().expect("selection lock poisoned") = None;
+    }
+
+    /// Text of the current selection, as shown on the screen now.
+    ///
+    /// # Panics
+    /// Panics if the selection or screen lock is poisoned.
+    #[must_use]
+    pub fn selected_text(&self) -> Option<String> {
+        let selection = self.selection()?;
+        let mut parser = self.screen.write().expect("screen lock poisoned");
+        Some(selection.text(parser.parser_mut()))
+    }
+
+    /// Search the pane's screen and the scrollback it can show for
+    /// `pattern`, replacing any active search, and scroll to the match
+    /// closest to the bottom. Returns the number of matches.
+    ///
+    /// Matches are found once: rows move up as output arrives, so search
+    /// again to follow them.
+    ///
+    /// # Panics
+    /// Panics if the search or screen lock is poisoned.
+    pub fn search(&self, pattern: impl Into<Pattern>) -> usize {
+        let search = {
+            let mut parser = self.screen.write().expect("screen lock poisoned");
+            Search::find(parser.parser_mut(), &pattern.into())
+        };
+        let count = search.matches.len();
+        self.set_search(search);
+        count
+    }
+
+    /// The active search, if any.
+    ///
+    /// # Panics
+    /// Panics if the search lock is poisoned.
+    #[must_use]
+    pub fn search_state(&self) -> Option<Search> {
+        self.search.lock().expect("search lock poisoned").clone()
+    }
+
+    /// Make the next match of the active search current, scrolling to it.
+    ///
+    /// # Panics
+    /// Panics if the search or screen lock is poisoned.
+    pub fn search_next(&self) {
+        if let Some(mut search) = self.search_state() {
+            search.next();
+            self.set_search(search);
+        }
+    }
+
+    /// Make the previous match of the active search current, scrolling to
+    /// it.
+    ///
+    /// # Panics
+    /// Panics if the search or screen lock is poisoned.
+    pub fn search_prev(&self) {
+        if let Some(mut search) = self.search_state() {
+            search.prev();
+            self.set_search(search);
+        }
+    }
+
+    /// End the active search, removing its highlights.
+    ///
+    /// # Panics
+    /// Panics if the search lock is poisoned.
+    pub fn clear_search(&self) {
+        if self
+            .search
+            .lock()
+            .expect("search lock poisoned")
+            .take()
+            .is_some()
+        {
+            self.generation.send_modify(|g| *g = g.wrapping_add(1));
+        }
+    }
+
+    /// Make `search` active, scrolling its current match into view.
+    fn set_search(&self, search: Search) {
+        let current = search.current_match().copied();
+        *self.search.lock().expect("search lock poisoned") = Some(search);
+        let Some(current) = current else {
+            self.generation.send_modify(|g| *g = g.wrapping_add(1));
+            return;
+        };
+        let (rows, offset) = {
+            let parser = self.screen.read().expect("screen lock poisoned");
+            let screen = parser.screen();
+            (i32::from(screen.size().0), screen.scrollback())
+        };
+        let offset = i32::try_from(offset).unwrap_or(i32::MAX);
+        // Shown rows run from -offset on the top line
+        if (-offset..rows - offset).contains(&current.row) {
+            self.generation.send_modify(|g| *g = g.wrapping_add(1));
+        } else {
+            self.set_scroll_offset(usize::try_from(-current.row).unwrap_or(0));
+        }
+    }
+
+    /// Subscribe to screen updates.
+    ///
+    /// The receiver holds an output generation counter that is bumped by
+    /// the reader task each time the pane's screen changes, so async
+    /// frontends can `await` `changed()` instead of polling.
+    #[must_use]
+    pub fn subscribe_updates(&self) -> watch::Receiver<u64> {
+        self.generation.subscribe()
+    }
+
+    /// Wait until `pattern` appears in the pane's output, returning the
+    /// matched text.
+    ///
+    /// The visible screen is searched as a whole, so text already shown
+    /// matches immediately. Output that scrolls past between checks is
+    /// still found, as long as it arrived after the call.
+    ///
+    /// # Errors
+    /// Returns [`Error::Timeout`] if the pattern does not appear in time.
+    ///
+    /// # Panics
+    /// Panics if the transcript or screen lock is poisoned.
+    pub async fn wait_for(&self, pattern: impl Into<Pattern>, timeout: Duration) -> Result<String> {
+        let pattern = pattern.into();
+        let mut updates = self.generation.subscribe();
+        let start = self
+            .transcript
+            .lock()
+            .expect("transcript lock poisoned")
+            .position();
+        let deadline = task::Instant::now() + timeout;
+        loop {
+            updates.mark_unchanged();
+            let recent = self
+                .transcript
+                .lock()
+                .expect("transcript lock poisoned")
+                .since(start);
+            if let Some(found) = pattern.find(&recent) {
+                return Ok(found.to_string());
+            }
+            if let Some(found) = pattern.find(&self.screen_snapshot().to_text()) {
+                return Ok(found.to_string());
+            }
+
+            let now = task::Instant::now();
+            if now >= deadline {
+                return Err(Error::Timeout);
+            }
+            // Hidden panes log output without updating the screen, so
+            // re-check periodically as well
+            let wait = (deadline - now).min(WAIT_RECHECK_INTERVAL);
+            let _ = task::timeout(wait, updates.changed()).await;
+        }
+    }
+
+    /// Plain-text output logged since `position`, with the position the
+    /// returned text starts at.
+    pub(crate) fn output_since(&self, position: u64) -> (u64, String) {
+        self.transcript
+            .lock()
+            .expect("transcript lock poisoned")
+            .read_from(position)
+    }
+
+    /// I/O and render statistics of this pane.
+    ///
+    /// # Panics
+    /// Panics if the metrics lock is poisoned.
+    #[must_use]
+    pub fn stats(&self) -> PaneStats {
+        self.metrics.snapshot(Instant::now())
+    }
+
+    /// CPU and memory usage of the pane's process and everything it
+    /// started, sampled at most once per
+    /// [`resource_interval`](crate::ManagerConfig::resource_interval).
+    ///
+    /// `None` once the process has exited, or if it has no PID.
+    ///
+    /// # Panics
+    /// Panics if the resource monitor lock is poisoned.
+    #[cfg(feature = "resource-usage")]
+    #[must_use]
+    pub fn resource_usage(&self) -> Option<ResourceUsage> {
+        let pid = self.child_pid?;
+        self.resources
+            .as_ref()?
+            .lock()
+            .expect("resource monitor lock poisoned")
+            .usage(pid, Instant::now())
+    }
+
+    /// Counters updated by widgets drawing this pane.
+    pub(crate) fn metrics(&self) -> &PaneMetrics {
+        &self.metrics
+    }
+
+    /// Resize the terminal emulator to match a resized PTY.
+    ///
+    /// Returns `false` if the screen already had that size.
+    ///
+    /// # Panics
+    /// Panics if the screen lock is poisoned.
+    pub(crate) fn resize_screen(&self, size: PaneSize) -> bool {
+        {
+            let mut screen = self.screen.write().expect("screen lock poisoned");
+            if screen.screen().size() == (size.rows, size.cols) {
+                return false;
+            }
+            screen.set_size(size.rows, size.cols);
+        }
+        self.generation.send_modify(|g| *g = g.wrapping_add(1));
+        true
+    }
+
+    /// Current output generation.
+    pub(crate) fn generation(&self) -> u64 {
+        *self.generation.borrow()
+    }
+
+    /// Output generation last drawn by a widget, or the current generation
+    /// if the pane has never been drawn through the render cache.
+    ///
+    /// A widget may show an older frame while the reader holds the screen.
+    /// `None` if that frame was drawn for another area and must be drawn
+    /// again.
+    pub(crate) fn rendered_generation(&self) -> Option<u64> {
+        let cache = self
+            .render_cache
+            .lock()
+            .expect("render cache lock poisoned");
+        if cache.is_stale() {
+            return None;
+        }
+        Some(cache.generation().unwrap_or_else(|| self.generation()))
+    }
+
+    /// Render cache shared by all widgets drawing this pane.
+    pub(crate) fn render_cache(&self) -> &Arc<Mutex<RenderCache>> {
+        &self.render_cache
     }
 
     /// Get direct access to the screen parser for widget rendering.
-    pub(crate) fn screen(&self) -> &Arc<RwLock<vt100::Parser>> {
+    pub(crate) fn screen(&self) -> &Arc<RwLock<Emulator>> {
         &self.screen
     }
 
-    /// Get the pane title.
+    /// Name given in the spawn config.
+    #[must_use]
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    /// Colors given in the spawn config.
+    #[must_use]
+    pub fn colors(&self) -> PaneColors {
+        self.colors
+    }
+
+    /// How the cursor is drawn, as given in the spawn config.
+    #[must_use]
+    pub fn cursor_config(&self) -> CursorConfig {
+        self.cursor
+    }
+
+    /// Title the program set with an OSC escape sequence, empty if none.
+    ///
+    /// # Panics
+    /// Panics if the title lock is poisoned.
     #[must_use]
     pub fn title(&self) -> String {
         self.title.read().expect("title lock poisoned").clone()
     }
 
-    /// Set the pane title.
-    #[allow(dead_code)]
-    pub(crate) fn set_title(&self, title: String) {
-        *self.title.write().expect("title lock poisoned") = title;
+    /// Text shown in the pane's border: its name, or else its title.
+    #[must_use]
+    pub fn display_title(&self) -> String {
+        self.name.clone().unwrap_or_else(|| self.title())
     }
 }
 
@@ -380,3 +1609,67 @@ impl std::fmt::Debug for PaneHandle {
             .finish_non_exhaustive()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_snapshot_text() {
+        let mut emulator = Emulator::new(3, 10, 0);
+        emulator.process("ab 日本  \r\n\r\n  x".as_bytes());
+        let snapshot = ScreenSnapshot::from_emulator(&emulator);
+        assert_eq!(snapshot.row_text(0).as_deref(), Some("ab 日本"));
+        assert_eq!(snapshot.row_text(1).as_deref(), Some(""));
+        assert_eq!(snapshot.row_text(3), None);
+        assert_eq!(snapshot.to_text(), "ab 日本\n\n  x");
+        assert!(snapshot.contains("日本"));
+        assert!(!snapshot.contains("ab 日本  x"));
+    }
+
+    #[test]
+    fn test_restart_delay() {
+        let second = Duration::from_secs(1);
+        assert_eq!(restart_delay(second, 0), second);
+        assert_eq!(restart_delay(second, 3), 8 * second);
+        assert_eq!(restart_delay(second, 30), MAX_RESTART_DELAY);
+        let hour = Duration::from_hours(1);
+        assert_eq!(restart_delay(hour, 2), hour);
+
+        // Staying up as long as the next wait resets the count
+        assert_eq!(restarts_in_a_row(second, 3, 7 * second), 3);
+        assert_eq!(restarts_in_a_row(second, 3, 8 * second), 0);
+        assert_eq!(restarts_in_a_row(Duration::ZERO, 3, second / 2), 3);
+        assert_eq!(restarts_in_a_row(Duration::ZERO, 3, second), 0);
+    }
+
+    #[test]
+    fn test_from_command_line() {
+        let config = SpawnConfig::from_command_line("cargo test -- 'name with spaces'").unwrap();
+        assert_eq!(config.command.as_deref(), Some("cargo"));
+        assert_eq!(config.args, ["test", "--", "name with spaces"]);
+        assert!(SpawnConfig::from_command_line("  ").is_err());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_configs_deserialize_with_defaults() {
+        use serde::de::value::{Error as ValueError, MapDeserializer};
+        use serde::Deserialize;
+
+        let spawn = SpawnConfig::deserialize(MapDeserializer::<_, ValueError>::new(
+            [("login_shell", true), ("clear_env", true)].into_iter(),
+        ))
+        .unwrap();
+        assert!(spawn.login_shell && spawn.clear_env);
+        assert!(spawn.command.is_none());
+
+        let manager = crate::ManagerConfig::deserialize(MapDeserializer::<_, ValueError>::new(
+            [("spawn_focus", "Never")].into_iter(),
+        ))
+        .unwrap();
+        assert_eq!(manager.spawn_focus, crate::SpawnFocus::Never);
+        assert_eq!(manager.max_panes, 4);
+        assert_eq!(manager.term.as_deref(), Some("xterm-256color"));
+    }
+}
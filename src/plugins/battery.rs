@@ -0,0 +1,195 @@
+//! Battery plugin - displays the charge and charging state of the battery.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use ratatui::layout::Alignment;
+use ratatui::style::{Color, Style};
+
+use crate::plugins::context::PluginContext;
+use crate::plugins::{Plugin, PluginConfig, PluginResult};
+use crate::status_bar::StatusBarSegment;
+
+/// Where Linux lists batteries and chargers.
+const POWER_SUPPLY_DIR: &str = "/sys/class/power_supply";
+
+/// Charge of the batteries together, and whether they are charging.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct BatteryInfo {
+    percent: u8,
+    charging: bool,
+}
+
+impl BatteryInfo {
+    /// Read the batteries listed in `dir`, averaging their charge. `None`
+    /// if there are none.
+    fn read(dir: &Path) -> Option<Self> {
+        let mut percents = Vec::new();
+        let mut charging = false;
+        for entry in fs::read_dir(dir).ok()?.flatten() {
+            let path = entry.path();
+            let read = |name: &str| fs::read_to_string(path.join(name)).ok();
+            if read("type").as_deref().map(str::trim) != Some("Battery") {
+                continue;
+            }
+            let Some(percent) = read("capacity").and_then(|c| c.trim().parse::<u32>().ok()) else {
+                continue;
+            };
+            percents.push(percent.min(100));
+            let status = read("status").unwrap_or_default();
+            charging |= matches!(status.trim(), "Charging" | "Full");
+        }
+        let count = u32::try_from(percents.len()).ok().filter(|&n| n > 0)?;
+        let average = percents.iter().sum::<u32>() / count;
+        Some(Self {
+            percent: u8::try_from(average).unwrap_or(100),
+            charging,
+        })
+    }
+}
+
+/// Plugin that displays the battery's charge, with a `⚡` while it is
+/// charging, in yellow and then red as it runs low.
+///
+/// Reads the batteries the Linux kernel lists in `/sys/class/power_supply`;
+/// without one, such as on a desktop, the segment is empty.
+///
+/// Only Linux is supported. On macOS, Windows and other systems there is
+/// no such directory, so the segment is always empty.
+pub struct BatteryPlugin {
+    dir: PathBuf,
+    config: PluginConfig,
+    low: u8,
+    critical: u8,
+    info: Option<BatteryInfo>,
+}
+
+impl BatteryPlugin {
+    /// Create a plugin refreshed every 30 seconds, yellow at 20% and red
+    /// at 10% while discharging.
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            dir: PathBuf::from(POWER_SUPPLY_DIR),
+            config: PluginConfig {
+                refresh_interval: Duration::from_secs(30),
+                alignment: Alignment::Right,
+                ..PluginConfig::default()
+            },
+            low: 20,
+            critical: 10,
+            info: None,
+        }
+    }
+
+    /// Set how often the battery is read.
+    #[must_use]
+    pub fn refresh_interval(mut self, interval: Duration) -> Self {
+        self.config.refresh_interval = interval;
+        self
+    }
+
+    /// Set the charge, in percent, at or below which the segment turns
+    /// yellow and red while discharging.
+    #[must_use]
+    pub fn thresholds(mut self, low: u8, critical: u8) -> Self {
+        self.low = low;
+        self.critical = critical;
+        self
+    }
+}
+
+impl Default for BatteryPlugin {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Plugin for BatteryPlugin {
+    fn name(&self) -> &'static str {
+        "battery"
+    }
+
+    fn config(&self) -> PluginConfig {
+        self.config.clone()
+    }
+
+    fn refresh(&mut self, _ctx: &PluginContext) -> PluginResult<()> {
+        self.info = BatteryInfo::read(&self.dir);
+        Ok(())
+    }
+
+    fn render(&self) -> StatusBarSegment {
+        let Some(info) = self.info else {
+            return StatusBarSegment::new("");
+        };
+        let segment = StatusBarSegment::new(format!("{}%", info.percent));
+        if info.charging {
+            return segment.icon("⚡").style(Style::default().fg(Color::Green));
+        }
+        let style = if info.percent <= self.critical {
+            Style::default().fg(Color::Red)
+        } else if info.percent <= self.low {
+            Style::default().fg(Color::Yellow)
+        } else {
+            Style::default()
+        };
+        segment.style(style)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn supply(dir: &Path, name: &str, files: &[(&str, &str)]) {
+        let path = dir.join(name);
+        fs::create_dir_all(&path).unwrap();
+        for (file, contents) in files {
+            fs::write(path.join(file), contents).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_battery_status() {
+        let dir = std::env::temp_dir().join(format!("cockpit-battery-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        supply(&dir, "AC", &[("type", "Mains\n"), ("online", "0\n")]);
+        supply(
+            &dir,
+            "BAT0",
+            &[
+                ("type", "Battery\n"),
+                ("capacity", "15\n"),
+                ("status", "Discharging\n"),
+            ],
+        );
+        let ctx = PluginContext::new(dir.clone());
+        let mut plugin = BatteryPlugin::new();
+        plugin.dir.clone_from(&dir);
+
+        plugin.refresh(&ctx).unwrap();
+        let segment = plugin.render();
+        assert_eq!(segment.text(), "15%");
+        assert_eq!(segment.style.fg, Some(Color::Yellow));
+
+        // A second battery is averaged in, and plugging in shows a bolt
+        supply(
+            &dir,
+            "BAT1",
+            &[
+                ("type", "Battery\n"),
+                ("capacity", "45\n"),
+                ("status", "Charging\n"),
+            ],
+        );
+        plugin.refresh(&ctx).unwrap();
+        assert_eq!(plugin.render().text(), "⚡ 30%");
+
+        plugin.dir = dir.join("missing");
+        plugin.refresh(&ctx).unwrap();
+        assert!(plugin.render().is_empty());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
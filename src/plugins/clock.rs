@@ -0,0 +1,149 @@
+//! Clock plugin - displays the current date and time.
+
+use std::time::Duration;
+
+use jiff::fmt::strtime;
+use jiff::tz::TimeZone;
+use jiff::Timestamp;
+use ratatui::layout::Alignment;
+use ratatui::style::Style;
+
+use crate::plugins::context::PluginContext;
+use crate::plugins::{Plugin, PluginConfig, PluginError, PluginResult};
+use crate::status_bar::StatusBarSegment;
+
+/// Plugin that displays the current time, right-aligned and refreshed
+/// every second by default.
+pub struct ClockPlugin {
+    format: String,
+    /// IANA name of the time zone, or `None` for the system's.
+    time_zone_name: Option<String>,
+    time_zone: TimeZone,
+    config: PluginConfig,
+    style: Style,
+    /// The time as last formatted.
+    text: String,
+}
+
+impl ClockPlugin {
+    /// Create a clock showing hours and minutes in the system time zone.
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            format: "%H:%M".to_string(),
+            time_zone_name: None,
+            time_zone: TimeZone::system(),
+            config: PluginConfig {
+                refresh_interval: Duration::from_secs(1),
+                alignment: Alignment::Right,
+                ..PluginConfig::default()
+            },
+            style: Style::default(),
+            text: String::new(),
+        }
+    }
+
+    /// Set the `strftime` format of the time, e.g. `"%a %d %b %H:%M:%S"`.
+    /// Defaults to `"%H:%M"`.
+    #[must_use]
+    pub fn format(mut self, format: impl Into<String>) -> Self {
+        self.format = format.into();
+        self
+    }
+
+    /// Show the time in the IANA time zone `name`, e.g. `"Europe/Paris"`,
+    /// instead of the system's. An unknown zone fails registration.
+    #[must_use]
+    pub fn time_zone(mut self, name: impl Into<String>) -> Self {
+        self.time_zone_name = Some(name.into());
+        self
+    }
+
+    /// Set how often the time is formatted again.
+    #[must_use]
+    pub fn refresh_interval(mut self, interval: Duration) -> Self {
+        self.config.refresh_interval = interval;
+        self
+    }
+
+    /// Set the status bar group the clock is drawn in.
+    #[must_use]
+    pub fn alignment(mut self, alignment: Alignment) -> Self {
+        self.config.alignment = alignment;
+        self
+    }
+
+    /// Set the style of the segment.
+    #[must_use]
+    pub fn style(mut self, style: Style) -> Self {
+        self.style = style;
+        self
+    }
+
+    /// `timestamp` in the clock's format and time zone.
+    fn format_at(&self, timestamp: Timestamp) -> PluginResult<String> {
+        let zoned = timestamp.to_zoned(self.time_zone.clone());
+        strtime::format(&self.format, &zoned)
+            .map_err(|e| PluginError::RefreshFailed(format!("invalid clock format: {e}")))
+    }
+}
+
+impl Default for ClockPlugin {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Plugin for ClockPlugin {
+    fn name(&self) -> &'static str {
+        "clock"
+    }
+
+    fn config(&self) -> PluginConfig {
+        self.config.clone()
+    }
+
+    fn init(&mut self, _ctx: &PluginContext) -> PluginResult<()> {
+        if let Some(name) = &self.time_zone_name {
+            self.time_zone = TimeZone::get(name)
+                .map_err(|e| PluginError::InitFailed(format!("unknown time zone {name}: {e}")))?;
+        }
+        Ok(())
+    }
+
+    fn refresh(&mut self, _ctx: &PluginContext) -> PluginResult<()> {
+        self.text = self.format_at(Timestamp::now())?;
+        Ok(())
+    }
+
+    fn render(&self) -> StatusBarSegment {
+        StatusBarSegment::new(self.text.clone()).style(self.style)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_clock_format() {
+        let ctx = PluginContext::new(std::env::temp_dir());
+        let epoch = Timestamp::UNIX_EPOCH;
+        let mut clock = ClockPlugin::new()
+            .format("%Y-%m-%d %H:%M:%S")
+            .time_zone("UTC");
+        clock.init(&ctx).unwrap();
+        assert_eq!(clock.format_at(epoch).unwrap(), "1970-01-01 00:00:00");
+        assert_eq!(clock.config().alignment, Alignment::Right);
+
+        let mut tokyo = ClockPlugin::new().time_zone("Asia/Tokyo");
+        tokyo.init(&ctx).unwrap();
+        assert_eq!(tokyo.format_at(epoch).unwrap(), "09:00");
+
+        assert!(ClockPlugin::new()
+            .time_zone("Nowhere/City")
+            .init(&ctx)
+            .is_err());
+        assert!(ClockPlugin::new().format("%").format_at(epoch).is_err());
+    }
+}
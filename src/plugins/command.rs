@@ -0,0 +1,278 @@
+//! Command plugin - displays the output of a shell command.
+
+use std::io::Read;
+use std::process::{Child, Command, Stdio};
+use std::sync::mpsc;
+use std::time::{Duration, Instant};
+
+use ratatui::layout::Alignment;
+use ratatui::style::{Color, Style};
+
+use crate::plugins::context::PluginContext;
+use crate::plugins::{Plugin, PluginConfig, PluginError, PluginResult};
+use crate::status_bar::StatusBarSegment;
+
+/// How long the command may run before it is killed, by default.
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// How often a running command is checked on.
+const POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// Plugin that runs a shell command, such as `kubectl config
+/// current-context`, on every refresh and shows the first non-blank line
+/// of its output, trimmed.
+///
+/// The command runs through `sh -c` (`cmd /C` on Windows) in the plugin
+/// context's working directory. While it fails, or runs past its
+/// [`timeout`](Self::timeout) and is killed, the segment shows the error
+/// text instead.
+pub struct CommandPlugin {
+    command: String,
+    config: PluginConfig,
+    timeout: Duration,
+    icon: Option<String>,
+    style: Style,
+    error_text: String,
+    /// Output of the last successful run, or `None` if it failed.
+    output: Option<String>,
+}
+
+impl CommandPlugin {
+    /// Create a plugin that runs the shell command `command`.
+    #[must_use]
+    pub fn new(command: impl Into<String>) -> Self {
+        Self {
+            command: command.into(),
+            config: PluginConfig::default(),
+            timeout: DEFAULT_TIMEOUT,
+            icon: None,
+            style: Style::default(),
+            error_text: "?".to_string(),
+            output: None,
+        }
+    }
+
+    /// Set how often the command runs. Defaults to every 5 seconds.
+    #[must_use]
+    pub fn refresh_interval(mut self, interval: Duration) -> Self {
+        self.config.refresh_interval = interval;
+        self
+    }
+
+    /// Set how long the command may run before it, and on Unix the
+    /// processes it started, are killed. Defaults to 5 seconds.
+    ///
+    /// The plugin's [`refresh_timeout`](PluginConfig::refresh_timeout) is
+    /// raised past it if needed, so the command is killed before its
+    /// refresh is abandoned.
+    #[must_use]
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self.config.refresh_timeout = self
+            .config
+            .refresh_timeout
+            .max(timeout.saturating_add(Duration::from_secs(1)));
+        self
+    }
+
+    /// Set the status bar group the segment is drawn in.
+    #[must_use]
+    pub fn alignment(mut self, alignment: Alignment) -> Self {
+        self.config.alignment = alignment;
+        self
+    }
+
+    /// Set the icon drawn before the output.
+    #[must_use]
+    pub fn icon(mut self, icon: impl Into<String>) -> Self {
+        self.icon = Some(icon.into());
+        self
+    }
+
+    /// Set the style of the segment.
+    #[must_use]
+    pub fn style(mut self, style: Style) -> Self {
+        self.style = style;
+        self
+    }
+
+    /// Set the text shown, dimmed, while the command fails. Defaults to
+    /// `?`.
+    #[must_use]
+    pub fn error_text(mut self, text: impl Into<String>) -> Self {
+        self.error_text = text.into();
+        self
+    }
+
+    /// Run the command, returning the first non-blank line of its stdout,
+    /// trimmed.
+    ///
+    /// # Errors
+    /// Returns [`PluginError::TimedOut`] if the command, or anything
+    /// holding its output open, runs past the timeout.
+    fn run(&self, cwd: &std::path::Path) -> PluginResult<String> {
+        let (shell, flag) = if cfg!(windows) {
+            ("cmd.exe", "/C")
+        } else {
+            ("/bin/sh", "-c")
+        };
+        let mut command = Command::new(shell);
+        command
+            .args([flag, &self.command])
+            .current_dir(cwd)
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped());
+        // A group of its own, so a timeout also kills what it started
+        #[cfg(unix)]
+        std::os::unix::process::CommandExt::process_group(&mut command, 0);
+        let mut child = command.spawn()?;
+        let deadline = Instant::now() + self.timeout;
+
+        // Drain the pipes while waiting, so a chatty command cannot block
+        // on a full one
+        let stdout = read_pipe(child.stdout.take());
+        let stderr = read_pipe(child.stderr.take());
+        let status = loop {
+            if let Some(status) = child.try_wait()? {
+                break status;
+            }
+            if Instant::now() >= deadline {
+                kill(&mut child);
+                return Err(PluginError::TimedOut(self.timeout));
+            }
+            std::thread::sleep(POLL_INTERVAL);
+        };
+        let (Some(stdout), Some(stderr)) = (
+            collect_pipe(&stdout, deadline),
+            collect_pipe(&stderr, deadline),
+        ) else {
+            // Exited, but left something behind with its output open
+            kill(&mut child);
+            return Err(PluginError::TimedOut(self.timeout));
+        };
+
+        if !status.success() {
+            let stderr = String::from_utf8_lossy(&stderr);
+            let reason = stderr.trim();
+            return Err(PluginError::RefreshFailed(if reason.is_empty() {
+                status.to_string()
+            } else {
+                reason.to_string()
+            }));
+        }
+        let stdout = String::from_utf8_lossy(&stdout);
+        let line = stdout.lines().map(str::trim).find(|line| !line.is_empty());
+        Ok(line.unwrap_or_default().to_string())
+    }
+}
+
+/// Read `pipe` to the end on a thread of its own, sending what was read.
+fn read_pipe(pipe: Option<impl Read + Send + 'static>) -> mpsc::Receiver<Vec<u8>> {
+    let (tx, rx) = mpsc::channel();
+    if let Some(mut pipe) = pipe {
+        std::thread::spawn(move || {
+            let mut buf = Vec::new();
+            // Whatever was read before an error is still worth showing
+            let _ = pipe.read_to_end(&mut buf);
+            let _ = tx.send(buf);
+        });
+    } else {
+        let _ = tx.send(Vec::new());
+    }
+    rx
+}
+
+/// What a [`read_pipe`] thread read, or `None` if the pipe is still open
+/// at `deadline`.
+fn collect_pipe(rx: &mpsc::Receiver<Vec<u8>>, deadline: Instant) -> Option<Vec<u8>> {
+    rx.recv_timeout(deadline.saturating_duration_since(Instant::now()))
+        .ok()
+}
+
+/// Kill `child`, and on Unix its process group, and reap it.
+fn kill(child: &mut Child) {
+    #[cfg(unix)]
+    if let Ok(pid) = i32::try_from(child.id()) {
+        let _ = nix::sys::signal::killpg(
+            nix::unistd::Pid::from_raw(pid),
+            nix::sys::signal::Signal::SIGKILL,
+        );
+    }
+    let _ = child.kill();
+    let _ = child.wait();
+}
+
+impl Plugin for CommandPlugin {
+    fn name(&self) -> &'static str {
+        "command"
+    }
+
+    fn config(&self) -> PluginConfig {
+        self.config.clone()
+    }
+
+    fn refresh(&mut self, ctx: &PluginContext) -> PluginResult<()> {
+        let result = self.run(&ctx.cwd);
+        self.output = result.as_ref().ok().cloned();
+        result.map(drop)
+    }
+
+    fn render(&self) -> StatusBarSegment {
+        let segment = match &self.output {
+            Some(output) => StatusBarSegment::new(output.clone()).style(self.style),
+            None => {
+                StatusBarSegment::new(self.error_text.clone()).style(self.style.fg(Color::DarkGray))
+            }
+        };
+        match &self.icon {
+            Some(icon) => segment.icon(icon.clone()),
+            None => segment,
+        }
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_command_output() {
+        let ctx = PluginContext::new(std::env::temp_dir());
+        let mut plugin = CommandPlugin::new("printf '  prod  \\nsecond\\n'")
+            .icon("⎈")
+            .error_text("no context");
+        plugin.refresh(&ctx).unwrap();
+        assert_eq!(plugin.render().text(), "⎈ prod");
+
+        let mut failing = CommandPlugin::new("echo 'not found' >&2; exit 3").error_text("none");
+        let error = failing.refresh(&ctx).unwrap_err();
+        assert_eq!(error.to_string(), "plugin refresh failed: not found");
+        assert_eq!(failing.render().content, "none");
+        assert_eq!(failing.render().style.fg, Some(Color::DarkGray));
+    }
+
+    #[test]
+    fn test_command_timeout() {
+        let ctx = PluginContext::new(std::env::temp_dir());
+        let timeout = Duration::from_millis(50);
+        let mut plugin = CommandPlugin::new("sleep 10; echo late").timeout(timeout);
+        assert!(plugin.config().refresh_timeout > timeout);
+
+        // The shell and the sleep it started are both killed
+        let started = Instant::now();
+        let error = plugin.refresh(&ctx).unwrap_err();
+        assert!(matches!(error, PluginError::TimedOut(t) if t == timeout));
+        assert!(started.elapsed() < Duration::from_secs(5));
+        assert_eq!(plugin.render().content, "?");
+
+        // Nor does a background process holding the output open hang it
+        let mut plugin = CommandPlugin::new("sleep 10 & echo early").timeout(timeout);
+        let started = Instant::now();
+        assert!(matches!(
+            plugin.refresh(&ctx),
+            Err(PluginError::TimedOut(_))
+        ));
+        assert!(started.elapsed() < Duration::from_secs(5));
+    }
+}
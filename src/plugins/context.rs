@@ -1,13 +1,81 @@
 //! Plugin context - provides plugins with access to cockpit state.
 
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
-use crate::pane::PaneId;
+use crate::pane::{PaneHandle, PaneId, PaneState, ScreenSnapshot};
+
+/// What keys do at the moment.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum InputMode {
+    /// Keys trigger their bindings or go to the focused pane.
+    #[default]
+    Normal,
+    /// Pane numbers are shown and a number key focuses that pane; see
+    /// [`PaneManager::display_panes`](crate::PaneManager::display_panes).
+    DisplayPanes,
+}
+
+/// Read-only view of a pane, for plugins.
+///
+/// Name, title and state are read when asked for, so they are current even
+/// in a context cloned for a refresh; the screen is only copied by
+/// [`screen_snapshot`](Self::screen_snapshot).
+#[derive(Clone)]
+pub struct PaneView {
+    handle: PaneHandle,
+}
+
+impl PaneView {
+    /// Create a view of the pane behind `handle`.
+    pub(crate) fn new(handle: PaneHandle) -> Self {
+        Self { handle }
+    }
+
+    /// The pane's ID.
+    #[must_use]
+    pub fn id(&self) -> PaneId {
+        self.handle.id()
+    }
+
+    /// Name given in the spawn config.
+    #[must_use]
+    pub fn name(&self) -> Option<&str> {
+        self.handle.name()
+    }
+
+    /// Title the program set with an OSC escape sequence, empty if none.
+    #[must_use]
+    pub fn title(&self) -> String {
+        self.handle.title()
+    }
+
+    /// Current state of the pane's process.
+    #[must_use]
+    pub fn state(&self) -> PaneState {
+        self.handle.state()
+    }
+
+    /// A snapshot of the pane's screen.
+    #[must_use]
+    pub fn screen_snapshot(&self) -> ScreenSnapshot {
+        self.handle.screen_snapshot()
+    }
+}
+
+impl std::fmt::Debug for PaneView {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PaneView")
+            .field("id", &self.id())
+            .field("name", &self.name())
+            .finish_non_exhaustive()
+    }
+}
 
 /// Context provided to plugins for accessing cockpit state.
 ///
-/// This is read-only for now. Future versions may add write capabilities
-/// for interactive plugins.
+/// This is a read-only snapshot of cockpit state. Plugins change it by
+/// returning [`PluginAction`](crate::plugins::PluginAction)s from
+/// [`Plugin::take_actions`](crate::plugins::Plugin::take_actions) instead.
 #[derive(Clone, Debug)]
 pub struct PluginContext {
     /// Current working directory (for git plugins, etc.)
@@ -18,6 +86,12 @@ pub struct PluginContext {
     pub pane_count: usize,
     /// Terminal width.
     pub terminal_width: u16,
+    /// Working directory of the focused pane, if known.
+    pub focused_cwd: Option<PathBuf>,
+    /// What keys do at the moment.
+    pub input_mode: InputMode,
+    /// Every pane, in every workspace, in the order they were spawned.
+    pub panes: Vec<PaneView>,
 }
 
 impl PluginContext {
@@ -29,9 +103,31 @@ impl PluginContext {
             focused_pane: None,
             pane_count: 0,
             terminal_width: 80,
+            focused_cwd: None,
+            input_mode: InputMode::Normal,
+            panes: Vec::new(),
         }
     }
 
+    /// Directory to run commands in: the focused pane's working directory
+    /// if known, `cwd` otherwise.
+    #[must_use]
+    pub fn working_dir(&self) -> &Path {
+        self.focused_cwd.as_deref().unwrap_or(&self.cwd)
+    }
+
+    /// View of the pane `pane_id`, if it is open.
+    #[must_use]
+    pub fn pane(&self, pane_id: PaneId) -> Option<&PaneView> {
+        self.panes.iter().find(|pane| pane.id() == pane_id)
+    }
+
+    /// View of the focused pane, if any.
+    #[must_use]
+    pub fn focused(&self) -> Option<&PaneView> {
+        self.pane(self.focused_pane?)
+    }
+
     /// Update context from `PaneManager` state.
     pub fn update(&mut self, focused: Option<PaneId>, pane_count: usize, width: u16) {
         self.focused_pane = focused;
@@ -0,0 +1,173 @@
+//! Docker plugin - displays how many containers are running.
+
+use std::process::Command;
+use std::time::Duration;
+
+use ratatui::layout::Alignment;
+use ratatui::style::{Color, Style};
+
+use crate::plugins::context::PluginContext;
+use crate::plugins::{Plugin, PluginConfig, PluginError, PluginResult};
+use crate::status_bar::StatusBarSegment;
+
+/// Label Docker Compose puts on the containers of a project.
+const PROJECT_LABEL: &str = "com.docker.compose.project";
+
+/// Running and total container counts.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+struct ContainerCounts {
+    running: usize,
+    total: usize,
+}
+
+impl ContainerCounts {
+    /// Count the states listed by `docker ps --all --format {{.State}}`,
+    /// one per line.
+    fn parse(states: &str) -> Self {
+        let states: Vec<_> = states
+            .lines()
+            .map(str::trim)
+            .filter(|state| !state.is_empty())
+            .collect();
+        Self {
+            running: states.iter().filter(|&&state| state == "running").count(),
+            total: states.len(),
+        }
+    }
+}
+
+/// Plugin that displays running and total Docker containers, as `3/4`:
+/// green when all are running, yellow when some are not.
+///
+/// Runs the `docker` CLI, so it follows `DOCKER_HOST` and the current
+/// context. While Docker cannot be reached, the segment shows a dimmed `?`.
+pub struct DockerPlugin {
+    project: Option<String>,
+    config: PluginConfig,
+    /// Counts of the last successful query, or `None` if it failed.
+    counts: Option<ContainerCounts>,
+}
+
+impl DockerPlugin {
+    /// Create a plugin counting all containers every 5 seconds.
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            project: None,
+            config: PluginConfig {
+                alignment: Alignment::Right,
+                ..PluginConfig::default()
+            },
+            counts: None,
+        }
+    }
+
+    /// Only count the containers of the Docker Compose project `project`.
+    #[must_use]
+    pub fn project(mut self, project: impl Into<String>) -> Self {
+        self.project = Some(project.into());
+        self
+    }
+
+    /// Set how often Docker is queried.
+    #[must_use]
+    pub fn refresh_interval(mut self, interval: Duration) -> Self {
+        self.config.refresh_interval = interval;
+        self
+    }
+
+    /// Arguments of the `docker` command listing container states.
+    fn args(&self) -> Vec<String> {
+        let mut args = vec![
+            "ps".to_string(),
+            "--all".to_string(),
+            "--format".to_string(),
+            "{{.State}}".to_string(),
+        ];
+        if let Some(project) = &self.project {
+            args.push("--filter".to_string());
+            args.push(format!("label={PROJECT_LABEL}={project}"));
+        }
+        args
+    }
+}
+
+impl Default for DockerPlugin {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Plugin for DockerPlugin {
+    fn name(&self) -> &'static str {
+        "docker"
+    }
+
+    fn config(&self) -> PluginConfig {
+        self.config.clone()
+    }
+
+    fn refresh(&mut self, _ctx: &PluginContext) -> PluginResult<()> {
+        self.counts = None;
+        let output = Command::new("docker").args(self.args()).output()?;
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            let reason = stderr.trim();
+            return Err(PluginError::RefreshFailed(if reason.is_empty() {
+                output.status.to_string()
+            } else {
+                reason.to_string()
+            }));
+        }
+        self.counts = Some(ContainerCounts::parse(&String::from_utf8_lossy(
+            &output.stdout,
+        )));
+        Ok(())
+    }
+
+    fn render(&self) -> StatusBarSegment {
+        let Some(ContainerCounts { running, total }) = self.counts else {
+            return StatusBarSegment::new("?")
+                .icon("🐳")
+                .style(Style::default().fg(Color::DarkGray));
+        };
+        let style = if total == 0 {
+            Style::default()
+        } else if running == total {
+            Style::default().fg(Color::Green)
+        } else {
+            Style::default().fg(Color::Yellow)
+        };
+        StatusBarSegment::new(format!("{running}/{total}"))
+            .icon("🐳")
+            .style(style)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_container_counts() {
+        let counts = ContainerCounts::parse("running\nexited\nrunning\n\n");
+        assert_eq!(
+            counts,
+            ContainerCounts {
+                running: 2,
+                total: 3
+            }
+        );
+
+        let mut plugin = DockerPlugin::new().project("shop");
+        assert_eq!(
+            plugin.args().last().unwrap(),
+            "label=com.docker.compose.project=shop"
+        );
+        assert_eq!(plugin.render().text(), "🐳 ?");
+        plugin.counts = Some(counts);
+        let segment = plugin.render();
+        assert_eq!(segment.text(), "🐳 2/3");
+        assert_eq!(segment.style.fg, Some(Color::Yellow));
+    }
+}
@@ -0,0 +1,158 @@
+//! Git branch plugin - displays the current branch and its status.
+
+use std::process::Command;
+use std::time::Duration;
+
+use ratatui::layout::Alignment;
+use ratatui::style::{Color, Style};
+
+use crate::plugins::context::PluginContext;
+use crate::plugins::{Plugin, PluginConfig, PluginResult};
+use crate::status_bar::StatusBarSegment;
+
+/// Branch and status of a git work tree.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+struct BranchInfo {
+    /// Branch name, or the short commit hash when detached.
+    branch: String,
+    /// Commits not on the upstream branch.
+    ahead: u32,
+    /// Commits on the upstream branch not yet merged.
+    behind: u32,
+    /// Whether there are uncommitted or untracked changes.
+    dirty: bool,
+}
+
+impl BranchInfo {
+    /// Parse the output of `git status --porcelain=v2 --branch`.
+    fn parse(status: &str) -> Self {
+        let mut info = Self::default();
+        let mut oid = "";
+        for line in status.lines() {
+            if let Some(head) = line.strip_prefix("# branch.head ") {
+                info.branch = head.to_string();
+            } else if let Some(commit) = line.strip_prefix("# branch.oid ") {
+                oid = commit;
+            } else if let Some(counts) = line.strip_prefix("# branch.ab ") {
+                for count in counts.split_whitespace() {
+                    if let Some(ahead) = count.strip_prefix('+') {
+                        info.ahead = ahead.parse().unwrap_or(0);
+                    } else if let Some(behind) = count.strip_prefix('-') {
+                        info.behind = behind.parse().unwrap_or(0);
+                    }
+                }
+            } else if !line.starts_with('#') && !line.is_empty() {
+                info.dirty = true;
+            }
+        }
+        if info.branch == "(detached)" {
+            info.branch = oid.chars().take(7).collect();
+        }
+        info
+    }
+
+    /// Branch with a `*` if dirty, then the ahead and behind counts.
+    fn text(&self) -> String {
+        let dirty = if self.dirty { "*" } else { "" };
+        let mut parts = vec![format!("{}{dirty}", self.branch)];
+        if self.ahead > 0 {
+            parts.push(format!("↑{}", self.ahead));
+        }
+        if self.behind > 0 {
+            parts.push(format!("↓{}", self.behind));
+        }
+        parts.join(" ")
+    }
+}
+
+/// Plugin that displays the current git branch, how far it is ahead of and
+/// behind its upstream, and whether the work tree is dirty.
+///
+/// Runs in the focused pane's working directory when it is known; see
+/// [`PluginContext::working_dir`]. Outside a git repository the segment is
+/// empty.
+pub struct GitBranchPlugin {
+    info: Option<BranchInfo>,
+}
+
+impl GitBranchPlugin {
+    /// Create a new `GitBranchPlugin`.
+    #[must_use]
+    pub fn new() -> Self {
+        Self { info: None }
+    }
+}
+
+impl Default for GitBranchPlugin {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Plugin for GitBranchPlugin {
+    fn name(&self) -> &'static str {
+        "git-branch"
+    }
+
+    fn config(&self) -> PluginConfig {
+        PluginConfig {
+            // Follows commits and edits, and the focused pane's directory
+            refresh_interval: Duration::from_secs(2),
+            priority: 20,
+            alignment: Alignment::Left,
+            ..PluginConfig::default()
+        }
+    }
+
+    fn refresh(&mut self, ctx: &PluginContext) -> PluginResult<()> {
+        let output = Command::new("git")
+            .args(["status", "--porcelain=v2", "--branch"])
+            .current_dir(ctx.working_dir())
+            .output()?;
+        // Fails outside a repository
+        self.info = output
+            .status
+            .success()
+            .then(|| BranchInfo::parse(&String::from_utf8_lossy(&output.stdout)));
+        Ok(())
+    }
+
+    fn render(&self) -> StatusBarSegment {
+        match &self.info {
+            Some(info) => {
+                let color = if info.dirty {
+                    Color::Yellow
+                } else {
+                    Color::Magenta
+                };
+                StatusBarSegment::new(info.text())
+                    .icon("⎇")
+                    .style(Style::default().fg(color))
+            }
+            None => StatusBarSegment::new(""),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_status() {
+        let clean = BranchInfo::parse(
+            "# branch.oid 1a2b3c4d5e6f\n# branch.head main\n# branch.upstream origin/main\n# branch.ab +0 -0\n",
+        );
+        assert_eq!(clean.text(), "main");
+
+        let diverged = BranchInfo::parse(
+            "# branch.oid 1a2b3c4d5e6f\n# branch.head feature\n# branch.ab +2 -1\n\
+             1 .M N... 100644 100644 100644 abc abc src/lib.rs\n? notes.txt\n",
+        );
+        assert!(diverged.dirty);
+        assert_eq!(diverged.text(), "feature* ↑2 ↓1");
+
+        let detached = BranchInfo::parse("# branch.oid 1a2b3c4d5e6f\n# branch.head (detached)\n");
+        assert_eq!(detached.text(), "1a2b3c4");
+    }
+}
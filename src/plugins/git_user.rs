@@ -3,6 +3,7 @@
 use std::process::Command;
 use std::time::Duration;
 
+use ratatui::layout::Alignment;
 use ratatui::style::{Color, Style};
 
 use crate::plugins::context::PluginContext;
@@ -65,14 +66,15 @@ impl Plugin for GitUserPlugin {
         PluginConfig {
             // Git user doesn't change often, refresh every 30 seconds
             refresh_interval: Duration::from_secs(30),
-            // Show on the left side of the status bar
             priority: 10,
+            // Show on the left side of the status bar
+            alignment: Alignment::Left,
+            ..PluginConfig::default()
         }
     }
 
     fn refresh(&mut self, ctx: &PluginContext) -> PluginResult<()> {
-        // Execute git config commands synchronously
-        // Note: These are fast local operations, so blocking is acceptable
+        // Runs on a worker, so waiting on git does not hold up rendering
         self.info.name = Self::git_config(&ctx.cwd, "user.name");
         self.info.email = Self::git_config(&ctx.cwd, "user.email");
         Ok(())
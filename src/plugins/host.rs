@@ -0,0 +1,146 @@
+//! Host plugin - displays `user@host`, highlighted over SSH.
+
+use std::time::Duration;
+
+use ratatui::layout::Alignment;
+use ratatui::style::{Color, Modifier, Style};
+
+use crate::plugins::context::PluginContext;
+use crate::plugins::{Plugin, PluginConfig, PluginResult};
+use crate::status_bar::StatusBarSegment;
+
+/// Who and where cockpit runs.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+struct HostInfo {
+    user: Option<String>,
+    host: Option<String>,
+    /// Whether cockpit runs in an SSH session.
+    ssh: bool,
+}
+
+impl HostInfo {
+    /// Read the user and SSH session from the environment variables `var`
+    /// returns, with `host` as the host name.
+    fn read(var: impl Fn(&str) -> Option<String>, host: Option<String>) -> Self {
+        let host = host
+            .or_else(|| var("HOSTNAME"))
+            .or_else(|| var("COMPUTERNAME"));
+        Self {
+            user: var("USER").or_else(|| var("USERNAME")),
+            // Drop the domain, as prompts do
+            host: host.map(|host| host.split('.').next().unwrap_or_default().to_string()),
+            ssh: ["SSH_CONNECTION", "SSH_CLIENT", "SSH_TTY"]
+                .iter()
+                .any(|name| var(name).is_some()),
+        }
+    }
+
+    /// `user@host`, or whichever of them is known.
+    fn text(&self) -> String {
+        match (&self.user, &self.host) {
+            (Some(user), Some(host)) => format!("{user}@{host}"),
+            (Some(name), None) | (None, Some(name)) => name.clone(),
+            (None, None) => String::new(),
+        }
+    }
+}
+
+/// The host name from the OS, on Unix.
+fn hostname() -> Option<String> {
+    #[cfg(unix)]
+    {
+        nix::unistd::gethostname().ok()?.into_string().ok()
+    }
+    #[cfg(not(unix))]
+    {
+        None
+    }
+}
+
+/// Plugin that displays `user@host`, in bold on red when cockpit runs over
+/// SSH so shells on remote machines are not mistaken for local ones.
+pub struct HostPlugin {
+    info: HostInfo,
+}
+
+impl HostPlugin {
+    /// Create a new `HostPlugin`.
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            info: HostInfo::default(),
+        }
+    }
+}
+
+impl Default for HostPlugin {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Plugin for HostPlugin {
+    fn name(&self) -> &'static str {
+        "host"
+    }
+
+    fn config(&self) -> PluginConfig {
+        PluginConfig {
+            // Only the host name can change, and rarely
+            refresh_interval: Duration::from_mins(1),
+            priority: 5,
+            alignment: Alignment::Left,
+            ..PluginConfig::default()
+        }
+    }
+
+    fn refresh(&mut self, _ctx: &PluginContext) -> PluginResult<()> {
+        self.info = HostInfo::read(|name| std::env::var(name).ok(), hostname());
+        Ok(())
+    }
+
+    fn render(&self) -> StatusBarSegment {
+        let segment = StatusBarSegment::new(self.info.text());
+        if self.info.ssh {
+            segment.icon("ssh").style(
+                Style::default()
+                    .fg(Color::White)
+                    .bg(Color::Red)
+                    .add_modifier(Modifier::BOLD),
+            )
+        } else {
+            segment.style(Style::default().fg(Color::Green))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_host_info() {
+        let env = |vars: &'static [(&str, &str)]| {
+            move |name: &str| {
+                vars.iter()
+                    .find(|(var, _)| *var == name)
+                    .map(|(_, value)| (*value).to_string())
+            }
+        };
+        let mut plugin = HostPlugin::new();
+        plugin.info = HostInfo::read(env(&[("USER", "alice")]), Some("db1.prod.example".into()));
+        assert_eq!(plugin.render().text(), "alice@db1");
+        assert_eq!(plugin.render().style.bg, None);
+
+        plugin.info = HostInfo::read(
+            env(&[
+                ("USERNAME", "bob"),
+                ("COMPUTERNAME", "WORKSTATION"),
+                ("SSH_CONNECTION", "10.0.0.2 51000 10.0.0.9 22"),
+            ]),
+            None,
+        );
+        assert_eq!(plugin.render().text(), "ssh bob@WORKSTATION");
+        assert_eq!(plugin.render().style.bg, Some(Color::Red));
+    }
+}
@@ -0,0 +1,153 @@
+//! Key hint plugin - displays the keys of the most useful actions.
+
+use std::time::Duration;
+
+use ratatui::style::{Color, Style};
+
+use crate::actions::{Action, KeyMap};
+use crate::keys::key_name;
+use crate::plugins::context::{InputMode, PluginContext};
+use crate::plugins::{Plugin, PluginConfig, PluginResult};
+use crate::status_bar::StatusBarSegment;
+
+/// Plugin that displays keybindings for what keys do at the moment: the
+/// bound keys of a few actions normally, or the number keys while pane
+/// numbers are shown.
+///
+/// Each hint is a segment of its own, at a lower priority than other
+/// plugins, so the last hints give way first when the bar runs out of
+/// room.
+///
+/// Only cockpit's [`InputMode`]s get hints: it has no copy mode or prefix
+/// key, so there are none for those.
+pub struct KeyHintPlugin {
+    keymap: KeyMap,
+    actions: Vec<Action>,
+    mode: InputMode,
+}
+
+impl KeyHintPlugin {
+    /// Create a plugin hinting at the command palette, help, focusing the
+    /// next pane, pane numbers, restarting and quitting, as bound in
+    /// `keymap`; see [`RunOptions::get_keymap`](crate::runtime::RunOptions::get_keymap).
+    #[must_use]
+    pub fn new(keymap: KeyMap) -> Self {
+        Self {
+            keymap,
+            actions: vec![
+                Action::CommandPalette,
+                Action::Help,
+                Action::FocusNext,
+                Action::DisplayPanes,
+                Action::RestartPane,
+                Action::Quit,
+            ],
+            mode: InputMode::Normal,
+        }
+    }
+
+    /// Set the actions hinted at, most relevant first. Unbound actions are
+    /// skipped.
+    #[must_use]
+    pub fn actions(mut self, actions: Vec<Action>) -> Self {
+        self.actions = actions;
+        self
+    }
+
+    /// Hints for the current mode. Every mode is matched, without a
+    /// wildcard, so new modes need hints.
+    fn hints(&self) -> Vec<String> {
+        match self.mode {
+            InputMode::Normal => self
+                .actions
+                .iter()
+                .filter_map(|action| {
+                    let key = self.keymap.key_for(action)?;
+                    Some(format!("{} {}", key_name(key), label(action)))
+                })
+                .collect(),
+            InputMode::DisplayPanes => vec!["1-9 focus pane".to_string()],
+        }
+    }
+}
+
+/// Short name of `action` for a hint.
+fn label(action: &Action) -> &str {
+    match action {
+        Action::Quit => "quit",
+        Action::FocusNext => "next",
+        Action::FocusPrev => "prev",
+        Action::DisplayPanes => "panes",
+        Action::Resize(..) => "resize",
+        Action::CommandPalette => "commands",
+        Action::Help => "help",
+        Action::RestartPane => "restart",
+        Action::SpawnProfile(name) => name,
+    }
+}
+
+impl Plugin for KeyHintPlugin {
+    fn name(&self) -> &'static str {
+        "key-hint"
+    }
+
+    fn config(&self) -> PluginConfig {
+        PluginConfig {
+            // Follow mode changes promptly
+            refresh_interval: Duration::from_millis(200),
+            priority: 100,
+            ..PluginConfig::default()
+        }
+    }
+
+    fn refresh(&mut self, ctx: &PluginContext) -> PluginResult<()> {
+        self.mode = ctx.input_mode;
+        Ok(())
+    }
+
+    fn render(&self) -> StatusBarSegment {
+        StatusBarSegment::new(self.hints().join("  ")).style(Style::default().fg(Color::DarkGray))
+    }
+
+    fn render_all(&self) -> Vec<StatusBarSegment> {
+        self.hints()
+            .into_iter()
+            .map(|hint| {
+                StatusBarSegment::new(hint)
+                    .style(Style::default().fg(Color::DarkGray))
+                    .priority(-1)
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+    use super::*;
+
+    #[test]
+    fn test_key_hints() {
+        let mut keymap = KeyMap::new();
+        keymap.bind(
+            KeyEvent::new(KeyCode::Char('p'), KeyModifiers::CONTROL),
+            Action::CommandPalette,
+        );
+        keymap.bind(
+            KeyEvent::new(KeyCode::F(1), KeyModifiers::NONE),
+            Action::Help,
+        );
+        let mut plugin = KeyHintPlugin::new(keymap);
+        let mut ctx = PluginContext::new(std::env::temp_dir());
+
+        plugin.refresh(&ctx).unwrap();
+        let hints: Vec<_> = plugin.render_all().into_iter().map(|s| s.content).collect();
+        assert_eq!(hints, ["C-p commands", "F1 help"]);
+        assert_eq!(plugin.render().content, "C-p commands  F1 help");
+
+        ctx.input_mode = InputMode::DisplayPanes;
+        plugin.refresh(&ctx).unwrap();
+        assert_eq!(plugin.render().content, "1-9 focus pane");
+    }
+}
@@ -1,18 +1,45 @@
 //! Plugin system for cockpit status bar.
 //!
-//! Plugins provide content for the status bar. They can be display-only
-//! (current implementation) or interactive (future capability).
-
+//! Plugins provide content for the status bar. They can also react to
+//! pane events and ask the manager for [`PluginAction`]s, such as focusing
+//! or restarting a pane.
+
+#[cfg(feature = "battery")]
+mod battery;
+#[cfg(feature = "clock")]
+mod clock;
+mod command;
 mod context;
+mod docker;
+mod git_branch;
 mod git_user;
+mod host;
+mod key_hint;
 mod registry;
-
-pub use context::PluginContext;
+#[cfg(feature = "resource-usage")]
+mod system_stats;
+
+#[cfg(feature = "battery")]
+pub use battery::BatteryPlugin;
+#[cfg(feature = "clock")]
+pub use clock::ClockPlugin;
+pub use command::CommandPlugin;
+pub use context::{InputMode, PaneView, PluginContext};
+pub use docker::DockerPlugin;
+pub use git_branch::GitBranchPlugin;
 pub use git_user::GitUserPlugin;
+pub use host::HostPlugin;
+pub use key_hint::KeyHintPlugin;
 pub use registry::PluginRegistry;
+#[cfg(feature = "resource-usage")]
+pub use system_stats::{StatThresholds, SystemStatsPlugin};
 
 use std::time::Duration;
 
+use ratatui::layout::Alignment;
+
+use crate::pane::PaneId;
+use crate::pty::PaneEvent;
 use crate::status_bar::StatusBarSegment;
 
 /// Unique identifier for a plugin instance.
@@ -25,6 +52,19 @@ impl std::fmt::Display for PluginId {
     }
 }
 
+/// Something a plugin asks the manager to do; see
+/// [`Plugin::take_actions`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum PluginAction {
+    /// Focus a pane, switching to its workspace if needed.
+    FocusPane(PaneId),
+    /// Run a pane's command again in its place; see
+    /// [`PaneManager::restart_pane`](crate::PaneManager::restart_pane).
+    RestartPane(PaneId),
+    /// Show a message on the right of the status bar for a few seconds.
+    Notify(String),
+}
+
 /// Configuration for plugin behavior.
 #[derive(Clone, Debug)]
 pub struct PluginConfig {
@@ -32,6 +72,26 @@ pub struct PluginConfig {
     pub refresh_interval: Duration,
     /// Position in the status bar (lower = more left).
     pub priority: i32,
+    /// Group of the status bar the plugin's segments are drawn in, unless
+    /// a segment sets its own. Clocks and battery indicators belong on
+    /// the right.
+    pub alignment: Alignment,
+    /// Row of the status bar the plugin's segments are drawn on, unless a
+    /// segment sets its own; see [`StatusBarConfig::rows`](crate::StatusBarConfig::rows).
+    pub row: u16,
+    /// How long a refresh may run before it is abandoned. The segments are
+    /// then marked [`stale`](StatusBarSegment::stale) and the plugin's
+    /// error is [`PluginError::TimedOut`].
+    ///
+    /// A refresh cannot be interrupted from outside the plugin: it keeps
+    /// its worker until it returns, and only then is its result dropped
+    /// and a fresh refresh started. A refresh that never returns leaves
+    /// the plugin stale for good, so plugins that run commands or reach
+    /// the network should bound those themselves.
+    pub refresh_timeout: Duration,
+    /// Whether the plugin receives pane events through
+    /// [`Plugin::on_event`].
+    pub pane_events: bool,
 }
 
 impl Default for PluginConfig {
@@ -39,14 +99,18 @@ impl Default for PluginConfig {
         Self {
             refresh_interval: Duration::from_secs(5),
             priority: 0,
+            alignment: Alignment::Left,
+            row: 0,
+            refresh_timeout: Duration::from_secs(10),
+            pane_events: false,
         }
     }
 }
 
 /// The core plugin trait.
 ///
-/// Plugins provide content for the status bar. They can be display-only
-/// (current implementation) or interactive (future capability).
+/// Plugins provide content for the status bar, and may ask the manager
+/// for actions through [`take_actions`](Self::take_actions).
 pub trait Plugin: Send + Sync {
     /// Unique name for this plugin type.
     fn name(&self) -> &'static str;
@@ -66,7 +130,9 @@ pub trait Plugin: Send + Sync {
     }
 
     /// Refresh the plugin's data.
-    /// This is called periodically based on `refresh_interval`.
+    /// This is called periodically based on `refresh_interval`, on a
+    /// background worker rather than the render loop, so it may block on
+    /// commands or I/O. It is not called again until it returns.
     ///
     /// # Errors
     /// Returns an error if refresh fails.
@@ -75,6 +141,30 @@ pub trait Plugin: Send + Sync {
     /// Render the plugin's status bar segment.
     fn render(&self) -> StatusBarSegment;
 
+    /// Render all of the plugin's status bar segments, in order. Each is
+    /// styled and placed on its own, so a plugin can show, say, CPU and
+    /// memory usage in separate segments. Defaults to the one from
+    /// [`render`](Self::render).
+    fn render_all(&self) -> Vec<StatusBarSegment> {
+        vec![self.render()]
+    }
+
+    /// Handle a pane event: an exit, a crash, output and so on. Called on
+    /// the render loop during [`PaneManager::tick_plugins`](crate::PaneManager::tick_plugins)
+    /// for the events polled since the last tick, so it should not block.
+    /// Only called if [`PluginConfig::pane_events`] is set; the segments
+    /// are rendered again afterwards.
+    fn on_event(&mut self, event: &PaneEvent, ctx: &PluginContext) {
+        let _ = (event, ctx);
+    }
+
+    /// Actions for the manager to run, taken after every refresh and
+    /// every batch of pane events. The manager runs them at the end of
+    /// [`PaneManager::tick_plugins`](crate::PaneManager::tick_plugins).
+    fn take_actions(&mut self) -> Vec<PluginAction> {
+        Vec::new()
+    }
+
     /// Cleanup when plugin is removed. Called once.
     fn shutdown(&mut self) {
         // Default: no cleanup needed
@@ -99,6 +189,10 @@ pub enum PluginError {
     #[error("command execution failed: {0}")]
     CommandFailed(#[from] std::io::Error),
 
+    /// Plugin refresh ran longer than its timeout.
+    #[error("plugin refresh timed out after {0:?}")]
+    TimedOut(Duration),
+
     /// Plugin not found.
     #[error("plugin not found: {0}")]
     NotFound(PluginId),
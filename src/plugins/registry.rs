@@ -1,20 +1,88 @@
 //! Plugin registry - manages plugin lifecycle and refresh scheduling.
+//!
+//! Refreshes may block, on git or the network, so they run on background
+//! workers (tokio's blocking pool, or threads without the runtime) rather
+//! than on the render loop. A plugin is handed to a worker for each
+//! refresh and comes back with its new segment over a channel, which
+//! [`PluginRegistry::tick`] drains.
 
 use std::collections::HashMap;
 use std::sync::atomic::{AtomicU64, Ordering};
-use std::time::Instant;
+use std::time::{Duration, Instant};
+
+use ratatui::layout::Alignment;
+use ratatui::style::{Modifier, Style};
+use tokio::sync::mpsc;
 
 use crate::pane::PaneId;
-use crate::plugins::context::PluginContext;
-use crate::plugins::{Plugin, PluginConfig, PluginError, PluginId, PluginResult};
+use crate::plugins::context::{InputMode, PaneView, PluginContext};
+use crate::plugins::{Plugin, PluginAction, PluginConfig, PluginError, PluginId, PluginResult};
+use crate::pty::PaneEvent;
 use crate::status_bar::StatusBarSegment;
 
+/// Failed refreshes in a row after which the refresh interval stops
+/// doubling.
+const MAX_BACKOFF_DOUBLINGS: u32 = 6;
+
+/// How long a [`PluginAction::Notify`] message is shown.
+const NOTIFICATION_TIME: Duration = Duration::from_secs(5);
+
 /// Internal representation of a registered plugin.
 struct RegisteredPlugin {
-    plugin: Box<dyn Plugin>,
+    /// The plugin, or `None` while a worker refreshes it.
+    plugin: Option<Box<dyn Plugin>>,
+    /// When the running refresh started.
+    refresh_started: Instant,
     config: PluginConfig,
     last_refresh: Instant,
-    cached_segment: StatusBarSegment,
+    cached_segments: Vec<StatusBarSegment>,
+    /// Animation frame of the segments, kept across refreshes.
+    frame: usize,
+    /// Whether the running refresh is past its timeout.
+    stale: bool,
+    /// Refreshes that failed since the last one that succeeded.
+    failures: u32,
+    /// Error of the last refresh, if it failed.
+    error: Option<PluginError>,
+    /// Pane events held back while a worker has the plugin.
+    pending_events: Vec<PaneEvent>,
+}
+
+impl RegisteredPlugin {
+    /// Time until the next refresh: the plugin's interval, doubled for
+    /// every failed refresh in a row up to [`MAX_BACKOFF_DOUBLINGS`].
+    fn refresh_delay(&self) -> Duration {
+        let doublings = self.failures.min(MAX_BACKOFF_DOUBLINGS);
+        self.config.refresh_interval.saturating_mul(1 << doublings)
+    }
+
+    /// Show `segments`, as the plugin's config places them, the first
+    /// marked if the last refresh failed. Returns `true` if they changed.
+    fn update_segments(&mut self, segments: Vec<StatusBarSegment>) -> bool {
+        let mut segments: Vec<_> = segments
+            .into_iter()
+            .map(|segment| placed(segment, &self.config))
+            .collect();
+        for segment in &mut segments {
+            segment.frame = self.frame;
+        }
+        if let Some(first) = segments.first_mut() {
+            first.failed = self.error.is_some();
+        }
+        if segments == self.cached_segments {
+            return false;
+        }
+        self.cached_segments = segments;
+        true
+    }
+}
+
+/// A refresh finished by a worker, with the plugin handed back.
+struct Refreshed {
+    id: PluginId,
+    plugin: Box<dyn Plugin>,
+    result: PluginResult<()>,
+    segments: Vec<StatusBarSegment>,
 }
 
 /// Registry for managing plugins.
@@ -22,41 +90,71 @@ pub struct PluginRegistry {
     plugins: HashMap<PluginId, RegisteredPlugin>,
     next_id: AtomicU64,
     context: PluginContext,
+    refreshed_tx: mpsc::UnboundedSender<Refreshed>,
+    refreshed_rx: mpsc::UnboundedReceiver<Refreshed>,
+    /// Actions plugins asked for since they were last taken.
+    actions: Vec<PluginAction>,
+    /// Message shown after the plugins' segments, and when it goes.
+    notification: Option<(StatusBarSegment, Instant)>,
 }
 
 impl PluginRegistry {
     /// Create a new plugin registry.
     #[must_use]
     pub fn new(cwd: std::path::PathBuf) -> Self {
+        let (refreshed_tx, refreshed_rx) = mpsc::unbounded_channel();
         Self {
             plugins: HashMap::new(),
             next_id: AtomicU64::new(1),
             context: PluginContext::new(cwd),
+            refreshed_tx,
+            refreshed_rx,
+            actions: Vec::new(),
+            notification: None,
         }
     }
 
     /// Register a plugin.
     ///
+    /// Its first refresh starts in the background right away; until it
+    /// finishes, the status bar shows what the plugin renders before any
+    /// refresh.
+    ///
     /// # Errors
-    /// Returns an error if plugin initialization or initial refresh fails.
+    /// Returns an error if plugin initialization fails.
+    ///
+    /// # Panics
+    /// Panics if called outside a tokio runtime with the default
+    /// `tokio-runtime` feature.
     pub fn register(&mut self, mut plugin: Box<dyn Plugin>) -> PluginResult<PluginId> {
         let id = PluginId(self.next_id.fetch_add(1, Ordering::SeqCst));
         let config = plugin.config();
 
         // Initialize plugin
         plugin.init(&self.context)?;
+        let segments = plugin
+            .render_all()
+            .into_iter()
+            .map(|segment| placed(segment, &config))
+            .collect();
 
         // Initial refresh
-        plugin.refresh(&self.context)?;
-        let segment = plugin.render();
+        spawn_refresh(id, plugin, &self.context, &self.refreshed_tx);
 
+        let now = Instant::now();
         self.plugins.insert(
             id,
             RegisteredPlugin {
-                plugin,
+                plugin: None,
+                refresh_started: now,
                 config,
-                last_refresh: Instant::now(),
-                cached_segment: segment,
+                last_refresh: now,
+                cached_segments: segments,
+                frame: 0,
+                stale: false,
+                failures: 0,
+                error: None,
+                pending_events: Vec::new(),
             },
         );
 
@@ -65,11 +163,15 @@ impl PluginRegistry {
 
     /// Unregister a plugin.
     ///
+    /// A plugin being refreshed is shut down once its refresh finishes.
+    ///
     /// # Errors
     /// Returns an error if the plugin is not found.
     pub fn unregister(&mut self, id: PluginId) -> PluginResult<()> {
-        let mut registered = self.plugins.remove(&id).ok_or(PluginError::NotFound(id))?;
-        registered.plugin.shutdown();
+        let registered = self.plugins.remove(&id).ok_or(PluginError::NotFound(id))?;
+        if let Some(mut plugin) = registered.plugin {
+            plugin.shutdown();
+        }
         Ok(())
     }
 
@@ -78,27 +180,598 @@ impl PluginRegistry {
         self.context.update(focused, pane_count, width);
     }
 
-    /// Tick all plugins - refresh those that need it.
-    pub fn tick(&mut self) {
+    /// Set the working directory of the focused pane in the context.
+    pub fn set_focused_cwd(&mut self, cwd: Option<std::path::PathBuf>) {
+        self.context.focused_cwd = cwd;
+    }
+
+    /// Set the panes in the context.
+    pub fn set_panes(&mut self, panes: Vec<PaneView>) {
+        self.context.panes = panes;
+    }
+
+    /// Set what keys do in the context.
+    pub fn set_input_mode(&mut self, mode: InputMode) {
+        self.context.input_mode = mode;
+    }
+
+    /// Tick all plugins - start refreshes of those that need it, and take
+    /// in the refreshes that finished. Never waits for a refresh.
+    ///
+    /// Animated segments advance a frame on every tick, whatever their
+    /// refresh interval. A plugin whose refresh fails keeps showing its
+    /// last data, marked [`failed`](StatusBarSegment::failed), and is
+    /// refreshed less often until a refresh succeeds. One whose refresh
+    /// runs past its [`refresh_timeout`](PluginConfig::refresh_timeout) is
+    /// marked [`stale`](StatusBarSegment::stale); the refresh is abandoned,
+    /// and a fresh one starts as soon as it returns.
+    ///
+    /// Returns `true` if any segment changed.
+    ///
+    /// # Panics
+    /// Panics if called outside a tokio runtime with the default
+    /// `tokio-runtime` feature.
+    #[cfg_attr(
+        feature = "trace",
+        tracing::instrument(level = "debug", skip_all, fields(plugins = self.plugins.len()))
+    )]
+    pub fn tick(&mut self) -> bool {
         let now = Instant::now();
+        let mut changed = false;
 
-        for registered in self.plugins.values_mut() {
+        for (id, registered) in &mut self.plugins {
             let elapsed = now.duration_since(registered.last_refresh);
-            if elapsed >= registered.config.refresh_interval {
-                // Refresh plugin
-                if registered.plugin.refresh(&self.context).is_ok() {
-                    registered.cached_segment = registered.plugin.render();
+            if elapsed >= registered.refresh_delay() {
+                // Unless the last refresh is still running
+                if let Some(plugin) = registered.plugin.take() {
+                    spawn_refresh(*id, plugin, &self.context, &self.refreshed_tx);
+                    registered.refresh_started = now;
+                }
+            }
+
+            let timeout = registered.config.refresh_timeout;
+            if registered.plugin.is_none()
+                && !registered.stale
+                && now.duration_since(registered.refresh_started) >= timeout
+            {
+                // Blocking refreshes cannot be interrupted; the result is
+                // dropped once the worker hands the plugin back
+                registered.error = Some(PluginError::TimedOut(timeout));
+                registered.stale = true;
+                for segment in &mut registered.cached_segments {
+                    segment.stale = true;
                 }
-                registered.last_refresh = now;
+                changed = true;
+            }
+
+            let segments = &mut registered.cached_segments;
+            if segments.iter().any(|segment| !segment.frames.is_empty()) {
+                registered.frame = registered.frame.wrapping_add(1);
+                for segment in segments {
+                    segment.frame = registered.frame;
+                }
+                changed = true;
+            }
+        }
+
+        while let Ok(refreshed) = self.refreshed_rx.try_recv() {
+            changed |= self.finish_refresh(refreshed, now);
+        }
+
+        if self
+            .notification
+            .as_ref()
+            .is_some_and(|(_, until)| now >= *until)
+        {
+            self.notification = None;
+            changed = true;
+        }
+        changed
+    }
+
+    /// Swap in the segments of a finished refresh. Returns `true` if they
+    /// changed.
+    fn finish_refresh(&mut self, refreshed: Refreshed, now: Instant) -> bool {
+        let Refreshed {
+            id,
+            mut plugin,
+            result,
+            segments,
+        } = refreshed;
+        let Some(registered) = self.plugins.get_mut(&id) else {
+            // Unregistered while refreshing
+            plugin.shutdown();
+            return false;
+        };
+        let had_events = !registered.pending_events.is_empty();
+        for event in registered.pending_events.drain(..) {
+            plugin.on_event(&event, &self.context);
+        }
+        self.actions.extend(plugin.take_actions());
+
+        if registered.stale {
+            // Abandoned for running past its timeout; its data may be as
+            // old as the refresh took, so start over
+            registered.stale = false;
+            registered.failures = registered.failures.saturating_add(1);
+            registered.refresh_started = now;
+            spawn_refresh(id, plugin, &self.context, &self.refreshed_tx);
+            return false;
+        }
+
+        registered.last_refresh = now;
+        registered.failures = match result {
+            Ok(()) => 0,
+            Err(_) => registered.failures.saturating_add(1),
+        };
+        registered.error = result.err();
+
+        let segments = if had_events {
+            plugin.render_all()
+        } else {
+            segments
+        };
+        registered.plugin = Some(plugin);
+        registered.update_segments(segments)
+    }
+
+    /// Whether any plugin takes pane events.
+    #[must_use]
+    pub fn wants_events(&self) -> bool {
+        self.plugins.values().any(|r| r.config.pane_events)
+    }
+
+    /// Hand `events` to the plugins that take them; see
+    /// [`PluginConfig::pane_events`]. A plugin being refreshed gets them
+    /// once its refresh finishes.
+    ///
+    /// Returns `true` if any segment changed.
+    pub fn dispatch(&mut self, events: &[PaneEvent]) -> bool {
+        let mut changed = false;
+        if events.is_empty() {
+            return changed;
+        }
+        for registered in self.plugins.values_mut() {
+            if !registered.config.pane_events {
+                continue;
+            }
+            let Some(plugin) = &mut registered.plugin else {
+                registered.pending_events.extend_from_slice(events);
+                continue;
+            };
+            for event in events {
+                plugin.on_event(event, &self.context);
             }
+            self.actions.extend(plugin.take_actions());
+            let segments = plugin.render_all();
+            changed |= registered.update_segments(segments);
         }
+        changed
+    }
+
+    /// Take the actions plugins asked for since the last call, in order.
+    pub fn take_actions(&mut self) -> Vec<PluginAction> {
+        std::mem::take(&mut self.actions)
+    }
+
+    /// Show `message` on the right of the status bar for a few seconds,
+    /// replacing any message shown.
+    pub fn notify(&mut self, message: impl Into<String>) {
+        let segment = StatusBarSegment::new(message)
+            .style(Style::default().add_modifier(Modifier::BOLD))
+            .alignment(Alignment::Right)
+            .priority(i32::MAX);
+        self.notification = Some((segment, Instant::now() + NOTIFICATION_TIME));
+    }
+
+    /// Whether any plugin is being refreshed.
+    #[cfg(any(test, feature = "testing"))]
+    pub(crate) fn is_refreshing(&self) -> bool {
+        self.plugins.values().any(|r| r.plugin.is_none())
     }
 
-    /// Get all segments for rendering, sorted by priority.
+    /// Error of the plugin's last refresh, if it failed.
+    #[must_use]
+    pub fn error(&self, id: PluginId) -> Option<&PluginError> {
+        self.plugins.get(&id)?.error.as_ref()
+    }
+
+    /// Get all segments for rendering, sorted by priority, followed by
+    /// the message of the last [`notify`](Self::notify) while it is shown.
     #[must_use]
     pub fn segments(&self) -> Vec<&StatusBarSegment> {
         let mut entries: Vec<_> = self.plugins.values().collect();
         entries.sort_by_key(|r| r.config.priority);
-        entries.iter().map(|r| &r.cached_segment).collect()
+        entries
+            .iter()
+            .flat_map(|r| &r.cached_segments)
+            .chain(self.notification.as_ref().map(|(segment, _)| segment))
+            .collect()
+    }
+}
+
+/// Refresh `plugin` and render its segments on a worker, sending them back
+/// on `refreshed_tx`.
+fn spawn_refresh(
+    id: PluginId,
+    mut plugin: Box<dyn Plugin>,
+    context: &PluginContext,
+    refreshed_tx: &mpsc::UnboundedSender<Refreshed>,
+) {
+    let context = context.clone();
+    let refreshed_tx = refreshed_tx.clone();
+    crate::task::spawn_blocking("cockpit-plugin", move || {
+        #[cfg(feature = "trace")]
+        let _span = tracing::debug_span!("plugin_refresh", plugin_id = %id, plugin = plugin.name())
+            .entered();
+
+        let result = plugin.refresh(&context);
+        let segments = plugin.render_all();
+        // Fails only once the registry is gone, and the plugin with it
+        let _ = refreshed_tx.send(Refreshed {
+            id,
+            plugin,
+            result,
+            segments,
+        });
+    });
+}
+
+/// `segment` on the row and in the group the plugin's `config` places it,
+/// unless it has its own.
+fn placed(mut segment: StatusBarSegment, config: &PluginConfig) -> StatusBarSegment {
+    segment.alignment.get_or_insert(config.alignment);
+    segment.row.get_or_insert(config.row);
+    segment
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::AtomicBool;
+    use std::sync::Arc;
+
+    use super::*;
+
+    /// Tick until no refresh is running. Returns `true` if any tick
+    /// changed a segment.
+    async fn settle(registry: &mut PluginRegistry) -> bool {
+        let mut changed = registry.tick();
+        while registry.is_refreshing() {
+            tokio::time::sleep(Duration::from_millis(1)).await;
+            changed |= registry.tick();
+        }
+        changed
+    }
+
+    /// Shows a spinner while busy, then a result.
+    struct Fetch {
+        done: bool,
+    }
+
+    impl Plugin for Fetch {
+        fn name(&self) -> &'static str {
+            "fetch"
+        }
+
+        fn config(&self) -> PluginConfig {
+            PluginConfig {
+                refresh_interval: Duration::MAX,
+                ..PluginConfig::default()
+            }
+        }
+
+        fn refresh(&mut self, _ctx: &PluginContext) -> PluginResult<()> {
+            Ok(())
+        }
+
+        fn render(&self) -> StatusBarSegment {
+            if self.done {
+                StatusBarSegment::new("ok")
+            } else {
+                StatusBarSegment::new("fetching").spinner()
+            }
+        }
+    }
+
+    /// Fails to refresh while `failing` is set.
+    struct Flaky {
+        failing: Arc<AtomicBool>,
+    }
+
+    impl Plugin for Flaky {
+        fn name(&self) -> &'static str {
+            "flaky"
+        }
+
+        fn config(&self) -> PluginConfig {
+            PluginConfig {
+                refresh_interval: Duration::ZERO,
+                ..PluginConfig::default()
+            }
+        }
+
+        fn refresh(&mut self, _ctx: &PluginContext) -> PluginResult<()> {
+            if self.failing.load(Ordering::SeqCst) {
+                return Err(PluginError::RefreshFailed("offline".to_string()));
+            }
+            Ok(())
+        }
+
+        fn render(&self) -> StatusBarSegment {
+            StatusBarSegment::new("flaky")
+        }
+    }
+
+    #[tokio::test]
+    async fn test_failing_plugin() {
+        let failing = Arc::new(AtomicBool::new(false));
+        let mut registry = PluginRegistry::new(std::env::temp_dir());
+        let id = registry
+            .register(Box::new(Flaky {
+                failing: Arc::clone(&failing),
+            }))
+            .unwrap();
+
+        assert!(!settle(&mut registry).await);
+        failing.store(true, Ordering::SeqCst);
+        assert!(settle(&mut registry).await);
+        assert!(registry.segments()[0].failed);
+        assert_eq!(
+            registry.error(id).map(ToString::to_string).as_deref(),
+            Some("plugin refresh failed: offline")
+        );
+
+        // Each failure in a row doubles the wait, up to a limit
+        let registered = registry.plugins.get_mut(&id).unwrap();
+        registered.config.refresh_interval = Duration::from_secs(1);
+        assert_eq!(registered.refresh_delay(), Duration::from_secs(2));
+        registered.failures = 100;
+        assert_eq!(registered.refresh_delay(), Duration::from_secs(64));
+        registered.config.refresh_interval = Duration::ZERO;
+
+        failing.store(false, Ordering::SeqCst);
+        assert!(settle(&mut registry).await);
+        assert!(!registry.segments()[0].failed);
+        assert!(registry.error(id).is_none());
+        assert_eq!(registry.plugins[&id].refresh_delay(), Duration::ZERO);
+    }
+
+    #[tokio::test]
+    async fn test_animated_segment() {
+        let mut registry = PluginRegistry::new(std::env::temp_dir());
+        registry.register(Box::new(Fetch { done: false })).unwrap();
+        assert_eq!(registry.segments()[0].current_icon(), Some("⠋"));
+
+        // Frames advance on every tick, long before the next refresh
+        assert!(registry.tick());
+        assert!(registry.tick());
+        assert_eq!(registry.segments()[0].current_icon(), Some("⠹"));
+        assert_eq!(registry.segments()[0].text(), "⠹ fetching");
+
+        // Still segments leave nothing to redraw
+        let mut registry = PluginRegistry::new(std::env::temp_dir());
+        registry.register(Box::new(Fetch { done: true })).unwrap();
+        assert!(!settle(&mut registry).await);
+    }
+
+    /// Counts crashed panes.
+    struct Crashes {
+        crashed: usize,
+        actions: Vec<PluginAction>,
+    }
+
+    impl Plugin for Crashes {
+        fn name(&self) -> &'static str {
+            "crashes"
+        }
+
+        fn config(&self) -> PluginConfig {
+            PluginConfig {
+                refresh_interval: Duration::MAX,
+                pane_events: true,
+                ..PluginConfig::default()
+            }
+        }
+
+        fn refresh(&mut self, _ctx: &PluginContext) -> PluginResult<()> {
+            Ok(())
+        }
+
+        fn render(&self) -> StatusBarSegment {
+            StatusBarSegment::new(format!("{} crashed", self.crashed))
+        }
+
+        fn on_event(&mut self, event: &PaneEvent, _ctx: &PluginContext) {
+            if let PaneEvent::Crashed { pane_id, .. } = event {
+                self.crashed += 1;
+                self.actions.push(PluginAction::RestartPane(*pane_id));
+                self.actions
+                    .push(PluginAction::Notify(format!("{pane_id} crashed")));
+            }
+        }
+
+        fn take_actions(&mut self) -> Vec<PluginAction> {
+            std::mem::take(&mut self.actions)
+        }
+    }
+
+    #[tokio::test]
+    async fn test_pane_events() {
+        let crash = PaneEvent::Crashed {
+            pane_id: PaneId(1),
+            signal: Some(9),
+            error: "killed".to_string(),
+        };
+        let output = PaneEvent::Output {
+            pane_id: PaneId(1),
+            size: 3,
+        };
+        let mut registry = PluginRegistry::new(std::env::temp_dir());
+        assert!(!registry.wants_events());
+        registry
+            .register(Box::new(Crashes {
+                crashed: 0,
+                actions: Vec::new(),
+            }))
+            .unwrap();
+        assert!(registry.wants_events());
+
+        // Held back until the first refresh hands the plugin back
+        assert!(!registry.dispatch(&[crash.clone(), output]));
+        assert!(settle(&mut registry).await);
+        assert_eq!(registry.segments()[0].content, "1 crashed");
+
+        assert!(registry.dispatch(&[crash]));
+        assert_eq!(registry.segments()[0].content, "2 crashed");
+        assert!(!registry.dispatch(&[]));
+    }
+
+    #[tokio::test]
+    async fn test_plugin_actions() {
+        let crash = PaneEvent::Crashed {
+            pane_id: PaneId(2),
+            signal: None,
+            error: "exit".to_string(),
+        };
+        let mut registry = PluginRegistry::new(std::env::temp_dir());
+        registry
+            .register(Box::new(Crashes {
+                crashed: 0,
+                actions: Vec::new(),
+            }))
+            .unwrap();
+        settle(&mut registry).await;
+        registry.dispatch(&[crash]);
+        let actions = registry.take_actions();
+        assert_eq!(
+            actions,
+            [
+                PluginAction::RestartPane(PaneId(2)),
+                PluginAction::Notify("2 crashed".to_string())
+            ]
+        );
+        assert!(registry.take_actions().is_empty());
+
+        // Notifications follow the plugins' segments until they expire
+        registry.notify("2 crashed");
+        assert_eq!(registry.segments()[1].content, "2 crashed");
+        assert_eq!(registry.segments()[1].alignment, Some(Alignment::Right));
+        if let Some((_, until)) = &mut registry.notification {
+            *until = Instant::now();
+        }
+        assert!(registry.tick());
+        assert_eq!(registry.segments().len(), 1);
+    }
+
+    /// Shows CPU and memory usage in segments of their own.
+    struct Stats;
+
+    impl Plugin for Stats {
+        fn name(&self) -> &'static str {
+            "stats"
+        }
+
+        fn refresh(&mut self, _ctx: &PluginContext) -> PluginResult<()> {
+            Err(PluginError::RefreshFailed("no /proc".to_string()))
+        }
+
+        fn render(&self) -> StatusBarSegment {
+            StatusBarSegment::new("cpu 3%")
+        }
+
+        fn render_all(&self) -> Vec<StatusBarSegment> {
+            vec![
+                self.render(),
+                StatusBarSegment::new("mem 1G").alignment(Alignment::Right),
+            ]
+        }
+    }
+
+    #[tokio::test]
+    async fn test_multiple_segments() {
+        let mut registry = PluginRegistry::new(std::env::temp_dir());
+        registry.register(Box::new(Stats)).unwrap();
+        settle(&mut registry).await;
+        let segments = registry.segments();
+        assert_eq!(segments.len(), 2);
+        assert_eq!(segments[0].alignment, Some(Alignment::Left));
+        assert_eq!(segments[1].alignment, Some(Alignment::Right));
+        assert_eq!(segments[1].content, "mem 1G");
+
+        // A failure is shown once for the plugin
+        assert!(segments[0].failed);
+        assert!(!segments[1].failed);
+    }
+
+    /// Blocks its refresh until `release` is set, and counts refreshes.
+    struct Slow {
+        release: Arc<AtomicBool>,
+        refreshes: usize,
+    }
+
+    impl Plugin for Slow {
+        fn name(&self) -> &'static str {
+            "slow"
+        }
+
+        fn refresh(&mut self, _ctx: &PluginContext) -> PluginResult<()> {
+            while !self.release.load(Ordering::SeqCst) {
+                std::thread::sleep(Duration::from_millis(1));
+            }
+            self.refreshes += 1;
+            Ok(())
+        }
+
+        fn render(&self) -> StatusBarSegment {
+            if self.refreshes == 0 {
+                StatusBarSegment::new("waiting")
+            } else {
+                StatusBarSegment::new(format!("done {}", self.refreshes))
+            }
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_background_refresh() {
+        let release = Arc::new(AtomicBool::new(false));
+        let mut registry = PluginRegistry::new(std::env::temp_dir());
+        let id = registry
+            .register(Box::new(Slow {
+                release: Arc::clone(&release),
+                refreshes: 0,
+            }))
+            .unwrap();
+
+        // Neither registering nor ticking waits for the refresh
+        assert!(!registry.tick());
+        assert!(registry.is_refreshing());
+        assert_eq!(registry.segments()[0].content, "waiting");
+
+        // A refresh past its timeout leaves the segment stale
+        registry
+            .plugins
+            .get_mut(&id)
+            .unwrap()
+            .config
+            .refresh_timeout = Duration::ZERO;
+        assert!(registry.tick());
+        assert!(registry.segments()[0].stale);
+        assert!(matches!(registry.error(id), Some(PluginError::TimedOut(_))));
+
+        assert!(!registry.tick());
+
+        // The abandoned refresh's result is dropped when it returns, and a
+        // fresh refresh takes its place
+        registry
+            .plugins
+            .get_mut(&id)
+            .unwrap()
+            .config
+            .refresh_timeout = Duration::MAX;
+        release.store(true, Ordering::SeqCst);
+        assert!(settle(&mut registry).await);
+        assert_eq!(registry.segments()[0].content, "done 2");
+        assert!(!registry.segments()[0].stale);
+        assert!(registry.error(id).is_none());
     }
 }
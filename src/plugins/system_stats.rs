@@ -0,0 +1,213 @@
+//! System stats plugin - displays CPU, memory and load of the machine.
+
+use std::time::Duration;
+
+use ratatui::layout::Alignment;
+use ratatui::style::{Color, Style};
+use sysinfo::System;
+
+use crate::plugins::context::PluginContext;
+use crate::plugins::{Plugin, PluginConfig, PluginResult};
+use crate::status_bar::StatusBarSegment;
+
+/// Bytes in a gibibyte.
+const GIB: f64 = 1024.0 * 1024.0 * 1024.0;
+
+/// Levels at which a reading is drawn in the warning and critical colors.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct StatThresholds {
+    /// Readings at or above this are yellow.
+    pub warning: f64,
+    /// Readings at or above this are red.
+    pub critical: f64,
+}
+
+impl StatThresholds {
+    /// Style of `value`: the default, yellow or red.
+    fn style(self, value: f64) -> Style {
+        if value >= self.critical {
+            Style::default().fg(Color::Red)
+        } else if value >= self.warning {
+            Style::default().fg(Color::Yellow)
+        } else {
+            Style::default()
+        }
+    }
+}
+
+/// One reading of the machine's usage.
+#[derive(Clone, Copy, Debug, Default)]
+struct Sample {
+    /// CPU usage of all cores together, in percent.
+    cpu_percent: f32,
+    used_memory: u64,
+    total_memory: u64,
+    /// One-minute load average.
+    load: f64,
+    cpus: usize,
+}
+
+/// Plugin that displays CPU usage, memory usage and the load average of
+/// the machine, each in a segment of its own that turns yellow, then red,
+/// past its thresholds.
+///
+/// CPU usage is measured between refreshes, so it reads zero until the
+/// second. The load average is zero on Windows.
+pub struct SystemStatsPlugin {
+    system: System,
+    config: PluginConfig,
+    /// CPU usage in percent.
+    cpu: StatThresholds,
+    /// Memory in use, in percent of the total.
+    memory: StatThresholds,
+    /// Load average per core.
+    load: StatThresholds,
+    sample: Sample,
+}
+
+impl SystemStatsPlugin {
+    /// Create a plugin refreshed every 2 seconds, warning at 70% CPU or
+    /// memory and a load of 0.7 per core, and critical at 90% and 1.0.
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            system: System::new(),
+            config: PluginConfig {
+                refresh_interval: Duration::from_secs(2),
+                alignment: Alignment::Right,
+                ..PluginConfig::default()
+            },
+            cpu: StatThresholds {
+                warning: 70.0,
+                critical: 90.0,
+            },
+            memory: StatThresholds {
+                warning: 70.0,
+                critical: 90.0,
+            },
+            load: StatThresholds {
+                warning: 0.7,
+                critical: 1.0,
+            },
+            sample: Sample::default(),
+        }
+    }
+
+    /// Set how often the machine is sampled.
+    #[must_use]
+    pub fn refresh_interval(mut self, interval: Duration) -> Self {
+        self.config.refresh_interval = interval;
+        self
+    }
+
+    /// Set the thresholds of CPU usage, in percent.
+    #[must_use]
+    pub fn cpu_thresholds(mut self, thresholds: StatThresholds) -> Self {
+        self.cpu = thresholds;
+        self
+    }
+
+    /// Set the thresholds of memory in use, in percent of the total.
+    #[must_use]
+    pub fn memory_thresholds(mut self, thresholds: StatThresholds) -> Self {
+        self.memory = thresholds;
+        self
+    }
+
+    /// Set the thresholds of the one-minute load average, per core.
+    #[must_use]
+    pub fn load_thresholds(mut self, thresholds: StatThresholds) -> Self {
+        self.load = thresholds;
+        self
+    }
+}
+
+impl Default for SystemStatsPlugin {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Plugin for SystemStatsPlugin {
+    fn name(&self) -> &'static str {
+        "system-stats"
+    }
+
+    fn config(&self) -> PluginConfig {
+        self.config.clone()
+    }
+
+    fn refresh(&mut self, _ctx: &PluginContext) -> PluginResult<()> {
+        self.system.refresh_cpu_usage();
+        self.system.refresh_memory();
+        self.sample = Sample {
+            cpu_percent: self.system.global_cpu_usage(),
+            used_memory: self.system.used_memory(),
+            total_memory: self.system.total_memory(),
+            load: System::load_average().one,
+            cpus: self.system.cpus().len(),
+        };
+        Ok(())
+    }
+
+    fn render(&self) -> StatusBarSegment {
+        let cpu = f64::from(self.sample.cpu_percent);
+        StatusBarSegment::new(format!("cpu {cpu:.0}%")).style(self.cpu.style(cpu))
+    }
+
+    #[allow(clippy::cast_precision_loss)]
+    fn render_all(&self) -> Vec<StatusBarSegment> {
+        let Sample {
+            used_memory,
+            total_memory,
+            load,
+            cpus,
+            ..
+        } = self.sample;
+        let memory_percent = if total_memory == 0 {
+            0.0
+        } else {
+            used_memory as f64 / total_memory as f64 * 100.0
+        };
+        let load_per_cpu = load / cpus.max(1) as f64;
+        vec![
+            self.render(),
+            StatusBarSegment::new(format!(
+                "mem {:.1}/{:.1}G",
+                used_memory as f64 / GIB,
+                total_memory as f64 / GIB
+            ))
+            .style(self.memory.style(memory_percent)),
+            StatusBarSegment::new(format!("load {load:.2}")).style(self.load.style(load_per_cpu)),
+        ]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stats_segments() {
+        let mut plugin = SystemStatsPlugin::new();
+        plugin.sample = Sample {
+            cpu_percent: 12.4,
+            used_memory: 12 * 1024 * 1024 * 1024,
+            total_memory: 16 * 1024 * 1024 * 1024,
+            load: 7.5,
+            cpus: 8,
+        };
+        let segments = plugin.render_all();
+        let texts: Vec<_> = segments.iter().map(|s| s.content.as_str()).collect();
+        assert_eq!(texts, ["cpu 12%", "mem 12.0/16.0G", "load 7.50"]);
+        assert_eq!(segments[0].style.fg, None);
+        assert_eq!(segments[1].style.fg, Some(Color::Yellow));
+        assert_eq!(segments[2].style.fg, Some(Color::Yellow));
+
+        let plugin = plugin.load_thresholds(StatThresholds {
+            warning: 0.5,
+            critical: 0.9,
+        });
+        assert_eq!(plugin.render_all()[2].style.fg, Some(Color::Red));
+    }
+}
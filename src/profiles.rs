@@ -0,0 +1,67 @@
+//! Named spawn templates.
+//!
+//! Apps register the panes they know how to open ("db-shell", "test-watch")
+//! once in a [`ProfileRegistry`], then spawn them by name from key
+//! bindings or a command palette with `PaneManager::spawn_profile`.
+
+use std::collections::BTreeMap;
+
+use crate::pane::SpawnConfig;
+
+/// Named [`SpawnConfig`] templates.
+#[derive(Clone, Debug, Default)]
+pub struct ProfileRegistry {
+    profiles: BTreeMap<String, SpawnConfig>,
+}
+
+impl ProfileRegistry {
+    /// Create an empty registry.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a profile, returning the one it replaced.
+    pub fn register(
+        &mut self,
+        name: impl Into<String>,
+        config: SpawnConfig,
+    ) -> Option<SpawnConfig> {
+        self.profiles.insert(name.into(), config)
+    }
+
+    /// Add a profile, builder style.
+    #[must_use]
+    pub fn with(mut self, name: impl Into<String>, config: SpawnConfig) -> Self {
+        self.register(name, config);
+        self
+    }
+
+    /// Remove a profile, returning it.
+    pub fn remove(&mut self, name: &str) -> Option<SpawnConfig> {
+        self.profiles.remove(name)
+    }
+
+    /// Get a profile by name.
+    #[must_use]
+    pub fn get(&self, name: &str) -> Option<&SpawnConfig> {
+        self.profiles.get(name)
+    }
+
+    /// Profile names in alphabetical order.
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.profiles.keys().map(String::as_str)
+    }
+
+    /// Number of registered profiles.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.profiles.len()
+    }
+
+    /// Whether no profiles are registered.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.profiles.is_empty()
+    }
+}
@@ -1,14 +1,23 @@
 //! PTY spawning and I/O management.
 
 use std::io::{Read, Write};
-use std::sync::{Arc, RwLock};
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::Instant;
 
 use portable_pty::{native_pty_system, CommandBuilder, PtySize};
 use tokio::sync::{mpsc, watch};
-use tokio::task::JoinHandle;
 
+use crate::emulator::Emulator;
 use crate::error::{Error, Result};
+use crate::events::EventSender;
+#[cfg(any(test, feature = "mock-pty"))]
+use crate::mock::{MockBackend, MockMaster};
+use crate::output::OutputProcessor;
 use crate::pane::{PaneHandle, PaneId, PaneSize, PaneState, SpawnConfig};
+use crate::sequences::{ExtendedState, TrackingOptions};
+use crate::stats::PaneMetrics;
+use crate::task::{self, TaskHandle};
+use crate::transcript::Transcript;
 
 /// Events emitted by panes.
 #[derive(Clone, Debug)]
@@ -23,11 +32,59 @@ pub enum PaneEvent {
         error: String,
     },
 
+    /// Program entered (`alternate: true`) or left the alternate screen.
+    ScreenModeChanged { pane_id: PaneId, alternate: bool },
+
     /// Title changed (via OSC escape sequence).
     TitleChanged { pane_id: PaneId, title: String },
 
-    /// Output received (for debugging).
+    /// Output received. Notifications are coalesced: `size` is the total
+    /// number of bytes read since the previous `Output` event for the pane.
     Output { pane_id: PaneId, size: usize },
+
+    /// A pane was spawned.
+    PaneSpawned { pane_id: PaneId },
+
+    /// A pane was closed and removed from the layout.
+    PaneClosed { pane_id: PaneId },
+
+    /// Focus moved from one pane to another. `None` means no pane.
+    FocusChanged {
+        from: Option<PaneId>,
+        to: Option<PaneId>,
+    },
+
+    /// A pane's terminal was resized, e.g. after a layout change.
+    Resized { pane_id: PaneId, size: PaneSize },
+
+    /// Text was selected with the mouse.
+    Selected { pane_id: PaneId, text: String },
+}
+
+/// Where panes get their PTYs from.
+#[derive(Debug, Default)]
+pub(crate) enum PtyBackend {
+    /// Real PTYs and processes on the host.
+    #[default]
+    Native,
+    /// Scripted PTYs for tests.
+    #[cfg(any(test, feature = "mock-pty"))]
+    Mock(MockBackend),
+}
+
+/// PTY master side, kept for resizing.
+pub(crate) enum PtyMaster {
+    Native(Box<dyn portable_pty::MasterPty + Send>),
+    #[cfg(any(test, feature = "mock-pty"))]
+    Mock(MockMaster),
+}
+
+/// A freshly opened PTY with its process running.
+struct OpenedPty {
+    master: PtyMaster,
+    reader: Box<dyn Read + Send>,
+    writer: Box<dyn Write + Send>,
+    child: Box<dyn portable_pty::Child + Send + Sync>,
 }
 
 /// Result of spawning a PTY process.
@@ -35,100 +92,125 @@ pub(crate) struct SpawnedPty {
     /// Handle for controlling the pane.
     pub handle: PaneHandle,
 
-    /// PTY master for resize operations.
-    pub pty_master: Box<dyn portable_pty::MasterPty + Send>,
+    /// PTY master for resize operations, shared weakly with the handle.
+    pub pty_master: Arc<Mutex<PtyMaster>>,
 
     /// Handle to the reader task.
-    pub reader_handle: JoinHandle<()>,
+    pub reader_handle: TaskHandle,
 
     /// Handle to the writer task.
-    pub writer_handle: JoinHandle<()>,
+    pub writer_handle: TaskHandle,
 
     /// Handle to the process monitor task.
-    pub monitor_handle: JoinHandle<()>,
+    pub monitor_handle: TaskHandle,
+
+    /// Output processing state shared with the reader task.
+    pub output: Arc<OutputProcessor>,
 }
 
 /// Spawns a new PTY process.
 ///
 /// # Errors
 /// Returns an error if PTY creation or process spawning fails.
+#[cfg_attr(
+    feature = "trace",
+    tracing::instrument(
+        name = "spawn_pane",
+        skip_all,
+        fields(pane_id = %pane_id, command = config.command.as_deref().unwrap_or("<shell>"))
+    )
+)]
 pub(crate) fn spawn_pty(
     pane_id: PaneId,
     config: &SpawnConfig,
-    event_tx: mpsc::Sender<PaneEvent>,
+    backend: &PtyBackend,
+    tracking: TrackingOptions,
+    event_tx: EventSender,
 ) -> Result<SpawnedPty> {
-    let pty_system = native_pty_system();
-
-    // Create PTY pair
-    let pty_pair = pty_system
-        .openpty(PtySize {
-            rows: config.size.rows,
-            cols: config.size.cols,
-            pixel_width: 0,
-            pixel_height: 0,
-        })
-        .map_err(|e| Error::PtyCreate(e.to_string()))?;
-
-    // Build command
-    let shell = std::env::var("SHELL").unwrap_or_else(|_| "/bin/sh".to_string());
-    let mut cmd = match &config.command {
-        Some(c) => {
-            let mut builder = CommandBuilder::new(c);
-            for arg in &config.args {
-                builder.arg(arg);
+    let OpenedPty {
+        master,
+        reader,
+        writer,
+        child,
+    } = match backend {
+        PtyBackend::Native => open_native(config)?,
+        #[cfg(any(test, feature = "mock-pty"))]
+        PtyBackend::Mock(mock) => {
+            let pty = mock.open(pane_id, config);
+            OpenedPty {
+                master: PtyMaster::Mock(pty.master()),
+                reader: pty.reader(),
+                writer: pty.writer(),
+                child: pty.child(),
             }
-            builder
         }
-        None => CommandBuilder::new(&shell),
     };
 
-    // Set working directory
-    if let Some(cwd) = &config.cwd {
-        cmd.cwd(cwd);
-    }
-
-    // Set environment variables
-    for (key, value) in &config.env {
-        cmd.env(key, value);
-    }
-
-    // Spawn the child process
-    let child = pty_pair.slave.spawn_command(cmd)?;
-
     // Get the child PID before moving ownership
     let child_pid = child.process_id();
 
     // Create vt100 parser for terminal emulation
-    let parser = vt100::Parser::new(config.size.rows, config.size.cols, config.scrollback);
+    let parser = Emulator::new(config.size.rows, config.size.cols, config.scrollback);
     let screen = Arc::new(RwLock::new(parser));
+    let extended = Arc::new(Mutex::new(ExtendedState::default()));
+    let generation = Arc::new(watch::channel(0u64).0);
+    let metrics = Arc::new(PaneMetrics::default());
+    let transcript = Arc::new(Mutex::new(Transcript::default()));
+    let title = Arc::new(RwLock::new(String::new()));
 
     // Create channels
     let (input_tx, input_rx) = mpsc::channel::<Vec<u8>>(256);
     let (state_tx, state_rx) = watch::channel(PaneState::Running);
 
     // Spawn reader task
-    let reader_handle = spawn_reader_task(
+    let output = Arc::new(OutputProcessor::new(
         pane_id,
-        pty_pair.master.try_clone_reader()?,
         screen.clone(),
+        extended.clone(),
+        generation.clone(),
+        transcript.clone(),
+        title.clone(),
+        tracking,
+        config.flow_control,
+        metrics.clone(),
+        input_tx.clone(),
         event_tx.clone(),
-    );
+    ));
+    let reader_handle = spawn_reader_task(reader, output.clone());
 
     // Spawn writer task
-    let writer_handle = spawn_writer_task(pty_pair.master.take_writer()?, input_rx);
+    let writer_handle = spawn_writer_task(pane_id, writer, input_rx, metrics.clone());
 
     // Spawn process monitor task
-    let monitor_handle = spawn_monitor_task(pane_id, child, state_tx, event_tx);
+    let monitor_handle = spawn_monitor_task(pane_id, child, state_tx, event_tx, metrics.clone());
 
     // Create pane handle
-    let handle = PaneHandle::new(pane_id, child_pid, input_tx, state_rx, screen);
+    let master = Arc::new(Mutex::new(master));
+    let handle = PaneHandle::new(
+        pane_id,
+        child_pid,
+        Arc::downgrade(&master),
+        input_tx,
+        state_rx,
+        screen,
+        config.scrollback,
+        config.name.clone(),
+        config.colors,
+        config.cursor,
+        title,
+        extended,
+        generation,
+        metrics,
+        transcript,
+    );
 
     Ok(SpawnedPty {
         handle,
-        pty_master: pty_pair.master,
+        pty_master: master,
         reader_handle,
         writer_handle,
         monitor_handle,
+        output,
     })
 }
 
@@ -136,44 +218,213 @@ pub(crate) fn spawn_pty(
 ///
 /// # Errors
 /// Returns an error if the resize operation fails.
-pub(crate) fn resize_pty(pty_master: &dyn portable_pty::MasterPty, size: PaneSize) -> Result<()> {
-    pty_master
-        .resize(PtySize {
-            rows: size.rows,
-            cols: size.cols,
+pub(crate) fn resize_pty(pty_master: &PtyMaster, size: PaneSize) -> Result<()> {
+    match pty_master {
+        PtyMaster::Native(master) => master
+            .resize(PtySize {
+                rows: size.rows,
+                cols: size.cols,
+                pixel_width: 0,
+                pixel_height: 0,
+            })
+            .map_err(|e| Error::Resize(e.to_string())),
+        #[cfg(any(test, feature = "mock-pty"))]
+        PtyMaster::Mock(master) => {
+            master.resize(size);
+            Ok(())
+        }
+    }
+}
+
+impl PtyMaster {
+    /// Process group in the foreground of the terminal, i.e. the program
+    /// the user is interacting with.
+    #[cfg(unix)]
+    pub fn foreground_pid(&self) -> Option<u32> {
+        match self {
+            Self::Native(master) => master
+                .process_group_leader()
+                .and_then(|pid| u32::try_from(pid).ok()),
+            #[cfg(any(test, feature = "mock-pty"))]
+            Self::Mock(master) => master.foreground_pid(),
+        }
+    }
+}
+
+/// Open a PTY on the host and spawn the configured command in it.
+fn open_native(config: &SpawnConfig) -> Result<OpenedPty> {
+    let pty_system = native_pty_system();
+
+    // Create PTY pair
+    let pty_pair = pty_system
+        .openpty(PtySize {
+            rows: config.size.rows,
+            cols: config.size.cols,
             pixel_width: 0,
             pixel_height: 0,
         })
-        .map_err(|e| Error::Resize(e.to_string()))
+        .map_err(|e| Error::PtyCreate(e.to_string()))?;
+
+    // Spawn the child process
+    let child = pty_pair.slave.spawn_command(build_command(config)?)?;
+
+    Ok(OpenedPty {
+        reader: pty_pair.master.try_clone_reader()?,
+        writer: pty_pair.master.take_writer()?,
+        master: PtyMaster::Native(pty_pair.master),
+        child,
+    })
+}
+
+/// Build the command line and environment for a pane's process.
+///
+/// # Errors
+/// Returns [`Error::Config`] for an `argv0` the PTY backend cannot set.
+fn build_command(config: &SpawnConfig) -> Result<CommandBuilder> {
+    let program = match &config.command {
+        Some(c) => c.clone(),
+        None => default_shell(),
+    };
+    let mut cmd = match config.argv0.as_deref() {
+        None if config.login_shell && cfg!(unix) => login_command(&program, &config.args),
+        Some(argv0) if argv0 != program => {
+            // portable-pty always passes the program as argv0, except that
+            // it runs `$SHELL` as `-<name>` when no program is given
+            let login = argv0.strip_prefix('-') == Some(program_name(&program));
+            if !(login && config.args.is_empty() && cfg!(unix)) {
+                return Err(Error::Config(format!(
+                    "argv0 {argv0:?} is only supported as {:?} without arguments",
+                    format!("-{}", program_name(&program))
+                )));
+            }
+            CommandBuilder::new_default_prog()
+        }
+        _ => {
+            let mut builder = CommandBuilder::new(&program);
+            builder.args(&config.args);
+            builder
+        }
+    };
+
+    // Set working directory
+    if let Some(cwd) = &config.cwd {
+        cmd.cwd(cwd);
+    }
+
+    // Set environment variables
+    if config.clear_env {
+        cmd.env_clear();
+    }
+    for key in &config.env_remove {
+        cmd.env_remove(key);
+    }
+    if cmd.is_default_prog() {
+        // portable-pty runs `$SHELL` with the `-` prefix for the default program
+        cmd.env("SHELL", &program);
+    }
+    for (key, value) in &config.env {
+        cmd.env(key, value);
+    }
+    Ok(cmd)
+}
+
+/// File name of a program path, without a `.exe` suffix.
+fn program_name(program: &str) -> &str {
+    let name = program.rsplit(['/', '\\']).next().unwrap_or(program);
+    name.strip_suffix(".exe").unwrap_or(name)
+}
+
+/// The user's shell: `$SHELL`, falling back to `/bin/sh`.
+#[cfg(not(windows))]
+fn default_shell() -> String {
+    std::env::var("SHELL").unwrap_or_else(|_| "/bin/sh".to_string())
+}
+
+/// The user's shell: `$SHELL` when set (e.g. under MSYS), then PowerShell 7
+/// if it is on the `PATH`, then `%ComSpec%`, falling back to `cmd.exe`.
+#[cfg(windows)]
+fn default_shell() -> String {
+    if let Ok(shell) = std::env::var("SHELL") {
+        return shell;
+    }
+    let pwsh = std::env::var_os("PATH").and_then(|path| {
+        std::env::split_paths(&path)
+            .map(|dir| dir.join("pwsh.exe"))
+            .find(|candidate| candidate.is_file())
+    });
+    if let Some(pwsh) = pwsh {
+        return pwsh.to_string_lossy().into_owned();
+    }
+    std::env::var("ComSpec").unwrap_or_else(|_| "cmd.exe".to_string())
+}
+
+/// Shell families that differ in how a login shell is requested.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum ShellKind {
+    /// `sh`, `bash`, `zsh`, `fish`, `nu` and most others: `-l`.
+    Posix,
+    /// `csh` and `tcsh` only accept `-l` as their sole argument.
+    Csh,
+    /// `pwsh` wants `-Login` first.
+    PowerShell,
+}
+
+impl ShellKind {
+    fn of(program: &str) -> Self {
+        match program_name(program) {
+            "csh" | "tcsh" => Self::Csh,
+            "pwsh" | "powershell" => Self::PowerShell,
+            _ => Self::Posix,
+        }
+    }
+
+    /// Flag that makes the shell a login shell when other arguments follow.
+    fn login_flag(self) -> Option<&'static str> {
+        match self {
+            Self::Posix => Some("-l"),
+            Self::Csh => None,
+            Self::PowerShell => Some("-Login"),
+        }
+    }
+}
+
+/// Build a command that starts `shell` as a login shell, so profile and rc
+/// files load the same way they would in a terminal emulator.
+///
+/// Without arguments the shell gets the conventional `-` prefix on argv0
+/// (`-bash`), which every shell understands. With arguments the
+/// shell-specific login flag is prepended instead.
+fn login_command(shell: &str, args: &[String]) -> CommandBuilder {
+    if args.is_empty() {
+        // `build_command` points `$SHELL` at the shell
+        return CommandBuilder::new_default_prog();
+    }
+    let mut builder = CommandBuilder::new(shell);
+    if let Some(flag) = ShellKind::of(shell).login_flag() {
+        builder.arg(flag);
+    }
+    builder.args(args);
+    builder
 }
 
 /// Spawns the task that reads PTY output.
-fn spawn_reader_task(
-    pane_id: PaneId,
-    mut reader: Box<dyn Read + Send>,
-    screen: Arc<RwLock<vt100::Parser>>,
-    event_tx: mpsc::Sender<PaneEvent>,
-) -> JoinHandle<()> {
-    tokio::task::spawn_blocking(move || {
+fn spawn_reader_task(mut reader: Box<dyn Read + Send>, output: Arc<OutputProcessor>) -> TaskHandle {
+    let pane_id = output.pane_id;
+    task::spawn_blocking(&format!("cockpit-reader-{pane_id}"), move || {
         let mut buf = [0u8; 4096];
+        #[cfg(feature = "trace")]
+        let _span = tracing::debug_span!("pty_reader", pane_id = %pane_id).entered();
 
         loop {
+            output.wait_for_flow();
             match reader.read(&mut buf) {
                 Ok(0) => {
                     // EOF - process has closed
                     break;
                 }
                 Ok(n) => {
-                    let data = &buf[..n];
-
-                    // Update screen state
-                    {
-                        let mut screen = screen.write().expect("screen lock poisoned");
-                        screen.process(data);
-                    }
-
-                    // Emit output event (optional, for debugging)
-                    let _ = event_tx.blocking_send(PaneEvent::Output { pane_id, size: n });
+                    output.metrics.record_read(n, Instant::now());
+                    output.feed(&buf[..n]);
                 }
                 Err(e) => {
                     tracing::debug!("PTY read error for pane {}: {}", pane_id, e);
@@ -188,22 +439,28 @@ fn spawn_reader_task(
 
 /// Spawns the task that writes to PTY.
 fn spawn_writer_task(
+    pane_id: PaneId,
     mut writer: Box<dyn Write + Send>,
     mut input_rx: mpsc::Receiver<Vec<u8>>,
-) -> JoinHandle<()> {
-    tokio::task::spawn_blocking(move || {
+    metrics: Arc<PaneMetrics>,
+) -> TaskHandle {
+    task::spawn_blocking(&format!("cockpit-writer-{pane_id}"), move || {
+        #[cfg(feature = "trace")]
+        let _span = tracing::debug_span!("pty_writer", pane_id = %pane_id).entered();
+
         while let Some(data) = input_rx.blocking_recv() {
             if let Err(e) = writer.write_all(&data) {
                 tracing::debug!("PTY write error: {}", e);
                 break;
             }
+            metrics.record_write(data.len(), Instant::now());
             if let Err(e) = writer.flush() {
                 tracing::debug!("PTY flush error: {}", e);
                 break;
             }
         }
 
-        tracing::debug!("Writer task finished");
+        tracing::debug!("Writer task for pane {} finished", pane_id);
     })
 }
 
@@ -212,22 +469,30 @@ fn spawn_monitor_task(
     pane_id: PaneId,
     mut child: Box<dyn portable_pty::Child + Send>,
     state_tx: watch::Sender<PaneState>,
-    event_tx: mpsc::Sender<PaneEvent>,
-) -> JoinHandle<()> {
-    tokio::task::spawn_blocking(move || {
-        match child.wait() {
+    event_tx: EventSender,
+    metrics: Arc<PaneMetrics>,
+) -> TaskHandle {
+    task::spawn_blocking(&format!("cockpit-monitor-{pane_id}"), move || {
+        #[cfg(feature = "trace")]
+        let _span = tracing::debug_span!("pty_monitor", pane_id = %pane_id).entered();
+
+        let result = child.wait();
+        // Before the state changes, so the runtime is final once it has
+        metrics.record_exit(Instant::now());
+        match result {
             Ok(status) => {
+                #[allow(clippy::cast_possible_wrap)]
                 let code = status.exit_code() as i32;
                 if status.success() {
                     let new_state = PaneState::Exited { code };
                     let _ = state_tx.send(new_state);
-                    let _ = event_tx.blocking_send(PaneEvent::Exited { pane_id, code });
+                    event_tx.send(PaneEvent::Exited { pane_id, code });
                 } else {
                     // Non-zero exit - could be error or signal
                     // portable-pty doesn't expose signal info directly
                     let new_state = PaneState::Exited { code };
                     let _ = state_tx.send(new_state);
-                    let _ = event_tx.blocking_send(PaneEvent::Exited { pane_id, code });
+                    event_tx.send(PaneEvent::Exited { pane_id, code });
                 }
             }
             Err(e) => {
@@ -236,7 +501,7 @@ fn spawn_monitor_task(
                     error: Some(e.to_string()),
                 };
                 let _ = state_tx.send(new_state);
-                let _ = event_tx.blocking_send(PaneEvent::Crashed {
+                event_tx.send(PaneEvent::Crashed {
                     pane_id,
                     signal: None,
                     error: e.to_string(),
@@ -247,3 +512,74 @@ fn spawn_monitor_task(
         tracing::debug!("Monitor task for pane {} finished", pane_id);
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn argv(builder: &CommandBuilder) -> Vec<&str> {
+        builder
+            .get_argv()
+            .iter()
+            .map(|arg| arg.to_str().unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_login_command() {
+        let bare = build_command(&SpawnConfig::new_command("/bin/bash").login_shell(true)).unwrap();
+        assert!(bare.is_default_prog());
+        assert_eq!(bare.get_env("SHELL").unwrap(), "/bin/bash");
+
+        let args = vec!["-c".to_string(), "env".to_string()];
+        assert_eq!(
+            argv(&login_command("/usr/bin/zsh", &args)),
+            ["/usr/bin/zsh", "-l", "-c", "env"]
+        );
+        assert_eq!(
+            argv(&login_command("pwsh", &args)),
+            ["pwsh", "-Login", "-c", "env"]
+        );
+        assert_eq!(
+            argv(&login_command("/bin/tcsh", &args)),
+            ["/bin/tcsh", "-c", "env"]
+        );
+    }
+
+    #[test]
+    fn test_clean_environment() {
+        let config = SpawnConfig::new_command("env")
+            .clear_env()
+            .env("KEEP", "1")
+            .env("DROP", "1")
+            .env_remove("DROP");
+        let cmd = build_command(&config).unwrap();
+        assert_eq!(cmd.get_env("KEEP").unwrap(), "1");
+        assert!(cmd.get_env("DROP").is_none());
+        assert!(cmd.get_env("PATH").is_none());
+
+        let cmd = build_command(&SpawnConfig::new_command("env").env_remove("PATH")).unwrap();
+        assert!(cmd.get_env("PATH").is_none());
+        assert!(cmd.get_env("HOME").is_some());
+    }
+
+    #[test]
+    fn test_argv() {
+        let config =
+            SpawnConfig::new_shell().argv(vec!["git".into(), "log".into(), "--oneline".into()]);
+        assert_eq!(
+            argv(&build_command(&config).unwrap()),
+            ["git", "log", "--oneline"]
+        );
+
+        let login = SpawnConfig::new_shell()
+            .argv(vec!["/bin/zsh".into()])
+            .argv0("-zsh");
+        let cmd = build_command(&login).unwrap();
+        assert!(cmd.is_default_prog());
+        assert_eq!(cmd.get_env("SHELL").unwrap(), "/bin/zsh");
+
+        let multicall = SpawnConfig::new_command("/bin/busybox").argv0("ls");
+        assert!(matches!(build_command(&multicall), Err(Error::Config(_))));
+    }
+}
@@ -0,0 +1,103 @@
+//! Per-pane CPU and memory usage, sampled with `sysinfo`.
+
+use std::collections::HashSet;
+use std::time::{Duration, Instant};
+
+use sysinfo::{Pid, ProcessRefreshKind, ProcessesToUpdate, System};
+
+/// CPU and memory usage of a pane's process and its descendants.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ResourceUsage {
+    /// CPU usage in percent of one core, so a busy multi-threaded build
+    /// can exceed 100.
+    pub cpu_percent: f32,
+    /// Resident memory in bytes.
+    pub memory: u64,
+    /// Number of processes started from the pane's process.
+    pub child_processes: usize,
+}
+
+/// Process table shared by the panes of a manager.
+///
+/// Refreshing it reads every process on the machine, so it is refreshed
+/// at most once per interval no matter how many panes are queried.
+#[derive(Debug)]
+pub(crate) struct ResourceMonitor {
+    system: System,
+    interval: Duration,
+    refreshed: Option<Instant>,
+}
+
+impl ResourceMonitor {
+    pub fn new(interval: Duration) -> Self {
+        Self {
+            system: System::new(),
+            interval,
+            refreshed: None,
+        }
+    }
+
+    /// Usage of the process tree rooted at `pid`, or `None` if the process
+    /// is gone.
+    ///
+    /// CPU usage is measured between refreshes, so it reads zero until the
+    /// second sample.
+    pub fn usage(&mut self, pid: u32, now: Instant) -> Option<ResourceUsage> {
+        if self
+            .refreshed
+            .is_none_or(|at| now.saturating_duration_since(at) >= self.interval)
+        {
+            self.system.refresh_processes_specifics(
+                ProcessesToUpdate::All,
+                true,
+                ProcessRefreshKind::nothing().with_cpu().with_memory(),
+            );
+            self.refreshed = Some(now);
+        }
+
+        let root = self.system.process(Pid::from_u32(pid))?;
+        let mut usage = ResourceUsage {
+            cpu_percent: root.cpu_usage(),
+            memory: root.memory(),
+            child_processes: 0,
+        };
+        let mut tree = HashSet::from([root.pid()]);
+        // Parents may be listed after their children, so sweep until the
+        // tree stops growing.
+        loop {
+            let before = tree.len();
+            for process in self.system.processes().values() {
+                if tree.contains(&process.pid()) || process.thread_kind().is_some() {
+                    continue;
+                }
+                if process
+                    .parent()
+                    .is_some_and(|parent| tree.contains(&parent))
+                {
+                    tree.insert(process.pid());
+                    usage.cpu_percent += process.cpu_usage();
+                    usage.memory += process.memory();
+                    usage.child_processes += 1;
+                }
+            }
+            if tree.len() == before {
+                break;
+            }
+        }
+        Some(usage)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_usage_of_own_process() {
+        let mut monitor = ResourceMonitor::new(Duration::from_secs(10));
+        let usage = monitor.usage(std::process::id(), Instant::now()).unwrap();
+        assert!(usage.memory > 0);
+        assert!(monitor.usage(u32::MAX, Instant::now()).is_none());
+    }
+}
@@ -0,0 +1,753 @@
+//! Ready-made event loop for full-screen cockpit apps.
+//!
+//! [`run`] takes over the terminal, draws the manager every frame and
+//! routes input to the panes, with the same default keybindings as the
+//! examples. Apps customize it through [`RunOptions`]: change or disable
+//! the bindings in its [`KeyMap`], and hook into key handling, ticks and
+//! rendering.
+//!
+//! ```no_run
+//! use cockpit::runtime::{self, Flow, RunOptions};
+//! use cockpit::{PaneManager, SpawnConfig};
+//! use crossterm::event::KeyCode;
+//!
+//! #[tokio::main]
+//! async fn main() -> cockpit::Result<()> {
+//!     let mut manager = PaneManager::new();
+//!     manager.spawn(SpawnConfig::new_shell())?;
+//!     manager.spawn(SpawnConfig::new_shell())?;
+//!
+//!     let options = RunOptions::new().on_key(|manager, key| {
+//!         if key.code == KeyCode::F(2) {
+//!             let _ = manager.spawn(SpawnConfig::new_shell());
+//!             return Flow::Consumed;
+//!         }
+//!         Flow::Continue
+//!     });
+//!     runtime::run(manager, options).await?;
+//!     Ok(())
+//! }
+//! ```
+
+use std::io::{self, Write};
+use std::pin::Pin;
+use std::time::{Duration, Instant};
+
+use crossterm::event::{
+    DisableMouseCapture, EnableMouseCapture, Event, EventStream, KeyCode, KeyEvent, KeyEventKind,
+    KeyModifiers, MouseEventKind,
+};
+use crossterm::execute;
+use crossterm::terminal::{
+    disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen,
+};
+use futures_core::Stream;
+use ratatui::backend::CrosstermBackend;
+use ratatui::layout::Rect;
+use ratatui::{Frame, Terminal};
+
+use crate::actions::{Action, KeyMap, RepeatAccelerator};
+use crate::error::{Error, Result};
+use crate::frame::{CockpitFrame, FrameAreas};
+use crate::help::HelpOverlay;
+use crate::manager::PaneManager;
+use crate::palette::{CommandPalette, CommandPaletteState};
+use crate::pty::PaneEvent;
+use crate::widget::DialogState;
+
+/// Time window for detecting a double Ctrl+C press.
+const CTRL_C_WINDOW: Duration = Duration::from_millis(500);
+
+/// What the event loop should do after a hook ran.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Flow {
+    /// Carry on with the default handling.
+    #[default]
+    Continue,
+    /// The hook handled the event; skip the default handling.
+    Consumed,
+    /// Leave the event loop.
+    Quit,
+}
+
+/// Called for each key press before the default bindings.
+pub type KeyHook = Box<dyn FnMut(&mut PaneManager, KeyEvent) -> Flow>;
+
+/// Called once per loop iteration with the pane events polled in it.
+pub type TickHook = Box<dyn FnMut(&mut PaneManager, &[PaneEvent]) -> Flow>;
+
+/// Called after the cockpit is drawn, to draw on top of it.
+pub type RenderHook = Box<dyn FnMut(&mut Frame<'_>, &PaneManager, &FrameAreas)>;
+
+/// Keybindings, dialog text and hooks for [`run`].
+pub struct RunOptions {
+    keymap: KeyMap,
+    confirm_exit: bool,
+    dialog_title: String,
+    dialog_message: String,
+    mouse: bool,
+    tick_rate: Duration,
+    on_key: Option<KeyHook>,
+    on_tick: Option<TickHook>,
+    custom_render: Option<RenderHook>,
+}
+
+impl Default for RunOptions {
+    fn default() -> Self {
+        let mut keymap = KeyMap::new().with_resize_bindings();
+        keymap.bind(
+            KeyEvent::new(KeyCode::Char('q'), KeyModifiers::CONTROL),
+            Action::Quit,
+        );
+        keymap.bind(
+            KeyEvent::new(KeyCode::Char('n'), KeyModifiers::CONTROL),
+            Action::FocusNext,
+        );
+        keymap.bind(
+            KeyEvent::new(KeyCode::Char('p'), KeyModifiers::CONTROL),
+            Action::CommandPalette,
+        );
+        keymap.bind(
+            KeyEvent::new(KeyCode::F(1), KeyModifiers::NONE),
+            Action::Help,
+        );
+        keymap.bind(
+            KeyEvent::new(KeyCode::Char('r'), KeyModifiers::ALT),
+            Action::RestartPane,
+        );
+        keymap.bind(
+            KeyEvent::new(KeyCode::Char('q'), KeyModifiers::ALT),
+            Action::DisplayPanes,
+        );
+        Self {
+            keymap,
+            confirm_exit: true,
+            dialog_title: " Exit Cockpit? ".to_string(),
+            dialog_message: "Are you sure you want to quit?".to_string(),
+            mouse: true,
+            tick_rate: Duration::from_millis(16),
+            on_key: None,
+            on_tick: None,
+            custom_render: None,
+        }
+    }
+}
+
+impl RunOptions {
+    /// Default options: Ctrl+Q quits, Ctrl+N focuses the next pane, Ctrl+P
+    /// opens the command palette, F1 lists the keybindings, Alt+R restarts
+    /// the focused pane once it has exited, Alt+Q numbers the panes to
+    /// pick one by its number, Alt+Shift+arrows resize the focused pane, a
+    /// double Ctrl+C asks for confirmation before quitting, and clicks
+    /// focus panes.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Key that quits immediately. `None` disables it.
+    #[must_use]
+    pub fn quit_key(self, key: Option<KeyEvent>) -> Self {
+        self.rebind(key, Action::Quit)
+    }
+
+    /// Key that focuses the next pane. `None` disables it.
+    #[must_use]
+    pub fn focus_next_key(self, key: Option<KeyEvent>) -> Self {
+        self.rebind(key, Action::FocusNext)
+    }
+
+    /// Bind `key` to `action`, in addition to the existing bindings.
+    #[must_use]
+    pub fn bind(mut self, key: KeyEvent, action: Action) -> Self {
+        self.keymap.bind(key, action);
+        self
+    }
+
+    /// Replace all keybindings.
+    #[must_use]
+    pub fn keymap(mut self, keymap: KeyMap) -> Self {
+        self.keymap = keymap;
+        self
+    }
+
+    /// The keybindings, e.g. for a [`KeyHintPlugin`](crate::KeyHintPlugin).
+    #[must_use]
+    pub fn get_keymap(&self) -> &KeyMap {
+        &self.keymap
+    }
+
+    /// Make `key` the only binding of `action`.
+    fn rebind(mut self, key: Option<KeyEvent>, action: Action) -> Self {
+        self.keymap.unbind_action(&action);
+        if let Some(key) = key {
+            self.keymap.bind(key, action);
+        }
+        self
+    }
+
+    /// Ask for confirmation when Ctrl+C is pressed twice in a row. A
+    /// single Ctrl+C always goes to the focused pane.
+    #[must_use]
+    pub fn confirm_exit(mut self, confirm: bool) -> Self {
+        self.confirm_exit = confirm;
+        self
+    }
+
+    /// Title and message of the exit confirmation dialog.
+    #[must_use]
+    pub fn dialog(mut self, title: impl Into<String>, message: impl Into<String>) -> Self {
+        self.dialog_title = title.into();
+        self.dialog_message = message.into();
+        self
+    }
+
+    /// Capture the mouse so clicks focus panes and toggle expansion, and
+    /// dragging selects text.
+    #[must_use]
+    pub fn mouse(mut self, enabled: bool) -> Self {
+        self.mouse = enabled;
+        self
+    }
+
+    /// Longest time to wait for terminal input before the next tick.
+    #[must_use]
+    pub fn tick_rate(mut self, rate: Duration) -> Self {
+        self.tick_rate = rate;
+        self
+    }
+
+    /// Hook called for each key press before the default bindings.
+    #[must_use]
+    pub fn on_key(
+        mut self,
+        hook: impl FnMut(&mut PaneManager, KeyEvent) -> Flow + 'static,
+    ) -> Self {
+        self.on_key = Some(Box::new(hook));
+        self
+    }
+
+    /// Hook called once per loop iteration, after plugins were ticked,
+    /// with the pane events polled in that iteration.
+    #[must_use]
+    pub fn on_tick(
+        mut self,
+        hook: impl FnMut(&mut PaneManager, &[PaneEvent]) -> Flow + 'static,
+    ) -> Self {
+        self.on_tick = Some(Box::new(hook));
+        self
+    }
+
+    /// Hook called after the cockpit is drawn, to draw on top of it.
+    #[must_use]
+    pub fn custom_render(
+        mut self,
+        hook: impl FnMut(&mut Frame<'_>, &PaneManager, &FrameAreas) + 'static,
+    ) -> Self {
+        self.custom_render = Some(Box::new(hook));
+        self
+    }
+}
+
+/// Run `manager` full screen until the user quits.
+///
+/// Sets up the terminal (raw mode, alternate screen, mouse capture),
+/// runs the event loop and restores the terminal on the way out, also
+/// when the loop fails. Returns the manager with its panes still running.
+///
+/// # Errors
+/// Returns an error if the terminal cannot be set up or drawn to.
+pub async fn run(mut manager: PaneManager, mut options: RunOptions) -> Result<PaneManager> {
+    let guard = TerminalGuard::enter(options.mouse)?;
+    let mut terminal =
+        Terminal::new(CrosstermBackend::new(io::stdout())).map_err(Error::Terminal)?;
+    let result = event_loop(&mut terminal, &mut manager, &mut options).await;
+    drop(guard);
+    terminal.show_cursor().map_err(Error::Terminal)?;
+    result.map(|()| manager)
+}
+
+async fn event_loop(
+    terminal: &mut Terminal<CrosstermBackend<io::Stdout>>,
+    manager: &mut PaneManager,
+    options: &mut RunOptions,
+) -> Result<()> {
+    let mut state = LoopState::default();
+    let mut input = EventStream::new();
+    loop {
+        manager.tick_plugins();
+        let events = manager.poll_events();
+        if let Some(on_tick) = &mut options.on_tick {
+            if on_tick(manager, &events) == Flow::Quit {
+                return Ok(());
+            }
+        }
+
+        terminal
+            .draw(|frame| {
+                let areas = CockpitFrame::new(manager)
+                    .dialog(
+                        &state.dialog,
+                        &options.dialog_title,
+                        &options.dialog_message,
+                    )
+                    .render(frame);
+                state.dialog_area = areas.dialog.unwrap_or_default();
+                if state.palette.visible {
+                    let theme = manager.theme();
+                    let palette = CommandPalette::new(&state.palette)
+                        .keymap(&options.keymap)
+                        .border_style(theme.dialog_border_style)
+                        .selected_style(theme.dialog_selected_style);
+                    let area = CommandPaletteState::calculate_area(frame.area());
+                    frame.render_widget(palette, area);
+                }
+                if state.help {
+                    let help = HelpOverlay::new(&options.keymap)
+                        .border_style(manager.theme().dialog_border_style);
+                    let area = help.calculate_area(frame.area());
+                    frame.render_widget(help, area);
+                }
+                if let Some(custom_render) = &mut options.custom_render {
+                    custom_render(frame, manager, &areas);
+                }
+            })
+            .map_err(Error::Terminal)?;
+        let passthrough = manager.take_passthrough();
+        if !passthrough.is_empty() {
+            let out = terminal.backend_mut();
+            out.write_all(&passthrough).map_err(Error::Terminal)?;
+            out.flush().map_err(Error::Terminal)?;
+        }
+
+        // Wait for input without blocking the thread, so pane tasks on
+        // the same runtime keep running
+        let Some(event) = crate::task::timeout(options.tick_rate, next_event(&mut input)).await
+        else {
+            continue;
+        };
+        let Some(event) = event else {
+            // The terminal's input is gone
+            return Ok(());
+        };
+        let event = event.map_err(Error::Terminal)?;
+        if state.handle_event(manager, options, event).await? == Flow::Quit {
+            return Ok(());
+        }
+    }
+}
+
+/// The next terminal event, or `None` once `input` has ended.
+async fn next_event(input: &mut EventStream) -> Option<io::Result<Event>> {
+    std::future::poll_fn(|cx| Pin::new(&mut *input).poll_next(cx)).await
+}
+
+/// Input handling state carried across loop iterations.
+#[derive(Default)]
+struct LoopState {
+    dialog: DialogState,
+    dialog_area: Rect,
+    palette: CommandPaletteState,
+    help: bool,
+    last_ctrl_c: Option<Instant>,
+    accelerator: RepeatAccelerator,
+}
+
+impl LoopState {
+    /// Apply one terminal event. Returns [`Flow::Quit`] to leave the loop.
+    async fn handle_event(
+        &mut self,
+        manager: &mut PaneManager,
+        options: &mut RunOptions,
+        event: Event,
+    ) -> Result<Flow> {
+        match event {
+            Event::Key(key) => self.handle_key(manager, options, key).await,
+            Event::Mouse(_) if self.palette.visible || self.help => Ok(Flow::Continue),
+            Event::Mouse(mouse) => {
+                if !self.dialog.visible {
+                    manager.handle_mouse(mouse);
+                } else if matches!(mouse.kind, MouseEventKind::Down(_))
+                    && self
+                        .dialog
+                        .handle_mouse(mouse.column, mouse.row, self.dialog_area)
+                        == Some(true)
+                {
+                    return Ok(Flow::Quit);
+                }
+                Ok(Flow::Continue)
+            }
+            // Resizes are picked up when the next frame is drawn
+            _ => Ok(Flow::Continue),
+        }
+    }
+
+    async fn handle_key(
+        &mut self,
+        manager: &mut PaneManager,
+        options: &mut RunOptions,
+        key: KeyEvent,
+    ) -> Result<Flow> {
+        if self.dialog.visible {
+            if self.dialog.handle_key(key) == Some(true) {
+                return Ok(Flow::Quit);
+            }
+            return Ok(Flow::Continue);
+        }
+        if self.help {
+            // Any key dismisses the overlay
+            if key.kind != KeyEventKind::Release {
+                self.help = false;
+            }
+            return Ok(Flow::Continue);
+        }
+        if self.palette.visible {
+            if key.kind == KeyEventKind::Release {
+                return Ok(Flow::Continue);
+            }
+            return Ok(match self.palette.handle_key(key) {
+                Some(action) => self.perform(manager, &options.keymap, action),
+                None => Flow::Continue,
+            });
+        }
+        if manager.display_panes_key(key) {
+            return Ok(Flow::Continue);
+        }
+
+        if let Some(on_key) = &mut options.on_key {
+            match on_key(manager, key) {
+                Flow::Continue => {}
+                Flow::Consumed => return Ok(Flow::Continue),
+                Flow::Quit => return Ok(Flow::Quit),
+            }
+        }
+
+        let action = options
+            .keymap
+            .action(key)
+            .filter(|_| key.kind != KeyEventKind::Release);
+        if let Some(action) = action {
+            self.last_ctrl_c = None;
+            let repeat = key.kind == KeyEventKind::Repeat;
+            let action = self
+                .accelerator
+                .accelerate(action.clone(), repeat, Instant::now());
+            return Ok(self.perform(manager, &options.keymap, action));
+        }
+
+        let is_ctrl_c =
+            key.code == KeyCode::Char('c') && key.modifiers.contains(KeyModifiers::CONTROL);
+        if options.confirm_exit && is_ctrl_c {
+            let now = Instant::now();
+            if self
+                .last_ctrl_c
+                .is_some_and(|last| now.duration_since(last) < CTRL_C_WINDOW)
+            {
+                self.dialog.show();
+                self.last_ctrl_c = None;
+                return Ok(Flow::Continue);
+            }
+            self.last_ctrl_c = Some(now);
+        } else {
+            self.last_ctrl_c = None;
+        }
+
+        // Panes may have exited; input to them is dropped
+        match manager.route_key(key).await {
+            Ok(()) | Err(Error::PaneClosed | Error::PaneNotFound(_) | Error::InputSend) => {}
+            Err(e) => return Err(e),
+        }
+        Ok(Flow::Continue)
+    }
+}
+
+impl LoopState {
+    /// Carry out an action from a keybinding or the command palette.
+    fn perform(&mut self, manager: &mut PaneManager, keymap: &KeyMap, action: Action) -> Flow {
+        match action {
+            Action::Quit => return Flow::Quit,
+            Action::FocusNext => manager.focus_next(),
+            Action::FocusPrev => manager.focus_prev(),
+            Action::DisplayPanes => manager.display_panes(),
+            Action::Resize(direction, cells) => {
+                manager.resize_focused(direction, cells);
+            }
+            Action::CommandPalette => self.palette.open(palette_actions(manager, keymap)),
+            Action::Help => self.help = true,
+            Action::RestartPane => {
+                let dead = manager
+                    .focused()
+                    .filter(|id| manager.get_pane(*id).is_some_and(|pane| !pane.is_alive()));
+                if let Some(pane_id) = dead {
+                    if let Err(e) = manager.restart_pane(pane_id) {
+                        tracing::warn!("Failed to restart pane {}: {}", pane_id, e);
+                    }
+                }
+            }
+            Action::SpawnProfile(name) => {
+                if let Err(e) = manager.spawn_profile(&name) {
+                    tracing::warn!("Failed to spawn profile {}: {}", name, e);
+                }
+            }
+        }
+        Flow::Continue
+    }
+}
+
+/// Actions offered by the command palette: the basic ones, those bound to
+/// keys, and spawning each registered profile.
+fn palette_actions(manager: &PaneManager, keymap: &KeyMap) -> Vec<Action> {
+    let bound = keymap
+        .bindings()
+        .map(|(_, action)| action.clone())
+        .filter(|action| *action != Action::CommandPalette);
+    let profiles = manager
+        .profiles()
+        .names()
+        .map(|name| Action::SpawnProfile(name.to_string()));
+    [Action::FocusNext, Action::FocusPrev]
+        .into_iter()
+        .chain(bound)
+        .chain(profiles)
+        .chain([Action::Quit])
+        .collect()
+}
+
+/// Puts the terminal into full-screen mode and restores it when dropped.
+struct TerminalGuard {
+    mouse: bool,
+}
+
+impl TerminalGuard {
+    fn enter(mouse: bool) -> Result<Self> {
+        enable_raw_mode().map_err(Error::Terminal)?;
+        // Restore raw mode if the rest of the setup fails
+        let guard = Self { mouse };
+        execute!(io::stdout(), EnterAlternateScreen).map_err(Error::Terminal)?;
+        if mouse {
+            execute!(io::stdout(), EnableMouseCapture).map_err(Error::Terminal)?;
+        }
+        Ok(guard)
+    }
+}
+
+impl Drop for TerminalGuard {
+    fn drop(&mut self) {
+        let mut stdout = io::stdout();
+        if self.mouse {
+            let _ = execute!(stdout, DisableMouseCapture);
+        }
+        let _ = execute!(stdout, LeaveAlternateScreen);
+        let _ = disable_raw_mode();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::manager::ManagerConfig;
+    use crate::mock::MockBackend;
+    use crate::pane::SpawnConfig;
+    use crate::profiles::ProfileRegistry;
+
+    struct Driver {
+        manager: PaneManager,
+        options: RunOptions,
+        state: LoopState,
+    }
+
+    impl Driver {
+        async fn key(&mut self, code: KeyCode, modifiers: KeyModifiers) -> Flow {
+            let event = Event::Key(KeyEvent::new(code, modifiers));
+            self.state
+                .handle_event(&mut self.manager, &mut self.options, event)
+                .await
+                .unwrap()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_default_bindings() {
+        let backend = MockBackend::new();
+        let mut manager = PaneManager::with_mock_backend(ManagerConfig::default(), backend.clone());
+        let first = manager.spawn(SpawnConfig::new_shell()).unwrap().id();
+        let second = manager.spawn(SpawnConfig::new_shell()).unwrap().id();
+        let options = RunOptions::new().on_key(|_, key| {
+            if key.code == KeyCode::F(1) {
+                Flow::Consumed
+            } else {
+                Flow::Continue
+            }
+        });
+        let mut driver = Driver {
+            manager,
+            options,
+            state: LoopState::default(),
+        };
+
+        // Plain keys and a single Ctrl+C reach the focused pane
+        let flow = driver.key(KeyCode::Char('a'), KeyModifiers::NONE).await;
+        assert_eq!(flow, Flow::Continue);
+        driver.key(KeyCode::F(1), KeyModifiers::NONE).await;
+        driver.key(KeyCode::Char('c'), KeyModifiers::CONTROL).await;
+        assert!(!driver.state.dialog.visible);
+
+        // A second Ctrl+C opens the dialog, which then takes the keys
+        driver.key(KeyCode::Char('c'), KeyModifiers::CONTROL).await;
+        assert!(driver.state.dialog.visible);
+        driver.key(KeyCode::Esc, KeyModifiers::NONE).await;
+        assert!(!driver.state.dialog.visible);
+
+        driver.key(KeyCode::Char('n'), KeyModifiers::CONTROL).await;
+        assert_eq!(driver.manager.focused(), Some(second));
+        let flow = driver.key(KeyCode::Char('q'), KeyModifiers::CONTROL).await;
+        assert_eq!(flow, Flow::Quit);
+
+        let pty = backend.pane(first).unwrap();
+        tokio::time::timeout(Duration::from_secs(5), async {
+            while pty.input().len() < 2 {
+                tokio::time::sleep(Duration::from_millis(1)).await;
+            }
+        })
+        .await
+        .unwrap();
+        assert_eq!(pty.input(), b"a\x03");
+    }
+
+    #[tokio::test]
+    async fn test_resize_bindings() {
+        let mut manager =
+            PaneManager::with_mock_backend(ManagerConfig::default(), MockBackend::new());
+        manager.set_terminal_size(Rect::new(0, 0, 80, 20));
+        let pane = manager.spawn(SpawnConfig::new_shell()).unwrap().id();
+        let mut driver = Driver {
+            manager,
+            options: RunOptions::new().quit_key(None),
+            state: LoopState::default(),
+        };
+
+        // Holding the key moves the border faster: 5 steps of 1, then of 2
+        for _ in 0..10 {
+            let flow = driver
+                .key(KeyCode::Right, KeyModifiers::ALT | KeyModifiers::SHIFT)
+                .await;
+            assert_eq!(flow, Flow::Continue);
+        }
+        assert_eq!(driver.manager.get_areas()[&pane].width, 35);
+
+        let flow = driver.key(KeyCode::Char('q'), KeyModifiers::CONTROL).await;
+        assert_eq!(flow, Flow::Continue);
+    }
+
+    #[tokio::test]
+    async fn test_command_palette() {
+        let profiles = ProfileRegistry::new().with("logs", SpawnConfig::new_command("tail"));
+        let manager = PaneManager::with_mock_backend(ManagerConfig::default(), MockBackend::new())
+            .with_profiles(profiles);
+        let mut driver = Driver {
+            manager,
+            options: RunOptions::new(),
+            state: LoopState::default(),
+        };
+
+        driver.key(KeyCode::Char('p'), KeyModifiers::CONTROL).await;
+        assert!(driver.state.palette.visible);
+        assert!(driver
+            .state
+            .palette
+            .matches()
+            .contains(&&Action::SpawnProfile("logs".to_string())));
+
+        // Typing filters the actions; Enter runs the selected one
+        for c in "spawn".chars() {
+            driver.key(KeyCode::Char(c), KeyModifiers::NONE).await;
+        }
+        assert_eq!(
+            driver.state.palette.matches()[0],
+            &Action::SpawnProfile("logs".to_string())
+        );
+        let flow = driver.key(KeyCode::Enter, KeyModifiers::NONE).await;
+        assert_eq!(flow, Flow::Continue);
+        assert!(!driver.state.palette.visible);
+        assert_eq!(driver.manager.pane_count(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_help_overlay() {
+        let manager = PaneManager::with_mock_backend(ManagerConfig::default(), MockBackend::new());
+        let mut driver = Driver {
+            manager,
+            options: RunOptions::new(),
+            state: LoopState::default(),
+        };
+
+        driver.key(KeyCode::F(1), KeyModifiers::NONE).await;
+        assert!(driver.state.help);
+        // The key that dismisses the overlay does nothing else
+        let flow = driver.key(KeyCode::Char('q'), KeyModifiers::CONTROL).await;
+        assert_eq!(flow, Flow::Continue);
+        assert!(!driver.state.help);
+    }
+
+    #[tokio::test]
+    async fn test_restart_pane() {
+        let backend = MockBackend::new();
+        let mut manager = PaneManager::with_mock_backend(ManagerConfig::default(), backend.clone());
+        let server = manager
+            .spawn(SpawnConfig::new_command("server"))
+            .unwrap()
+            .id();
+        let mut driver = Driver {
+            manager,
+            options: RunOptions::new(),
+            state: LoopState::default(),
+        };
+
+        // A running pane is left alone
+        driver.key(KeyCode::Char('r'), KeyModifiers::ALT).await;
+        assert_eq!(driver.manager.pane_ids(), [server]);
+
+        backend.pane(server).unwrap().exit(1);
+        driver.manager.get_pane(server).unwrap().wait_exit().await;
+        driver.key(KeyCode::Char('r'), KeyModifiers::ALT).await;
+        let restarted = driver.manager.focused().unwrap();
+        assert_ne!(restarted, server);
+        assert_eq!(driver.manager.pane_ids(), [restarted]);
+        assert_eq!(backend.pane(restarted).unwrap().command(), "server");
+    }
+
+    #[tokio::test]
+    async fn test_display_panes() {
+        let mut manager =
+            PaneManager::with_mock_backend(ManagerConfig::default(), MockBackend::new());
+        manager.set_terminal_size(Rect::new(0, 0, 80, 20));
+        let first = manager.spawn(SpawnConfig::new_shell()).unwrap().id();
+        let second = manager.spawn(SpawnConfig::new_shell()).unwrap().id();
+        let mut driver = Driver {
+            manager,
+            options: RunOptions::new(),
+            state: LoopState::default(),
+        };
+
+        driver.key(KeyCode::Char('q'), KeyModifiers::ALT).await;
+        assert!(driver.manager.displaying_panes());
+        let numbered: Vec<_> = driver
+            .manager
+            .numbered_panes()
+            .into_iter()
+            .map(|(id, _)| id)
+            .collect();
+        assert_eq!(numbered, [first, second]);
+
+        // The number key is not sent to the pane
+        driver.key(KeyCode::Char('2'), KeyModifiers::NONE).await;
+        assert!(!driver.manager.displaying_panes());
+        assert_eq!(driver.manager.focused(), Some(second));
+
+        // Other keys, or missing panes, just hide the numbers
+        driver.key(KeyCode::Char('q'), KeyModifiers::ALT).await;
+        driver.key(KeyCode::Char('7'), KeyModifiers::NONE).await;
+        assert!(!driver.manager.displaying_panes());
+        assert_eq!(driver.manager.focused(), Some(second));
+    }
+}
@@ -0,0 +1,189 @@
+//! Searching a pane's screen and scrollback.
+//!
+//! [`PaneHandle::search`](crate::PaneHandle::search) finds every match of a
+//! [`Pattern`] in the lines a pane can show. The widgets highlight the
+//! matches, the current one in its own style, and show the current match's
+//! number in the pane's border.
+
+use crate::transcript::Pattern;
+
+/// Text matching a search, on a single row.
+///
+/// Rows count like [`Selection`](crate::Selection) rows: from the top of
+/// the live screen, negative in scrollback.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SearchMatch {
+    /// Row of the match.
+    pub row: i32,
+    /// First column of the match.
+    pub start: u16,
+    /// Last column of the match. Inclusive.
+    pub end: u16,
+}
+
+impl SearchMatch {
+    /// Whether the cell at `row`, `col` is part of the match.
+    #[must_use]
+    pub fn contains(&self, row: i32, col: u16) -> bool {
+        row == self.row && (self.start..=self.end).contains(&col)
+    }
+}
+
+/// Matches of a search in a pane, in reading order, and which one is
+/// current.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Search {
+    /// All matches, top to bottom.
+    pub matches: Vec<SearchMatch>,
+    /// Index of the current match in `matches`.
+    pub current: usize,
+}
+
+impl Search {
+    /// The current match, if anything matched.
+    #[must_use]
+    pub fn current_match(&self) -> Option<&SearchMatch> {
+        self.matches.get(self.current)
+    }
+
+    /// Make the match below the current one current, wrapping to the first.
+    pub fn next(&mut self) {
+        if !self.matches.is_empty() {
+            self.current = (self.current + 1) % self.matches.len();
+        }
+    }
+
+    /// Make the match above the current one current, wrapping to the last.
+    pub fn prev(&mut self) {
+        if !self.matches.is_empty() {
+            self.current = (self.current + self.matches.len() - 1) % self.matches.len();
+        }
+    }
+
+    /// Search the scrollback the parser can show and its screen, starting
+    /// on the match closest to the bottom.
+    ///
+    /// Matches do not span rows. The parser is scrolled to reach rows in
+    /// scrollback, then scrolled back.
+    pub(crate) fn find(parser: &mut vt100::Parser, pattern: &Pattern) -> Self {
+        let offset = parser.screen().scrollback();
+        let (rows, _) = parser.screen().size();
+        // vt100 shows at most a screen height of scrollback
+        parser.set_scrollback(usize::from(rows));
+        let reach = i32::try_from(parser.screen().scrollback()).unwrap_or(0);
+        let mut matches = Vec::new();
+        for row in -reach..i32::from(rows) {
+            parser.set_scrollback(usize::try_from(-row).unwrap_or(0));
+            let visible = u16::try_from(row.max(0)).unwrap_or(0);
+            find_in_row(parser.screen(), visible, row, pattern, &mut matches);
+        }
+        parser.set_scrollback(offset);
+        Self {
+            current: matches.len().saturating_sub(1),
+            matches,
+        }
+    }
+}
+
+/// Add the matches on row `visible` of the screen, reported as `row`.
+fn find_in_row(
+    screen: &vt100::Screen,
+    visible: u16,
+    row: i32,
+    pattern: &Pattern,
+    matches: &mut Vec<SearchMatch>,
+) {
+    let (_, cols) = screen.size();
+    // The row's text, and the column each byte of it is drawn in
+    let mut text = String::new();
+    let mut columns = Vec::new();
+    for col in 0..cols {
+        let Some(cell) = screen.cell(visible, col) else {
+            break;
+        };
+        if cell.is_wide_continuation() {
+            continue;
+        }
+        let contents = if cell.has_contents() {
+            cell.contents()
+        } else {
+            " ".to_string()
+        };
+        columns.extend(std::iter::repeat_n(col, contents.len()));
+        text.push_str(&contents);
+    }
+
+    let mut searched = 0;
+    while searched < text.len() {
+        let Some(range) = pattern.find_range(&text[searched..]) else {
+            break;
+        };
+        let (start, end) = (searched + range.start, searched + range.end);
+        if end > start {
+            let last = columns[end - 1];
+            let wide = screen.cell(visible, last).is_some_and(vt100::Cell::is_wide);
+            matches.push(SearchMatch {
+                row,
+                start: columns[start],
+                end: last + u16::from(wide),
+            });
+        }
+        // Step over empty matches
+        let step = text[end..].chars().next().map_or(1, char::len_utf8);
+        searched = if end > start { end } else { end + step };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_matches() {
+        let mut parser = vt100::Parser::new(3, 20, 100);
+        parser.process(b"error: one\r\nok\r\n\xe5\xad\x97error\r\nerror error\r\ndone");
+        let mut search = Search::find(&mut parser, &Pattern::from("error"));
+        assert_eq!(
+            search.matches,
+            [
+                SearchMatch {
+                    row: -2,
+                    start: 0,
+                    end: 4
+                },
+                SearchMatch {
+                    row: 0,
+                    start: 2,
+                    end: 6
+                },
+                SearchMatch {
+                    row: 1,
+                    start: 0,
+                    end: 4
+                },
+                SearchMatch {
+                    row: 1,
+                    start: 6,
+                    end: 10
+                },
+            ]
+        );
+        assert_eq!(parser.screen().scrollback(), 0);
+
+        // The search starts at the bottom and wraps around
+        assert_eq!(search.current, 3);
+        search.next();
+        assert_eq!(search.current_match().map(|m| m.row), Some(-2));
+        search.prev();
+        search.prev();
+        assert_eq!(search.current, 2);
+
+        let wide = Search::find(&mut parser, &Pattern::regex("字e").unwrap());
+        assert_eq!(wide.matches[0].end, 2);
+        assert!(Search::find(&mut parser, &Pattern::regex("x*").unwrap())
+            .matches
+            .is_empty());
+    }
+}
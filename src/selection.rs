@@ -0,0 +1,282 @@
+//! Text selection inside a pane, made with the mouse.
+//!
+//! Dragging selects a range of cells, scrolling into scrollback at the
+//! pane's edges. A double click selects the word under the pointer and a
+//! triple click the whole line. Selected text can be copied to the host
+//! terminal's clipboard with OSC 52.
+
+use std::time::{Duration, Instant};
+
+/// Clicks closer together than this count as one double or triple click.
+pub(crate) const MULTI_CLICK_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Characters that end a word for double-click selection, besides blanks.
+///
+/// Dots, slashes and colons are not among them, so paths and URLs are
+/// selected whole.
+const WORD_DELIMITERS: &str = "\"'`()[]{}<>|,;";
+
+/// What a selection was made of.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum SelectionMode {
+    /// Individual cells.
+    #[default]
+    Cell,
+    /// Whole words, from a double click.
+    Word,
+    /// Whole lines, from a triple click. Lines that wrapped are selected
+    /// together with their continuation.
+    Line,
+}
+
+/// A range of cells on a pane's screen, in reading order.
+///
+/// Rows count from the top of the live screen, as shown when the pane is
+/// not scrolled back; rows in scrollback are negative.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Selection {
+    /// First selected cell, as (row, column).
+    pub start: (i32, u16),
+    /// Last selected cell, as (row, column). Inclusive.
+    pub end: (i32, u16),
+    /// How the selection was made.
+    pub mode: SelectionMode,
+}
+
+impl Selection {
+    /// Selection between two cells, given in either order.
+    #[must_use]
+    pub fn new(anchor: (i32, u16), head: (i32, u16), mode: SelectionMode) -> Self {
+        let (start, end) = if anchor <= head {
+            (anchor, head)
+        } else {
+            (head, anchor)
+        };
+        Self { start, end, mode }
+    }
+
+    /// Whether the cell at `row`, `col` is selected.
+    #[must_use]
+    pub fn contains(&self, row: i32, col: u16) -> bool {
+        (self.start..=self.end).contains(&(row, col))
+    }
+
+    /// Select the word at `row`, `col` of the visible screen: a run of
+    /// word characters, or of blanks. A delimiter such as a bracket is
+    /// selected on its own.
+    pub(crate) fn word_at(screen: &vt100::Screen, row: u16, col: u16) -> Self {
+        let (_, cols) = screen.size();
+        let class = char_class(screen, row, col);
+        let (mut first, mut last) = (col, col);
+        if class != CharClass::Delimiter {
+            while first > 0 && char_class(screen, row, first - 1) == class {
+                first -= 1;
+            }
+            while last + 1 < cols && char_class(screen, row, last + 1) == class {
+                last += 1;
+            }
+        }
+        let row = selection_row(screen.scrollback(), row);
+        Self::new((row, first), (row, last), SelectionMode::Word)
+    }
+
+    /// Select the line at `row` of the visible screen, following soft
+    /// wraps in both directions.
+    pub(crate) fn line_at(screen: &vt100::Screen, row: u16) -> Self {
+        let (rows, cols) = screen.size();
+        let mut first = row;
+        while first > 0 && screen.row_wrapped(first - 1) {
+            first -= 1;
+        }
+        let mut last = row;
+        while last + 1 < rows && screen.row_wrapped(last) {
+            last += 1;
+        }
+        Self::new(
+            (selection_row(screen.scrollback(), first), 0),
+            (
+                selection_row(screen.scrollback(), last),
+                cols.saturating_sub(1),
+            ),
+            SelectionMode::Line,
+        )
+    }
+
+    /// The selected text. Trailing blanks are dropped from each line, and
+    /// lines are joined with `\n` unless they wrapped.
+    ///
+    /// The parser is scrolled to reach rows in scrollback, then scrolled
+    /// back. Rows that have left the scrollback are skipped.
+    pub(crate) fn text(&self, parser: &mut vt100::Parser) -> String {
+        let offset = parser.screen().scrollback();
+        let (rows, cols) = parser.screen().size();
+        let mut text = String::new();
+        for row in self.start.0..=self.end.0 {
+            let Ok(visible) = u16::try_from(row.max(0)) else {
+                break;
+            };
+            if visible >= rows {
+                break;
+            }
+            // A row in scrollback is shown at the top when scrolled back by
+            // as many lines
+            let scroll = usize::try_from(-row).unwrap_or(0);
+            parser.set_scrollback(scroll);
+            let screen = parser.screen();
+            if screen.scrollback() != scroll {
+                continue;
+            }
+            let first = if row == self.start.0 { self.start.1 } else { 0 };
+            let last = if row == self.end.0 {
+                (self.end.1 + 1).min(cols)
+            } else {
+                cols
+            };
+            if first < last {
+                text.extend(screen.rows(first, last - first).nth(usize::from(visible)));
+            }
+            if row != self.end.0 && !screen.row_wrapped(visible) {
+                text.push('\n');
+            }
+        }
+        parser.set_scrollback(offset);
+        text
+    }
+}
+
+/// Selection row of a row on the visible screen, scrolled back `offset`
+/// lines.
+pub(crate) fn selection_row(offset: usize, row: u16) -> i32 {
+    i32::from(row) - i32::try_from(offset).unwrap_or(i32::MAX)
+}
+
+/// Character classes that make up words for double-click selection.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum CharClass {
+    Blank,
+    Word,
+    Delimiter,
+}
+
+fn char_class(screen: &vt100::Screen, row: u16, col: u16) -> CharClass {
+    let Some(cell) = screen.cell(row, col) else {
+        return CharClass::Blank;
+    };
+    if cell.is_wide_continuation() {
+        // The right half of a wide character belongs with its left half
+        return if col > 0 {
+            char_class(screen, row, col - 1)
+        } else {
+            CharClass::Word
+        };
+    }
+    let contents = cell.contents();
+    match contents.chars().next() {
+        None => CharClass::Blank,
+        Some(c) if c.is_whitespace() => CharClass::Blank,
+        Some(c) if WORD_DELIMITERS.contains(c) => CharClass::Delimiter,
+        Some(_) => CharClass::Word,
+    }
+}
+
+/// Counts repeated clicks on the same cell.
+#[derive(Debug, Default)]
+pub(crate) struct ClickCounter {
+    last: Option<(Instant, u16, u16)>,
+    count: u8,
+}
+
+impl ClickCounter {
+    /// Record a click at `x`, `y` and return how many clicks in a row it
+    /// makes: 1, 2 or 3. A fourth click starts over.
+    pub fn click(&mut self, x: u16, y: u16, now: Instant) -> u8 {
+        let repeated = self.last.is_some_and(|(at, last_x, last_y)| {
+            (last_x, last_y) == (x, y) && now.saturating_duration_since(at) < MULTI_CLICK_INTERVAL
+        });
+        self.count = if repeated { self.count % 3 + 1 } else { 1 };
+        self.last = Some((now, x, y));
+        self.count
+    }
+}
+
+/// OSC 52 sequence that asks the host terminal to put `text` on the
+/// clipboard.
+pub(crate) fn osc52(text: &str) -> Vec<u8> {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+    let mut output = b"\x1b]52;c;".to_vec();
+    for chunk in text.as_bytes().chunks(3) {
+        let b = [
+            chunk[0],
+            chunk.get(1).copied().unwrap_or(0),
+            chunk.get(2).copied().unwrap_or(0),
+        ];
+        let n = u32::from(b[0]) << 16 | u32::from(b[1]) << 8 | u32::from(b[2]);
+        for i in 0..4 {
+            if i <= chunk.len() {
+                output.push(ALPHABET[(n >> (18 - 6 * i) & 0x3f) as usize]);
+            } else {
+                output.push(b'=');
+            }
+        }
+    }
+    output.push(0x07);
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_word_and_line_selection() {
+        let mut parser = vt100::Parser::new(4, 10, 0);
+        parser.process(b"cat /tmp/a.txt\r\n(x) y");
+        let screen = parser.screen();
+
+        // The first line wraps at 10 columns
+        let word = Selection::word_at(screen, 0, 6);
+        let paren = Selection::word_at(screen, 2, 0);
+        let x = Selection::word_at(screen, 2, 1);
+        let line = Selection::line_at(screen, 1);
+        assert_eq!((word.start, word.end), ((0, 4), (0, 9)));
+        assert_eq!(word.text(&mut parser), "/tmp/a");
+        assert_eq!(paren.text(&mut parser), "(");
+        assert_eq!(x.text(&mut parser), "x");
+
+        assert_eq!((line.start, line.end), ((0, 0), (1, 9)));
+        assert_eq!(line.text(&mut parser), "cat /tmp/a.txt");
+        assert!(line.contains(1, 0) && !line.contains(2, 0));
+    }
+
+    #[test]
+    fn test_text_from_scrollback() {
+        let mut parser = vt100::Parser::new(2, 10, 10);
+        parser.process(b"one\r\ntwo\r\nthree\r\nfour");
+        parser.set_scrollback(1);
+        let selection = Selection::new((-2, 1), (0, 2), SelectionMode::Cell);
+        assert_eq!(selection.text(&mut parser), "ne\ntwo\nthr");
+        assert_eq!(parser.screen().scrollback(), 1);
+        assert_eq!(selection_row(parser.screen().scrollback(), 0), -1);
+    }
+
+    #[test]
+    fn test_click_counter() {
+        let mut counter = ClickCounter::default();
+        let now = Instant::now();
+        assert_eq!(counter.click(1, 1, now), 1);
+        assert_eq!(counter.click(1, 1, now), 2);
+        assert_eq!(counter.click(1, 1, now), 3);
+        assert_eq!(counter.click(1, 1, now), 1);
+        assert_eq!(counter.click(2, 1, now), 1);
+        assert_eq!(counter.click(2, 1, now + MULTI_CLICK_INTERVAL), 1);
+    }
+
+    #[test]
+    fn test_osc52() {
+        assert_eq!(osc52("hi!"), b"\x1b]52;c;aGkh\x07");
+        assert_eq!(osc52("hi"), b"\x1b]52;c;aGk=\x07");
+    }
+}
@@ -0,0 +1,533 @@
+//! Escape sequence scanning for features the vt100 emulator ignores.
+//!
+//! The reader task feeds every chunk of PTY output through a
+//! [`SequenceScanner`], which extracts complete CSI, OSC, DCS and APC
+//! sequences (even when they are split across reads). The pane then
+//! interprets the few it cares about, such as keyboard protocol
+//! negotiation, and tracks the result in [`ExtendedState`].
+
+use std::path::PathBuf;
+
+use crate::graphics::InlineImage;
+use crate::keys::KeyboardProtocol;
+use crate::pane::{CursorShape, CursorStyle};
+
+/// Upper bound for a CSI sequence; longer ones are discarded.
+const MAX_CSI_LEN: usize = 64;
+
+/// Upper bound for string sequences (OSC/DCS/APC); longer ones are discarded.
+/// Large enough for typical inline images.
+const MAX_STRING_LEN: usize = 4 << 20;
+
+/// Maximum depth of the kitty keyboard flag stack.
+const MAX_KITTY_STACK: usize = 16;
+
+/// Maximum number of inline images waiting to be forwarded.
+const MAX_PENDING_IMAGES: usize = 32;
+
+/// Kind of a scanned escape sequence.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum SequenceKind {
+    /// Control Sequence Introducer (`ESC [`).
+    Csi,
+    /// Operating System Command (`ESC ]`).
+    Osc,
+    /// Device Control String (`ESC P`).
+    Dcs,
+    /// Application Program Command (`ESC _`).
+    Apc,
+}
+
+/// A complete escape sequence extracted from PTY output.
+#[derive(Clone, Debug)]
+pub(crate) struct Sequence {
+    /// Sequence kind.
+    pub kind: SequenceKind,
+    /// The raw bytes, including introducer and terminator.
+    pub raw: Vec<u8>,
+    /// Offset just past the terminator in the chunk that completed it.
+    pub end: usize,
+}
+
+impl Sequence {
+    /// Bytes between the introducer and the terminator.
+    ///
+    /// For CSI sequences this is the parameter and intermediate bytes,
+    /// without the final byte.
+    pub fn body(&self) -> &[u8] {
+        let start = 2;
+        let end = match self.kind {
+            SequenceKind::Csi => self.raw.len() - 1,
+            _ if self.raw.ends_with(b"\x1b\\") => self.raw.len() - 2,
+            _ => self.raw.len() - 1, // BEL terminated
+        };
+        &self.raw[start.min(end)..end]
+    }
+
+    /// Final byte of a CSI sequence.
+    pub fn final_byte(&self) -> u8 {
+        self.raw.last().copied().unwrap_or(0)
+    }
+
+    /// Whether this is a CSI sequence with the given private marker and final byte.
+    pub fn is_csi(&self, marker: Option<u8>, final_byte: u8) -> bool {
+        self.kind == SequenceKind::Csi
+            && self.final_byte() == final_byte
+            && self
+                .body()
+                .first()
+                .copied()
+                .filter(|b| (0x3c..=0x3f).contains(b))
+                == marker
+    }
+
+    /// Numeric CSI parameters, skipping any private marker.
+    ///
+    /// Empty parameters are reported as `None`.
+    pub fn params(&self) -> Vec<Option<u32>> {
+        let body = self.body();
+        let body = match body.first() {
+            Some(0x3c..=0x3f) => &body[1..],
+            _ => body,
+        };
+        let digits_end = body
+            .iter()
+            .position(|b| !(b.is_ascii_digit() || *b == b';' || *b == b':'))
+            .unwrap_or(body.len());
+        body[..digits_end]
+            .split(|&b| b == b';')
+            .map(|p| {
+                std::str::from_utf8(p)
+                    .ok()
+                    .and_then(|s| s.split(':').next())
+                    .and_then(|s| s.parse().ok())
+            })
+            .collect()
+    }
+}
+
+/// Scanner state between calls to [`SequenceScanner::feed`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum State {
+    Ground,
+    Escape,
+    Csi,
+    String(SequenceKind),
+    StringEscape(SequenceKind),
+}
+
+/// Incremental scanner for escape sequences in a byte stream.
+#[derive(Debug)]
+pub(crate) struct SequenceScanner {
+    state: State,
+    buf: Vec<u8>,
+}
+
+impl SequenceScanner {
+    /// Create a new scanner.
+    pub fn new() -> Self {
+        Self {
+            state: State::Ground,
+            buf: Vec::new(),
+        }
+    }
+
+    /// Feed a chunk of output, returning all sequences completed in it.
+    pub fn feed(&mut self, data: &[u8]) -> Vec<Sequence> {
+        let mut sequences = Vec::new();
+
+        for (i, &byte) in data.iter().enumerate() {
+            match self.state {
+                State::Ground => {
+                    if byte == 0x1b {
+                        self.buf.clear();
+                        self.buf.push(byte);
+                        self.state = State::Escape;
+                    }
+                }
+                State::Escape => {
+                    self.buf.push(byte);
+                    self.state = match byte {
+                        b'[' => State::Csi,
+                        b']' => State::String(SequenceKind::Osc),
+                        b'P' => State::String(SequenceKind::Dcs),
+                        b'_' => State::String(SequenceKind::Apc),
+                        0x1b => {
+                            self.buf.clear();
+                            self.buf.push(byte);
+                            State::Escape
+                        }
+                        _ => State::Ground,
+                    };
+                }
+                State::Csi => {
+                    self.buf.push(byte);
+                    if (0x40..=0x7e).contains(&byte) {
+                        sequences.push(self.finish(SequenceKind::Csi, i + 1));
+                    } else if byte == 0x1b {
+                        self.buf.clear();
+                        self.buf.push(byte);
+                        self.state = State::Escape;
+                    } else if self.buf.len() > MAX_CSI_LEN {
+                        self.state = State::Ground;
+                    }
+                }
+                State::String(kind) => {
+                    self.buf.push(byte);
+                    if byte == 0x07 && kind == SequenceKind::Osc {
+                        sequences.push(self.finish(kind, i + 1));
+                    } else if byte == 0x1b {
+                        self.state = State::StringEscape(kind);
+                    } else if self.buf.len() > MAX_STRING_LEN {
+                        self.state = State::Ground;
+                    }
+                }
+                State::StringEscape(kind) => {
+                    self.buf.push(byte);
+                    if byte == b'\\' {
+                        sequences.push(self.finish(kind, i + 1));
+                    } else {
+                        // Any other escape aborts the string; reprocess it as a new
+                        // escape (a single byte can't complete a sequence)
+                        self.buf.clear();
+                        self.buf.push(0x1b);
+                        self.state = State::Escape;
+                        let _ = self.feed(&[byte]);
+                    }
+                }
+            }
+        }
+
+        sequences
+    }
+
+    fn finish(&mut self, kind: SequenceKind, end: usize) -> Sequence {
+        self.state = State::Ground;
+        Sequence {
+            kind,
+            raw: std::mem::take(&mut self.buf),
+            end,
+        }
+    }
+}
+
+/// Manager settings that control which sequences are tracked and forwarded.
+#[derive(Clone, Copy, Debug, Default)]
+pub(crate) struct TrackingOptions {
+    /// Keyboard protocol panes may negotiate.
+    pub keyboard_protocol: KeyboardProtocol,
+    /// Forward cursor shape changes (DECSCUSR) to the host terminal.
+    pub forward_cursor_shape: bool,
+    /// Keep inline images for forwarding instead of drawing a placeholder.
+    pub graphics_passthrough: bool,
+}
+
+/// Keyboard protocol state negotiated by a pane's program.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub(crate) struct KeyboardState {
+    /// Kitty keyboard protocol flag stack (last = active flags).
+    pub kitty_flags: Vec<u8>,
+    /// xterm `modifyOtherKeys` level (0 = disabled).
+    pub modify_other_keys: u8,
+}
+
+impl KeyboardState {
+    /// Sequences that take a terminal in this state to `target`.
+    ///
+    /// Kitty flags above the common base of both stacks are popped, then
+    /// the rest of `target`'s stack is pushed.
+    pub fn transition_to(&self, target: &Self) -> Vec<u8> {
+        let common = self
+            .kitty_flags
+            .iter()
+            .zip(&target.kitty_flags)
+            .take_while(|(current, wanted)| current == wanted)
+            .count();
+        let mut output = Vec::new();
+        let pops = self.kitty_flags.len() - common;
+        if pops > 0 {
+            output.extend(format!("\x1b[<{pops}u").into_bytes());
+        }
+        for flags in &target.kitty_flags[common..] {
+            output.extend(format!("\x1b[>{flags}u").into_bytes());
+        }
+        if self.modify_other_keys != target.modify_other_keys {
+            output.extend(format!("\x1b[>4;{}m", target.modify_other_keys).into_bytes());
+        }
+        output
+    }
+}
+
+/// Pane terminal state that is tracked outside of the vt100 emulator.
+#[derive(Debug, Default)]
+pub(crate) struct ExtendedState {
+    /// Cursor style requested via DECSCUSR.
+    pub cursor_style: CursorStyle,
+    /// Kitty keyboard protocol flag stack (last = active flags).
+    pub kitty_flags: Vec<u8>,
+    /// xterm `modifyOtherKeys` level (0 = disabled).
+    pub modify_other_keys: u8,
+    /// Bytes that should be forwarded verbatim to the host terminal.
+    ///
+    /// Keyboard protocol requests are not forwarded; the manager brings
+    /// the host in line with the focused pane's [`KeyboardState`] instead.
+    pub passthrough: Vec<u8>,
+    /// Inline images waiting to be forwarded to the host terminal.
+    pub images: Vec<InlineImage>,
+    /// Working directory reported by the shell via OSC 7.
+    pub cwd: Option<PathBuf>,
+}
+
+impl ExtendedState {
+    /// Queue an inline image, dropping the oldest if too many are pending.
+    pub fn push_image(&mut self, image: InlineImage) {
+        if self.images.len() >= MAX_PENDING_IMAGES {
+            self.images.remove(0);
+        }
+        self.images.push(image);
+    }
+
+    /// Currently active kitty keyboard flags.
+    pub fn active_kitty_flags(&self) -> u8 {
+        self.kitty_flags.last().copied().unwrap_or(0)
+    }
+
+    /// The negotiated keyboard protocol state.
+    pub fn keyboard(&self) -> KeyboardState {
+        KeyboardState {
+            kitty_flags: self.kitty_flags.clone(),
+            modify_other_keys: self.modify_other_keys,
+        }
+    }
+
+    /// Apply a scanned sequence to the tracked state.
+    ///
+    /// Returns a reply that should be written back to the pane's PTY,
+    /// if the sequence was a query.
+    pub fn apply(&mut self, seq: &Sequence, options: TrackingOptions) -> Option<Vec<u8>> {
+        if seq.kind == SequenceKind::Osc {
+            if let Some(cwd) = parse_osc7(seq.body()) {
+                self.cwd = Some(cwd);
+            }
+            return None;
+        }
+        if seq.kind != SequenceKind::Csi {
+            return None;
+        }
+        if seq.final_byte() == b'q' && seq.body().ends_with(b" ") {
+            self.apply_cursor_style(seq, options.forward_cursor_shape);
+            return None;
+        }
+        match options.keyboard_protocol {
+            KeyboardProtocol::Legacy => None,
+            KeyboardProtocol::Kitty => self.apply_kitty(seq),
+            KeyboardProtocol::ModifyOtherKeys => {
+                self.apply_modify_other_keys(seq);
+                None
+            }
+        }
+    }
+
+    /// Track DECSCUSR (`CSI Ps SP q`) cursor style changes.
+    fn apply_cursor_style(&mut self, seq: &Sequence, forward: bool) {
+        let (shape, blinking) = match seq.params().first().copied().flatten().unwrap_or(0) {
+            0 | 1 => (CursorShape::Block, true),
+            2 => (CursorShape::Block, false),
+            3 => (CursorShape::Underline, true),
+            4 => (CursorShape::Underline, false),
+            5 => (CursorShape::Bar, true),
+            6 => (CursorShape::Bar, false),
+            _ => return,
+        };
+        self.cursor_style = CursorStyle { shape, blinking };
+        if forward {
+            self.passthrough.extend_from_slice(&seq.raw);
+        }
+    }
+
+    /// Track kitty keyboard protocol push/pop/set/query requests.
+    #[allow(clippy::cast_possible_truncation)]
+    fn apply_kitty(&mut self, seq: &Sequence) -> Option<Vec<u8>> {
+        let first = |default: u32| seq.params().first().copied().flatten().unwrap_or(default);
+
+        if seq.is_csi(Some(b'?'), b'u') {
+            return Some(format!("\x1b[?{}u", self.active_kitty_flags()).into_bytes());
+        }
+        if seq.is_csi(Some(b'>'), b'u') {
+            if self.kitty_flags.len() >= MAX_KITTY_STACK {
+                self.kitty_flags.remove(0);
+            }
+            self.kitty_flags.push(first(0) as u8);
+        } else if seq.is_csi(Some(b'<'), b'u') {
+            let count = first(1) as usize;
+            let keep = self.kitty_flags.len().saturating_sub(count);
+            self.kitty_flags.truncate(keep);
+        } else if seq.is_csi(Some(b'='), b'u') {
+            let params = seq.params();
+            let flags = params.first().copied().flatten().unwrap_or(0) as u8;
+            let mode = params.get(1).copied().flatten().unwrap_or(1);
+            let current = self.active_kitty_flags();
+            let updated = match mode {
+                2 => current | flags,
+                3 => current & !flags,
+                _ => flags,
+            };
+            match self.kitty_flags.last_mut() {
+                Some(top) => *top = updated,
+                None => self.kitty_flags.push(updated),
+            }
+        }
+        None
+    }
+
+    /// Track xterm `modifyOtherKeys` requests (`CSI > 4 ; level m`).
+    #[allow(clippy::cast_possible_truncation)]
+    fn apply_modify_other_keys(&mut self, seq: &Sequence) {
+        if !seq.is_csi(Some(b'>'), b'm') {
+            return;
+        }
+        let params = seq.params();
+        if params.first().copied().flatten() != Some(4) {
+            return;
+        }
+        self.modify_other_keys = params.get(1).copied().flatten().unwrap_or(0).min(2) as u8;
+    }
+}
+
+/// Parse an OSC 7 working directory report (`7;file://host/path`).
+///
+/// The path is percent-decoded; the host is ignored.
+fn parse_osc7(body: &[u8]) -> Option<PathBuf> {
+    let url = body.strip_prefix(b"7;")?;
+    let rest = url
+        .strip_prefix(b"file://")
+        .or_else(|| url.strip_prefix(b"kitty-shell-cwd://"))?;
+    let path = &rest[rest.iter().position(|&b| b == b'/')?..];
+
+    let mut decoded = Vec::with_capacity(path.len());
+    let mut bytes = path.iter();
+    while let Some(&b) = bytes.next() {
+        if b == b'%' {
+            let hex = [*bytes.next()?, *bytes.next()?];
+            let hex = std::str::from_utf8(&hex).ok()?;
+            decoded.push(u8::from_str_radix(hex, 16).ok()?);
+        } else {
+            decoded.push(b);
+        }
+    }
+    String::from_utf8(decoded).ok().map(PathBuf::from)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scans_csi_split_across_reads() {
+        let mut scanner = SequenceScanner::new();
+        assert!(scanner.feed(b"hello \x1b[>").is_empty());
+        let seqs = scanner.feed(b"1u world");
+        assert_eq!(seqs.len(), 1);
+        assert!(seqs[0].is_csi(Some(b'>'), b'u'));
+        assert_eq!(seqs[0].params(), vec![Some(1)]);
+    }
+
+    #[test]
+    fn test_scans_string_sequences() {
+        let mut scanner = SequenceScanner::new();
+        let seqs = scanner.feed(b"\x1b]0;title\x07\x1b_Gabc\x1b\\");
+        assert_eq!(seqs.len(), 2);
+        assert_eq!(seqs[0].kind, SequenceKind::Osc);
+        assert_eq!(seqs[0].body(), b"0;title");
+        assert_eq!(seqs[1].kind, SequenceKind::Apc);
+        assert_eq!(seqs[1].body(), b"Gabc");
+    }
+
+    #[test]
+    fn test_tracks_osc7_cwd() {
+        let mut scanner = SequenceScanner::new();
+        let mut state = ExtendedState::default();
+        for seq in scanner.feed(b"\x1b]7;file://host/home/me/my%20dir\x07\x1b]7;bogus\x07") {
+            state.apply(&seq, TrackingOptions::default());
+        }
+        assert_eq!(state.cwd, Some(PathBuf::from("/home/me/my dir")));
+    }
+
+    #[test]
+    fn test_params_with_defaults() {
+        let mut scanner = SequenceScanner::new();
+        let seqs = scanner.feed(b"\x1b[>4;2m\x1b[;5H");
+        assert_eq!(seqs[0].params(), vec![Some(4), Some(2)]);
+        assert_eq!(seqs[1].params(), vec![None, Some(5)]);
+    }
+
+    #[test]
+    fn test_kitty_flag_stack() {
+        let kitty = TrackingOptions {
+            keyboard_protocol: KeyboardProtocol::Kitty,
+            ..TrackingOptions::default()
+        };
+        let mut scanner = SequenceScanner::new();
+        let mut state = ExtendedState::default();
+        for seq in scanner.feed(b"\x1b[>1u\x1b[>3u") {
+            state.apply(&seq, kitty);
+        }
+        assert_eq!(state.active_kitty_flags(), 3);
+
+        let query = scanner.feed(b"\x1b[?u");
+        let reply = state.apply(&query[0], kitty);
+        assert_eq!(reply.as_deref(), Some(&b"\x1b[?3u"[..]));
+
+        for seq in scanner.feed(b"\x1b[<u") {
+            state.apply(&seq, kitty);
+        }
+        assert_eq!(state.active_kitty_flags(), 1);
+        assert!(state.passthrough.is_empty());
+    }
+
+    #[test]
+    fn test_keyboard_transition() {
+        let plain = KeyboardState::default();
+        let nested = KeyboardState {
+            kitty_flags: vec![1, 3],
+            modify_other_keys: 0,
+        };
+        let other = KeyboardState {
+            kitty_flags: vec![1, 5],
+            modify_other_keys: 2,
+        };
+        assert!(plain.transition_to(&plain).is_empty());
+        assert_eq!(plain.transition_to(&nested), b"\x1b[>1u\x1b[>3u");
+        assert_eq!(nested.transition_to(&plain), b"\x1b[<2u");
+        assert_eq!(nested.transition_to(&other), b"\x1b[<1u\x1b[>5u\x1b[>4;2m");
+    }
+
+    #[test]
+    fn test_legacy_ignores_negotiation() {
+        let mut scanner = SequenceScanner::new();
+        let mut state = ExtendedState::default();
+        for seq in scanner.feed(b"\x1b[>1u\x1b[>4;2m") {
+            assert!(state.apply(&seq, TrackingOptions::default()).is_none());
+        }
+        assert_eq!(state.active_kitty_flags(), 0);
+        assert_eq!(state.modify_other_keys, 0);
+        assert!(state.passthrough.is_empty());
+    }
+
+    #[test]
+    fn test_cursor_style_tracking() {
+        let mut scanner = SequenceScanner::new();
+        let mut state = ExtendedState::default();
+        let options = TrackingOptions {
+            forward_cursor_shape: true,
+            ..TrackingOptions::default()
+        };
+        // DECLL (no space intermediate) must not be mistaken for DECSCUSR
+        for seq in scanner.feed(b"\x1b[6 q\x1b[1q") {
+            state.apply(&seq, options);
+        }
+        assert_eq!(state.cursor_style.shape, CursorShape::Bar);
+        assert!(!state.cursor_style.blinking);
+        assert_eq!(state.passthrough, b"\x1b[6 q");
+    }
+}
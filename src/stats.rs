@@ -0,0 +1,249 @@
+//! Per-pane I/O and render metrics.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::pane::PaneId;
+
+/// Period over which the read rate is averaged.
+const RATE_WINDOW: Duration = Duration::from_secs(1);
+
+/// A pane counts as busy for this long after it last printed output.
+const BUSY_WINDOW: Duration = Duration::from_millis(500);
+
+/// I/O and render statistics of a single pane.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct PaneStats {
+    /// Bytes read from the PTY.
+    pub bytes_read: u64,
+    /// Bytes written to the PTY (input and replies to terminal queries).
+    pub bytes_written: u64,
+    /// Output rate in bytes per second, averaged over the last second.
+    pub read_rate: f64,
+    /// Total time spent in the terminal emulator.
+    pub parse_time: Duration,
+    /// Number of times widgets rebuilt the pane's cells.
+    pub renders: u64,
+    /// Total time spent rebuilding the pane's cells.
+    pub render_time: Duration,
+    /// When output was last read.
+    pub last_output: Option<Instant>,
+    /// When input was last written.
+    pub last_input: Option<Instant>,
+    /// How long the process has run, or ran until it exited.
+    pub runtime: Duration,
+}
+
+impl PaneStats {
+    /// Whether the pane printed output shortly before `now`.
+    #[must_use]
+    pub fn is_busy(&self, now: Instant) -> bool {
+        self.last_output
+            .is_some_and(|at| now.saturating_duration_since(at) < BUSY_WINDOW)
+    }
+}
+
+/// Statistics of all panes of a manager.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ManagerStats {
+    /// Per-pane statistics, busiest pane (highest read rate) first.
+    pub panes: Vec<(PaneId, PaneStats)>,
+    /// Bytes read by all panes.
+    pub bytes_read: u64,
+    /// Bytes written to all panes.
+    pub bytes_written: u64,
+    /// Combined output rate in bytes per second.
+    pub read_rate: f64,
+}
+
+impl ManagerStats {
+    /// Summarize per-pane statistics.
+    pub(crate) fn new(mut panes: Vec<(PaneId, PaneStats)>) -> Self {
+        panes.sort_by(|a, b| b.1.read_rate.total_cmp(&a.1.read_rate));
+        Self {
+            bytes_read: panes.iter().map(|(_, s)| s.bytes_read).sum(),
+            bytes_written: panes.iter().map(|(_, s)| s.bytes_written).sum(),
+            read_rate: panes.iter().map(|(_, s)| s.read_rate).sum(),
+            panes,
+        }
+    }
+
+    /// The pane producing the most output right now, if any is active.
+    #[must_use]
+    pub fn busiest(&self) -> Option<PaneId> {
+        self.panes
+            .first()
+            .filter(|(_, stats)| stats.read_rate > 0.0)
+            .map(|(id, _)| *id)
+    }
+}
+
+/// Counters updated by a pane's tasks and widgets.
+#[derive(Debug)]
+pub(crate) struct PaneMetrics {
+    bytes_read: AtomicU64,
+    bytes_written: AtomicU64,
+    parse_nanos: AtomicU64,
+    renders: AtomicU64,
+    render_nanos: AtomicU64,
+    activity: Mutex<Activity>,
+}
+
+/// Timestamps and the read rate window.
+#[derive(Debug)]
+struct Activity {
+    last_output: Option<Instant>,
+    last_input: Option<Instant>,
+    started: Instant,
+    exited: Option<Instant>,
+    window_start: Instant,
+    window_bytes: u64,
+    /// Rate over the last completed window.
+    rate: f64,
+}
+
+impl Default for PaneMetrics {
+    fn default() -> Self {
+        Self {
+            bytes_read: AtomicU64::new(0),
+            bytes_written: AtomicU64::new(0),
+            parse_nanos: AtomicU64::new(0),
+            renders: AtomicU64::new(0),
+            render_nanos: AtomicU64::new(0),
+            activity: Mutex::new(Activity {
+                last_output: None,
+                last_input: None,
+                started: Instant::now(),
+                exited: None,
+                window_start: Instant::now(),
+                window_bytes: 0,
+                rate: 0.0,
+            }),
+        }
+    }
+}
+
+impl PaneMetrics {
+    /// Record output read from the PTY.
+    pub fn record_read(&self, bytes: usize, now: Instant) {
+        self.bytes_read.fetch_add(bytes as u64, Ordering::Relaxed);
+        let mut activity = self.activity.lock().expect("metrics lock poisoned");
+        activity.last_output = Some(now);
+        let elapsed = now.saturating_duration_since(activity.window_start);
+        if elapsed >= RATE_WINDOW {
+            #[allow(clippy::cast_precision_loss)]
+            let rate = activity.window_bytes as f64 / elapsed.as_secs_f64();
+            activity.rate = rate;
+            activity.window_start = now;
+            activity.window_bytes = 0;
+        }
+        activity.window_bytes += bytes as u64;
+    }
+
+    /// Record input written to the PTY.
+    pub fn record_write(&self, bytes: usize, now: Instant) {
+        self.bytes_written
+            .fetch_add(bytes as u64, Ordering::Relaxed);
+        self.activity
+            .lock()
+            .expect("metrics lock poisoned")
+            .last_input = Some(now);
+    }
+
+    /// Record the process exiting.
+    pub fn record_exit(&self, now: Instant) {
+        self.activity
+            .lock()
+            .expect("metrics lock poisoned")
+            .exited
+            .get_or_insert(now);
+    }
+
+    /// Record time spent in the terminal emulator.
+    pub fn record_parse(&self, elapsed: Duration) {
+        self.parse_nanos
+            .fetch_add(duration_nanos(elapsed), Ordering::Relaxed);
+    }
+
+    /// Record a widget rebuilding the pane's cells.
+    pub fn record_render(&self, elapsed: Duration) {
+        self.renders.fetch_add(1, Ordering::Relaxed);
+        self.render_nanos
+            .fetch_add(duration_nanos(elapsed), Ordering::Relaxed);
+    }
+
+    /// Current statistics.
+    pub fn snapshot(&self, now: Instant) -> PaneStats {
+        let activity = self.activity.lock().expect("metrics lock poisoned");
+        let elapsed = now.saturating_duration_since(activity.window_start);
+        // A window that is overdue has seen no output since it ended, so
+        // its rate decays towards zero while the pane is idle.
+        #[allow(clippy::cast_precision_loss)]
+        let read_rate = if elapsed >= RATE_WINDOW {
+            activity.window_bytes as f64 / elapsed.as_secs_f64()
+        } else {
+            activity.rate
+        };
+        PaneStats {
+            bytes_read: self.bytes_read.load(Ordering::Relaxed),
+            bytes_written: self.bytes_written.load(Ordering::Relaxed),
+            read_rate,
+            parse_time: Duration::from_nanos(self.parse_nanos.load(Ordering::Relaxed)),
+            renders: self.renders.load(Ordering::Relaxed),
+            render_time: Duration::from_nanos(self.render_nanos.load(Ordering::Relaxed)),
+            last_output: activity.last_output,
+            last_input: activity.last_input,
+            runtime: activity
+                .exited
+                .unwrap_or(now)
+                .saturating_duration_since(activity.started),
+        }
+    }
+}
+
+fn duration_nanos(duration: Duration) -> u64 {
+    u64::try_from(duration.as_nanos()).unwrap_or(u64::MAX)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read_rate_window() {
+        let metrics = PaneMetrics::default();
+        let start = metrics.activity.lock().unwrap().window_start;
+        metrics.record_read(500, start);
+        metrics.record_read(1500, start + Duration::from_millis(500));
+        // Window still open: no completed rate yet
+        let stats = metrics.snapshot(start + Duration::from_millis(900));
+        assert_eq!(stats.bytes_read, 2000);
+        assert!(stats.read_rate.abs() < f64::EPSILON);
+
+        metrics.record_read(100, start + Duration::from_secs(1));
+        let stats = metrics.snapshot(start + Duration::from_millis(1500));
+        assert!((stats.read_rate - 2000.0).abs() < 1.0);
+
+        // Idle panes decay towards zero
+        let stats = metrics.snapshot(start + Duration::from_secs(11));
+        assert!((stats.read_rate - 10.0).abs() < 1.0);
+    }
+
+    #[test]
+    fn test_summary_orders_by_rate() {
+        let quiet = PaneStats {
+            bytes_read: 10,
+            ..PaneStats::default()
+        };
+        let busy = PaneStats {
+            bytes_read: 100,
+            read_rate: 50.0,
+            ..PaneStats::default()
+        };
+        let summary = ManagerStats::new(vec![(PaneId(1), quiet), (PaneId(2), busy)]);
+        assert_eq!(summary.busiest(), Some(PaneId(2)));
+        assert_eq!(summary.bytes_read, 110);
+        assert_eq!(summary.panes[1].0, PaneId(1));
+    }
+}
@@ -4,23 +4,97 @@ mod segment;
 
 pub use segment::StatusBarSegment;
 
+use std::cmp::Reverse;
+
 use ratatui::{
     buffer::Buffer,
-    layout::Rect,
-    style::{Color, Style},
+    layout::{Alignment, Rect},
+    style::{Color, Modifier, Style},
+    text::Span,
     widgets::Widget,
 };
 
-/// The height of the status bar (always 1 row).
+/// The height of a status bar of one row, the default; see
+/// [`StatusBarConfig::height`].
 pub const STATUS_BAR_HEIGHT: u16 = 1;
 
+/// Where the status bar is drawn.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum StatusBarPlacement {
+    /// Above the panes and the tab bar.
+    #[default]
+    Top,
+    /// Below the panes.
+    Bottom,
+    /// Split in two: the first row above the panes, the other rows below
+    /// them. The bar has at least two rows.
+    Both,
+}
+
+/// Powerline glyphs drawn between segments in place of the plain
+/// separator, each in the colors of the two backgrounds it joins.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
+pub struct PowerlineSeparators {
+    /// Points right, after the segments of the left and center groups.
+    pub left: String,
+    /// Points left, before the segments of the right group.
+    pub right: String,
+}
+
+impl PowerlineSeparators {
+    /// Separator of the group of `alignment`.
+    fn glyph(&self, alignment: Alignment) -> &str {
+        match alignment {
+            Alignment::Left | Alignment::Center => &self.left,
+            Alignment::Right => &self.right,
+        }
+    }
+}
+
+impl Default for PowerlineSeparators {
+    /// The solid arrows of powerline fonts.
+    fn default() -> Self {
+        Self {
+            left: "\u{e0b0}".to_string(),
+            right: "\u{e0b2}".to_string(),
+        }
+    }
+}
+
 /// Configuration for the status bar.
 #[derive(Clone, Debug)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
 pub struct StatusBarConfig {
     /// Background style.
     pub style: Style,
     /// Separator between segments.
     pub separator: String,
+    /// Powerline separators drawn instead of `separator`. Segments are
+    /// then padded by a space on either side.
+    pub powerline: Option<PowerlineSeparators>,
+    /// Rows of the bar. Segments are drawn on their
+    /// [`row`](StatusBarSegment::row); those past the last row on the last.
+    pub rows: u16,
+    /// Where the bar is drawn.
+    pub placement: StatusBarPlacement,
+    /// Drawn before the segments of plugins whose refresh is failing; see
+    /// [`StatusBarSegment::failed`].
+    pub error_glyph: String,
+    /// Style patched over the segment's for the error glyph.
+    pub error_style: Style,
+    /// Style patched over the segments of plugins whose refresh timed out;
+    /// see [`StatusBarSegment::stale`].
+    pub stale_style: Style,
 }
 
 impl Default for StatusBarConfig {
@@ -28,14 +102,75 @@ impl Default for StatusBarConfig {
         Self {
             style: Style::default().bg(Color::DarkGray).fg(Color::White),
             separator: " | ".to_string(),
+            powerline: None,
+            rows: STATUS_BAR_HEIGHT,
+            placement: StatusBarPlacement::Top,
+            error_glyph: "✗".to_string(),
+            error_style: Style::default().fg(Color::LightRed),
+            stale_style: Style::default().add_modifier(Modifier::DIM),
         }
     }
 }
 
+impl StatusBarConfig {
+    /// Set the rows of the bar.
+    #[must_use]
+    pub fn rows(mut self, rows: u16) -> Self {
+        self.rows = rows;
+        self
+    }
+
+    /// Set the powerline separators drawn instead of the plain one.
+    #[must_use]
+    pub fn powerline(mut self, separators: Option<PowerlineSeparators>) -> Self {
+        self.powerline = separators;
+        self
+    }
+
+    /// Set where the bar is drawn.
+    #[must_use]
+    pub fn placement(mut self, placement: StatusBarPlacement) -> Self {
+        self.placement = placement;
+        self
+    }
+
+    /// Height of the bar: its rows, at least one, or two when split.
+    #[must_use]
+    pub fn height(&self) -> u16 {
+        match self.placement {
+            StatusBarPlacement::Top | StatusBarPlacement::Bottom => self.rows.max(1),
+            StatusBarPlacement::Both => self.rows.max(2),
+        }
+    }
+
+    /// Rows of the bar above and below the panes.
+    pub(crate) fn split_rows(&self) -> (u16, u16) {
+        let height = self.height();
+        match self.placement {
+            StatusBarPlacement::Top => (height, 0),
+            StatusBarPlacement::Bottom => (0, height),
+            StatusBarPlacement::Both => (1, height - 1),
+        }
+    }
+}
+
+/// Whether `segment` is drawn on `row`; the `last` row also takes the
+/// segments of the rows past it.
+fn on_row(segment: &StatusBarSegment, row: u16, last: bool) -> bool {
+    let segment_row = segment.row.unwrap_or(0);
+    !segment.is_empty() && (segment_row == row || (last && segment_row > row))
+}
+
+/// Group of the bar `segment` is drawn in.
+fn alignment_of(segment: &StatusBarSegment) -> Alignment {
+    segment.alignment.unwrap_or(Alignment::Left)
+}
+
 /// Status bar widget for rendering at the top of the terminal.
 pub struct StatusBarWidget<'a> {
     segments: &'a [&'a StatusBarSegment],
     config: StatusBarConfig,
+    first_row: u16,
 }
 
 impl<'a> StatusBarWidget<'a> {
@@ -45,6 +180,7 @@ impl<'a> StatusBarWidget<'a> {
         Self {
             segments,
             config: StatusBarConfig::default(),
+            first_row: 0,
         }
     }
 
@@ -61,10 +197,253 @@ impl<'a> StatusBarWidget<'a> {
         self.config.style = style;
         self
     }
+
+    /// Draw the rows of the bar from `row` on, such as the part of a
+    /// [split](StatusBarPlacement::Both) bar below the panes.
+    #[must_use]
+    pub fn first_row(mut self, row: u16) -> Self {
+        self.first_row = row;
+        self
+    }
+}
+
+impl StatusBarWidget<'_> {
+    fn separator_width(&self) -> u16 {
+        text_width(&self.config.separator)
+    }
+
+    /// Width of `segment`, with the error glyph and its space if failed.
+    fn segment_width(&self, segment: &StatusBarSegment) -> u16 {
+        let error = if segment.failed {
+            text_width(&self.config.error_glyph).saturating_add(1)
+        } else {
+            0
+        };
+        segment.width().saturating_add(error)
+    }
+
+    /// Draw `segment` in `width` columns from `x` on row `y`, after the
+    /// error glyph if failed and dimmed if stale, ending in an ellipsis if
+    /// cut short. A wide character is never split.
+    fn render_segment(
+        &self,
+        segment: &StatusBarSegment,
+        mut x: u16,
+        mut width: u16,
+        y: u16,
+        buf: &mut Buffer,
+    ) {
+        let style = if segment.stale {
+            segment.style.patch(self.config.stale_style)
+        } else {
+            segment.style
+        };
+        // The segment's background covers its padding too
+        buf.set_style(Rect::new(x, y, width, 1), style);
+        if segment.failed {
+            let glyph = &self.config.error_glyph;
+            let error_style = style.patch(self.config.error_style);
+            let (end, _) = buf.set_stringn(x, y, glyph, usize::from(width), error_style);
+            let drawn = (end - x).saturating_add(1).min(width);
+            x += drawn;
+            width -= drawn;
+        }
+        if width == 0 {
+            return;
+        }
+        let text = segment.text();
+        if Span::raw(text.as_ref()).width() <= usize::from(width) {
+            buf.set_stringn(x, y, &text, usize::from(width), style);
+        } else {
+            let (end, _) = buf.set_stringn(x, y, &text, usize::from(width - 1), style);
+            buf.set_string(end, y, "…", style);
+        }
+    }
+
+    /// Background of the bar.
+    fn background(&self) -> Color {
+        self.config.style.bg.unwrap_or(Color::Reset)
+    }
+
+    /// Width of the group of `alignment` among `segments`: its segments and
+    /// the separators between them, or with powerline separators, the
+    /// padded segments and a separator each.
+    fn group_width(&self, segments: &[&StatusBarSegment], alignment: Alignment) -> u16 {
+        let group = segments
+            .iter()
+            .filter(|segment| alignment_of(segment) == alignment);
+        if let Some(powerline) = &self.config.powerline {
+            let glyph = text_width(powerline.glyph(alignment));
+            return group.fold(0u16, |width, segment| {
+                width
+                    .saturating_add(self.segment_width(segment))
+                    .saturating_add(2)
+                    .saturating_add(glyph)
+            });
+        }
+        group.enumerate().fold(0u16, |width, (i, segment)| {
+            let separator = if i > 0 { self.separator_width() } else { 0 };
+            width
+                .saturating_add(separator)
+                .saturating_add(self.segment_width(segment))
+        })
+    }
+
+    /// Width of a row of `segments`: its groups, a column apart.
+    fn row_width(&self, segments: &[&StatusBarSegment]) -> u16 {
+        let widths = [Alignment::Left, Alignment::Center, Alignment::Right]
+            .map(|alignment| self.group_width(segments, alignment));
+        let groups = widths.iter().filter(|width| **width > 0).count();
+        let gaps = u16::try_from(groups.saturating_sub(1)).unwrap_or(0);
+        widths
+            .iter()
+            .fold(gaps, |total, width| total.saturating_add(*width))
+    }
+
+    /// Drop segments until the row fits in `width`: the lowest priority
+    /// first, and the later of equal priority. A last segment too wide is
+    /// kept, to be truncated.
+    fn fit(&self, segments: &mut Vec<&StatusBarSegment>, width: u16) {
+        while segments.len() > 1 && self.row_width(segments) > width {
+            let lowest = segments
+                .iter()
+                .enumerate()
+                .min_by_key(|(i, segment)| (segment.priority, Reverse(*i)))
+                .map_or(0, |(i, _)| i);
+            segments.remove(lowest);
+        }
+    }
+
+    /// Draw the group of `alignment` among `segments` on row `y` from
+    /// column `x`, clipped before column `limit`. Returns the column after
+    /// the group.
+    fn render_group(
+        &self,
+        segments: &[&StatusBarSegment],
+        alignment: Alignment,
+        (mut x, limit): (u16, u16),
+        y: u16,
+        buf: &mut Buffer,
+    ) -> u16 {
+        let group = segments
+            .iter()
+            .filter(|segment| alignment_of(segment) == alignment);
+        if let Some(powerline) = &self.config.powerline {
+            return self.render_powerline_group(group, powerline, alignment, (x, limit), y, buf);
+        }
+        for (i, segment) in group.enumerate() {
+            // Add separator between segments
+            let sep_width = self.separator_width();
+            if i > 0 && x.saturating_add(sep_width) <= limit {
+                buf.set_string(x, y, &self.config.separator, Style::default());
+                x += sep_width;
+            }
+            let width = self.segment_width(segment).min(limit.saturating_sub(x));
+            self.render_segment(segment, x, width, y, buf);
+            x += width;
+        }
+        x
+    }
+
+    /// Draw a group of segments padded and filled with their background,
+    /// with powerline separators: after each segment on the left and in
+    /// the center, before each on the right.
+    fn render_powerline_group<'s>(
+        &self,
+        group: impl Iterator<Item = &'s &'s StatusBarSegment>,
+        powerline: &PowerlineSeparators,
+        alignment: Alignment,
+        (mut x, limit): (u16, u16),
+        y: u16,
+        buf: &mut Buffer,
+    ) -> u16 {
+        let glyph = powerline.glyph(alignment);
+        let leading = alignment == Alignment::Right;
+        let mut previous = None;
+        for segment in group {
+            let background = segment.style.bg.unwrap_or(self.background());
+            // A separator runs from the background of its left side to
+            // that of its right side, the color of its point
+            let joined = previous.unwrap_or(self.background());
+            if leading {
+                x = render_glyph(glyph, background, joined, (x, limit), y, buf);
+            } else if let Some(previous) = previous {
+                x = render_glyph(glyph, previous, background, (x, limit), y, buf);
+            }
+            let width = self
+                .segment_width(segment)
+                .saturating_add(2)
+                .min(limit.saturating_sub(x));
+            let area = Rect::new(x, y, width, 1);
+            buf.set_style(area, segment.style.bg(background));
+            self.render_segment(segment, x + 1, width.saturating_sub(2), y, buf);
+            x += width;
+            previous = Some(background);
+        }
+        if let Some(previous) = previous.filter(|_| !leading) {
+            x = render_glyph(glyph, previous, self.background(), (x, limit), y, buf);
+        }
+        x
+    }
+
+    /// Draw the segments of `row` of the bar on the single row `area`.
+    /// The `last` row also takes the segments of the rows past it.
+    fn render_row(&self, row: u16, last: bool, area: Rect, buf: &mut Buffer) {
+        let y = area.y;
+        // A column of padding on either side
+        let (start, end) = (area.x + 1, area.right() - 1);
+        let mut segments: Vec<_> = self
+            .segments
+            .iter()
+            .copied()
+            .filter(|segment| on_row(segment, row, last))
+            .collect();
+        self.fit(&mut segments, end - start);
+
+        let gap = |width: u16| u16::from(width > 0);
+        let left_width = self.group_width(&segments, Alignment::Left);
+        let left_end =
+            self.render_group(&segments, Alignment::Left, (start, end), y, buf) + gap(left_width);
+
+        let right_width = self.group_width(&segments, Alignment::Right);
+        let right_start = end.saturating_sub(right_width).max(left_end).min(end);
+        self.render_group(&segments, Alignment::Right, (right_start, end), y, buf);
+
+        let center_end = right_start.saturating_sub(gap(right_width)).max(left_end);
+        let center_width = self.group_width(&segments, Alignment::Center);
+        let centered = area.x + area.width.saturating_sub(center_width) / 2;
+        let center_start = centered
+            .min(center_end.saturating_sub(center_width))
+            .max(left_end);
+        let center = (center_start, center_end);
+        self.render_group(&segments, Alignment::Center, center, y, buf);
+    }
+}
+
+/// Display width of `text`.
+fn text_width(text: &str) -> u16 {
+    u16::try_from(Span::raw(text).width()).unwrap_or(u16::MAX)
+}
+
+/// Draw a powerline `glyph` in `fg` on `bg` at column `x` of row `y` if
+/// it fits before `limit`. Returns the column after it.
+fn render_glyph(
+    glyph: &str,
+    fg: Color,
+    bg: Color,
+    (x, limit): (u16, u16),
+    y: u16,
+    buf: &mut Buffer,
+) -> u16 {
+    let width = text_width(glyph);
+    if x.saturating_add(width) > limit {
+        return x;
+    }
+    buf.set_string(x, y, glyph, Style::default().fg(fg).bg(bg));
+    x + width
 }
 
 impl Widget for StatusBarWidget<'_> {
-    #[allow(clippy::cast_possible_truncation)]
     fn render(self, area: Rect, buf: &mut Buffer) {
         // Fill background
         for x in area.x..area.x + area.width {
@@ -72,49 +451,183 @@ impl Widget for StatusBarWidget<'_> {
                 buf[(x, y)].set_style(self.config.style);
             }
         }
+        if area.width < 2 {
+            return;
+        }
+        let last_row = self
+            .config
+            .height()
+            .max(self.first_row.saturating_add(area.height))
+            - 1;
+        for line in 0..area.height {
+            let row = self.first_row.saturating_add(line);
+            let line_area = Rect {
+                y: area.y + line,
+                height: 1,
+                ..area
+            };
+            self.render_row(row, row == last_row, line_area, buf);
+        }
+    }
+}
 
-        // Render segments
-        let mut x = area.x + 1; // Small padding
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-        for (i, segment) in self.segments.iter().enumerate() {
-            if segment.is_empty() {
-                continue;
-            }
+    fn row(buf: &Buffer) -> String {
+        row_at(buf, 0)
+    }
 
-            // Add separator between segments
-            if i > 0 && x < area.x + area.width {
-                let sep_width = self.config.separator.chars().count() as u16;
-                if x + sep_width < area.x + area.width {
-                    for (j, ch) in self.config.separator.chars().enumerate() {
-                        buf[(x + j as u16, area.y)].set_char(ch);
-                    }
-                    x += sep_width;
-                }
-            }
+    fn row_at(buf: &Buffer, y: u16) -> String {
+        (0..buf.area.width).map(|x| buf[(x, y)].symbol()).collect()
+    }
 
-            // Render icon if present
-            if let Some(icon) = &segment.icon {
-                for ch in icon.chars() {
-                    if x >= area.x + area.width {
-                        break;
-                    }
-                    buf[(x, area.y)].set_char(ch).set_style(segment.style);
-                    x += 1;
-                }
-                // Space after icon
-                if x < area.x + area.width {
-                    x += 1;
-                }
-            }
+    #[test]
+    fn test_alignment_groups() {
+        let clock = StatusBarSegment::new("12:00").alignment(Alignment::Right);
+        let battery = StatusBarSegment::new("80%").alignment(Alignment::Right);
+        let title = StatusBarSegment::new("build")
+            .alignment(Alignment::Center)
+            .priority(-1);
+        let user = StatusBarSegment::new("alice");
+        let segments = [&clock, &user, &title, &battery];
+        let config = StatusBarConfig {
+            separator: "|".to_string(),
+            ..StatusBarConfig::default()
+        };
 
-            // Render content
-            for ch in segment.content.chars() {
-                if x >= area.x + area.width {
-                    break;
-                }
-                buf[(x, area.y)].set_char(ch).set_style(segment.style);
-                x += 1;
-            }
-        }
+        let mut buf = Buffer::empty(Rect::new(0, 0, 30, 1));
+        StatusBarWidget::new(&segments)
+            .config(config.clone())
+            .render(buf.area, &mut buf);
+        assert_eq!(row(&buf), " alice      build   12:00|80% ");
+
+        // Without room, the lowest priority segment is dropped
+        let mut buf = Buffer::empty(Rect::new(0, 0, 18, 1));
+        StatusBarWidget::new(&segments)
+            .config(config)
+            .render(buf.area, &mut buf);
+        assert_eq!(row(&buf), " alice  12:00|80% ");
+    }
+
+    #[test]
+    fn test_rows() {
+        let user = StatusBarSegment::new("alice");
+        let branch = StatusBarSegment::new("main").row(1);
+        let stats = StatusBarSegment::new("cpu 3%")
+            .row(4)
+            .alignment(Alignment::Right);
+        let segments = [&user, &branch, &stats];
+        let config = StatusBarConfig::default().rows(2);
+        assert_eq!(config.height(), 2);
+        assert_eq!(StatusBarConfig::default().rows(0).height(), 1);
+
+        let mut buf = Buffer::empty(Rect::new(0, 0, 16, 2));
+        StatusBarWidget::new(&segments)
+            .config(config)
+            .render(buf.area, &mut buf);
+        assert_eq!(row_at(&buf, 0), " alice          ");
+        // Rows past the last are drawn on the last
+        assert_eq!(row_at(&buf, 1), " main    cpu 3% ");
+
+        // Split, the part below the panes takes the rows past the first
+        let config = StatusBarConfig::default()
+            .rows(2)
+            .placement(StatusBarPlacement::Both);
+        let mut top = Buffer::empty(Rect::new(0, 0, 16, 1));
+        StatusBarWidget::new(&segments)
+            .config(config.clone())
+            .render(top.area, &mut top);
+        assert_eq!(row(&top), " alice          ");
+        let mut bottom = Buffer::empty(Rect::new(0, 0, 16, 1));
+        StatusBarWidget::new(&segments)
+            .config(config)
+            .first_row(1)
+            .render(bottom.area, &mut bottom);
+        assert_eq!(row(&bottom), " main    cpu 3% ");
+    }
+
+    #[test]
+    fn test_overflow() {
+        let branch = StatusBarSegment::new("feature/long-branch")
+            .max_width(8)
+            .priority(1);
+        let stats = StatusBarSegment::new("cpu 3% mem 1G").alignment(Alignment::Right);
+        let segments = [&branch, &stats];
+        assert_eq!(branch.width(), 8);
+
+        let mut buf = Buffer::empty(Rect::new(0, 0, 24, 1));
+        StatusBarWidget::new(&segments).render(buf.area, &mut buf);
+        assert_eq!(row(&buf), " feature… cpu 3% mem 1G ");
+
+        // Too narrow for both: the lower priority stats go
+        let mut buf = Buffer::empty(Rect::new(0, 0, 16, 1));
+        StatusBarWidget::new(&segments).render(buf.area, &mut buf);
+        assert_eq!(row(&buf), " feature…       ");
+
+        // A segment alone is cut short without splitting a wide character
+        let wide = StatusBarSegment::new("日本語テキスト");
+        let mut buf = Buffer::empty(Rect::new(0, 0, 10, 1));
+        StatusBarWidget::new(&[&wide]).render(buf.area, &mut buf);
+        assert_eq!(buf[(5, 0)].symbol(), "語");
+        assert_eq!(buf[(7, 0)].symbol(), "…");
+        assert_eq!(buf[(8, 0)].symbol(), " ");
+    }
+
+    #[test]
+    fn test_powerline() {
+        let user = StatusBarSegment::new("alice").style(Style::default().bg(Color::Blue));
+        let branch = StatusBarSegment::new("main");
+        let clock = StatusBarSegment::new("12:00")
+            .style(Style::default().bg(Color::Green))
+            .alignment(Alignment::Right);
+        let segments = [&user, &branch, &clock];
+        let config = StatusBarConfig::default().powerline(Some(PowerlineSeparators::default()));
+
+        let mut buf = Buffer::empty(Rect::new(0, 0, 26, 1));
+        StatusBarWidget::new(&segments)
+            .config(config)
+            .render(buf.area, &mut buf);
+        assert_eq!(row(&buf), "  alice \u{e0b0} main \u{e0b0} \u{e0b2} 12:00  ");
+        // Segments are padded in their own background
+        assert!((1..8).all(|x| buf[(x, 0)].bg == Color::Blue));
+        assert!((18..25).all(|x| buf[(x, 0)].bg == Color::Green));
+        // Separators join the backgrounds on either side
+        assert_eq!(
+            (buf[(8, 0)].fg, buf[(8, 0)].bg),
+            (Color::Blue, Color::DarkGray)
+        );
+        assert_eq!(
+            (buf[(17, 0)].fg, buf[(17, 0)].bg),
+            (Color::Green, Color::DarkGray)
+        );
+    }
+
+    #[test]
+    fn test_failed_segment() {
+        let branch = StatusBarSegment {
+            failed: true,
+            ..StatusBarSegment::new("main")
+        };
+        let mut buf = Buffer::empty(Rect::new(0, 0, 10, 1));
+        StatusBarWidget::new(&[&branch]).render(buf.area, &mut buf);
+        assert_eq!(row(&buf), " ✗ main   ");
+        assert_eq!(buf[(1, 0)].fg, Color::LightRed);
+        assert_ne!(buf[(3, 0)].fg, Color::LightRed);
+
+        // The glyph stays when the text is cut short
+        let mut buf = Buffer::empty(Rect::new(0, 0, 6, 1));
+        StatusBarWidget::new(&[&branch]).render(buf.area, &mut buf);
+        assert_eq!(row(&buf), " ✗ m… ");
+
+        // Stale segments are dimmed
+        let stale = StatusBarSegment {
+            stale: true,
+            ..StatusBarSegment::new("main")
+        };
+        let mut buf = Buffer::empty(Rect::new(0, 0, 6, 1));
+        StatusBarWidget::new(&[&stale]).render(buf.area, &mut buf);
+        assert!(buf[(1, 0)].modifier.contains(Modifier::DIM));
     }
 }
@@ -1,9 +1,13 @@
 //! Status bar segment - a unit of content from a plugin.
 
-use ratatui::style::Style;
+use std::borrow::Cow;
+
+use ratatui::{layout::Alignment, style::Style, text::Span};
+
+use crate::dialog::SPINNER_FRAMES;
 
 /// A segment of text for the status bar.
-#[derive(Clone, Debug, Default)]
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
 pub struct StatusBarSegment {
     /// The text content.
     pub content: String,
@@ -11,8 +15,39 @@ pub struct StatusBarSegment {
     pub style: Style,
     /// Optional icon/prefix (symbol).
     pub icon: Option<String>,
+    /// Animation frames drawn in place of the icon, one after the other as
+    /// the [`PluginRegistry`](crate::PluginRegistry) ticks. Empty for a
+    /// still icon.
+    pub frames: Vec<String>,
+    /// Index of the frame drawn, wrapping around `frames`.
+    pub frame: usize,
+    /// Whether the plugin the segment comes from failed its last refresh;
+    /// its first segment then shows its last data after the bar's
+    /// [`error_glyph`](crate::StatusBarConfig::error_glyph). The error is
+    /// available from [`PluginRegistry::error`](crate::PluginRegistry::error).
+    pub failed: bool,
+    /// Whether the plugin's refresh is taking longer than its
+    /// [`refresh_timeout`](crate::PluginConfig::refresh_timeout);
+    /// the segment's data may be out of date and is drawn in the bar's
+    /// [`stale_style`](crate::StatusBarConfig::stale_style).
+    pub stale: bool,
     /// Minimum width (for alignment).
     pub min_width: Option<u16>,
+    /// Maximum width; longer text is cut short with an ellipsis.
+    pub max_width: Option<u16>,
+    /// How much the segment matters when a row of the bar is too narrow:
+    /// segments of the lowest priority are dropped first. Unlike
+    /// [`PluginConfig::priority`](crate::PluginConfig::priority), it does
+    /// not change the order.
+    pub priority: i32,
+    /// Group of the status bar the segment is drawn in. `None` leaves it
+    /// to the plugin's [`PluginConfig::alignment`](crate::PluginConfig::alignment),
+    /// or the left group for segments not from a plugin.
+    pub alignment: Option<Alignment>,
+    /// Row of the status bar the segment is drawn on, from 0. `None`
+    /// leaves it to the plugin's [`PluginConfig::row`](crate::PluginConfig::row),
+    /// or the first row for segments not from a plugin.
+    pub row: Option<u16>,
 }
 
 impl StatusBarSegment {
@@ -39,6 +74,24 @@ impl StatusBarSegment {
         self
     }
 
+    /// Set animation frames drawn in place of the icon.
+    #[must_use]
+    pub fn frames<I>(mut self, frames: I) -> Self
+    where
+        I: IntoIterator,
+        I::Item: Into<String>,
+    {
+        self.frames = frames.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Animate a spinner in place of the icon, e.g. while a plugin's data
+    /// is being fetched in the background.
+    #[must_use]
+    pub fn spinner(self) -> Self {
+        self.frames(SPINNER_FRAMES.map(String::from))
+    }
+
     /// Set minimum width.
     #[must_use]
     pub fn min_width(mut self, width: u16) -> Self {
@@ -46,19 +99,65 @@ impl StatusBarSegment {
         self
     }
 
+    /// Set maximum width.
+    #[must_use]
+    pub fn max_width(mut self, width: u16) -> Self {
+        self.max_width = Some(width);
+        self
+    }
+
+    /// Set how much the segment matters when the bar is too narrow.
+    #[must_use]
+    pub fn priority(mut self, priority: i32) -> Self {
+        self.priority = priority;
+        self
+    }
+
+    /// Set the group of the status bar the segment is drawn in.
+    #[must_use]
+    pub fn alignment(mut self, alignment: Alignment) -> Self {
+        self.alignment = Some(alignment);
+        self
+    }
+
+    /// Set the row of the status bar the segment is drawn on.
+    #[must_use]
+    pub fn row(mut self, row: u16) -> Self {
+        self.row = Some(row);
+        self
+    }
+
     /// Check if segment is empty.
     #[must_use]
     pub fn is_empty(&self) -> bool {
-        self.content.is_empty() && self.icon.is_none()
+        self.content.is_empty() && self.current_icon().is_none()
+    }
+
+    /// The animation frame drawn, or the icon.
+    #[must_use]
+    pub fn current_icon(&self) -> Option<&str> {
+        if self.frames.is_empty() {
+            return self.icon.as_deref();
+        }
+        self.frames
+            .get(self.frame % self.frames.len())
+            .map(String::as_str)
     }
 
-    /// Get display width (approximate).
+    /// Get display width: that of the icon and content, limited by the
+    /// maximum and minimum widths.
     #[must_use]
-    #[allow(clippy::cast_possible_truncation)]
     pub fn width(&self) -> u16 {
-        let icon_width = self.icon.as_ref().map_or(0, |i| i.chars().count() + 1);
-        let content_width = self.content.chars().count();
-        let total = (icon_width + content_width) as u16;
-        self.min_width.map_or(total, |min| min.max(total))
+        let text = u16::try_from(Span::raw(self.text()).width()).unwrap_or(u16::MAX);
+        let width = self.max_width.map_or(text, |max| max.min(text));
+        self.min_width.map_or(width, |min| min.max(width))
+    }
+
+    /// The icon, if any, a space and the content.
+    pub(crate) fn text(&self) -> Cow<'_, str> {
+        match self.current_icon() {
+            Some(icon) => Cow::Owned(format!("{icon} {}", self.content)),
+            None => Cow::Borrowed(&self.content),
+        }
     }
 }
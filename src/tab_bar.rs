@@ -0,0 +1,198 @@
+//! Tab bar widget showing the manager's workspaces.
+
+use ratatui::buffer::Buffer;
+use ratatui::layout::Rect;
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::Span;
+use ratatui::widgets::Widget;
+
+/// The height of the tab bar (always 1 row).
+pub const TAB_BAR_HEIGHT: u16 = 1;
+
+/// A workspace as shown in the tab bar.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct WorkspaceTab {
+    /// Workspace name.
+    pub name: String,
+    /// Whether this is the active workspace.
+    pub active: bool,
+    /// Whether a pane of this background workspace printed output since
+    /// it was last shown.
+    pub activity: bool,
+}
+
+/// Configuration for the tab bar.
+#[derive(Clone, Debug)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
+pub struct TabBarConfig {
+    /// Background and inactive tab style.
+    pub style: Style,
+    /// Style of the active tab.
+    pub active_style: Style,
+    /// Style of the activity badge.
+    pub activity_style: Style,
+    /// Shown after the name of tabs with activity.
+    pub activity_badge: String,
+}
+
+impl Default for TabBarConfig {
+    fn default() -> Self {
+        Self {
+            style: Style::default().bg(Color::DarkGray).fg(Color::White),
+            active_style: Style::default()
+                .bg(Color::Cyan)
+                .fg(Color::Black)
+                .add_modifier(Modifier::BOLD),
+            activity_style: Style::default().fg(Color::Yellow),
+            activity_badge: "●".to_string(),
+        }
+    }
+}
+
+impl TabBarConfig {
+    /// Areas of the tabs drawn into `area`, in order. Tabs that do not fit
+    /// are left out.
+    #[must_use]
+    pub fn tab_areas(&self, tabs: &[WorkspaceTab], area: Rect) -> Vec<Rect> {
+        let mut x = area.x;
+        let mut areas = Vec::with_capacity(tabs.len());
+        for tab in tabs {
+            let width = u16::try_from(self.tab_width(tab)).unwrap_or(u16::MAX);
+            if x.saturating_add(width) > area.right() {
+                break;
+            }
+            areas.push(Rect {
+                x,
+                y: area.y,
+                width,
+                height: area.height.min(TAB_BAR_HEIGHT),
+            });
+            x += width;
+        }
+        areas
+    }
+
+    /// Index of the tab at `x`, `y` when drawn into `area`.
+    #[must_use]
+    pub fn tab_at(&self, tabs: &[WorkspaceTab], area: Rect, x: u16, y: u16) -> Option<usize> {
+        self.tab_areas(tabs, area)
+            .iter()
+            .position(|tab| tab.contains((x, y).into()))
+    }
+
+    /// Width of a tab: its name padded by a space on each side, and the
+    /// badge with its own trailing space.
+    fn tab_width(&self, tab: &WorkspaceTab) -> usize {
+        let badge = if tab.activity {
+            Span::raw(&self.activity_badge).width() + 1
+        } else {
+            0
+        };
+        Span::raw(&tab.name).width() + 2 + badge
+    }
+}
+
+/// Tab bar widget listing workspaces, with the active one highlighted and
+/// a badge on background workspaces with new output.
+///
+/// Clicks on tabs are handled by
+/// [`PaneManager::handle_click`](crate::PaneManager::handle_click) once the
+/// manager knows where the bar is; see
+/// [`PaneManager::set_tab_bar_area`](crate::PaneManager::set_tab_bar_area).
+pub struct TabBarWidget<'a> {
+    tabs: &'a [WorkspaceTab],
+    config: TabBarConfig,
+}
+
+impl<'a> TabBarWidget<'a> {
+    /// Create a new tab bar widget.
+    #[must_use]
+    pub fn new(tabs: &'a [WorkspaceTab]) -> Self {
+        Self {
+            tabs,
+            config: TabBarConfig::default(),
+        }
+    }
+
+    /// Set the configuration.
+    #[must_use]
+    pub fn config(mut self, config: TabBarConfig) -> Self {
+        self.config = config;
+        self
+    }
+}
+
+impl Widget for TabBarWidget<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        buf.set_style(area, self.config.style);
+        let areas = self.config.tab_areas(self.tabs, area);
+        for (tab, tab_area) in self.tabs.iter().zip(areas) {
+            let style = if tab.active {
+                self.config.style.patch(self.config.active_style)
+            } else {
+                self.config.style
+            };
+            buf.set_style(tab_area, style);
+            let (x, _) = buf.set_stringn(
+                tab_area.x + 1,
+                tab_area.y,
+                &tab.name,
+                usize::from(tab_area.width),
+                style,
+            );
+            if tab.activity {
+                buf.set_stringn(
+                    x + 1,
+                    tab_area.y,
+                    &self.config.activity_badge,
+                    usize::from(tab_area.right().saturating_sub(x + 1)),
+                    style.patch(self.config.activity_style),
+                );
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tab_bar() {
+        let tabs = [
+            WorkspaceTab {
+                name: "default".to_string(),
+                active: true,
+                activity: false,
+            },
+            WorkspaceTab {
+                name: "logs".to_string(),
+                active: false,
+                activity: true,
+            },
+            WorkspaceTab {
+                name: "scratch".to_string(),
+                active: false,
+                activity: false,
+            },
+        ];
+        let area = Rect::new(0, 0, 20, 1);
+        let mut buf = Buffer::empty(area);
+        TabBarWidget::new(&tabs).render(area, &mut buf);
+
+        let text: String = (0..20).map(|x| buf[(x, 0)].symbol()).collect();
+        assert_eq!(text, " default  logs ●    ");
+        let config = TabBarConfig::default();
+        assert_eq!(buf[(1, 0)].bg, Color::Cyan);
+        assert_eq!(buf[(15, 0)].fg, Color::Yellow);
+
+        // The third tab does not fit
+        assert_eq!(config.tab_at(&tabs, area, 12, 0), Some(1));
+        assert_eq!(config.tab_areas(&tabs, area).len(), 2);
+        assert_eq!(config.tab_at(&tabs, area, 18, 0), None);
+    }
+}
@@ -0,0 +1,188 @@
+//! Background threads and timers, independent of the async runtime.
+//!
+//! Reading, writing and process monitoring block, so each pane runs them
+//! on their own threads: tokio's blocking pool with the default
+//! `tokio-runtime` feature, or plain OS threads otherwise (and always with
+//! the `sync` feature). Timeouts likewise use tokio's timer when it is
+//! available and a shared timer thread otherwise, so cockpit's futures run
+//! on any executor.
+
+use std::future::Future;
+use std::time::Duration;
+
+#[cfg(feature = "tokio-runtime")]
+pub(crate) use tokio::time::Instant;
+
+#[cfg(not(feature = "tokio-runtime"))]
+pub(crate) use std::time::Instant;
+
+/// Handle to a pane I/O task.
+#[derive(Debug)]
+pub(crate) struct TaskHandle {
+    #[cfg(all(feature = "tokio-runtime", not(feature = "sync")))]
+    inner: tokio::task::JoinHandle<()>,
+    #[cfg(any(not(feature = "tokio-runtime"), feature = "sync"))]
+    #[allow(dead_code)]
+    inner: std::thread::JoinHandle<()>,
+}
+
+impl TaskHandle {
+    /// Cancel the task if it has not started yet.
+    ///
+    /// Blocking I/O cannot be interrupted; a running task ends once its
+    /// PTY or channel is closed. Threads start right away, so this does
+    /// nothing for them.
+    #[cfg_attr(
+        any(not(feature = "tokio-runtime"), feature = "sync"),
+        allow(clippy::unused_self)
+    )]
+    pub fn abort(&self) {
+        #[cfg(all(feature = "tokio-runtime", not(feature = "sync")))]
+        self.inner.abort();
+    }
+}
+
+/// Run blocking `f` in the background.
+///
+/// # Panics
+/// Panics if called outside a tokio runtime.
+#[cfg(all(feature = "tokio-runtime", not(feature = "sync")))]
+pub(crate) fn spawn_blocking(_name: &str, f: impl FnOnce() + Send + 'static) -> TaskHandle {
+    TaskHandle {
+        inner: tokio::task::spawn_blocking(f),
+    }
+}
+
+/// Run blocking `f` on a new thread.
+///
+/// # Panics
+/// Panics if the OS fails to create a thread.
+#[cfg(any(not(feature = "tokio-runtime"), feature = "sync"))]
+pub(crate) fn spawn_blocking(name: &str, f: impl FnOnce() + Send + 'static) -> TaskHandle {
+    let inner = std::thread::Builder::new()
+        .name(name.to_string())
+        .spawn(f)
+        .expect("failed to spawn pane thread");
+    TaskHandle { inner }
+}
+
+/// Await `future` for at most `duration`. Returns `None` on timeout.
+#[cfg(feature = "tokio-runtime")]
+pub(crate) async fn timeout<F: Future>(duration: Duration, future: F) -> Option<F::Output> {
+    tokio::time::timeout(duration, future).await.ok()
+}
+
+/// Await `future` for at most `duration`. Returns `None` on timeout.
+#[cfg(not(feature = "tokio-runtime"))]
+pub(crate) async fn timeout<F: Future>(duration: Duration, future: F) -> Option<F::Output> {
+    use std::pin::pin;
+    use std::task::Poll;
+
+    let mut future = pin!(future);
+    let mut sleep = pin!(timer::Sleep::new(Instant::now() + duration));
+    std::future::poll_fn(|cx| {
+        if let Poll::Ready(output) = future.as_mut().poll(cx) {
+            return Poll::Ready(Some(output));
+        }
+        sleep.as_mut().poll(cx).map(|()| None)
+    })
+    .await
+}
+
+/// Timer thread shared by all [`timeout`]s when tokio's timer is not
+/// available.
+#[cfg(not(feature = "tokio-runtime"))]
+mod timer {
+    use std::future::Future;
+    use std::pin::Pin;
+    use std::sync::{Condvar, Mutex, OnceLock};
+    use std::task::{Context, Poll, Waker};
+    use std::time::Instant;
+
+    /// Pending deadlines and the tasks to wake at them.
+    struct Timers {
+        pending: Mutex<Vec<(Instant, Waker)>>,
+        changed: Condvar,
+    }
+
+    fn timers() -> &'static Timers {
+        static TIMERS: OnceLock<Timers> = OnceLock::new();
+        TIMERS.get_or_init(|| {
+            std::thread::Builder::new()
+                .name("cockpit-timer".to_string())
+                .spawn(run)
+                .expect("failed to spawn timer thread");
+            Timers {
+                pending: Mutex::new(Vec::new()),
+                changed: Condvar::new(),
+            }
+        })
+    }
+
+    fn run() {
+        let timers = timers();
+        let mut pending = timers.pending.lock().expect("timer lock poisoned");
+        loop {
+            let now = Instant::now();
+            pending.retain(|(deadline, waker)| {
+                if *deadline <= now {
+                    waker.wake_by_ref();
+                }
+                *deadline > now
+            });
+            pending = match pending.iter().map(|(deadline, _)| *deadline).min() {
+                Some(next) => {
+                    timers
+                        .changed
+                        .wait_timeout(pending, next - now)
+                        .expect("timer lock poisoned")
+                        .0
+                }
+                None => timers.changed.wait(pending).expect("timer lock poisoned"),
+            };
+        }
+    }
+
+    /// Future that completes at a deadline.
+    pub(super) struct Sleep {
+        deadline: Instant,
+    }
+
+    impl Sleep {
+        pub fn new(deadline: Instant) -> Self {
+            Self { deadline }
+        }
+    }
+
+    impl Future for Sleep {
+        type Output = ();
+
+        fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+            if Instant::now() >= self.deadline {
+                return Poll::Ready(());
+            }
+            let timers = timers();
+            timers
+                .pending
+                .lock()
+                .expect("timer lock poisoned")
+                .push((self.deadline, cx.waker().clone()));
+            timers.changed.notify_one();
+            Poll::Pending
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_timeout() {
+        assert_eq!(timeout(Duration::from_secs(5), async { 7 }).await, Some(7));
+        let start = std::time::Instant::now();
+        let never = std::future::pending::<()>();
+        assert_eq!(timeout(Duration::from_millis(20), never).await, None);
+        assert!(start.elapsed() >= Duration::from_millis(20));
+    }
+}
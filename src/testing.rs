@@ -0,0 +1,1903 @@
+//! Headless harness for end-to-end tests of cockpit UIs.
+//!
+//! [`Harness`] runs a [`PaneManager`] on a [`MockBackend`], feeds it input
+//! events and renders it into an in-memory [`Buffer`], so applications can
+//! assert on what would appear on screen without a terminal or real
+//! processes.
+//!
+//! ```no_run
+//! use cockpit::testing::Harness;
+//! use cockpit::SpawnConfig;
+//! use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+//!
+//! # #[tokio::main]
+//! # async fn main() -> cockpit::Result<()> {
+//! let mut harness = Harness::new(80, 24);
+//! let pane = harness.spawn(SpawnConfig::new_shell())?;
+//!
+//! harness.feed(pane, b"$ ");
+//! harness.key(KeyEvent::new(KeyCode::Char('l'), KeyModifiers::NONE)).await?;
+//! harness.settle().await;
+//! assert_eq!(harness.pty(pane).input(), b"l");
+//!
+//! harness.render();
+//! harness.assert_contains("$ ");
+//! # Ok(())
+//! # }
+//! ```
+
+use std::path::PathBuf;
+use std::time::Duration;
+
+use crossterm::event::{KeyEvent, MouseEvent};
+use ratatui::buffer::{Buffer, Cell};
+use ratatui::layout::Rect;
+use ratatui::widgets::{StatefulWidget, Widget};
+
+use crate::display_panes::PaneNumbers;
+use crate::error::Result;
+use crate::manager::{ManagerConfig, PaneManager};
+use crate::mock::{MockBackend, MockPty};
+use crate::pane::{PaneId, SpawnConfig};
+use crate::pty::PaneEvent;
+use crate::widget::CockpitWidget;
+
+/// Longest time [`Harness::settle`] waits for panes to catch up.
+const SETTLE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Drives a [`PaneManager`] headlessly for tests.
+pub struct Harness {
+    manager: PaneManager,
+    backend: MockBackend,
+    buffer: Buffer,
+    events: Vec<PaneEvent>,
+}
+
+impl Harness {
+    /// Create a harness with a `width` x `height` screen and the default
+    /// manager configuration.
+    #[must_use]
+    pub fn new(width: u16, height: u16) -> Self {
+        Self::with_config(ManagerConfig::default(), width, height)
+    }
+
+    /// Create a harness with a custom manager configuration.
+    #[must_use]
+    pub fn with_config(config: ManagerConfig, width: u16, height: u16) -> Self {
+        let backend = MockBackend::new();
+        let mut manager = PaneManager::with_mock_backend(config, backend.clone());
+        let area = Rect::new(0, 0, width, height);
+        manager.set_terminal_size(area);
+        Self {
+            manager,
+            backend,
+            buffer: Buffer::empty(area),
+            events: Vec::new(),
+        }
+    }
+
+    /// Enable status bar plugins, as [`PaneManager::with_plugins`] does.
+    #[must_use]
+    pub fn with_plugins(mut self, cwd: PathBuf) -> Self {
+        self.manager = self.manager.with_plugins(cwd);
+        self
+    }
+
+    /// The manager under test.
+    #[must_use]
+    pub fn manager(&self) -> &PaneManager {
+        &self.manager
+    }
+
+    /// Mutable access to the manager under test.
+    pub fn manager_mut(&mut self) -> &mut PaneManager {
+        &mut self.manager
+    }
+
+    /// The mock backend panes are spawned on, e.g. to register scripts.
+    #[must_use]
+    pub fn backend(&self) -> &MockBackend {
+        &self.backend
+    }
+
+    /// Spawn a pane on a mock PTY.
+    ///
+    /// # Errors
+    /// Returns an error if the manager refuses the pane.
+    pub fn spawn(&mut self, config: SpawnConfig) -> Result<PaneId> {
+        Ok(self.manager.spawn(config)?.id())
+    }
+
+    /// The mock PTY of a pane.
+    ///
+    /// # Panics
+    /// Panics if the pane was not spawned through this harness.
+    #[must_use]
+    pub fn pty(&self, pane_id: PaneId) -> MockPty {
+        self.backend
+            .pane(pane_id)
+            .unwrap_or_else(|| panic!("pane {pane_id} was not spawned by the harness"))
+    }
+
+    /// Produce output in a pane, as if written by its process.
+    ///
+    /// # Panics
+    /// Panics if the pane was not spawned through this harness.
+    pub fn feed(&self, pane_id: PaneId, data: &[u8]) {
+        self.pty(pane_id).feed(data);
+    }
+
+    /// Send a key event to the focused pane.
+    ///
+    /// # Errors
+    /// Returns an error if no pane is focused or the pane is closed.
+    pub async fn key(&mut self, key: KeyEvent) -> Result<()> {
+        self.manager.route_key(key).await
+    }
+
+    /// Click a cell, as the app would on a mouse press.
+    ///
+    /// Returns `true` if the click changed focus or expansion.
+    pub fn click(&mut self, x: u16, y: u16) -> bool {
+        self.manager.handle_click(x, y)
+    }
+
+    /// Send a mouse event (press, drag or release), as the app would.
+    ///
+    /// Returns `true` if it changed focus, expansion or the selection.
+    pub fn mouse(&mut self, event: MouseEvent) -> bool {
+        self.manager.handle_mouse(event)
+    }
+
+    /// Resize the screen.
+    pub fn resize(&mut self, width: u16, height: u16) {
+        let area = Rect::new(0, 0, width, height);
+        self.manager.set_terminal_size(area);
+        self.buffer = Buffer::empty(area);
+    }
+
+    /// Wait until every pane has processed its output and written its
+    /// input, then collect pending events.
+    ///
+    /// # Panics
+    /// Panics if the panes do not settle within a few seconds.
+    pub async fn settle(&mut self) {
+        let start = std::time::Instant::now();
+        while !self.is_settled() {
+            assert!(
+                start.elapsed() < SETTLE_TIMEOUT,
+                "panes did not settle within {SETTLE_TIMEOUT:?}"
+            );
+            tokio::time::sleep(Duration::from_millis(1)).await;
+        }
+        self.events.extend(self.manager.poll_events());
+    }
+
+    /// Let `duration` pass, then settle and tick plugins, waiting for the
+    /// refreshes the tick starts.
+    ///
+    /// Under a paused tokio clock this returns without actually waiting.
+    ///
+    /// # Panics
+    /// Panics if plugin refreshes are still running after a few seconds.
+    pub async fn advance(&mut self, duration: Duration) {
+        tokio::time::sleep(duration).await;
+        self.settle().await;
+        self.manager.tick_plugins();
+        let start = std::time::Instant::now();
+        while self.manager.plugins_refreshing() {
+            assert!(
+                start.elapsed() < SETTLE_TIMEOUT,
+                "plugins did not refresh within {SETTLE_TIMEOUT:?}"
+            );
+            tokio::time::sleep(Duration::from_millis(1)).await;
+            self.manager.tick_plugins();
+        }
+    }
+
+    fn is_settled(&self) -> bool {
+        self.manager.pane_ids().into_iter().all(|id| {
+            let handle = self.manager.get_pane(id);
+            let input_pending = handle.is_some_and(crate::pane::PaneHandle::input_pending);
+            // An exit is reported once the pane has seen it
+            let exit_pending = handle.is_some_and(crate::pane::PaneHandle::is_alive)
+                && self.backend.pane(id).is_some_and(|pty| pty.has_exited());
+            !input_pending && !exit_pending && self.backend.pane(id).is_none_or(|pty| pty.is_idle())
+        })
+    }
+
+    /// Take the pane events collected by [`settle`](Self::settle).
+    pub fn take_events(&mut self) -> Vec<PaneEvent> {
+        std::mem::take(&mut self.events)
+    }
+
+    /// Draw the cockpit into the screen buffer.
+    pub fn render(&mut self) -> &Buffer {
+        self.buffer.reset();
+        let area = self.buffer.area;
+        StatefulWidget::render(
+            CockpitWidget::default().theme(self.manager.theme()),
+            area,
+            &mut self.buffer,
+            &mut self.manager.cockpit_state(),
+        );
+        if self.manager.displaying_panes() {
+            let numbered = self.manager.numbered_panes();
+            let theme = self.manager.theme();
+            PaneNumbers::new(&numbered, self.manager.focused())
+                .style(theme.pane_number_style)
+                .focused_style(theme.pane_number_focused_style)
+                .render(area, &mut self.buffer);
+        }
+        self.manager.mark_rendered();
+        &self.buffer
+    }
+
+    /// The screen as last rendered.
+    #[must_use]
+    pub fn buffer(&self) -> &Buffer {
+        &self.buffer
+    }
+
+    /// A rendered cell.
+    ///
+    /// # Panics
+    /// Panics if the position is outside the screen.
+    #[must_use]
+    pub fn cell(&self, x: u16, y: u16) -> &Cell {
+        &self.buffer[(x, y)]
+    }
+
+    /// Text of a rendered row.
+    ///
+    /// # Panics
+    /// Panics if the row is outside the screen.
+    #[must_use]
+    pub fn row_text(&self, y: u16) -> String {
+        let area = self.buffer.area;
+        assert!(y < area.height, "row {y} outside the screen");
+        (0..area.width)
+            .map(|x| self.buffer[(area.x + x, area.y + y)].symbol())
+            .collect()
+    }
+
+    /// The rendered screen as text, one line per row.
+    #[must_use]
+    pub fn screen_text(&self) -> String {
+        (0..self.buffer.area.height)
+            .map(|y| self.row_text(y))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    /// Assert that `text` appears at a position of the rendered screen.
+    ///
+    /// # Panics
+    /// Panics with the rendered screen if it does not.
+    pub fn assert_text_at(&self, x: u16, y: u16, text: &str) {
+        let row = self.row_text(y);
+        let found: String = row
+            .chars()
+            .skip(usize::from(x))
+            .take(text.chars().count())
+            .collect();
+        assert!(
+            found == text,
+            "expected {text:?} at ({x}, {y}), found {found:?}\n{}",
+            self.screen_text()
+        );
+    }
+
+    /// Assert that `text` appears on a row of the rendered screen.
+    ///
+    /// # Panics
+    /// Panics with the rendered screen if it does not.
+    pub fn assert_contains(&self, text: &str) {
+        let screen = self.screen_text();
+        assert!(
+            screen.lines().any(|row| row.contains(text)),
+            "expected {text:?} on screen\n{screen}"
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crossterm::event::{KeyCode, KeyModifiers, MouseButton, MouseEventKind};
+    use ratatui::style::{Color, Modifier, Style};
+    use ratatui::widgets::Padding;
+
+    use std::path::Path;
+
+    use super::*;
+    use crate::actions::Direction;
+    use crate::arrows::{ArrowDirection, ArrowTheme, NavigationTarget};
+    use crate::border::{BorderSet, BorderTheme};
+    use crate::error::Error;
+    use crate::frame::Theme;
+    use crate::keys::KeyboardProtocol;
+    use crate::layout::SubPaneArrangement;
+    use crate::manager::SpawnFocus;
+    use crate::pane::{CursorConfig, ExitBehavior, PaneSize, Slot, UnfocusedCursor};
+    use crate::pane::{PaneState, ScreenColor, UnderlineStyle};
+    use crate::plugins::{PaneView, Plugin, PluginConfig, PluginContext, PluginResult};
+    use crate::selection::{osc52, Selection, SelectionMode};
+    use crate::status_bar::StatusBarSegment;
+    use crate::widget::{ActivityIndicator, DimMode, PaneWidget};
+
+    #[tokio::test]
+    async fn test_harness_drives_panes() {
+        let mut harness = Harness::new(40, 10);
+        let left = harness.spawn(SpawnConfig::new_shell()).unwrap();
+        let right = harness.spawn(SpawnConfig::new_shell()).unwrap();
+        harness.feed(left, b"left");
+        harness.feed(right, b"right");
+        harness.settle().await;
+
+        harness.render();
+        harness.assert_text_at(1, 1, "left");
+        harness.assert_contains("right");
+
+        // Focus the right pane with a click, then type into it
+        let right_area = harness.manager().get_areas()[&right];
+        assert!(harness.click(right_area.x + 2, right_area.y + 2));
+        harness
+            .key(KeyEvent::new(KeyCode::Char('x'), KeyModifiers::NONE))
+            .await
+            .unwrap();
+        harness.settle().await;
+        assert_eq!(harness.pty(right).input(), b"x");
+        assert!(harness.pty(left).input().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_manager_state_events() {
+        let mut harness = Harness::new(40, 10);
+        let left = harness.spawn(SpawnConfig::new_shell()).unwrap();
+        let right = harness.spawn(SpawnConfig::new_shell()).unwrap();
+        harness.settle().await;
+        let events = harness.take_events();
+        assert!(events
+            .iter()
+            .any(|e| matches!(e, PaneEvent::PaneSpawned { pane_id } if *pane_id == right)));
+        assert!(events.iter().any(|e| matches!(
+            e,
+            PaneEvent::FocusChanged { from: None, to } if *to == Some(left)
+        )));
+
+        // The emulator follows the PTY when the layout changes
+        let size = harness.pty(left).size();
+        assert!(events.iter().any(|e| matches!(
+            e,
+            PaneEvent::Resized { pane_id, size: s } if *pane_id == left && *s == size
+        )));
+        let screen = harness.manager().get_pane(left).unwrap().screen_snapshot();
+        assert_eq!(screen.size(), size);
+
+        harness.manager_mut().focus_next();
+        harness.manager_mut().close_pane(right);
+        harness.settle().await;
+        let events = harness.take_events();
+        assert!(events.iter().any(|e| matches!(
+            e,
+            PaneEvent::FocusChanged { from, to } if *from == Some(left) && *to == Some(right)
+        )));
+        assert!(events
+            .iter()
+            .any(|e| matches!(e, PaneEvent::PaneClosed { pane_id } if *pane_id == right)));
+
+        harness.resize(60, 12);
+        harness.settle().await;
+        assert!(harness
+            .take_events()
+            .iter()
+            .any(|e| matches!(e, PaneEvent::Resized { pane_id, .. } if *pane_id == left)));
+    }
+
+    #[tokio::test]
+    async fn test_subscribe_updates() {
+        let mut harness = Harness::new(40, 10);
+        let pane = harness.spawn(SpawnConfig::new_shell()).unwrap();
+        harness.settle().await;
+        let mut updates = harness
+            .manager()
+            .get_pane(pane)
+            .unwrap()
+            .subscribe_updates();
+        let seen = *updates.borrow_and_update();
+        assert!(!updates.has_changed().unwrap());
+
+        // The subscriber is woken by output, without polling the manager
+        let waiter = tokio::spawn(async move {
+            updates.changed().await.unwrap();
+            *updates.borrow()
+        });
+        harness.pty(pane).feed(b"hello");
+        let generation = tokio::time::timeout(SETTLE_TIMEOUT, waiter)
+            .await
+            .expect("subscriber was not woken")
+            .unwrap();
+        assert!(generation > seen);
+    }
+
+    #[tokio::test]
+    async fn test_output_coalescing() {
+        let mut harness = Harness::new(40, 10);
+        let pane = harness.spawn(SpawnConfig::new_shell()).unwrap();
+        harness.settle().await;
+        let handle = harness.manager().get_pane(pane).unwrap().clone();
+        let pty = harness.pty(pane);
+
+        // Separate reads, reported by a single event
+        let mut total = handle.stats().bytes_read;
+        for chunk in [&b"one"[..], b"two", b"three"] {
+            pty.feed(chunk);
+            total += chunk.len() as u64;
+            wait_for(|| handle.stats().bytes_read == total && pty.is_idle()).await;
+        }
+        let outputs: Vec<_> = harness
+            .manager_mut()
+            .poll_events()
+            .into_iter()
+            .filter_map(|e| match e {
+                PaneEvent::Output { pane_id, size } => Some((pane_id, size)),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(outputs, [(pane, 11)]);
+        assert!(harness.manager_mut().poll_events().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_render_while_screen_locked() {
+        let mut harness = Harness::new(40, 10);
+        let mut theme = harness.manager().theme().clone();
+        theme.activity_indicator = None;
+        harness.manager_mut().set_theme(theme);
+        let pane = harness.spawn(SpawnConfig::new_shell()).unwrap();
+        harness.feed(pane, b"first");
+        harness.settle().await;
+        let handle = harness.manager().get_pane(pane).unwrap().clone();
+        let render = |area: Rect| {
+            let mut buffer = Buffer::empty(area);
+            PaneWidget::new(&handle).render(area, &mut buffer);
+            buffer
+        };
+        let (area, wide) = (Rect::new(0, 0, 20, 5), Rect::new(0, 0, 30, 6));
+        assert_eq!(render(area)[(1, 1)].symbol(), "f");
+
+        // Newer output waits while the reader holds the screen, and the
+        // widget draws the last frame again instead of blocking
+        harness.feed(pane, b"\rsecond");
+        harness.settle().await;
+        let guard = handle.screen().write().unwrap();
+        assert_eq!(render(area)[(1, 1)].symbol(), "f");
+        drop(guard);
+        assert_eq!(render(area)[(1, 1)].symbol(), "s");
+
+        // A frame for another area is cut from the last one until the
+        // screen can be read again
+        let guard = handle.screen().write().unwrap();
+        assert_eq!(render(wide)[(1, 1)].symbol(), "s");
+        harness.manager_mut().mark_rendered();
+        assert!(harness.manager().needs_render());
+        drop(guard);
+        assert_eq!(render(wide)[(1, 1)].symbol(), "s");
+        harness.manager_mut().mark_rendered();
+        assert!(!harness.manager().needs_render());
+    }
+
+    #[tokio::test]
+    async fn test_screen_mode_events() {
+        let mut harness = Harness::new(40, 10);
+        let pane = harness.spawn(SpawnConfig::new_shell()).unwrap();
+        harness.settle().await;
+        harness.take_events();
+        let switches = |harness: &mut Harness| -> Vec<bool> {
+            harness
+                .take_events()
+                .iter()
+                .filter_map(|e| match e {
+                    PaneEvent::ScreenModeChanged { pane_id, alternate } if *pane_id == pane => {
+                        Some(*alternate)
+                    }
+                    _ => None,
+                })
+                .collect()
+        };
+
+        // One event per switch, however the program repeats itself
+        harness.feed(pane, b"\x1b[?1049h");
+        harness.settle().await;
+        assert_eq!(switches(&mut harness), [true]);
+        assert!(harness
+            .manager()
+            .get_pane(pane)
+            .unwrap()
+            .is_alternate_screen());
+        harness.feed(pane, b"\x1b[?1049hmenu");
+        harness.settle().await;
+        assert!(switches(&mut harness).is_empty());
+
+        harness.feed(pane, b"\x1b[?1049l");
+        harness.settle().await;
+        assert_eq!(switches(&mut harness), [false]);
+        assert!(!harness
+            .manager()
+            .get_pane(pane)
+            .unwrap()
+            .is_alternate_screen());
+        harness.feed(pane, b"$ ");
+        harness.settle().await;
+        assert!(switches(&mut harness).is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_manager_config() {
+        assert!(ManagerConfig::builder().max_panes(5).build().is_err());
+        assert!(ManagerConfig::builder()
+            .sub_pane_ratio(0.0)
+            .build()
+            .is_err());
+        assert!(ManagerConfig::builder().default_shell("").build().is_err());
+        assert!(PaneManager::try_with_config(ManagerConfig {
+            event_capacity: 0,
+            ..ManagerConfig::default()
+        })
+        .is_err());
+
+        let config = ManagerConfig::builder()
+            .spawn_focus(SpawnFocus::Never)
+            .pane_padding(Padding::uniform(1))
+            .build()
+            .unwrap();
+        let mut harness = Harness::with_config(config, 40, 10);
+        let pane = harness.spawn(SpawnConfig::new_shell()).unwrap();
+        assert_eq!(harness.manager().focused(), None);
+
+        // Padding shrinks the PTY and shifts the content inward
+        let area = harness.manager().get_areas()[&pane];
+        let size = harness.pty(pane).size();
+        assert_eq!(size.cols, area.width - 4);
+        assert_eq!(size.rows, area.height - 4);
+        harness.feed(pane, b"padded");
+        harness.settle().await;
+        harness.render();
+        harness.assert_text_at(area.x + 2, area.y + 2, "padded");
+
+        // Panes describe cockpit's emulator unless the spawn config says otherwise
+        let env = harness.pty(pane).env().clone();
+        assert_eq!(env["TERM"], "xterm-256color");
+        assert_eq!(env["COLORTERM"], "truecolor");
+        let other = harness
+            .spawn(SpawnConfig::new_shell().term("vt100"))
+            .unwrap();
+        assert_eq!(harness.pty(other).env()["TERM"], "vt100");
+    }
+
+    #[tokio::test]
+    async fn test_sub_pane_slots() {
+        let mut harness = Harness::new(80, 20);
+        let main = harness.spawn(SpawnConfig::new_shell()).unwrap();
+        let sub = harness
+            .spawn(SpawnConfig::new_command("top").slot(Slot::SubPane(112)))
+            .unwrap();
+        assert!(matches!(
+            harness.spawn(SpawnConfig::new_shell().slot(Slot::SubPane(112))),
+            Err(Error::Layout(_))
+        ));
+        assert!(harness
+            .spawn(SpawnConfig::new_shell().slot(Slot::SubPane(130)))
+            .is_err());
+        assert_eq!(harness.manager().get_sub_pane_slots()[1], Some(sub));
+
+        // The PTY is sized to the sub-pane, and the main pane keeps position 0
+        let area = harness.manager().get_sub_pane_areas()[1];
+        assert_eq!(harness.manager().get_areas()[&sub], area);
+        assert_eq!(harness.pty(sub).size().cols, area.width - 2);
+        assert_eq!(harness.manager().get_areas()[&main].x, 0);
+
+        harness.feed(sub, b"load");
+        harness.settle().await;
+        harness.render();
+        harness.assert_text_at(area.x, area.y + 1, "load");
+
+        assert!(harness.click(area.x + 2, area.y + 2));
+        assert_eq!(harness.manager().focused(), Some(sub));
+        harness
+            .key(KeyEvent::new(KeyCode::Char('q'), KeyModifiers::NONE))
+            .await
+            .unwrap();
+        harness.settle().await;
+        assert_eq!(harness.pty(sub).input(), b"q");
+
+        harness.manager_mut().close_pane(sub);
+        assert_eq!(harness.manager().get_sub_pane_slots()[1], None);
+    }
+
+    #[tokio::test]
+    async fn test_stateful_cockpit() {
+        let mut harness = Harness::new(80, 20);
+        let left = harness.spawn(SpawnConfig::new_shell()).unwrap();
+        let right = harness.spawn(SpawnConfig::new_shell()).unwrap();
+        let sub = harness
+            .spawn(SpawnConfig::new_command("top").slot(Slot::SubPane(112)))
+            .unwrap();
+        harness.feed(left, b"left");
+        harness.feed(right, b"right");
+        harness.feed(sub, b"load");
+        harness.settle().await;
+        let stateful = harness.render().clone();
+
+        // Drawing from the manager matches passing its layout by hand
+        let manager = harness.manager();
+        let areas: Vec<_> = manager
+            .get_areas()
+            .iter()
+            .map(|(&id, &a)| (id, a))
+            .collect();
+        let panes: Vec<_> = [left, right, sub]
+            .into_iter()
+            .map(|id| (id, manager.get_pane(id).unwrap()))
+            .collect();
+        let widget = CockpitWidget::new(&panes, &areas, manager.focused())
+            .theme(manager.theme())
+            .sub_panes(manager.get_sub_pane_areas())
+            .sub_pane_slots(manager.get_sub_pane_slots())
+            .sub_pane_arrangement(manager.sub_pane_arrangement())
+            .expansion(
+                *manager.get_expanded_positions(),
+                *manager.get_horizontal_expanded(),
+            )
+            .empty_panes(manager.get_empty_pane_areas())
+            .color_mode(manager.color_mode())
+            .cursor_blink_on(manager.cursor_blink_on());
+        let mut buffer = Buffer::empty(stateful.area);
+        Widget::render(widget, stateful.area, &mut buffer);
+        assert_eq!(buffer, stateful);
+        harness.assert_contains("load");
+
+        // The leftmost pane keeps its left border
+        assert_eq!(stateful[(0, 5)].symbol(), "│");
+    }
+
+    #[tokio::test]
+    async fn test_sub_pane_arrangement() {
+        let config = ManagerConfig::builder()
+            .sub_panes(
+                SubPaneArrangement::default()
+                    .position(0, vec![1, 1, 2])
+                    .position(1, vec![]),
+            )
+            .build()
+            .unwrap();
+        let mut harness = Harness::with_config(config, 80, 20);
+        let left = harness.spawn(SpawnConfig::new_shell()).unwrap();
+        let right = harness.spawn(SpawnConfig::new_shell()).unwrap();
+        assert!(harness
+            .spawn(SpawnConfig::new_shell().slot(Slot::SubPane(121)))
+            .is_err());
+        let sub = harness
+            .spawn(SpawnConfig::new_shell().slot(Slot::SubPane(113)))
+            .unwrap();
+
+        // Sub-panes follow the declared widths; 120 has none and gets the full height
+        let areas = harness.manager().get_sub_pane_areas().to_vec();
+        assert_eq!(areas.len(), 7);
+        assert_eq!((areas[0].width, areas[1].width, areas[2].width), (5, 5, 10));
+        assert_eq!(harness.manager().get_areas()[&sub], areas[2]);
+        assert_eq!(harness.manager().get_areas()[&right].height, 20);
+        assert!(harness.manager().get_areas()[&left].height < 20);
+
+        harness.render();
+        harness.assert_contains("V");
+
+        // The down arrow sits in 111, the outermost sub-pane of position 0
+        assert!(harness.click(areas[0].x + 3, areas[0].y + areas[0].height - 3));
+        assert!(harness.manager().get_expanded_positions()[0]);
+        assert_eq!(harness.manager().get_areas()[&left].height, 20);
+    }
+
+    #[tokio::test]
+    async fn test_arrow_theme() {
+        let theme = Theme {
+            arrows: ArrowTheme::buttons(),
+            ..Theme::default()
+        };
+        let config = ManagerConfig::builder().theme(theme).build().unwrap();
+        let mut harness = Harness::with_config(config, 80, 20);
+        let pane = harness.spawn(SpawnConfig::new_shell()).unwrap();
+
+        // Buttons sit on the bottom border of the sub-panes
+        let regions = harness.manager().navigation_regions();
+        let expand = regions
+            .iter()
+            .find(|r| r.direction == ArrowDirection::Down)
+            .copied()
+            .unwrap();
+        let sub_area = harness.manager().get_sub_pane_areas()[0];
+        assert_eq!(
+            expand.area,
+            Rect::new(sub_area.x + 1, sub_area.bottom() - 1, 3, 1)
+        );
+        harness.render();
+        harness.assert_text_at(expand.area.x, expand.area.y, "[+]");
+
+        assert!(harness.click(expand.area.x + 1, expand.area.y));
+        assert!(harness.manager().get_expanded_positions()[0]);
+        let collapse = harness.manager().navigation_regions()[0];
+        assert_eq!(collapse.target, NavigationTarget::Collapse(0));
+        harness.render();
+        harness.assert_text_at(collapse.area.x, collapse.area.y, "[−]");
+        assert_eq!(
+            collapse.area.y,
+            harness.manager().get_areas()[&pane].bottom() - 1
+        );
+    }
+
+    #[tokio::test]
+    async fn test_spawn_here_follows_focused_cwd() {
+        let mut harness = Harness::new(40, 10);
+        let first = harness.spawn(SpawnConfig::new_shell()).unwrap();
+        harness.feed(first, b"\x1b]7;file://host/srv/app\x1b\\");
+        harness.settle().await;
+
+        let here = harness
+            .manager_mut()
+            .spawn_here(SpawnConfig::new_shell())
+            .unwrap();
+        assert_eq!(harness.pty(here.id()).cwd(), Some(Path::new("/srv/app")));
+        let explicit = harness
+            .manager_mut()
+            .spawn_here(SpawnConfig::new_shell().cwd("/tmp"))
+            .unwrap();
+        assert_eq!(harness.pty(explicit.id()).cwd(), Some(Path::new("/tmp")));
+    }
+
+    #[tokio::test]
+    async fn test_duplicate_pane() {
+        let mut harness = Harness::new(40, 10);
+        let original = harness
+            .spawn(
+                SpawnConfig::new_command("tail")
+                    .args(vec!["-f".into(), "log".into()])
+                    .env("MODE", "dev"),
+            )
+            .unwrap();
+        harness.feed(original, b"\x1b]7;file://host/var/log\x07");
+        harness.settle().await;
+        let copy = harness.manager_mut().duplicate_pane(original).unwrap();
+        let pty = harness.pty(copy.id());
+        assert_eq!(pty.command(), "tail");
+        assert_eq!(pty.args(), ["-f", "log"]);
+        assert_eq!(pty.env()["MODE"], "dev");
+        assert_eq!(pty.cwd(), Some(Path::new("/var/log")));
+    }
+
+    #[tokio::test]
+    async fn test_spawn_profile() {
+        let mut harness = Harness::new(40, 10);
+        harness.manager_mut().profiles_mut().register(
+            "db-shell",
+            SpawnConfig::new_command("psql").env("PGDATABASE", "app"),
+        );
+        assert!(matches!(
+            harness.manager_mut().spawn_profile("missing"),
+            Err(Error::ProfileNotFound(name)) if name == "missing"
+        ));
+
+        let plain = harness.manager_mut().spawn_profile("db-shell").unwrap();
+        let custom = harness
+            .manager_mut()
+            .spawn_profile_with("db-shell", |config| {
+                config.cwd("/srv").env("PGDATABASE", "test")
+            })
+            .unwrap();
+        assert_eq!(harness.pty(plain.id()).command(), "psql");
+        assert_eq!(harness.pty(plain.id()).env()["PGDATABASE"], "app");
+        assert_eq!(harness.pty(custom.id()).env()["PGDATABASE"], "test");
+        assert_eq!(harness.pty(custom.id()).cwd(), Some(Path::new("/srv")));
+    }
+
+    #[tokio::test]
+    async fn test_exit_behavior() {
+        let mut harness = Harness::new(60, 10);
+        let restart = ExitBehavior::Restart {
+            max: Some(1),
+            backoff: Duration::ZERO,
+        };
+        let server = harness
+            .spawn(SpawnConfig::new_command("server").on_exit(restart.clone()))
+            .unwrap();
+        let oneshot = harness
+            .spawn(SpawnConfig::new_command("build").on_exit(ExitBehavior::ClosePane))
+            .unwrap();
+        let fallback = ExitBehavior::RunCommand(Box::new(SpawnConfig::new_command("sh")));
+        let repl = harness
+            .spawn(SpawnConfig::new_command("repl").on_exit(fallback))
+            .unwrap();
+        let slot = harness.manager().get_areas()[&server];
+
+        for id in [server, oneshot, repl] {
+            harness.pty(id).exit(0);
+        }
+        harness.settle().await;
+        let ids = harness.manager().pane_ids();
+        assert_eq!(ids.len(), 2);
+        let commands: Vec<_> = ids
+            .iter()
+            .map(|id| harness.pty(*id).command().to_string())
+            .collect();
+        assert!(commands.contains(&"server".to_string()));
+        assert!(commands.contains(&"sh".to_string()));
+
+        // The restarted server keeps its slot, and is not restarted twice
+        let restarted = ids[commands.iter().position(|c| c == "server").unwrap()];
+        assert_ne!(restarted, server);
+        assert_eq!(harness.manager().get_areas()[&restarted].x, slot.x);
+        harness.pty(restarted).exit(1);
+        harness.settle().await;
+        assert!(harness.manager().get_pane(restarted).is_some());
+
+        // Unless it stayed up long enough to count as having come up
+        harness.manager_mut().close_pane(restarted);
+        let watcher = harness
+            .spawn(SpawnConfig::new_command("watcher").on_exit(restart))
+            .unwrap();
+        let watchers = |harness: &Harness| -> Vec<PaneId> {
+            let manager = harness.manager();
+            manager
+                .pane_ids()
+                .into_iter()
+                .filter(|id| harness.pty(*id).command() == "watcher")
+                .filter(|id| {
+                    manager
+                        .get_pane(*id)
+                        .is_some_and(crate::pane::PaneHandle::is_alive)
+                })
+                .collect()
+        };
+        harness.pty(watcher).exit(0);
+        harness.settle().await;
+        let [restarted] = watchers(&harness)[..] else {
+            panic!("watcher was not restarted");
+        };
+        tokio::time::sleep(Duration::from_millis(1100)).await;
+        harness.pty(restarted).exit(0);
+        harness.settle().await;
+        let [again] = watchers(&harness)[..] else {
+            panic!("watcher was not restarted again");
+        };
+        assert_ne!(again, restarted);
+    }
+
+    #[tokio::test]
+    async fn test_default_exit_behavior() {
+        let config = ManagerConfig::builder()
+            .on_exit(ExitBehavior::ClosePane)
+            .build()
+            .unwrap();
+        let mut harness = Harness::with_config(config, 60, 10);
+        let build = harness.spawn(SpawnConfig::new_command("build")).unwrap();
+        let shell = harness
+            .spawn(SpawnConfig::new_command("sh").on_exit(ExitBehavior::KeepPane))
+            .unwrap();
+
+        harness.pty(build).exit(0);
+        harness.pty(shell).exit(0);
+        harness.settle().await;
+        assert_eq!(harness.manager().pane_ids(), [shell]);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_signal_exited_pane() {
+        let mut harness = Harness::new(40, 8);
+        let pane = harness.spawn(SpawnConfig::new_command("server")).unwrap();
+        harness.settle().await;
+        let handle = harness.manager().get_pane(pane).unwrap().clone();
+        // Mock processes have no process ID to signal
+        let error = handle.signal(crate::pane::Signal::Hup).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "failed to signal process: pane has no process ID"
+        );
+
+        harness.pty(pane).exit(0);
+        harness.settle().await;
+        let error = handle.signal(crate::pane::Signal::Hup).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "failed to signal process: pane process has exited"
+        );
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_foreground_pid() {
+        let mut harness = Harness::new(40, 8);
+        let pane = harness.spawn(SpawnConfig::new_shell()).unwrap();
+        let handle = harness.manager().get_pane(pane).unwrap().clone();
+        assert_eq!(handle.foreground_pid(), None);
+
+        // As if the shell had started this test's process
+        let pid = std::process::id();
+        harness.pty(pane).set_foreground_pid(Some(pid));
+        assert_eq!(handle.foreground_pid(), Some(pid));
+        #[cfg(target_os = "linux")]
+        assert_eq!(
+            handle.foreground_process().unwrap(),
+            std::fs::read_to_string("/proc/self/comm")
+                .unwrap()
+                .trim_end()
+        );
+
+        // Closed panes have no terminal to ask
+        harness.manager_mut().close_pane(pane);
+        assert_eq!(handle.foreground_pid(), None);
+    }
+
+    #[tokio::test]
+    async fn test_exit_overlay() {
+        let mut harness = Harness::new(160, 8);
+        let pane = harness.spawn(SpawnConfig::new_command("build")).unwrap();
+        harness.feed(pane, b"compiling");
+        harness.settle().await;
+        harness.render();
+        assert!(!harness.cell(1, 1).modifier.contains(Modifier::DIM));
+
+        harness.pty(pane).exit(3);
+        harness.settle().await;
+        harness.render();
+        harness.assert_contains("Exited with code 3 after 0s");
+        harness.assert_text_at(1, 1, "compiling");
+        assert!(harness.cell(1, 1).modifier.contains(Modifier::DIM));
+    }
+
+    #[tokio::test]
+    async fn test_activity_indicator() {
+        let mut harness = Harness::new(40, 8);
+        let mut theme = harness.manager().theme().clone();
+        theme.activity_indicator = Some(ActivityIndicator::badge("●"));
+        harness.manager_mut().set_theme(theme);
+        let pane = harness.spawn(SpawnConfig::new_command("build")).unwrap();
+        harness.feed(pane, b"compiling");
+        harness.settle().await;
+        harness.render();
+        assert_eq!(harness.cell(7, 0).symbol(), "●");
+        assert_eq!(harness.cell(7, 0).fg, Color::Yellow);
+        assert!(harness.manager().needs_render());
+
+        // Once output stops, one more render clears it
+        std::thread::sleep(Duration::from_millis(600));
+        assert!(harness.manager().needs_render());
+        harness.render();
+        assert_eq!(harness.cell(7, 0).symbol(), "─");
+        assert!(!harness.manager().needs_render());
+    }
+
+    #[tokio::test]
+    async fn test_needs_render() {
+        let mut harness = Harness::new(40, 10);
+        // Busy panes keep needing renders while the indicator is shown
+        let mut theme = harness.manager().theme().clone();
+        theme.activity_indicator = None;
+        harness.manager_mut().set_theme(theme);
+        let pane = harness.spawn(SpawnConfig::new_shell()).unwrap();
+        harness.spawn(SpawnConfig::new_shell()).unwrap();
+        harness.settle().await;
+        assert!(harness.manager().needs_render());
+        harness.render();
+        assert!(!harness.manager().needs_render());
+
+        harness.feed(pane, b"output");
+        harness.settle().await;
+        assert!(harness.manager().needs_render());
+        harness.render();
+        assert!(!harness.manager().needs_render());
+
+        harness.resize(60, 12);
+        assert!(harness.manager().needs_render());
+        harness.render();
+        assert!(!harness.manager().needs_render());
+
+        harness.manager_mut().focus_next();
+        assert!(harness.manager().needs_render());
+        harness.render();
+        assert!(!harness.manager().needs_render());
+    }
+
+    #[tokio::test]
+    async fn test_pane_titles() {
+        let mut harness = Harness::new(120, 8);
+        let server = harness
+            .spawn(SpawnConfig::new_command("npm").name("server"))
+            .unwrap();
+        let editor = harness.spawn(SpawnConfig::new_shell()).unwrap();
+        harness.feed(editor, b"\x1b]0;vim main.rs\x07");
+        harness.settle().await;
+        let events = harness.take_events();
+        assert!(events.iter().any(|event| matches!(
+            event,
+            PaneEvent::TitleChanged { pane_id, title }
+                if *pane_id == editor && title == "vim main.rs"
+        )));
+        assert_eq!(
+            harness.manager().get_pane(server).unwrap().name(),
+            Some("server")
+        );
+
+        harness.render();
+        harness.assert_text_at(0, 0, "┌ server ─");
+        harness.assert_text_at(31, 0, " vim main.rs ─");
+        assert!(harness.cell(2, 0).modifier.contains(Modifier::BOLD));
+    }
+
+    #[tokio::test]
+    async fn test_border_theme() {
+        let mut harness = Harness::new(40, 8);
+        let mut theme = harness.manager().theme().clone();
+        theme.borders = BorderTheme {
+            set: BorderSet::Rounded,
+            focus_style: Style::default().fg(Color::Green),
+            padding: Padding::horizontal(1),
+            ..BorderTheme::default()
+        };
+        harness.manager_mut().set_theme(theme);
+        let pane = harness.spawn(SpawnConfig::new_shell()).unwrap();
+        harness.feed(pane, b"$ ");
+        harness.settle().await;
+
+        harness.render();
+        assert_eq!(harness.cell(0, 0).symbol(), "╭");
+        assert_eq!(harness.cell(0, 0).fg, Color::Green);
+        harness.assert_text_at(2, 1, "$");
+        // Empty slots use the same line set
+        assert_eq!(harness.cell(0, 7).symbol(), "╰");
+        assert_eq!(harness.cell(19, 7).symbol(), "╯");
+    }
+
+    #[tokio::test]
+    async fn test_pane_content_area() {
+        let config = ManagerConfig::builder()
+            .sub_panes(SubPaneArrangement::default().position(0, vec![]))
+            .pane_padding(Padding::horizontal(1))
+            .build()
+            .unwrap();
+        let mut harness = Harness::with_config(config, 80, 20);
+        let left = harness.spawn(SpawnConfig::new_shell()).unwrap();
+        let right = harness.spawn(SpawnConfig::new_shell()).unwrap();
+        // The first visible sub-pane, though not the leftmost pane
+        let sub = harness
+            .spawn(SpawnConfig::new_shell().slot(Slot::SubPane(121)))
+            .unwrap();
+        for (pane, text) in [(left, "left"), (right, "right"), (sub, "sub")] {
+            harness.feed(pane, text.as_bytes());
+        }
+        harness.settle().await;
+        harness.render();
+
+        for (pane, text) in [(left, "left"), (right, "right"), (sub, "sub")] {
+            let inner = harness.manager().pane_content_area(pane).unwrap();
+            harness.assert_text_at(inner.x, inner.y, text);
+            // Padding, then the border, lies just outside it
+            assert_eq!(harness.cell(inner.x - 1, inner.y).symbol(), " ");
+            assert_eq!(harness.cell(inner.x - 2, inner.y).symbol(), "│");
+            assert_eq!(harness.cell(inner.x, inner.y - 1).symbol(), "─");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_dim_unfocused() {
+        let mut harness = Harness::new(40, 8);
+        let mut theme = harness.manager().theme().clone();
+        theme.dim_unfocused = Some(DimMode::Dim);
+        harness.manager_mut().set_theme(theme.clone());
+        let left = harness.spawn(SpawnConfig::new_shell()).unwrap();
+        let right = harness.spawn(SpawnConfig::new_shell()).unwrap();
+        harness.feed(left, b"left");
+        harness.feed(right, b"\x1b[31mright");
+        harness.settle().await;
+        assert_eq!(harness.manager().focused(), Some(left));
+
+        harness.render();
+        assert!(!harness.cell(1, 1).modifier.contains(Modifier::DIM));
+        assert!(harness.cell(10, 1).modifier.contains(Modifier::DIM));
+
+        theme.dim_unfocused = Some(DimMode::Desaturate);
+        harness.manager_mut().set_theme(theme);
+        harness.render();
+        assert!(!harness.cell(10, 1).modifier.contains(Modifier::DIM));
+        assert_eq!(harness.cell(10, 1).fg, Color::Rgb(61, 61, 61));
+    }
+
+    #[tokio::test]
+    async fn test_pane_colors() {
+        let mut harness = Harness::new(40, 8);
+        let frontend = harness
+            .spawn(SpawnConfig::new_shell().accent(Color::Blue))
+            .unwrap();
+        let backend = harness
+            .spawn(
+                SpawnConfig::new_shell()
+                    .accent(Color::Green)
+                    .tint(Color::Indexed(22)),
+            )
+            .unwrap();
+        harness.feed(backend, b"\x1b[41mred\x1b[m ok");
+        harness.settle().await;
+        assert_eq!(harness.manager().focused(), Some(frontend));
+
+        harness.render();
+        // The focused pane keeps the focus style
+        assert_eq!(harness.cell(0, 0).fg, Color::Cyan);
+        assert_eq!(harness.cell(15, 0).fg, Color::Green);
+        assert_eq!(harness.cell(15, 0).bg, Color::Indexed(22));
+        assert_eq!(harness.cell(10, 1).bg, Color::Indexed(1));
+        assert_eq!(harness.cell(14, 1).bg, Color::Indexed(22));
+        assert_eq!(harness.cell(15, 3).bg, Color::Indexed(22));
+
+        harness.manager_mut().set_focus(backend);
+        harness.render();
+        assert_eq!(harness.cell(0, 0).fg, Color::Blue);
+        assert_eq!(harness.cell(15, 0).fg, Color::Cyan);
+    }
+
+    #[tokio::test]
+    async fn test_extra_attributes() {
+        let mut harness = Harness::new(120, 6);
+        let pane = harness.spawn(SpawnConfig::new_shell()).unwrap();
+        harness.feed(
+            pane,
+            b"\x1b[2mdim\x1b[0m \x1b[5;9mgone\x1b[0m \x1b[4:3;58;5;1mcurly\x1b[0m",
+        );
+        harness.settle().await;
+
+        harness.render();
+        assert!(harness.cell(1, 1).modifier.contains(Modifier::DIM));
+        assert!(!harness.cell(4, 1).modifier.contains(Modifier::DIM));
+        let gone = harness.cell(5, 1).modifier;
+        assert!(gone.contains(Modifier::CROSSED_OUT | Modifier::SLOW_BLINK));
+        let curly = harness.cell(10, 1);
+        assert!(curly.modifier.contains(Modifier::UNDERLINED));
+        assert_eq!(curly.underline_color, Color::Indexed(1));
+
+        let snapshot = harness.manager().get_pane(pane).unwrap().screen_snapshot();
+        let cell = |col| snapshot.cell(0, col).unwrap().clone();
+        assert!(cell(0).dim);
+        assert!(cell(4).blink && cell(4).strikethrough);
+        assert!(cell(9).underline);
+        assert_eq!(cell(9).underline_style, UnderlineStyle::Curly);
+        assert_eq!(cell(9).underline_color, ScreenColor::Indexed(1));
+        assert_eq!(cell(14).underline_style, UnderlineStyle::None);
+    }
+
+    #[tokio::test]
+    async fn test_display_panes() {
+        let mut harness = Harness::new(40, 10);
+        let first = harness.spawn(SpawnConfig::new_shell()).unwrap();
+        harness.spawn(SpawnConfig::new_shell()).unwrap();
+        harness.manager_mut().display_panes();
+        assert!(harness.manager().needs_render());
+
+        harness.render();
+        assert_eq!(harness.cell(3, 1).symbol(), " ");
+        assert_eq!(harness.cell(4, 1).symbol(), "█");
+        assert_eq!(harness.cell(4, 1).fg, Color::Red);
+        assert_eq!(harness.cell(13, 1).symbol(), "█");
+        assert_eq!(harness.cell(13, 1).fg, Color::Blue);
+
+        // A key press picks a pane, or hides the numbers
+        let key = KeyEvent::new(KeyCode::Esc, KeyModifiers::NONE);
+        assert!(harness.manager_mut().display_panes_key(key));
+        assert_eq!(harness.manager().focused(), Some(first));
+        harness.render();
+        assert_eq!(harness.cell(4, 1).symbol(), " ");
+        assert!(!harness.manager_mut().display_panes_key(key));
+    }
+
+    #[tokio::test]
+    async fn test_cursor_config() {
+        let mut harness = Harness::new(80, 20);
+        let shell = harness.spawn(SpawnConfig::new_shell()).unwrap();
+        let cursor = CursorConfig {
+            unfocused: UnfocusedCursor::Hollow,
+            blink: true,
+            color: Some(Color::Green),
+            ..CursorConfig::default()
+        };
+        let editor = harness
+            .spawn(SpawnConfig::new_shell().cursor(cursor))
+            .unwrap();
+        harness.feed(shell, b"ab");
+        harness.settle().await;
+        let shell_area = harness.manager().get_areas()[&shell];
+        let editor_area = harness.manager().get_areas()[&editor];
+        let (x, y) = (shell_area.x + 1, shell_area.y + 1);
+
+        harness.render();
+        assert!(harness.cell(x + 2, y).modifier.contains(Modifier::REVERSED));
+        // Unfocused, the editor's cursor is an outline
+        assert_eq!(harness.cell(editor_area.x, y).symbol(), "▯");
+        assert_eq!(harness.cell(editor_area.x, y).fg, Color::Green);
+
+        // The program can hide its cursor
+        harness.feed(shell, b"\x1b[?25l");
+        harness.settle().await;
+        harness.render();
+        assert!(!harness.cell(x + 2, y).modifier.contains(Modifier::REVERSED));
+
+        // A blinking cursor is drawn in its visible phase only
+        harness.manager_mut().set_focus(editor);
+        let pane = harness.manager().get_pane(editor).unwrap().clone();
+        for (on, bg) in [(true, Color::Green), (false, Color::Reset)] {
+            let mut buffer = Buffer::empty(editor_area);
+            PaneWidget::new(&pane)
+                .focused(true)
+                .cursor_blink_on(on)
+                .render(editor_area, &mut buffer);
+            assert_eq!(buffer[(editor_area.x + 1, y)].bg, bg);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_scrollback_control() {
+        let mut harness = Harness::new(40, 10);
+        let id = harness
+            .spawn(SpawnConfig::new_shell().scrollback(100))
+            .unwrap();
+        let lines = (0..50)
+            .map(|i| format!("line {i}"))
+            .collect::<Vec<_>>()
+            .join("\r\n");
+        harness.feed(id, lines.as_bytes());
+        harness.settle().await;
+        let pane = harness.manager().get_pane(id).unwrap().clone();
+        assert!(pane.scrollback_len() > 30);
+
+        pane.clear_scrollback();
+        assert_eq!(pane.scrollback_len(), 0);
+        assert!(pane.screen_snapshot().contains("line 49"));
+
+        pane.set_scrollback(5);
+        assert_eq!(pane.scrollback_limit(), 5);
+        harness.feed(id, lines.as_bytes());
+        harness.settle().await;
+        assert_eq!(pane.scrollback_len(), 5);
+
+        pane.clear_screen();
+        assert!(!pane.screen_snapshot().contains("line"));
+    }
+
+    /// Wait until `condition` holds.
+    async fn wait_for(mut condition: impl FnMut() -> bool) {
+        let start = std::time::Instant::now();
+        while !condition() {
+            assert!(start.elapsed() < SETTLE_TIMEOUT, "condition not met");
+            tokio::time::sleep(Duration::from_millis(1)).await;
+        }
+    }
+
+    /// How long a paused reader is given to read anyway.
+    const PAUSE_CHECK: Duration = Duration::from_millis(50);
+
+    #[tokio::test]
+    async fn test_flow_control() {
+        let mut harness = Harness::new(80, 20);
+        harness.spawn(SpawnConfig::new_shell()).unwrap();
+        let pane = harness
+            .spawn(
+                SpawnConfig::new_shell()
+                    .flow_control(true)
+                    .slot(Slot::SubPane(111)),
+            )
+            .unwrap();
+        let handle = harness.manager().get_pane(pane).unwrap().clone();
+        let pty = harness.pty(pane);
+        harness.settle().await;
+
+        // Hidden: the chunk being read is kept for later, and reading stops
+        harness.manager_mut().toggle_pane_expansion(0);
+        pty.feed(b"one ");
+        wait_for(|| handle.stats().bytes_read == 4).await;
+        pty.feed(b"two");
+        tokio::time::sleep(PAUSE_CHECK).await;
+        assert!(!pty.is_idle());
+        assert_eq!(handle.stats().bytes_read, 4);
+
+        harness.manager_mut().toggle_pane_expansion(0);
+        harness.settle().await;
+        assert!(handle.screen_snapshot().to_text().contains("one two"));
+
+        // Scrolled back: reading stops until the view is live again
+        for i in 0..30 {
+            harness.feed(pane, format!("\r\nline {i}").as_bytes());
+        }
+        harness.settle().await;
+        handle.set_scroll_offset(1);
+        let read = handle.stats().bytes_read;
+        pty.feed(b"\r\nmore");
+        wait_for(|| handle.stats().bytes_read == read + 6).await;
+        pty.feed(b"\r\nagain");
+        tokio::time::sleep(PAUSE_CHECK).await;
+        assert!(!pty.is_idle());
+        assert_eq!(handle.stats().bytes_read, read + 6);
+
+        handle.set_scroll_offset(0);
+        harness.settle().await;
+        assert!(handle.screen_snapshot().to_text().ends_with("more\nagain"));
+    }
+
+    #[tokio::test]
+    async fn test_flow_control_unpolled_output() {
+        const MIB: u64 = 1 << 20;
+        let mut harness = Harness::new(80, 20);
+        let pane = harness
+            .spawn(SpawnConfig::new_shell().flow_control(true))
+            .unwrap();
+        let handle = harness.manager().get_pane(pane).unwrap().clone();
+        let pty = harness.pty(pane);
+        harness.settle().await;
+
+        // Without polling, reading stops once 1 MiB is unreported
+        let chunk = vec![b'x'; 64 * 1024];
+        for _ in 0..32 {
+            pty.feed(&chunk);
+        }
+        wait_for(|| handle.stats().bytes_read >= MIB).await;
+        tokio::time::sleep(PAUSE_CHECK).await;
+        assert!(!pty.is_idle());
+        assert!(handle.stats().bytes_read < 2 * MIB);
+
+        // Polling lets it go on
+        wait_for(|| {
+            harness.manager_mut().poll_events();
+            pty.is_idle()
+        })
+        .await;
+        assert_eq!(handle.stats().bytes_read, 2 * MIB);
+
+        // Closing a paused pane releases its reader, which runs to the end
+        for _ in 0..32 {
+            pty.feed(&chunk);
+        }
+        wait_for(|| handle.stats().bytes_read >= 3 * MIB).await;
+        tokio::time::sleep(PAUSE_CHECK).await;
+        assert!(!pty.is_idle());
+        harness.manager_mut().close_pane(pane);
+        wait_for(|| pty.is_idle()).await;
+        assert_eq!(handle.stats().bytes_read, 4 * MIB);
+    }
+
+    #[tokio::test]
+    async fn test_scrollbar() {
+        let mut harness = Harness::new(80, 20);
+        let id = harness.spawn(SpawnConfig::new_shell()).unwrap();
+        let lines = (0..30)
+            .map(|i| format!("line {i}"))
+            .collect::<Vec<_>>()
+            .join("\r\n");
+        harness.feed(id, lines.as_bytes());
+        harness.settle().await;
+        let pane = harness.manager().get_pane(id).unwrap().clone();
+        let area = harness.manager().get_areas()[&id];
+        let (x, top) = (area.right() - 1, area.y + 1);
+        let bottom = top + harness.pty(id).size().rows - 1;
+        let mouse = |kind, row| MouseEvent {
+            kind,
+            column: x,
+            row,
+            modifiers: KeyModifiers::NONE,
+        };
+        assert_eq!(pane.scrollback_len(), 18);
+        // vt100 shows at most a screen height of scrollback
+        assert_eq!(pane.max_scroll_offset(), 12);
+
+        // Only panes scrolled back show a scrollbar
+        harness.render();
+        assert_eq!(harness.cell(x, top).symbol(), "│");
+        assert!(!harness.mouse(mouse(MouseEventKind::Down(MouseButton::Left), top)));
+        pane.set_scroll_offset(18);
+        assert_eq!(pane.scroll_offset(), 12);
+        harness.render();
+        for y in top..top + 6 {
+            assert_eq!(harness.cell(x, y).symbol(), "┃");
+            assert_eq!(harness.cell(x, y).fg, Color::Yellow);
+        }
+        assert_eq!(harness.cell(x, top + 6).symbol(), "│");
+
+        // Clicking and dragging along it scrolls
+        assert!(harness.mouse(mouse(MouseEventKind::Down(MouseButton::Left), top + 6)));
+        assert_eq!(pane.scroll_offset(), 6);
+        assert_eq!(pane.selection(), None);
+        harness.render();
+        assert_eq!(harness.cell(x, top + 2).symbol(), "│");
+        assert_eq!(harness.cell(x, top + 3).symbol(), "┃");
+        assert!(harness.mouse(mouse(MouseEventKind::Drag(MouseButton::Left), 0)));
+        assert_eq!(pane.scroll_offset(), 12);
+        assert!(harness.mouse(mouse(MouseEventKind::Drag(MouseButton::Left), bottom)));
+        assert_eq!(pane.scroll_offset(), 0);
+        assert!(!harness.mouse(mouse(MouseEventKind::Up(MouseButton::Left), bottom)));
+        harness.render();
+        assert_eq!(harness.cell(x, bottom).symbol(), "│");
+    }
+
+    #[tokio::test]
+    async fn test_search_highlight() {
+        let mut harness = Harness::new(80, 20);
+        let id = harness.spawn(SpawnConfig::new_shell()).unwrap();
+        let lines = (0..20)
+            .map(|i| match i % 7 {
+                0 => format!("error {i}"),
+                _ => format!("line {i}"),
+            })
+            .collect::<Vec<_>>()
+            .join("\r\n");
+        harness.feed(id, lines.as_bytes());
+        harness.settle().await;
+        let pane = harness.manager().get_pane(id).unwrap().clone();
+        let area = harness.manager().get_areas()[&id];
+        let (x, y) = (area.x + 1, area.y + 1);
+
+        // The search starts on the last match, "error 14"
+        assert_eq!(pane.search("error"), 3);
+        harness.render();
+        assert_eq!(harness.cell(x, y + 6).bg, Color::LightRed);
+        assert_eq!(harness.cell(x + 4, y + 6).bg, Color::LightRed);
+        assert_eq!(harness.cell(x + 5, y + 6).bg, Color::Reset);
+        harness.assert_text_at(area.right() - 9, area.y, " 3/3 ");
+
+        // Moving to a match in scrollback scrolls to it
+        pane.search_prev();
+        assert_eq!(pane.scroll_offset(), 1);
+        harness.render();
+        harness.assert_text_at(x, y, "error 7");
+        assert_eq!(harness.cell(x, y).bg, Color::LightRed);
+        assert_eq!(harness.cell(x, y + 7).bg, Color::Yellow);
+        harness.assert_text_at(area.right() - 9, area.y, " 2/3 ");
+
+        pane.clear_search();
+        assert_eq!(pane.search_state(), None);
+        harness.render();
+        assert_eq!(harness.cell(x, y).bg, Color::Reset);
+        assert_eq!(pane.search("missing"), 0);
+        harness.render();
+        harness.assert_text_at(area.right() - 9, area.y, " 0/0 ");
+    }
+
+    #[tokio::test]
+    async fn test_keyboard_protocol_follows_focus() {
+        let config = ManagerConfig::builder()
+            .keyboard_protocol(KeyboardProtocol::Kitty)
+            .build()
+            .unwrap();
+        let mut harness = Harness::with_config(config, 80, 20);
+        let shell = harness.spawn(SpawnConfig::new_shell()).unwrap();
+        let editor = harness.spawn(SpawnConfig::new_command("nvim")).unwrap();
+        harness.manager_mut().set_focus(shell);
+        let _ = harness.manager_mut().take_passthrough();
+
+        // A background push is not forwarded, but replayed on focus
+        harness.feed(editor, b"\x1b[>1u\x1b[=3;2u");
+        harness.settle().await;
+        assert!(harness.manager_mut().take_passthrough().is_empty());
+        harness.manager_mut().set_focus(editor);
+        assert_eq!(harness.manager_mut().take_passthrough(), b"\x1b[>3u");
+
+        harness.manager_mut().set_focus(shell);
+        assert_eq!(harness.manager_mut().take_passthrough(), b"\x1b[<1u");
+        harness.manager_mut().set_focus(editor);
+        assert_eq!(harness.manager_mut().take_passthrough(), b"\x1b[>3u");
+
+        // Exiting without popping resets the host
+        harness.pty(editor).exit(0);
+        harness.settle().await;
+        assert_eq!(harness.manager_mut().take_passthrough(), b"\x1b[<1u");
+    }
+
+    #[tokio::test]
+    async fn test_multi_click_selection() {
+        let mut harness = Harness::new(80, 20);
+        let id = harness.spawn(SpawnConfig::new_shell()).unwrap();
+        harness.feed(id, b"echo hello world\r\n$ ");
+        harness.settle().await;
+        harness.take_events();
+        let _ = harness.manager_mut().take_passthrough();
+
+        let press = MouseEvent {
+            kind: MouseEventKind::Down(MouseButton::Left),
+            column: 8,
+            row: 1,
+            modifiers: KeyModifiers::NONE,
+        };
+        assert!(!harness.mouse(press));
+        assert!(harness.mouse(press));
+        let pane = harness.manager().get_pane(id).unwrap().clone();
+        assert_eq!(pane.selection().unwrap().mode, SelectionMode::Word);
+        assert_eq!(pane.selected_text().as_deref(), Some("hello"));
+        harness.settle().await;
+        assert!(harness.take_events().iter().any(|e| matches!(
+            e,
+            PaneEvent::Selected { pane_id, text } if *pane_id == id && text == "hello"
+        )));
+        assert_eq!(harness.manager_mut().take_passthrough(), osc52("hello"));
+
+        harness.render();
+        assert!(harness.cell(6, 1).modifier.contains(Modifier::REVERSED));
+        assert!(!harness.cell(5, 1).modifier.contains(Modifier::REVERSED));
+
+        assert!(harness.mouse(press));
+        assert_eq!(pane.selected_text().as_deref(), Some("echo hello world"));
+        harness.render();
+        assert!(harness.cell(1, 1).modifier.contains(Modifier::REVERSED));
+
+        // A fourth click starts over and clears the selection
+        assert!(harness.mouse(press));
+        assert!(pane.selection().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_selection_rendering() {
+        let mut harness = Harness::new(80, 20);
+        let mut theme = harness.manager().theme().clone();
+        theme.selection_style = Some(Style::default().bg(Color::Blue));
+        harness.manager_mut().set_theme(theme);
+        let id = harness.spawn(SpawnConfig::new_shell()).unwrap();
+        assert_eq!(harness.pty(id).size().cols, 18);
+        harness.feed(id, "012345678901234567wrap字!".as_bytes());
+        harness.settle().await;
+        let area = harness.manager().get_areas()[&id];
+        let (x, y) = (area.x + 1, area.y + 1);
+
+        // The selection runs from the end of the first row into the next
+        let pane = harness.manager().get_pane(id).unwrap().clone();
+        let selection = Selection::new((0, 14), (1, 4), SelectionMode::Cell);
+        pane.set_selection(selection);
+        harness.render();
+        assert_eq!(harness.cell(x + 13, y).bg, Color::Reset);
+        assert_eq!(harness.cell(x + 14, y).bg, Color::Blue);
+        assert_eq!(harness.cell(x + 17, y).bg, Color::Blue);
+        assert_eq!(harness.cell(x, y + 1).bg, Color::Blue);
+        assert_eq!(harness.cell(x + 5, y + 1).bg, Color::Blue);
+        assert_eq!(harness.cell(x + 6, y + 1).bg, Color::Reset);
+
+        // The widget can be given a selection of its own
+        let mut buffer = Buffer::empty(area);
+        PaneWidget::new(&pane)
+            .selection(Some(Selection::new((1, 0), (1, 0), SelectionMode::Cell)))
+            .render(area, &mut buffer);
+        assert!(!buffer[(x + 15, y)].modifier.contains(Modifier::REVERSED));
+        assert!(buffer[(x, y + 1)].modifier.contains(Modifier::REVERSED));
+        let mut buffer = Buffer::empty(area);
+        PaneWidget::new(&pane)
+            .selection(None)
+            .render(area, &mut buffer);
+        assert!(!buffer[(x + 15, y)].modifier.contains(Modifier::REVERSED));
+    }
+
+    #[tokio::test]
+    async fn test_drag_selection() {
+        let mut harness = Harness::new(80, 20);
+        let id = harness.spawn(SpawnConfig::new_shell()).unwrap();
+        let lines = (0..30)
+            .map(|i| format!("line {i}"))
+            .collect::<Vec<_>>()
+            .join("\r\n");
+        harness.feed(id, lines.as_bytes());
+        harness.settle().await;
+        harness.take_events();
+        let _ = harness.manager_mut().take_passthrough();
+        let rows = harness.pty(id).size().rows;
+        let area = harness.manager().get_areas()[&id];
+        let (x, y) = (area.x + 1, area.y + 1);
+        let mouse = |kind, column, row| MouseEvent {
+            kind,
+            column,
+            row,
+            modifiers: KeyModifiers::NONE,
+        };
+
+        // Press at the top-left cell, then drag up past the edge
+        harness.mouse(mouse(MouseEventKind::Down(MouseButton::Left), x, y));
+        assert!(harness.mouse(mouse(MouseEventKind::Drag(MouseButton::Left), x + 1, y - 1)));
+        let pane = harness.manager().get_pane(id).unwrap().clone();
+        assert_eq!(pane.scroll_offset(), 1);
+        let expected = format!("ine {}\nl", 29 - rows);
+        assert_eq!(pane.selected_text(), Some(expected.clone()));
+
+        harness.render();
+        assert!(harness.cell(x + 1, y).modifier.contains(Modifier::REVERSED));
+        assert!(!harness.cell(x, y).modifier.contains(Modifier::REVERSED));
+        assert!(harness.cell(x, y + 1).modifier.contains(Modifier::REVERSED));
+        assert!(!harness
+            .cell(x + 1, y + 1)
+            .modifier
+            .contains(Modifier::REVERSED));
+
+        assert!(harness.mouse(mouse(MouseEventKind::Up(MouseButton::Left), x + 1, y - 1)));
+        harness.settle().await;
+        assert!(harness.take_events().iter().any(|e| matches!(
+            e,
+            PaneEvent::Selected { pane_id, text } if *pane_id == id && *text == expected
+        )));
+        assert_eq!(harness.manager_mut().take_passthrough(), osc52(&expected));
+
+        // A plain click selects nothing
+        let x = x + 5;
+        harness.mouse(mouse(MouseEventKind::Down(MouseButton::Left), x, y));
+        assert!(!harness.mouse(mouse(MouseEventKind::Up(MouseButton::Left), x, y)));
+        assert!(pane.selection().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_resize_focused() {
+        let mut harness = Harness::new(80, 20);
+        let left = harness.spawn(SpawnConfig::new_shell()).unwrap();
+        let right = harness.spawn(SpawnConfig::new_shell()).unwrap();
+        let before = harness.manager().get_areas()[&left];
+        assert_eq!(before, Rect::new(0, 0, 20, 14));
+
+        assert!(harness.manager_mut().resize_focused(Direction::Right, 4));
+        assert!(harness.manager_mut().resize_focused(Direction::Down, 2));
+        let areas = harness.manager().get_areas();
+        assert_eq!(areas[&left], Rect::new(0, 0, 24, 16));
+        assert_eq!(areas[&right], Rect::new(24, 0, 16, 16));
+        assert_eq!(harness.pty(left).size().cols, 22);
+        assert_eq!(harness.manager().get_sub_pane_areas()[0].y, 15);
+
+        // Borders stop short of the minimum size, and come straight back
+        assert!(harness.manager_mut().resize_focused(Direction::Left, 100));
+        assert_eq!(harness.manager().get_areas()[&left].width, 3);
+        assert!(!harness.manager_mut().resize_focused(Direction::Left, 1));
+        assert!(harness.manager_mut().resize_focused(Direction::Right, 17));
+        assert_eq!(
+            harness.manager().get_areas()[&left],
+            Rect::new(0, 0, 20, 16)
+        );
+
+        // Panes move their right border, the rightmost one its left border
+        harness.manager_mut().set_focus(right);
+        assert!(harness.manager_mut().resize_focused(Direction::Left, 2));
+        assert_eq!(
+            harness.manager().get_areas()[&right],
+            Rect::new(20, 0, 18, 16)
+        );
+        harness.spawn(SpawnConfig::new_shell()).unwrap();
+        let last = harness.spawn(SpawnConfig::new_shell()).unwrap();
+        harness.manager_mut().set_focus(last);
+        assert!(harness.manager_mut().resize_focused(Direction::Left, 2));
+        assert_eq!(
+            harness.manager().get_areas()[&last],
+            Rect::new(58, 0, 22, 16)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_pinned_pane() {
+        let mut harness = Harness::new(80, 20);
+        let first = harness.spawn(SpawnConfig::new_shell()).unwrap();
+        let second = harness.spawn(SpawnConfig::new_shell()).unwrap();
+        let log = harness.spawn(SpawnConfig::new_shell()).unwrap();
+        assert!(harness.manager_mut().pin_pane(log));
+        assert!(harness.manager().is_pinned(log));
+        let pinned = harness.manager().get_areas()[&log];
+        assert_eq!(pinned, Rect::new(40, 0, 20, 14));
+
+        // Later panes move up around it, and new ones fill the gap
+        harness.manager_mut().close_pane(first);
+        assert_eq!(harness.manager().get_areas()[&second].x, 0);
+        assert_eq!(harness.manager().get_areas()[&log], pinned);
+        let scratch = harness.spawn(SpawnConfig::new_shell()).unwrap();
+        assert_eq!(harness.manager().get_areas()[&scratch].x, 20);
+
+        // Its position and row do not expand
+        harness.manager_mut().toggle_pane_expansion(2);
+        harness.manager_mut().toggle_horizontal_expansion(1, true);
+        assert_eq!(harness.manager().get_areas()[&log], pinned);
+        harness.manager_mut().toggle_horizontal_expansion(0, true);
+        assert_eq!(harness.manager().get_areas()[&second].width, 40);
+        harness.manager_mut().toggle_horizontal_expansion(0, true);
+
+        assert!(harness.manager_mut().unpin_pane(log));
+        assert!(!harness.manager_mut().unpin_pane(log));
+        harness.manager_mut().close_pane(scratch);
+        assert_eq!(harness.manager().get_areas()[&log].x, 20);
+    }
+
+    #[tokio::test]
+    async fn test_workspaces() {
+        let mut harness = Harness::new(80, 20);
+        let editor = harness.spawn(SpawnConfig::new_shell()).unwrap();
+        harness.manager_mut().create_workspace("logs").unwrap();
+        assert!(matches!(
+            harness.manager_mut().create_workspace("logs"),
+            Err(Error::WorkspaceExists(name)) if name == "logs"
+        ));
+        assert_eq!(harness.manager().workspaces(), ["default", "logs"]);
+
+        // A new workspace starts empty, and background panes keep running
+        harness.manager_mut().switch_workspace("logs").unwrap();
+        assert_eq!(harness.manager().active_workspace(), "logs");
+        assert_eq!(harness.manager().focused(), None);
+        assert!(harness.manager().get_areas().is_empty());
+        let tail = harness.spawn(SpawnConfig::new_shell()).unwrap();
+        harness.feed(editor, b"still here");
+        harness.settle().await;
+        assert_eq!(harness.manager().get_areas()[&tail].x, 0);
+        harness.manager_mut().focus_next();
+        assert_eq!(harness.manager().focused(), Some(tail));
+
+        // Focusing a pane shows its workspace
+        harness.manager_mut().set_focus(editor);
+        assert_eq!(harness.manager().active_workspace(), "default");
+        assert_eq!(harness.manager().workspace_of(tail), Some("logs"));
+        harness.render();
+        harness.assert_contains("still here");
+
+        // Output in a background workspace marks its tab
+        assert!(!harness.manager().workspace_tabs()[1].activity);
+        harness.feed(tail, b"new line");
+        harness.settle().await;
+        let tabs = harness.manager().workspace_tabs();
+        assert!(tabs[0].active && tabs[1].activity);
+        assert!(harness.manager().needs_render());
+
+        // Clicking its tab switches to it
+        harness
+            .manager_mut()
+            .set_tab_bar_area(Some(Rect::new(0, 0, 80, 1)));
+        assert!(harness.click(11, 0));
+        assert_eq!(harness.manager().active_workspace(), "logs");
+        assert!(!harness.manager().workspace_tabs()[1].activity);
+        assert!(!harness.click(11, 0));
+
+        harness.manager_mut().close_workspace("logs").unwrap();
+        assert!(harness.manager().get_pane(tail).is_none());
+        assert!(harness.manager_mut().close_workspace("default").is_err());
+        assert!(matches!(
+            harness.manager_mut().switch_workspace("logs"),
+            Err(Error::WorkspaceNotFound(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_background_restart_keeps_activity() {
+        let mut harness = Harness::new(80, 20);
+        harness.spawn(SpawnConfig::new_shell()).unwrap();
+        harness.manager_mut().create_workspace("logs").unwrap();
+        harness.manager_mut().switch_workspace("logs").unwrap();
+        let server = harness.spawn(SpawnConfig::new_command("server")).unwrap();
+        let tail = harness.spawn(SpawnConfig::new_shell()).unwrap();
+        harness.manager_mut().switch_workspace("default").unwrap();
+
+        harness.feed(tail, b"new line");
+        harness.settle().await;
+        assert!(harness.manager().workspace_tabs()[1].activity);
+        let restarted = harness.manager_mut().restart_pane(server).unwrap();
+        assert_eq!(harness.manager().active_workspace(), "default");
+        assert_eq!(harness.manager().workspace_of(restarted), Some("logs"));
+        assert!(harness.manager().workspace_tabs()[1].activity);
+
+        // The restarted pane's output counts too
+        harness.manager_mut().switch_workspace("logs").unwrap();
+        harness.manager_mut().switch_workspace("default").unwrap();
+        harness.feed(restarted, b"listening");
+        harness.settle().await;
+        assert!(harness.manager().workspace_tabs()[1].activity);
+    }
+
+    #[tokio::test]
+    async fn test_popup() {
+        let mut harness = Harness::new(80, 20);
+        let shell = harness.spawn(SpawnConfig::new_shell()).unwrap();
+        let popup = harness
+            .manager_mut()
+            .spawn_popup(SpawnConfig::new_command("fzf"), PaneSize::new(10, 40))
+            .unwrap()
+            .id();
+        assert!(harness
+            .manager_mut()
+            .spawn_popup(SpawnConfig::new_shell(), PaneSize::new(5, 5))
+            .is_err());
+        assert_eq!(
+            harness.manager().popup(),
+            Some((popup, Rect::new(19, 4, 42, 12)))
+        );
+        assert_eq!(harness.pty(popup).size(), PaneSize::new(10, 40));
+        assert!(!harness.manager().get_areas().contains_key(&popup));
+
+        // The popup is drawn above the layout and keeps focus
+        harness.feed(popup, b"> pick me");
+        harness.settle().await;
+        harness.render();
+        harness.assert_text_at(20, 5, "> pick me");
+        assert_eq!(harness.manager().focused(), Some(popup));
+        harness.manager_mut().focus_next();
+        assert!(!harness.click(2, 2));
+        assert_eq!(harness.manager().focused(), Some(popup));
+
+        // It closes when its process exits, returning focus
+        harness.pty(popup).exit(0);
+        harness.settle().await;
+        assert_eq!(harness.manager().popup(), None);
+        assert!(harness.manager().get_pane(popup).is_none());
+        assert_eq!(harness.manager().focused(), Some(shell));
+        assert!(!harness.manager_mut().close_popup());
+    }
+
+    /// Counts failing panes and shows the first line of the `tests` pane.
+    #[derive(Default)]
+    struct PaneSummary {
+        text: String,
+    }
+
+    impl Plugin for PaneSummary {
+        fn name(&self) -> &'static str {
+            "pane-summary"
+        }
+
+        fn config(&self) -> PluginConfig {
+            PluginConfig {
+                refresh_interval: Duration::ZERO,
+                ..PluginConfig::default()
+            }
+        }
+
+        fn refresh(&mut self, ctx: &PluginContext) -> PluginResult<()> {
+            let failing = ctx
+                .panes
+                .iter()
+                .filter(|pane| matches!(pane.state(), PaneState::Exited { code } if code != 0))
+                .count();
+            let tests = ctx
+                .panes
+                .iter()
+                .find(|pane| pane.name() == Some("tests"))
+                .and_then(|pane| pane.screen_snapshot().row_text(0))
+                .unwrap_or_default();
+            self.text = format!("{failing} failing, {}", tests.trim_end());
+            Ok(())
+        }
+
+        fn render(&self) -> StatusBarSegment {
+            StatusBarSegment::new(self.text.clone())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_plugin_pane_views() {
+        let mut harness = Harness::new(60, 10).with_plugins(std::env::temp_dir());
+        let tests = harness
+            .spawn(SpawnConfig::new_command("cargo test").name("tests"))
+            .unwrap();
+        let build = harness.spawn(SpawnConfig::new_command("make")).unwrap();
+        harness.feed(tests, b"12 passed");
+        harness.feed(build, b"\x1b]0;make all\x07");
+        harness.pty(build).exit(2);
+        harness.settle().await;
+        harness
+            .manager_mut()
+            .register_plugin(Box::new(PaneSummary::default()))
+            .unwrap();
+        // The first refresh runs on registration, before the context has
+        // the panes
+        harness.advance(Duration::ZERO).await;
+        harness.advance(Duration::ZERO).await;
+
+        let segments = harness.manager().status_bar_segments();
+        assert_eq!(segments[0].content, "1 failing, 12 passed");
+
+        let ctx = {
+            let mut ctx = PluginContext::new(std::env::temp_dir());
+            ctx.focused_pane = Some(tests);
+            ctx.panes = vec![PaneView::new(
+                harness.manager().get_pane(build).unwrap().clone(),
+            )];
+            ctx
+        };
+        assert!(ctx.focused().is_none());
+        let view = ctx.pane(build).unwrap();
+        assert_eq!(view.title(), "make all");
+        assert_eq!(view.name(), None);
+    }
+}
@@ -0,0 +1,180 @@
+//! Plain-text log of recent pane output, and patterns to match against it.
+//!
+//! Fast output can scroll past the visible screen between two checks, so
+//! waiting for text only on the screen misses it. The reader task also
+//! appends every chunk, stripped of escape sequences and control
+//! characters, to a bounded [`Transcript`].
+
+use std::ops::Range;
+
+use regex::Regex;
+
+use crate::error::Result;
+
+/// Text kept in a transcript; older output is discarded.
+const MAX_TRANSCRIPT_LEN: usize = 64 << 10;
+
+/// Text or regular expression to look for in pane output.
+#[derive(Clone, Debug)]
+pub enum Pattern {
+    /// Literal substring.
+    Text(String),
+    /// Regular expression.
+    Regex(Regex),
+}
+
+impl Pattern {
+    /// Compile a regular expression pattern.
+    ///
+    /// # Errors
+    /// Returns an error if `pattern` is not a valid regular expression.
+    pub fn regex(pattern: &str) -> Result<Self> {
+        Ok(Self::Regex(Regex::new(pattern)?))
+    }
+
+    /// Find the first match in `haystack`.
+    #[must_use]
+    pub fn find<'a>(&self, haystack: &'a str) -> Option<&'a str> {
+        self.find_range(haystack).map(|range| &haystack[range])
+    }
+
+    /// Byte range of the first match in `haystack`.
+    pub(crate) fn find_range(&self, haystack: &str) -> Option<Range<usize>> {
+        match self {
+            Self::Text(text) => haystack
+                .find(text.as_str())
+                .map(|start| start..start + text.len()),
+            Self::Regex(regex) => regex.find(haystack).map(|m| m.range()),
+        }
+    }
+}
+
+impl From<&str> for Pattern {
+    fn from(text: &str) -> Self {
+        Self::Text(text.to_string())
+    }
+}
+
+impl From<String> for Pattern {
+    fn from(text: String) -> Self {
+        Self::Text(text)
+    }
+}
+
+impl From<Regex> for Pattern {
+    fn from(regex: Regex) -> Self {
+        Self::Regex(regex)
+    }
+}
+
+/// Escape sequence stripping state.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+enum StripState {
+    #[default]
+    Ground,
+    /// After ESC.
+    Escape,
+    /// Inside a CSI sequence.
+    Csi,
+    /// Inside an OSC, DCS, APC, PM or SOS string.
+    String,
+    /// After ESC inside a string (possible ST).
+    StringEscape,
+}
+
+/// Bounded log of printable pane output.
+#[derive(Debug, Default)]
+pub(crate) struct Transcript {
+    state: StripState,
+    text: Vec<u8>,
+    /// Bytes discarded from the front of `text`.
+    discarded: u64,
+}
+
+impl Transcript {
+    /// Append a chunk of raw PTY output.
+    pub fn push(&mut self, data: &[u8]) {
+        for &byte in data {
+            self.state = match self.state {
+                StripState::Ground => match byte {
+                    0x1b => StripState::Escape,
+                    b'\n' | b'\t' | 0x20..=0x7e | 0x80.. => {
+                        self.text.push(byte);
+                        StripState::Ground
+                    }
+                    _ => StripState::Ground,
+                },
+                StripState::Escape => match byte {
+                    b'[' => StripState::Csi,
+                    b']' | b'P' | b'_' | b'^' | b'X' => StripState::String,
+                    // Intermediate bytes, e.g. charset designation `ESC ( B`
+                    0x20..=0x2f => StripState::Escape,
+                    _ => StripState::Ground,
+                },
+                StripState::Csi => match byte {
+                    0x40..=0x7e | 0x18 | 0x1a => StripState::Ground,
+                    _ => StripState::Csi,
+                },
+                StripState::String => match byte {
+                    0x07 | 0x18 | 0x1a => StripState::Ground,
+                    0x1b => StripState::StringEscape,
+                    _ => StripState::String,
+                },
+                StripState::StringEscape => match byte {
+                    b'\\' => StripState::Ground,
+                    _ => StripState::String,
+                },
+            };
+        }
+        if self.text.len() > 2 * MAX_TRANSCRIPT_LEN {
+            let excess = self.text.len() - MAX_TRANSCRIPT_LEN;
+            self.text.drain(..excess);
+            self.discarded += excess as u64;
+        }
+    }
+
+    /// Position after the last byte logged so far.
+    pub fn position(&self) -> u64 {
+        self.discarded + self.text.len() as u64
+    }
+
+    /// Text logged since `position`, or the whole log if that part has
+    /// already been discarded.
+    pub fn since(&self, position: u64) -> String {
+        self.read_from(position).1
+    }
+
+    /// Like [`since`](Self::since), also returning the position the text
+    /// starts at.
+    pub fn read_from(&self, position: u64) -> (u64, String) {
+        let start = usize::try_from(position.saturating_sub(self.discarded))
+            .unwrap_or(usize::MAX)
+            .min(self.text.len());
+        let text = String::from_utf8_lossy(&self.text[start..]).into_owned();
+        (self.discarded + start as u64, text)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_strips_escape_sequences() {
+        let mut transcript = Transcript::default();
+        transcript.push(b"\x1b[1;31mred\x1b[0m \x1b]0;title\x07ok\r\n\x1b(Bdo");
+        let mark = transcript.position();
+        transcript.push(b"ne\x1b]8;;http://x\x1b\\link");
+        assert_eq!(transcript.since(0), "red ok\ndonelink");
+        assert_eq!(transcript.since(mark), "nelink");
+    }
+
+    #[test]
+    fn test_pattern_find() {
+        let text = "build finished in 12.5s";
+        assert_eq!(Pattern::from("finished").find(text), Some("finished"));
+        let regex = Pattern::regex(r"in (\d+\.\d+)s").unwrap();
+        assert_eq!(regex.find(text), Some("in 12.5s"));
+        assert!(Pattern::from("failed").find(text).is_none());
+    }
+}
@@ -1,18 +1,30 @@
 //! Ratatui widgets for rendering panes.
 
+use std::borrow::Cow;
+use std::sync::{OnceLock, TryLockError};
+use std::time::{Duration, Instant};
+
 use ratatui::{
-    buffer::Buffer,
+    buffer::{Buffer, Cell},
     layout::{Alignment, Rect},
     style::{Color, Modifier, Style},
     text::{Line, Span},
-    widgets::{Block, Borders, Clear, Paragraph, Widget},
+    widgets::{Block, Borders, Clear, Padding, Paragraph, StatefulWidget, Widget},
 };
 
-use crate::arrows::{
-    is_left_arrow_position, ARROW_HEIGHT, ARROW_WIDTH, DOWN_ARROW, LEFT_ARROW, RIGHT_ARROW,
-    UP_ARROW,
+use crate::arrows::{render_regions, ArrowTheme, NavigationLayout};
+use crate::border::{BorderSet, BorderTheme};
+use crate::color::{grayscale, ColorMode};
+use crate::dialog::{SPINNER_FRAMES, SPINNER_INTERVAL};
+use crate::emulator::{Emulator, ExtraAttributes};
+use crate::frame::Theme;
+use crate::layout::SubPaneArrangement;
+use crate::manager::PaneManager;
+use crate::pane::{
+    CursorShape, PaneHandle, PaneId, PaneState, ScreenColor, UnderlineStyle, UnfocusedCursor,
 };
-use crate::pane::{PaneHandle, PaneId, ScreenColor};
+use crate::search::Search;
+use crate::selection::{selection_row, Selection};
 
 /// Which button is selected in a confirm dialog.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
@@ -35,13 +47,103 @@ impl DialogButton {
     }
 }
 
+impl From<DialogButton> for usize {
+    /// Index of the button in the default Yes/No buttons.
+    fn from(button: DialogButton) -> Self {
+        match button {
+            DialogButton::Yes => 0,
+            DialogButton::No => 1,
+        }
+    }
+}
+
+/// A button of a dialog.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DialogChoice {
+    /// Text of the button.
+    pub label: String,
+    /// Key that presses the button, matched case-insensitively.
+    pub key: Option<char>,
+    /// Drawn in the danger style, for destructive choices.
+    pub danger: bool,
+    /// Selected when the dialog is shown.
+    pub default: bool,
+    /// Pressed by Esc.
+    pub cancel: bool,
+}
+
+impl DialogChoice {
+    /// Button labelled `label`, without an accelerator key.
+    #[must_use]
+    pub fn new(label: impl Into<String>) -> Self {
+        Self {
+            label: label.into(),
+            key: None,
+            danger: false,
+            default: false,
+            cancel: false,
+        }
+    }
+
+    /// Press the button with `key`.
+    #[must_use]
+    pub fn key(mut self, key: char) -> Self {
+        self.key = Some(key);
+        self
+    }
+
+    /// Draw the button in the danger style.
+    #[must_use]
+    pub fn danger(mut self) -> Self {
+        self.danger = true;
+        self
+    }
+
+    /// Select the button when the dialog is shown.
+    #[must_use]
+    pub fn default(mut self) -> Self {
+        self.default = true;
+        self
+    }
+
+    /// Press the button with Esc.
+    #[must_use]
+    pub fn cancel(mut self) -> Self {
+        self.cancel = true;
+        self
+    }
+
+    /// The default buttons: Yes (y, selected) and No (n, Esc).
+    #[must_use]
+    pub fn yes_no() -> Vec<Self> {
+        vec![
+            Self::new("Yes").key('y').default(),
+            Self::new("No").key('n').cancel(),
+        ]
+    }
+}
+
 /// State for the confirm dialog.
-#[derive(Debug, Clone, Default)]
+///
+/// The dialog asks Yes/No unless given other buttons with
+/// [`with_buttons`](Self::with_buttons), e.g. "Save / Discard / Cancel".
+#[derive(Debug, Clone)]
 pub struct DialogState {
     /// Whether the dialog is visible.
     pub visible: bool,
-    /// Which button is currently selected.
-    pub selected: DialogButton,
+    /// Index of the selected button.
+    pub selected: usize,
+    buttons: Vec<DialogChoice>,
+}
+
+impl Default for DialogState {
+    fn default() -> Self {
+        Self {
+            visible: false,
+            selected: 0,
+            buttons: DialogChoice::yes_no(),
+        }
+    }
 }
 
 impl DialogState {
@@ -51,10 +153,32 @@ impl DialogState {
         Self::default()
     }
 
-    /// Show the dialog with Yes selected by default.
+    /// Hidden dialog with `buttons` instead of Yes/No. Without buttons it
+    /// keeps Yes/No.
+    #[must_use]
+    pub fn with_buttons(buttons: Vec<DialogChoice>) -> Self {
+        let mut state = Self::new();
+        if !buttons.is_empty() {
+            state.buttons = buttons;
+        }
+        state
+    }
+
+    /// The dialog's buttons.
+    #[must_use]
+    pub fn buttons(&self) -> &[DialogChoice] {
+        &self.buttons
+    }
+
+    /// Show the dialog with the default button selected (Yes, or else the
+    /// first button).
     pub fn show(&mut self) {
         self.visible = true;
-        self.selected = DialogButton::Yes;
+        self.selected = self
+            .buttons
+            .iter()
+            .position(|button| button.default)
+            .unwrap_or(0);
     }
 
     /// Hide the dialog.
@@ -62,78 +186,102 @@ impl DialogState {
         self.visible = false;
     }
 
-    /// Select the next button (toggle between Yes and No).
+    /// Select the next button, wrapping around.
     pub fn next(&mut self) {
-        self.selected = self.selected.toggle();
+        self.selected = (self.selected + 1) % self.buttons.len();
     }
 
-    /// Select the previous button (toggle between Yes and No).
+    /// Select the previous button, wrapping around.
     pub fn prev(&mut self) {
-        self.selected = self.selected.toggle();
+        self.selected = (self.selected + self.buttons.len() - 1) % self.buttons.len();
     }
 
     /// Handle a key press. Returns Some(true) for Yes, Some(false) for No, None if not handled.
+    ///
+    /// With other buttons than Yes/No, the first button counts as Yes; use
+    /// [`handle_button_key`](Self::handle_button_key) to tell them apart.
     #[must_use]
     pub fn handle_key(&mut self, key: crossterm::event::KeyEvent) -> Option<bool> {
+        let closing = self.visible && key.code == crossterm::event::KeyCode::Esc;
+        match self.handle_button_key(key) {
+            Some(index) => Some(index == 0),
+            None if closing => Some(false),
+            None => None,
+        }
+    }
+
+    /// Handle a key press. Returns the index of the pressed button, None
+    /// if no button was pressed.
+    ///
+    /// A button's key presses it, as does Enter for the selected button
+    /// and Esc for the cancel button. Esc closes a dialog without a cancel
+    /// button but returns None. Left/Right, Up/Down and Tab move the
+    /// selection.
+    #[must_use]
+    pub fn handle_button_key(&mut self, key: crossterm::event::KeyEvent) -> Option<usize> {
         use crossterm::event::KeyCode;
 
         if !self.visible {
             return None;
         }
 
-        match key.code {
-            KeyCode::Char('y' | 'Y') => {
+        let pressed = match key.code {
+            KeyCode::Char(c) => self.buttons.iter().position(|button| {
+                button
+                    .key
+                    .is_some_and(|key| key.to_lowercase().eq(c.to_lowercase()))
+            }),
+            KeyCode::Esc => {
                 self.hide();
-                Some(true)
+                return self.cancel_button();
             }
-            KeyCode::Char('n' | 'N') | KeyCode::Esc => {
-                self.hide();
-                Some(false)
-            }
-            KeyCode::Enter => {
-                let result = self.selected == DialogButton::Yes;
-                self.hide();
-                Some(result)
-            }
-            KeyCode::Left | KeyCode::Right | KeyCode::Tab => {
+            KeyCode::Enter => Some(self.selected),
+            KeyCode::Right | KeyCode::Down | KeyCode::Tab => {
                 self.next();
                 None
             }
-            KeyCode::Up | KeyCode::Down => {
-                // Arrow keys also toggle
-                self.next();
+            KeyCode::Left | KeyCode::Up | KeyCode::BackTab => {
+                self.prev();
                 None
             }
             _ => None,
+        };
+        if pressed.is_some() {
+            self.hide();
         }
+        pressed
     }
 
     /// Handle a mouse click. Returns Some(true) for Yes, Some(false) for No, None if not on a button.
+    ///
+    /// With other buttons than Yes/No, the first button counts as Yes; use
+    /// [`handle_button_mouse`](Self::handle_button_mouse) to tell them apart.
     #[must_use]
     pub fn handle_mouse(&mut self, x: u16, y: u16, dialog_area: Rect) -> Option<bool> {
+        self.handle_button_mouse(x, y, dialog_area)
+            .map(|index| index == 0)
+    }
+
+    /// Handle a mouse click. Returns the index of the clicked button, None
+    /// if not on a button.
+    #[must_use]
+    pub fn handle_button_mouse(&mut self, x: u16, y: u16, dialog_area: Rect) -> Option<usize> {
         if !self.visible {
             return None;
         }
 
-        // Calculate button areas (matching ConfirmDialog rendering)
-        let button_y = dialog_area.y + dialog_area.height - 3;
-        let yes_x_start = dialog_area.x + dialog_area.width / 2 - 8;
-        let yes_x_end = yes_x_start + 6;
-        let no_x_start = dialog_area.x + dialog_area.width / 2 + 2;
-        let no_x_end = no_x_start + 5;
-
-        if y == button_y {
-            if x >= yes_x_start && x < yes_x_end {
-                self.hide();
-                return Some(true);
-            }
-            if x >= no_x_start && x < no_x_end {
-                self.hide();
-                return Some(false);
-            }
+        // Button areas match ConfirmDialog rendering
+        let clicked = button_areas(&self.buttons, dialog_area)
+            .iter()
+            .position(|area| area.contains((x, y).into()));
+        if clicked.is_some() {
+            self.hide();
         }
+        clicked
+    }
 
-        None
+    fn cancel_button(&self) -> Option<usize> {
+        self.buttons.iter().position(|button| button.cancel)
     }
 
     /// Calculate the dialog area for a given terminal size.
@@ -147,20 +295,58 @@ impl DialogState {
     }
 }
 
+/// Cells between two buttons.
+const BUTTON_GAP: u16 = 3;
+
+/// Width of a button: its label in brackets, or padded as wide.
+fn button_width(button: &DialogChoice) -> u16 {
+    u16::try_from(Span::raw(&button.label).width() + 4).unwrap_or(u16::MAX)
+}
+
+/// Areas of the buttons of a dialog drawn in `dialog_area`: a centered
+/// row, two rows above the bottom border.
+fn button_areas(buttons: &[DialogChoice], dialog_area: Rect) -> Vec<Rect> {
+    let inner = Block::default().borders(Borders::ALL).inner(dialog_area);
+    let total = buttons
+        .iter()
+        .map(button_width)
+        .fold(0, u16::saturating_add)
+        .saturating_add(BUTTON_GAP * u16::try_from(buttons.len().saturating_sub(1)).unwrap_or(0));
+    let y = inner.y + inner.height.saturating_sub(2);
+    let mut x = inner.x + inner.width.saturating_sub(total) / 2;
+    buttons
+        .iter()
+        .map(|button| {
+            let width = button_width(button);
+            let area = Rect::new(x, y, width, 1).intersection(inner);
+            x = x.saturating_add(width + BUTTON_GAP);
+            area
+        })
+        .collect()
+}
+
 /// A confirmation dialog widget.
 pub struct ConfirmDialog<'a> {
     /// Title of the dialog.
     title: &'a str,
     /// Message to display.
     message: &'a str,
-    /// Which button is selected.
-    selected: DialogButton,
+    /// Buttons, Yes/No unless set.
+    buttons: Option<&'a [DialogChoice]>,
+    /// Index of the selected button.
+    selected: usize,
     /// Style for the dialog border.
     border_style: Style,
+    /// Line set of the border.
+    border_set: BorderSet,
+    /// Style patched over the border style for the title.
+    title_style: Style,
     /// Style for the selected button.
     selected_style: Style,
     /// Style for the unselected button.
     unselected_style: Style,
+    /// Style patched over danger buttons.
+    danger_style: Style,
 }
 
 impl<'a> ConfirmDialog<'a> {
@@ -170,20 +356,31 @@ impl<'a> ConfirmDialog<'a> {
         Self {
             title,
             message,
-            selected: DialogButton::Yes,
+            buttons: None,
+            selected: 0,
             border_style: Style::default().fg(Color::Yellow),
+            border_set: BorderSet::Plain,
+            title_style: Style::default(),
             selected_style: Style::default()
                 .fg(Color::Black)
                 .bg(Color::White)
                 .add_modifier(Modifier::BOLD),
             unselected_style: Style::default().fg(Color::White),
+            danger_style: Style::default().fg(Color::Red),
         }
     }
 
-    /// Set which button is selected.
+    /// Set the buttons, as held by [`DialogState::buttons`].
     #[must_use]
-    pub fn selected(mut self, selected: DialogButton) -> Self {
-        self.selected = selected;
+    pub fn buttons(mut self, buttons: &'a [DialogChoice]) -> Self {
+        self.buttons = Some(buttons);
+        self
+    }
+
+    /// Set which button is selected: a [`DialogButton`] or an index.
+    #[must_use]
+    pub fn selected(mut self, selected: impl Into<usize>) -> Self {
+        self.selected = selected.into();
         self
     }
 
@@ -194,6 +391,15 @@ impl<'a> ConfirmDialog<'a> {
         self
     }
 
+    /// Draw the border with the line set and title style of `theme`; the
+    /// border keeps its own color.
+    #[must_use]
+    pub fn border_theme(mut self, theme: &BorderTheme) -> Self {
+        self.border_set = theme.set;
+        self.title_style = theme.title_style;
+        self
+    }
+
     /// Set the selected button style.
     #[must_use]
     pub fn selected_style(mut self, style: Style) -> Self {
@@ -207,6 +413,14 @@ impl<'a> ConfirmDialog<'a> {
         self.unselected_style = style;
         self
     }
+
+    /// Set the style patched over danger buttons. A selected danger
+    /// button uses its foreground as background.
+    #[must_use]
+    pub fn danger_style(mut self, style: Style) -> Self {
+        self.danger_style = style;
+        self
+    }
 }
 
 impl Widget for ConfirmDialog<'_> {
@@ -217,8 +431,10 @@ impl Widget for ConfirmDialog<'_> {
         // Create dialog block
         let block = Block::default()
             .borders(Borders::ALL)
+            .border_type(self.border_set.into())
             .border_style(self.border_style)
-            .title(self.title);
+            .title(self.title)
+            .title_style(self.title_style);
 
         let inner = block.inner(area);
         block.render(area, buf);
@@ -229,45 +445,53 @@ impl Widget for ConfirmDialog<'_> {
         message.render(message_area, buf);
 
         // Render buttons
-        let yes_style = if self.selected == DialogButton::Yes {
-            self.selected_style
+        let yes_no;
+        let buttons = if let Some(buttons) = self.buttons {
+            buttons
         } else {
-            self.unselected_style
+            yes_no = DialogChoice::yes_no();
+            &yes_no
         };
-        let no_style = if self.selected == DialogButton::No {
-            self.selected_style
-        } else {
-            self.unselected_style
-        };
-
-        let yes_text = if self.selected == DialogButton::Yes {
-            "[ Yes ]"
-        } else {
-            "  Yes  "
-        };
-        let no_text = if self.selected == DialogButton::No {
-            "[ No ]"
-        } else {
-            "  No  "
-        };
-
-        let buttons = Line::from(vec![
-            Span::styled(yes_text, yes_style),
-            Span::raw("   "),
-            Span::styled(no_text, no_style),
-        ]);
-
-        let buttons_paragraph = Paragraph::new(buttons).alignment(Alignment::Center);
-        let buttons_area = Rect::new(
-            inner.x,
-            inner.y + inner.height.saturating_sub(2),
-            inner.width,
-            1,
-        );
-        buttons_paragraph.render(buttons_area, buf);
+        for (index, (button, button_area)) in
+            buttons.iter().zip(button_areas(buttons, area)).enumerate()
+        {
+            let selected = index == self.selected;
+            let mut style = if selected {
+                self.selected_style
+            } else {
+                self.unselected_style
+            };
+            if button.danger {
+                style = match (selected, self.danger_style.fg) {
+                    (true, Some(color)) => style.bg(color),
+                    _ => style.patch(self.danger_style),
+                };
+            }
+            let text = if selected {
+                format!("[ {} ]", button.label)
+            } else {
+                format!("  {}  ", button.label)
+            };
+            buf.set_stringn(
+                button_area.x,
+                button_area.y,
+                text,
+                usize::from(button_area.width),
+                style,
+            );
+        }
 
         // Render hint
-        let hint = Paragraph::new("y/n • Enter • ←→ • Click")
+        let keys: Vec<String> = buttons
+            .iter()
+            .filter_map(|button| button.key.map(String::from))
+            .collect();
+        let mut hint = vec!["Enter", "←→", "Click"];
+        let keys = keys.join("/");
+        if !keys.is_empty() {
+            hint.insert(0, &keys);
+        }
+        let hint = Paragraph::new(hint.join(" • "))
             .alignment(Alignment::Center)
             .style(Style::default().fg(Color::DarkGray));
         let hint_area = Rect::new(
@@ -280,6 +504,417 @@ impl Widget for ConfirmDialog<'_> {
     }
 }
 
+/// How a pane whose process has exited is drawn: its last output dimmed,
+/// with a banner giving the exit status and how long the process ran.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
+pub struct ExitOverlay {
+    /// Style of the banner.
+    pub style: Style,
+    /// Style of the banner of a pane that crashed or was killed.
+    pub crash_style: Style,
+    /// Shown below the banner, e.g. "press r to restart, x to close".
+    /// Nothing is shown if empty.
+    pub hint: String,
+    /// Style of the hint.
+    pub hint_style: Style,
+}
+
+impl Default for ExitOverlay {
+    fn default() -> Self {
+        Self {
+            style: Style::default()
+                .fg(Color::White)
+                .bg(Color::DarkGray)
+                .add_modifier(Modifier::BOLD),
+            crash_style: Style::default()
+                .fg(Color::White)
+                .bg(Color::Red)
+                .add_modifier(Modifier::BOLD),
+            hint: String::new(),
+            hint_style: Style::default().fg(Color::Gray),
+        }
+    }
+}
+
+impl ExitOverlay {
+    /// Set the hint shown below the banner.
+    #[must_use]
+    pub fn hint(mut self, hint: impl Into<String>) -> Self {
+        self.hint = hint.into();
+        self
+    }
+
+    /// Dim `area` and draw the banner for `state` across its middle, in
+    /// the crash style if the process crashed.
+    fn render(&self, state: &PaneState, runtime: Duration, area: Rect, buf: &mut Buffer) {
+        let Some(message) = exit_message(state, runtime) else {
+            return;
+        };
+        let area = area.intersection(buf.area);
+        buf.set_style(area, Style::default().add_modifier(Modifier::DIM));
+
+        let style = if matches!(state, PaneState::Crashed { .. }) {
+            self.crash_style
+        } else {
+            self.style
+        };
+        let mut lines = vec![Line::styled(format!(" {message} "), style)];
+        if !self.hint.is_empty() {
+            lines.push(Line::styled(self.hint.as_str(), self.hint_style));
+        }
+        let height = u16::try_from(lines.len())
+            .unwrap_or(u16::MAX)
+            .min(area.height);
+        let banner = Rect {
+            y: area.y + (area.height - height) / 2,
+            height,
+            ..area
+        };
+        buf.set_style(
+            Rect {
+                height: 1,
+                ..banner
+            },
+            style,
+        );
+        Paragraph::new(lines)
+            .alignment(Alignment::Center)
+            .render(banner, buf);
+    }
+}
+
+/// How the content of unfocused panes is toned down, so the focused pane
+/// stands out.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum DimMode {
+    /// Draw text with the dim (faint) attribute.
+    Dim,
+    /// Replace colors by grays of the same brightness.
+    Desaturate,
+}
+
+impl DimMode {
+    /// Tone down a cell drawn for a host terminal of `color_mode`.
+    fn apply(self, cell: &mut Cell, color_mode: ColorMode) {
+        match self {
+            Self::Dim => {
+                cell.modifier.insert(Modifier::DIM);
+            }
+            Self::Desaturate => {
+                cell.fg = color_mode.apply(grayscale(cell.fg));
+                cell.bg = color_mode.apply(grayscale(cell.bg));
+            }
+        }
+    }
+}
+
+/// How pane titles are drawn in the top border of each pane, styled by
+/// [`BorderTheme::title_style`].
+///
+/// A pane shows its [`name`](crate::SpawnConfig::name), or else the title
+/// its program set; panes with neither keep a plain border.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
+pub struct PaneTitle {
+    /// Where in the border the title goes.
+    #[cfg_attr(feature = "serde", serde(with = "AlignmentDef"))]
+    pub alignment: Alignment,
+    /// Longest title shown, in cells; longer titles end in "…". Titles
+    /// are also cut to fit the border.
+    pub max_width: Option<u16>,
+}
+
+#[cfg(feature = "serde")]
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(remote = "Alignment")]
+enum AlignmentDef {
+    Left,
+    Center,
+    Right,
+}
+
+impl PaneTitle {
+    /// Draw `title` in the top border of `area`, leaving `reserved` cells
+    /// free before the top-right corner.
+    fn render(self, title: &str, style: Style, area: Rect, reserved: u16, buf: &mut Buffer) {
+        // Corners, plus a space on each side of the title
+        let available = area.width.saturating_sub(4 + reserved);
+        let limit = self.max_width.map_or(available, |max| max.min(available));
+        let width = u16::try_from(Span::raw(title).width()).unwrap_or(u16::MAX);
+        if title.is_empty() || limit == 0 || !buf.area.contains(area.as_position()) {
+            return;
+        }
+        let shown = width.min(limit);
+        let x = match self.alignment {
+            Alignment::Left => area.x + 1,
+            Alignment::Center => area.x + 1 + (available - shown) / 2,
+            Alignment::Right => area.x + 1 + available - shown,
+        };
+        buf.set_string(x, area.y, " ", Style::default());
+        let end = if width > limit {
+            let (mut end, _) = buf.set_stringn(x + 1, area.y, title, usize::from(limit - 1), style);
+            // "cargo…", not "cargo …"
+            while end > x + 1 && buf[(end - 1, area.y)].symbol() == " " {
+                end -= 1;
+            }
+            buf.set_string(end, area.y, "…", style);
+            end + 1
+        } else {
+            buf.set_string(x + 1, area.y, title, style);
+            x + 1 + width
+        };
+        buf.set_string(end, area.y, " ", Style::default());
+    }
+}
+
+/// Shown in the top border of panes that are printing output.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
+pub struct ActivityIndicator {
+    /// Frames shown in turn; a single frame makes a static badge.
+    pub frames: Vec<String>,
+    /// How long each frame is shown.
+    pub interval: Duration,
+    /// Style of the indicator.
+    pub style: Style,
+}
+
+impl Default for ActivityIndicator {
+    /// A spinner.
+    fn default() -> Self {
+        Self {
+            frames: SPINNER_FRAMES.iter().map(char::to_string).collect(),
+            interval: SPINNER_INTERVAL,
+            style: Style::default().fg(Color::Yellow),
+        }
+    }
+}
+
+impl ActivityIndicator {
+    /// A static badge such as "●".
+    #[must_use]
+    pub fn badge(badge: impl Into<String>) -> Self {
+        Self {
+            frames: vec![badge.into()],
+            ..Self::default()
+        }
+    }
+
+    /// Cells the indicator takes up in the border.
+    fn width(&self) -> u16 {
+        let widest = self
+            .frames
+            .iter()
+            .map(|frame| Span::raw(frame).width())
+            .max()
+            .unwrap_or(0);
+        u16::try_from(widest + 2).unwrap_or(u16::MAX)
+    }
+
+    /// Draw the indicator in the top border of `area` if `handle` is busy.
+    /// The frame follows how long the process has run.
+    fn render(&self, handle: &PaneHandle, area: Rect, buf: &mut Buffer) {
+        let stats = handle.stats();
+        if self.frames.is_empty() || !stats.is_busy(Instant::now()) {
+            return;
+        }
+        let step = stats.runtime.as_millis() / self.interval.as_millis().max(1);
+        let frame = &self.frames[usize::try_from(step).unwrap_or(0) % self.frames.len()];
+        let text = format!(" {frame} ");
+        let width = u16::try_from(Span::raw(&text).width()).unwrap_or(u16::MAX);
+        // Keep the corner
+        if width + 2 > area.width || !buf.area.contains(area.as_position()) {
+            return;
+        }
+        buf.set_string(area.right() - width - 1, area.y, text, self.style);
+    }
+}
+
+/// Drawn over the right border of a pane scrolled back into its
+/// scrollback, showing which part of the history is in view.
+///
+/// Clicking or dragging along it scrolls the pane; see
+/// [`PaneManager::handle_mouse`](crate::PaneManager::handle_mouse).
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
+pub struct PaneScrollbar {
+    /// Symbol the thumb is drawn with; the border is the track.
+    pub thumb: String,
+    /// Style of the thumb.
+    pub style: Style,
+}
+
+impl Default for PaneScrollbar {
+    fn default() -> Self {
+        Self {
+            thumb: "┃".to_string(),
+            style: Style::default().fg(Color::Yellow),
+        }
+    }
+}
+
+impl PaneScrollbar {
+    /// Draw the thumb in column `x`, along the rows of `track`, for a pane
+    /// scrolled back `offset` of the `scrollback` lines it can show.
+    fn render(&self, scrollback: usize, offset: usize, x: u16, track: Rect, buf: &mut Buffer) {
+        let (start, size) = scrollbar_thumb(scrollback, offset, track.height);
+        for y in track.y + start..track.y + start + size {
+            if buf.area.contains((x, y).into()) {
+                buf[(x, y)].set_symbol(&self.thumb).set_style(self.style);
+            }
+        }
+    }
+}
+
+/// How the matches of a pane's active search are drawn; see
+/// [`PaneHandle::search`](crate::PaneHandle::search).
+///
+/// The current match's number, as in "3/17", is shown in the top border.
+/// [`PaneWidget`] only draws it in its default block.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
+pub struct SearchHighlight {
+    /// Style patched over matches.
+    pub match_style: Style,
+    /// Style patched over the current match.
+    pub current_style: Style,
+    /// Style of the match counter.
+    pub counter_style: Style,
+}
+
+impl Default for SearchHighlight {
+    fn default() -> Self {
+        Self {
+            match_style: Style::default().fg(Color::Black).bg(Color::Yellow),
+            current_style: Style::default().fg(Color::Black).bg(Color::LightRed),
+            counter_style: Style::default().fg(Color::Yellow),
+        }
+    }
+}
+
+impl SearchHighlight {
+    /// Highlight the matches of `search` on the rows of `inner_area`,
+    /// whose top row is selection row `top`.
+    fn highlight(self, search: &Search, top: i32, inner_area: Rect, buf: &mut Buffer) {
+        for (index, found) in search.matches.iter().enumerate() {
+            let Ok(row) = u16::try_from(found.row - top) else {
+                continue;
+            };
+            if row >= inner_area.height {
+                continue;
+            }
+            let style = if index == search.current {
+                self.current_style
+            } else {
+                self.match_style
+            };
+            let last = found.end.min(inner_area.width.saturating_sub(1));
+            for col in found.start..=last {
+                let (x, y) = (inner_area.x + col, inner_area.y + row);
+                if buf.area.contains((x, y).into()) {
+                    buf[(x, y)].set_style(style);
+                }
+            }
+        }
+    }
+
+    /// The match counter, such as " 3/17 ".
+    fn counter(search: &Search) -> String {
+        let current = if search.matches.is_empty() {
+            0
+        } else {
+            search.current + 1
+        };
+        format!(" {current}/{} ", search.matches.len())
+    }
+
+    /// Draw the match counter in the top border of `area`, leaving
+    /// `reserved` cells free before the top-right corner.
+    fn render_counter(self, search: &Search, area: Rect, reserved: u16, buf: &mut Buffer) {
+        let counter = Self::counter(search);
+        let width = u16::try_from(counter.len()).unwrap_or(u16::MAX);
+        // Keep the corners
+        if width + reserved + 2 > area.width || !buf.area.contains(area.as_position()) {
+            return;
+        }
+        let x = area.right() - reserved - width - 1;
+        buf.set_string(x, area.y, counter, self.counter_style);
+    }
+}
+
+/// First row and height of the scrollbar thumb in a track of `height`
+/// rows, for a view of `height` lines scrolled back `offset` of
+/// `scrollback` lines.
+fn scrollbar_thumb(scrollback: usize, offset: usize, height: u16) -> (u16, u16) {
+    let rows = usize::from(height);
+    let total = scrollback + rows;
+    let size = (rows * rows / total.max(1)).clamp(1, rows.max(1));
+    // Lines above the view, out of the `scrollback` that can be
+    let above = scrollback.saturating_sub(offset);
+    let start = (rows - size.min(rows)) * above / scrollback.max(1);
+    let convert = |n: usize| u16::try_from(n).unwrap_or(u16::MAX);
+    (convert(start), convert(size.min(rows)))
+}
+
+/// Scroll offset that puts the scrollbar thumb at `row` of a track of
+/// `height` rows: the top row shows the oldest line, the bottom row the
+/// live screen.
+pub(crate) fn scrollbar_offset(scrollback: usize, row: u16, height: u16) -> usize {
+    let last = usize::from(height.saturating_sub(1)).max(1);
+    let row = usize::from(row).min(last);
+    scrollback - scrollback * row / last
+}
+
+/// Banner text for a pane in `state` whose process ran for `runtime`;
+/// `None` while it is alive.
+fn exit_message(state: &PaneState, runtime: Duration) -> Option<String> {
+    let runtime = format_duration(runtime);
+    Some(match state {
+        PaneState::Running | PaneState::Paused => return None,
+        PaneState::Exited { code } => format!("Exited with code {code} after {runtime}"),
+        PaneState::Crashed {
+            signal: Some(signal),
+            ..
+        } => format!("Killed by signal {signal} after {runtime}"),
+        PaneState::Crashed {
+            error: Some(error), ..
+        } => format!("Crashed after {runtime}: {error}"),
+        PaneState::Crashed { .. } => format!("Crashed after {runtime}"),
+    })
+}
+
+/// Duration in its two largest units, e.g. "42s", "3m 12s" or "1h 5m".
+fn format_duration(duration: Duration) -> String {
+    let secs = duration.as_secs();
+    match (secs / 3600, secs / 60 % 60, secs % 60) {
+        (0, 0, s) => format!("{s}s"),
+        (0, m, s) => format!("{m}m {s}s"),
+        (h, m, _) => format!("{h}h {m}m"),
+    }
+}
+
 /// Widget for rendering a single pane's terminal content.
 pub struct PaneWidget<'a> {
     /// The pane handle to render.
@@ -288,10 +923,26 @@ pub struct PaneWidget<'a> {
     focused: bool,
     /// Border block.
     block: Option<Block<'a>>,
-    /// Style for focused state.
-    focus_style: Style,
+    /// Borders used when no block is set.
+    borders: BorderTheme,
     /// Show cursor.
     show_cursor: bool,
+    /// Color depth of the host terminal.
+    color_mode: ColorMode,
+    /// Drawn over the pane once its process has exited.
+    exit_overlay: Option<Cow<'a, ExitOverlay>>,
+    /// How the content is toned down while unfocused.
+    dim_unfocused: Option<DimMode>,
+    /// Drawn over the right border while scrolled back.
+    scrollbar: Option<Cow<'a, PaneScrollbar>>,
+    /// How matches of the active search are drawn.
+    search_highlight: SearchHighlight,
+    /// Highlighted range of cells.
+    selection: Option<Selection>,
+    /// Style patched over selected cells; reversed if unset.
+    selection_style: Option<Style>,
+    /// Whether a blinking cursor is in its visible phase.
+    cursor_blink_on: bool,
 }
 
 impl<'a> PaneWidget<'a> {
@@ -302,8 +953,16 @@ impl<'a> PaneWidget<'a> {
             handle,
             focused: false,
             block: None,
-            focus_style: Style::default().fg(Color::Cyan),
+            borders: BorderTheme::default(),
             show_cursor: true,
+            color_mode: ColorMode::TrueColor,
+            exit_overlay: default_theme().exit_overlay.as_ref().map(Cow::Borrowed),
+            dim_unfocused: None,
+            scrollbar: default_theme().scrollbar.as_ref().map(Cow::Borrowed),
+            search_highlight: SearchHighlight::default(),
+            selection: handle.selection(),
+            selection_style: None,
+            cursor_blink_on: true,
         }
     }
 
@@ -324,7 +983,14 @@ impl<'a> PaneWidget<'a> {
     /// Set the focus style.
     #[must_use]
     pub fn focus_style(mut self, style: Style) -> Self {
-        self.focus_style = style;
+        self.borders.focus_style = style;
+        self
+    }
+
+    /// Set the borders drawn when no [`block`](Self::block) is set.
+    #[must_use]
+    pub fn border_theme(mut self, theme: BorderTheme) -> Self {
+        self.borders = theme;
         self
     }
 
@@ -335,26 +1001,188 @@ impl<'a> PaneWidget<'a> {
         self
     }
 
+    /// Set the host terminal's color depth; pane colors are downgraded to fit.
+    #[must_use]
+    pub fn color_mode(mut self, mode: ColorMode) -> Self {
+        self.color_mode = mode;
+        self
+    }
+
+    /// Set how the pane is drawn once its process has exited. `None` shows
+    /// its last output unchanged.
+    #[must_use]
+    pub fn exit_overlay(mut self, overlay: Option<ExitOverlay>) -> Self {
+        self.exit_overlay = overlay.map(Cow::Owned);
+        self
+    }
+
+    /// Set how the content is toned down while the pane is not focused.
+    /// `None`, the default, draws it unchanged.
+    #[must_use]
+    pub fn dim_unfocused(mut self, dim: Option<DimMode>) -> Self {
+        self.dim_unfocused = dim;
+        self
+    }
+
+    /// Set the scrollbar drawn while the pane is scrolled back. `None`
+    /// draws none.
+    #[must_use]
+    pub fn scrollbar(mut self, scrollbar: Option<PaneScrollbar>) -> Self {
+        self.scrollbar = scrollbar.map(Cow::Owned);
+        self
+    }
+
+    /// Set how matches of the pane's active search are drawn.
+    #[must_use]
+    pub fn search_highlight(mut self, highlight: SearchHighlight) -> Self {
+        self.search_highlight = highlight;
+        self
+    }
+
+    /// Set the range of cells highlighted, in place of the pane's own
+    /// [`selection`](PaneHandle::selection). `None` highlights nothing.
+    #[must_use]
+    pub fn selection(mut self, selection: Option<Selection>) -> Self {
+        self.selection = selection;
+        self
+    }
+
+    /// Set the style patched over selected cells. `None`, the default,
+    /// reverses them.
+    #[must_use]
+    pub fn selection_style(mut self, style: Option<Style>) -> Self {
+        self.selection_style = style;
+        self
+    }
+
+    /// Set whether a blinking cursor is in its visible phase, for panes
+    /// whose [`CursorConfig::blink`](crate::CursorConfig::blink) is set.
+    /// Toggle it on a timer such as
+    /// [`PaneManager::cursor_blink_on`](crate::PaneManager::cursor_blink_on).
+    #[must_use]
+    pub fn cursor_blink_on(mut self, on: bool) -> Self {
+        self.cursor_blink_on = on;
+        self
+    }
+
     /// Create a default block for the pane.
     fn default_block(&self) -> Block<'a> {
-        let style = if self.focused {
-            self.focus_style
-        } else {
-            Style::default().fg(Color::DarkGray)
-        };
+        pane_block(&self.borders, self.handle, self.focused)
+    }
 
-        Block::default().borders(Borders::ALL).border_style(style)
+    /// Highlight the selected cells on the rows of `inner_area`, whose top
+    /// row is selection row `top`. Rows of a selection follow each other
+    /// in reading order, so a selection across wrapped lines covers the
+    /// end of one row and the start of the next.
+    fn highlight_selection(
+        &self,
+        selection: Selection,
+        top: i32,
+        inner_area: Rect,
+        buf: &mut Buffer,
+    ) {
+        for row in 0..inner_area.height {
+            let selection_row = top + i32::from(row);
+            if !(selection.start.0..=selection.end.0).contains(&selection_row) {
+                continue;
+            }
+            let mut col = 0;
+            while col < inner_area.width {
+                let (x, y) = (inner_area.x + col, inner_area.y + row);
+                if !selection.contains(selection_row, col) || !buf.area.contains((x, y).into()) {
+                    col += 1;
+                    continue;
+                }
+                // A wide character is highlighted across both of its cells
+                let width = Span::raw(buf[(x, y)].symbol()).width().max(1);
+                for x in (x..).take(width).filter(|&x| x < inner_area.right()) {
+                    let cell = &mut buf[(x, y)];
+                    match self.selection_style {
+                        Some(style) => {
+                            cell.set_style(style);
+                        }
+                        None => {
+                            cell.modifier.toggle(Modifier::REVERSED);
+                        }
+                    }
+                }
+                col += u16::try_from(width).unwrap_or(1);
+            }
+        }
+    }
+
+    /// Draw the cursor at `(row, col)` of `inner_area`, as the pane's
+    /// [`CursorConfig`](crate::CursorConfig) says.
+    fn render_cursor(
+        &self,
+        (cursor_row, cursor_col): (u16, u16),
+        hidden: bool,
+        inner_area: Rect,
+        buf: &mut Buffer,
+    ) {
+        let config = self.handle.cursor_config();
+        let style = self.handle.cursor_style();
+        if !self.show_cursor || (hidden && config.honor_visibility) {
+            return;
+        }
+        if self.focused && config.blink && style.blinking && !self.cursor_blink_on {
+            return;
+        }
+        if !self.focused && config.unfocused == UnfocusedCursor::Hide {
+            return;
+        }
+
+        let cursor_x = inner_area.x + cursor_col;
+        let cursor_y = inner_area.y + cursor_row;
+        if cursor_x >= inner_area.right()
+            || cursor_y >= inner_area.bottom()
+            || !buf.area.contains((cursor_x, cursor_y).into())
+        {
+            return;
+        }
+        let cell = &mut buf[(cursor_x, cursor_y)];
+        let underline = |cell: &mut Cell| {
+            let mut underline = Style::default().add_modifier(Modifier::UNDERLINED);
+            if let Some(color) = config.color {
+                underline = underline.underline_color(color);
+            }
+            cell.set_style(underline);
+        };
+        // A cell can't hold a thin bar or an outline next to a glyph, so
+        // draw them on empty cells and fall back to an underline over text
+        let outline = match (self.focused, style.shape) {
+            (false, _) => '▯',
+            (true, CursorShape::Bar) => '▏',
+            (true, CursorShape::Block) => {
+                if let Some(color) = config.color {
+                    cell.set_bg(color);
+                } else {
+                    // Toggle so the cursor stays visible on inverse text
+                    cell.modifier.toggle(Modifier::REVERSED);
+                }
+                return;
+            }
+            (true, CursorShape::Underline) => {
+                underline(cell);
+                return;
+            }
+        };
+        if cell.symbol().trim().is_empty() {
+            cell.set_char(outline);
+            if let Some(color) = config.color {
+                cell.set_fg(color);
+            }
+        } else {
+            underline(cell);
+        }
     }
 }
 
 impl Widget for PaneWidget<'_> {
-    fn render(self, area: Rect, buf: &mut Buffer) {
-        // Get screen state synchronously
-        let screen = self.handle.screen().read().expect("screen lock poisoned");
-        let vt_screen = screen.screen();
-
+    fn render(mut self, area: Rect, buf: &mut Buffer) {
         // Determine the block to use
-        let block = match self.block {
+        let uses_default_block = self.block.is_none();
+        let block = match self.block.take() {
             Some(b) => b,
             None => self.default_block(),
         };
@@ -363,9 +1191,36 @@ impl Widget for PaneWidget<'_> {
         let inner_area = block.inner(area);
         block.render(area, buf);
 
-        // Render terminal content
-        let (cursor_row, cursor_col) = vt_screen.cursor_position();
+        // Render terminal content, reusing the last frame's cells while the
+        // screen is unchanged. The generation is read before the screen so a
+        // concurrent update only ever causes an extra re-render.
+        let generation = self.handle.generation();
+        let mut cache = self
+            .handle
+            .render_cache()
+            .lock()
+            .expect("render cache lock poisoned");
+        if !cache.is_valid(generation, inner_area, self.color_mode) {
+            // Never wait for the reader task: while it is processing output,
+            // show the previous frame again, cut or padded to this area if
+            // it was drawn for another one, until a later frame.
+            match self.handle.screen().try_read() {
+                Ok(screen) => {
+                    let start = Instant::now();
+                    cache.update(&screen, generation, inner_area, self.color_mode);
+                    self.handle.metrics().record_render(start.elapsed());
+                }
+                Err(TryLockError::WouldBlock) => {
+                    if !cache.fits(inner_area, self.color_mode) {
+                        cache.reshape(inner_area, self.color_mode);
+                    }
+                }
+                Err(TryLockError::Poisoned(_)) => panic!("screen lock poisoned"),
+            }
+        }
 
+        let tint = self.handle.colors().tint;
+        let dim = self.dim_unfocused.filter(|_| !self.focused);
         for row in 0..inner_area.height {
             for col in 0..inner_area.width {
                 let x = inner_area.x + col;
@@ -375,64 +1230,223 @@ impl Widget for PaneWidget<'_> {
                     continue;
                 }
 
-                if let Some(cell) = vt_screen.cell(row, col) {
-                    let buf_cell = &mut buf[(x, y)];
+                let idx = usize::from(row) * usize::from(inner_area.width) + usize::from(col);
+                if let Some(cell) = &cache.cells[idx] {
+                    buf[(x, y)].clone_from(cell);
+                    if let Some(tint) = tint.filter(|_| cell.bg == Color::Reset) {
+                        buf[(x, y)].bg = self.color_mode.apply(tint);
+                    }
+                    if let Some(dim) = dim {
+                        dim.apply(&mut buf[(x, y)], self.color_mode);
+                    }
+                }
+            }
+        }
+        let (cursor_row, cursor_col) = cache.cursor;
+        let cursor_hidden = cache.cursor_hidden;
+        let scroll_offset = cache.scroll_offset;
+        let top = selection_row(scroll_offset, 0);
+        drop(cache);
+
+        if let Some(scrollbar) = self.scrollbar.as_ref().filter(|_| scroll_offset > 0) {
+            let scrollback = self.handle.max_scroll_offset();
+            let track = Rect::new(area.x, inner_area.y, area.width, inner_area.height);
+            if let Some(x) = area.right().checked_sub(1) {
+                scrollbar.render(scrollback, scroll_offset, x, track, buf);
+            }
+        }
 
-                    // Set character
-                    let ch = cell.contents().chars().next().unwrap_or(' ');
-                    buf_cell.set_char(ch);
+        // Highlight the selection on top of the cached cells
+        if let Some(selection) = self.selection {
+            self.highlight_selection(selection, top, inner_area, buf);
+        }
 
-                    // Convert colors
-                    let mut fg = convert_color(cell.fgcolor());
-                    let mut bg = convert_color(cell.bgcolor());
+        if let Some(search) = self.handle.search_state() {
+            self.search_highlight
+                .highlight(&search, top, inner_area, buf);
+            if uses_default_block {
+                self.search_highlight.render_counter(&search, area, 0, buf);
+            }
+        }
 
-                    // Handle inverse
-                    if cell.inverse() {
-                        std::mem::swap(&mut fg, &mut bg);
-                    }
+        // Mark a dead pane instead of showing its cursor
+        if let Some(overlay) = &self.exit_overlay {
+            let state = self.handle.state();
+            if !state.is_alive() {
+                overlay.render(&state, self.handle.stats().runtime, inner_area, buf);
+                return;
+            }
+        }
 
-                    buf_cell.set_fg(fg);
-                    buf_cell.set_bg(bg);
+        self.render_cursor((cursor_row, cursor_col), cursor_hidden, inner_area, buf);
+    }
+}
 
-                    // Apply modifiers
-                    let mut style = Style::default();
-                    if cell.bold() {
-                        style = style.add_modifier(Modifier::BOLD);
-                    }
-                    if cell.italic() {
-                        style = style.add_modifier(Modifier::ITALIC);
-                    }
-                    if cell.underline() {
-                        style = style.add_modifier(Modifier::UNDERLINED);
-                    }
-                    buf_cell.set_style(style);
-                }
+/// Pane contents converted to buffer cells, reused across frames.
+///
+/// vt100 exposes no per-cell damage information, so the cache is keyed on
+/// the pane's output generation: an unchanged pane is copied from here
+/// without touching the emulator.
+#[derive(Debug, Default)]
+pub(crate) struct RenderCache {
+    /// Output generation the cells were rendered from.
+    generation: Option<u64>,
+    /// Size of the area the cells were rendered for.
+    size: (u16, u16),
+    /// Color mode the cells were rendered with.
+    color_mode: ColorMode,
+    /// Row-major cells; `None` where the screen has no cell.
+    cells: Vec<Option<Cell>>,
+    /// Cursor position (row, col), moved down by the scroll offset.
+    cursor: (u16, u16),
+    /// Whether the program hid the cursor.
+    cursor_hidden: bool,
+    /// Lines the screen was scrolled back.
+    scroll_offset: usize,
+    /// Whether the cells were carried over from another area or color
+    /// mode without the screen, and must be rendered again.
+    stale: bool,
+}
+
+impl RenderCache {
+    /// Whether the cached cells can be reused for this frame.
+    fn is_valid(&self, generation: u64, area: Rect, color_mode: ColorMode) -> bool {
+        !self.stale
+            && self.generation == Some(generation)
+            && self.size == (area.width, area.height)
+            && self.color_mode == color_mode
+    }
+
+    /// Whether the cached cells were rendered for this area, possibly from
+    /// an older generation.
+    fn fits(&self, area: Rect, color_mode: ColorMode) -> bool {
+        self.size == (area.width, area.height) && self.color_mode == color_mode
+    }
+
+    /// Whether the cells are a placeholder that must be rendered again.
+    pub(crate) fn is_stale(&self) -> bool {
+        self.stale
+    }
+
+    /// Output generation the cached cells show, if rendered at all.
+    pub(crate) fn generation(&self) -> Option<u64> {
+        self.generation
+    }
+
+    /// Re-render the cells from the emulator's screen.
+    fn update(&mut self, emulator: &Emulator, generation: u64, area: Rect, color_mode: ColorMode) {
+        let vt_screen = emulator.screen();
+        self.generation = Some(generation);
+        self.size = (area.width, area.height);
+        self.color_mode = color_mode;
+        self.stale = false;
+        let (cursor_row, cursor_col) = vt_screen.cursor_position();
+        self.scroll_offset = vt_screen.scrollback();
+        self.cursor_hidden = vt_screen.hide_cursor();
+        let cursor_row = usize::from(cursor_row).saturating_add(self.scroll_offset);
+        self.cursor = (u16::try_from(cursor_row).unwrap_or(u16::MAX), cursor_col);
+        self.cells.clear();
+        self.cells
+            .reserve(usize::from(area.width) * usize::from(area.height));
+
+        for row in 0..area.height {
+            for col in 0..area.width {
+                let cell = vt_screen.cell(row, col).map(|cell| {
+                    // A wide character that would spill over the border is blanked
+                    let clipped = cell.is_wide() && col + 1 >= area.width;
+                    let extra = emulator.extra_attributes(row, col);
+                    convert_cell(cell, extra, clipped, color_mode)
+                });
+                self.cells.push(cell);
             }
         }
+    }
 
-        // Render cursor if focused and visible
-        if self.focused && self.show_cursor {
-            let cursor_x = inner_area.x + cursor_col;
-            let cursor_y = inner_area.y + cursor_row;
-
-            if cursor_x < inner_area.x + inner_area.width
-                && cursor_y < inner_area.y + inner_area.height
-                && cursor_x < buf.area.x + buf.area.width
-                && cursor_y < buf.area.y + buf.area.height
-            {
-                let cell = &mut buf[(cursor_x, cursor_y)];
-                cell.set_style(Style::default().add_modifier(Modifier::REVERSED));
+    /// Fit the cells to a new area without the screen: rows and columns
+    /// that were rendered are kept, the rest is left empty.
+    fn reshape(&mut self, area: Rect, color_mode: ColorMode) {
+        let (old_width, old_height) = self.size;
+        let mut cells = Vec::with_capacity(usize::from(area.width) * usize::from(area.height));
+        for row in 0..area.height {
+            for col in 0..area.width {
+                let cell = if row < old_height && col < old_width {
+                    let idx = usize::from(row) * usize::from(old_width) + usize::from(col);
+                    self.cells[idx].take()
+                } else {
+                    None
+                };
+                cells.push(cell);
             }
         }
+        self.cells = cells;
+        self.size = (area.width, area.height);
+        self.color_mode = color_mode;
+        self.stale = true;
+    }
+}
+
+/// Convert a vt100 cell, with the attributes vt100 drops, to a buffer cell.
+///
+/// Buffer cells have no underline styles, so every style is drawn as a
+/// single underline.
+fn convert_cell(
+    cell: &vt100::Cell,
+    extra: ExtraAttributes,
+    clipped: bool,
+    color_mode: ColorMode,
+) -> Cell {
+    let mut buf_cell = Cell::default();
+
+    // The previous wide character already covers this column
+    if cell.is_wide_continuation() {
+        return buf_cell;
+    }
+
+    // Set the full grapheme (base char plus combining marks)
+    if cell.has_contents() && !clipped {
+        buf_cell.set_symbol(&cell.contents());
+    }
+
+    buf_cell.set_fg(color_mode.apply(convert_color(cell.fgcolor())));
+    buf_cell.set_bg(color_mode.apply(convert_color(cell.bgcolor())));
+
+    // Apply modifiers. Inverse is left to the host terminal:
+    // swapping colors here would lose the default fg/bg.
+    let mut style = Style::default();
+    if cell.inverse() {
+        style = style.add_modifier(Modifier::REVERSED);
+    }
+    if cell.bold() {
+        style = style.add_modifier(Modifier::BOLD);
+    }
+    if cell.italic() {
+        style = style.add_modifier(Modifier::ITALIC);
+    }
+    if cell.underline() || extra.underline_style != UnderlineStyle::None {
+        style = style.add_modifier(Modifier::UNDERLINED);
+    }
+    if extra.underline_color != vt100::Color::Default {
+        style = style.underline_color(color_mode.apply(convert_color(extra.underline_color)));
+    }
+    if extra.dim {
+        style = style.add_modifier(Modifier::DIM);
+    }
+    if extra.blink {
+        style = style.add_modifier(Modifier::SLOW_BLINK);
     }
+    if extra.strikethrough {
+        style = style.add_modifier(Modifier::CROSSED_OUT);
+    }
+    buf_cell.set_style(style);
+    buf_cell
 }
 
 /// Widget for rendering an empty bordered sub-pane.
 pub struct SubPaneWidget<'a> {
     /// Optional title for the border.
     title: Option<&'a str>,
-    /// Border style.
-    border_style: Style,
+    /// Borders, drawn unfocused.
+    borders: BorderTheme,
 }
 
 impl<'a> SubPaneWidget<'a> {
@@ -441,7 +1455,7 @@ impl<'a> SubPaneWidget<'a> {
     pub fn new() -> Self {
         Self {
             title: None,
-            border_style: Style::default().fg(Color::DarkGray),
+            borders: BorderTheme::default(),
         }
     }
 
@@ -455,7 +1469,14 @@ impl<'a> SubPaneWidget<'a> {
     /// Set the border style.
     #[must_use]
     pub fn border_style(mut self, style: Style) -> Self {
-        self.border_style = style;
+        self.borders.unfocus_style = style;
+        self
+    }
+
+    /// Set the borders.
+    #[must_use]
+    pub fn border_theme(mut self, theme: BorderTheme) -> Self {
+        self.borders = theme;
         self
     }
 }
@@ -468,9 +1489,7 @@ impl Default for SubPaneWidget<'_> {
 
 impl Widget for SubPaneWidget<'_> {
     fn render(self, area: Rect, buf: &mut Buffer) {
-        let mut block = Block::default()
-            .borders(Borders::ALL)
-            .border_style(self.border_style);
+        let mut block = self.borders.block(false);
 
         if let Some(title) = self.title {
             block = block.title(title);
@@ -480,26 +1499,119 @@ impl Widget for SubPaneWidget<'_> {
     }
 }
 
+/// Look of the cockpit when none is set: the default [`Theme`], built once.
+fn default_theme() -> &'static Theme {
+    static THEME: OnceLock<Theme> = OnceLock::new();
+    THEME.get_or_init(Theme::default)
+}
+
+/// Where a [`CockpitWidget`] finds its panes and their areas.
+#[derive(Clone, Copy)]
+enum Panes<'a> {
+    /// Given to [`CockpitWidget::new`].
+    Slices {
+        panes: &'a [(PaneId, &'a PaneHandle)],
+        areas: &'a [(PaneId, Rect)],
+    },
+    /// Read from the manager of a [`CockpitState`].
+    Manager(&'a PaneManager),
+}
+
+impl<'a> Panes<'a> {
+    /// Handle of the pane `pane_id`.
+    fn handle(self, pane_id: PaneId) -> Option<&'a PaneHandle> {
+        match self {
+            Self::Slices { panes, .. } => panes
+                .iter()
+                .find(|(id, _)| *id == pane_id)
+                .map(|(_, handle)| *handle),
+            Self::Manager(manager) => manager.get_pane(pane_id),
+        }
+    }
+
+    /// Layout areas by pane ID.
+    fn areas(self) -> impl Iterator<Item = (PaneId, Rect)> + 'a {
+        let (slice, map) = match self {
+            Self::Slices { areas, .. } => (areas, None),
+            Self::Manager(manager) => (&[][..], Some(manager.get_areas())),
+        };
+        slice
+            .iter()
+            .copied()
+            .chain(map.into_iter().flatten().map(|(id, area)| (*id, *area)))
+    }
+}
+
+/// What a [`CockpitWidget`] draws when rendered as a [`StatefulWidget`]:
+/// the panes, layout, focus and popup of a [`PaneManager`], borrowed as
+/// they are so drawing a frame copies none of them.
+///
+/// The look still comes from the widget; pass the manager's with
+/// [`CockpitWidget::theme`].
+///
+/// ```no_run
+/// # fn draw(frame: &mut ratatui::Frame<'_>, manager: &cockpit::PaneManager) {
+/// let widget = cockpit::CockpitWidget::default().theme(manager.theme());
+/// frame.render_stateful_widget(widget, frame.area(), &mut manager.cockpit_state());
+/// # }
+/// ```
+#[derive(Clone, Copy)]
+pub struct CockpitState<'a> {
+    manager: &'a PaneManager,
+}
+
+impl<'a> CockpitState<'a> {
+    /// State drawing the panes of `manager`.
+    #[must_use]
+    pub fn new(manager: &'a PaneManager) -> Self {
+        Self { manager }
+    }
+}
+
 /// Widget for rendering the entire multiplexer.
 pub struct CockpitWidget<'a> {
-    /// Pane handles by ID.
-    panes: &'a [(PaneId, &'a PaneHandle)],
-    /// Layout areas by pane ID.
-    areas: &'a [(PaneId, Rect)],
+    /// Pane handles and their layout areas.
+    panes: Panes<'a>,
     /// Currently focused pane.
     focused: Option<PaneId>,
-    /// Style for focused pane border.
-    focus_style: Style,
-    /// Style for unfocused pane borders.
-    unfocus_style: Style,
+    /// Borders of panes, sub-panes and empty slots.
+    borders: BorderTheme,
     /// Sub-pane areas for rendering.
     sub_pane_areas: &'a [Rect],
-    /// Empty pane areas (pane_number, Rect) for slots without active PTYs.
+    /// Panes hosted in sub-pane slots, indexed like `sub_pane_areas`.
+    sub_pane_slots: &'a [Option<PaneId>],
+    /// How `sub_pane_areas` map to pane positions; two per position if unset.
+    arrangement: Option<&'a SubPaneArrangement>,
+    /// Vertical and horizontal expansion; inferred from `sub_pane_areas` if unset.
+    expansion: Option<([bool; 4], [Option<bool>; 2])>,
+    /// Look of the navigation arrows; the default arrows if unset.
+    arrows: Option<&'a ArrowTheme>,
+    /// Empty pane areas (`pane_number`, `Rect`) for slots without active PTYs.
     empty_pane_areas: &'a [(usize, Rect)],
     /// Whether to show pane labels/PIDs.
     show_numbers: bool,
     /// Process IDs mapped by pane label (e.g., "110" -> 12345).
     pane_pids: std::collections::HashMap<&'static str, u32>,
+    /// Color depth of the host terminal.
+    color_mode: ColorMode,
+    /// Pane floating above the others, and its area.
+    popup: Option<(PaneId, Rect)>,
+    /// Drawn over panes whose process has exited.
+    exit_overlay: Option<Cow<'a, ExitOverlay>>,
+    /// Shown in the border of panes printing output.
+    activity_indicator: Option<Cow<'a, ActivityIndicator>>,
+    /// How pane titles are drawn in borders.
+    titles: Option<PaneTitle>,
+    /// How the content of unfocused panes is toned down.
+    dim_unfocused: Option<DimMode>,
+    /// Drawn over the right border of panes scrolled back.
+    scrollbar: Option<Cow<'a, PaneScrollbar>>,
+    /// How matches of searches are drawn.
+    search_highlight: SearchHighlight,
+    /// Style patched over selected cells; reversed if unset.
+    selection_style: Option<Style>,
+    /// Whether blinking cursors are in their visible phase.
+    cursor_blink_on: bool,
 }
 
 impl<'a> CockpitWidget<'a> {
@@ -511,75 +1623,98 @@ impl<'a> CockpitWidget<'a> {
         focused: Option<PaneId>,
     ) -> Self {
         Self {
-            panes,
-            areas,
+            panes: Panes::Slices { panes, areas },
             focused,
-            focus_style: Style::default().fg(Color::Cyan),
-            unfocus_style: Style::default().fg(Color::DarkGray),
+            borders: BorderTheme::default(),
             sub_pane_areas: &[],
+            sub_pane_slots: &[],
+            arrangement: None,
+            expansion: None,
+            arrows: None,
             empty_pane_areas: &[],
             show_numbers: false,
             pane_pids: std::collections::HashMap::new(),
+            color_mode: ColorMode::TrueColor,
+            popup: None,
+            exit_overlay: None,
+            activity_indicator: None,
+            titles: None,
+            dim_unfocused: None,
+            scrollbar: None,
+            search_highlight: SearchHighlight::default(),
+            selection_style: None,
+            cursor_blink_on: true,
         }
+        .theme(default_theme())
+    }
+
+    /// Take the look of the panes from `theme`: borders, numbers, arrows,
+    /// exit overlay, activity indicator, titles, dimming, scrollbar, search
+    /// highlight and selection style. Nothing of it is copied.
+    #[must_use]
+    pub fn theme(mut self, theme: &'a Theme) -> Self {
+        self.borders = theme.borders;
+        self.show_numbers = theme.show_numbers;
+        self.arrows = Some(&theme.arrows);
+        self.exit_overlay = theme.exit_overlay.as_ref().map(Cow::Borrowed);
+        self.activity_indicator = theme.activity_indicator.as_ref().map(Cow::Borrowed);
+        self.titles = theme.pane_titles;
+        self.dim_unfocused = theme.dim_unfocused;
+        self.scrollbar = theme.scrollbar.as_ref().map(Cow::Borrowed);
+        self.search_highlight = theme.search_highlight;
+        self.selection_style = theme.selection_style;
+        self
     }
 
-    /// Infer which pane positions are expanded from sub_pane_areas.
+    /// Infer which pane positions are expanded from `sub_pane_areas`.
     /// A position is expanded if its sub-panes have zero size.
-    fn infer_expanded_positions(&self) -> [bool; 4] {
-        let mut expanded = [false; 4];
-        // Sub-pane indices: 0-1 for position 0, 2-3 for position 1, 4-5 for position 2, 6-7 for position 3
-        for position in 0..4 {
-            let first_idx = position * 2;
-            if let Some(sub_area) = self.sub_pane_areas.get(first_idx) {
-                expanded[position] = sub_area.width == 0 || sub_area.height == 0;
-            }
-        }
-        expanded
+    fn infer_expanded_positions(&self, arrangement: &SubPaneArrangement) -> [bool; 4] {
+        std::array::from_fn(|position| {
+            arrangement
+                .index(position, 0)
+                .and_then(|idx| self.sub_pane_areas.get(idx))
+                .is_some_and(|sub_area| sub_area.width == 0 || sub_area.height == 0)
+        })
     }
 
-    /// Infer horizontal expansion state from sub_pane_areas.
-    /// Returns [Option<bool>; 2] where:
+    /// Infer horizontal expansion state from `sub_pane_areas`.
+    /// Returns `[Option<bool>; 2]` where:
     /// - Index 0 = top row (positions 0,1), Index 1 = bottom row (positions 2,3)
     /// - None = no horizontal expansion
     /// - Some(true) = left pane expanded (right pane hidden)
     /// - Some(false) = right pane expanded (left pane hidden)
-    fn infer_horizontal_expanded(&self) -> [Option<bool>; 2] {
-        let mut h_expanded = [None; 2];
-
-        for row in 0..2 {
-            let left_idx = row * 4; // 0 for row 0, 4 for row 1
-            let right_idx = row * 4 + 2; // 2 for row 0, 6 for row 1
-
-            let left_visible = self
-                .sub_pane_areas
-                .get(left_idx)
-                .is_some_and(|r| r.width > 0 && r.height > 0);
-            let right_visible = self
-                .sub_pane_areas
-                .get(right_idx)
-                .is_some_and(|r| r.width > 0 && r.height > 0);
-
-            h_expanded[row] = match (left_visible, right_visible) {
-                (true, false) => Some(true),  // Left expanded, right hidden
-                (false, true) => Some(false), // Right expanded, left hidden
-                _ => None,                    // Both visible or both hidden
-            };
-        }
+    fn infer_horizontal_expanded(&self, arrangement: &SubPaneArrangement) -> [Option<bool>; 2] {
+        let visible = |position: usize| {
+            arrangement
+                .index(position, 0)
+                .and_then(|idx| self.sub_pane_areas.get(idx))
+                .is_some_and(|r| r.width > 0 && r.height > 0)
+        };
+        std::array::from_fn(|row| match (visible(row * 2), visible(row * 2 + 1)) {
+            (true, false) => Some(true),  // Left expanded, right hidden
+            (false, true) => Some(false), // Right expanded, left hidden
+            _ => None,                    // Both visible or both hidden
+        })
+    }
 
-        h_expanded
+    /// Set the borders of panes, sub-panes and empty slots.
+    #[must_use]
+    pub fn border_theme(mut self, theme: BorderTheme) -> Self {
+        self.borders = theme;
+        self
     }
 
     /// Set the focus style.
     #[must_use]
     pub fn focus_style(mut self, style: Style) -> Self {
-        self.focus_style = style;
+        self.borders.focus_style = style;
         self
     }
 
     /// Set the unfocus style.
     #[must_use]
     pub fn unfocus_style(mut self, style: Style) -> Self {
-        self.unfocus_style = style;
+        self.borders.unfocus_style = style;
         self
     }
 
@@ -590,6 +1725,37 @@ impl<'a> CockpitWidget<'a> {
         self
     }
 
+    /// Set the panes hosted in sub-pane slots; they are drawn in their
+    /// sub-pane area instead of as a pane position.
+    #[must_use]
+    pub fn sub_pane_slots(mut self, slots: &'a [Option<PaneId>]) -> Self {
+        self.sub_pane_slots = slots;
+        self
+    }
+
+    /// Set how the sub-pane areas are divided among the pane positions.
+    #[must_use]
+    pub fn sub_pane_arrangement(mut self, arrangement: &'a SubPaneArrangement) -> Self {
+        self.arrangement = Some(arrangement);
+        self
+    }
+
+    /// Set which positions are expanded vertically and which side of each
+    /// row is expanded horizontally, instead of inferring it from the
+    /// sub-pane areas. Needed when a position has no sub-panes.
+    #[must_use]
+    pub fn expansion(mut self, expanded: [bool; 4], horizontal: [Option<bool>; 2]) -> Self {
+        self.expansion = Some((expanded, horizontal));
+        self
+    }
+
+    /// Set the look of the navigation arrows.
+    #[must_use]
+    pub fn arrows(mut self, theme: &'a ArrowTheme) -> Self {
+        self.arrows = Some(theme);
+        self
+    }
+
     /// Set empty pane areas to render (slots without active PTYs).
     #[must_use]
     pub fn empty_panes(mut self, areas: &'a [(usize, Rect)]) -> Self {
@@ -606,93 +1772,273 @@ impl<'a> CockpitWidget<'a> {
 
     /// Set process ID for a pane by its label (e.g., "110", "121", "212").
     /// Valid labels: 110, 120, 210, 220, 111, 112, 121, 122, 211, 212, 221, 222
+    ///
+    /// The PID is usually taken from [`PaneHandle::pid`](crate::PaneHandle::pid).
     #[must_use]
     pub fn pane_pid(mut self, label: &'static str, pid: u32) -> Self {
         self.pane_pids.insert(label, pid);
         self
     }
+
+    /// Set the host terminal's color depth; pane colors are downgraded to fit.
+    #[must_use]
+    pub fn color_mode(mut self, mode: ColorMode) -> Self {
+        self.color_mode = mode;
+        self
+    }
+
+    /// Set the space between each pane's border and its content.
+    #[must_use]
+    pub fn padding(mut self, padding: Padding) -> Self {
+        self.borders.padding = padding;
+        self
+    }
+
+    /// Draw a pane floating above the others, as returned by
+    /// [`PaneManager::popup`](crate::PaneManager::popup).
+    #[must_use]
+    pub fn popup(mut self, popup: Option<(PaneId, Rect)>) -> Self {
+        self.popup = popup;
+        self
+    }
+
+    /// Set how panes are drawn once their process has exited. `None`
+    /// shows their last output unchanged.
+    #[must_use]
+    pub fn exit_overlay(mut self, overlay: Option<ExitOverlay>) -> Self {
+        self.exit_overlay = overlay.map(Cow::Owned);
+        self
+    }
+
+    /// Set what is shown in the border of panes printing output. `None`
+    /// shows nothing.
+    #[must_use]
+    pub fn activity_indicator(mut self, indicator: Option<ActivityIndicator>) -> Self {
+        self.activity_indicator = indicator.map(Cow::Owned);
+        self
+    }
+
+    /// Set how the content of unfocused panes is toned down. `None`, the
+    /// default, draws it unchanged.
+    #[must_use]
+    pub fn dim_unfocused(mut self, dim: Option<DimMode>) -> Self {
+        self.dim_unfocused = dim;
+        self
+    }
+
+    /// Set how pane titles are drawn in borders. `None` leaves borders
+    /// plain.
+    #[must_use]
+    pub fn titles(mut self, titles: Option<PaneTitle>) -> Self {
+        self.titles = titles;
+        self
+    }
+
+    /// Set the scrollbar drawn over the right border of panes scrolled
+    /// back. `None` draws none.
+    #[must_use]
+    pub fn scrollbar(mut self, scrollbar: Option<PaneScrollbar>) -> Self {
+        self.scrollbar = scrollbar.map(Cow::Owned);
+        self
+    }
+
+    /// Set how matches of the panes' active searches are drawn.
+    #[must_use]
+    pub fn search_highlight(mut self, highlight: SearchHighlight) -> Self {
+        self.search_highlight = highlight;
+        self
+    }
+
+    /// Set the style patched over the cells selected in panes. `None`,
+    /// the default, reverses them.
+    #[must_use]
+    pub fn selection_style(mut self, style: Option<Style>) -> Self {
+        self.selection_style = style;
+        self
+    }
+
+    /// Set whether blinking cursors are in their visible phase; see
+    /// [`PaneWidget::cursor_blink_on`].
+    #[must_use]
+    pub fn cursor_blink_on(mut self, on: bool) -> Self {
+        self.cursor_blink_on = on;
+        self
+    }
+
+    /// Draw the title, search counter and activity indicator of a pane
+    /// drawn in `area`.
+    fn render_border(&self, handle: &PaneHandle, area: Rect, buf: &mut Buffer) {
+        let mut reserved = self
+            .activity_indicator
+            .as_deref()
+            .map_or(0, ActivityIndicator::width);
+        if let Some(search) = handle.search_state() {
+            let counter = SearchHighlight::counter(&search);
+            self.search_highlight
+                .render_counter(&search, area, reserved, buf);
+            reserved += u16::try_from(counter.len()).unwrap_or(u16::MAX);
+        }
+        if let Some(titles) = self.titles {
+            let style = self.borders.title_style;
+            titles.render(&handle.display_title(), style, area, reserved, buf);
+        }
+        if let Some(indicator) = &self.activity_indicator {
+            indicator.render(handle, area, buf);
+        }
+    }
+
+    /// Widget drawing pane `handle` inside `block`, in the cockpit's look.
+    fn pane_widget<'b>(
+        &'b self,
+        handle: &'b PaneHandle,
+        focused: bool,
+        block: Block<'b>,
+    ) -> PaneWidget<'b> {
+        PaneWidget {
+            handle,
+            focused,
+            block: Some(block),
+            borders: self.borders,
+            show_cursor: true,
+            color_mode: self.color_mode,
+            exit_overlay: self.exit_overlay.as_deref().map(Cow::Borrowed),
+            dim_unfocused: self.dim_unfocused,
+            scrollbar: self.scrollbar.as_deref().map(Cow::Borrowed),
+            search_highlight: self.search_highlight,
+            selection: handle.selection(),
+            selection_style: self.selection_style,
+            cursor_blink_on: self.cursor_blink_on,
+        }
+    }
 }
 
-impl Widget for CockpitWidget<'_> {
-    fn render(self, _area: Rect, buf: &mut Buffer) {
-        // Infer which positions are expanded from sub_pane_areas
-        let expanded_positions = self.infer_expanded_positions();
-        let horizontal_expanded = self.infer_horizontal_expanded();
+impl Default for CockpitWidget<'_> {
+    /// A cockpit without panes, in the default look; render it with a
+    /// [`CockpitState`].
+    fn default() -> Self {
+        Self::new(&[], &[], None)
+    }
+}
 
-        // Pane labels: positions 1-4 (panes) and 5-12 (sub-panes)
-        const PANE_LABELS: [&str; 4] = ["110", "120", "210", "220"];
-        const SUB_PANE_LABELS: [&str; 8] = ["111", "112", "121", "122", "211", "212", "221", "222"];
+impl<'a> StatefulWidget for CockpitWidget<'a> {
+    type State = CockpitState<'a>;
+
+    /// Draw the panes of the state's manager. The panes, areas, focus,
+    /// sub-panes, expansion, popup, color mode and cursor blink phase set
+    /// on the widget are replaced by the manager's.
+    fn render(self, area: Rect, buf: &mut Buffer, state: &mut Self::State) {
+        let manager = state.manager;
+        let widget = Self {
+            panes: Panes::Manager(manager),
+            focused: manager.focused(),
+            sub_pane_areas: manager.get_sub_pane_areas(),
+            sub_pane_slots: manager.get_sub_pane_slots(),
+            arrangement: Some(manager.sub_pane_arrangement()),
+            expansion: Some((
+                *manager.get_expanded_positions(),
+                *manager.get_horizontal_expanded(),
+            )),
+            empty_pane_areas: manager.get_empty_pane_areas(),
+            color_mode: manager.color_mode(),
+            popup: manager.popup(),
+            cursor_blink_on: manager.cursor_blink_on(),
+            ..self
+        };
+        Widget::render(widget, area, buf);
+    }
+}
 
-        // Create a lookup for pane handles
-        let pane_map: std::collections::HashMap<_, _> =
-            self.panes.iter().map(|(id, h)| (*id, *h)).collect();
+/// Block with all borders for a pane, in its accent color while unfocused
+/// and filled with its tint.
+fn pane_block(borders: &BorderTheme, handle: &PaneHandle, focused: bool) -> Block<'static> {
+    let colors = handle.colors();
+    let mut block = borders.block(focused);
+    if let Some(accent) = colors.accent.filter(|_| !focused) {
+        block = block.border_style(borders.unfocus_style.fg(accent));
+    }
+    if let Some(tint) = colors.tint {
+        block = block.style(Style::default().bg(tint));
+    }
+    block
+}
 
-        // Sort areas by x coordinate to get correct position order (left to right)
-        let mut sorted_areas: Vec<_> = self.areas.iter().collect();
-        sorted_areas.sort_by_key(|(_, rect)| rect.x);
+/// Borders of a pane, sub-pane or empty slot: all of them for the first
+/// in its row, and no left border for the others, where the one before
+/// draws its right border.
+fn slot_borders(first: bool) -> Borders {
+    if first {
+        Borders::ALL
+    } else {
+        Borders::TOP | Borders::BOTTOM | Borders::RIGHT
+    }
+}
 
-        // Render each pane in its area
-        for (idx, (pane_id, pane_area)) in sorted_areas.iter().enumerate() {
-            if let Some(handle) = pane_map.get(pane_id) {
-                let is_focused = self.focused == Some(*pane_id);
-                let border_style = if is_focused {
-                    self.focus_style
-                } else {
-                    self.unfocus_style
-                };
+/// Content area of a pane drawn in `area` by [`CockpitWidget`], inside the
+/// borders and padding of `theme`; `first` as for [`slot_borders`].
+pub(crate) fn pane_inner_area(theme: &BorderTheme, area: Rect, first: bool) -> Rect {
+    theme.block(false).borders(slot_borders(first)).inner(area)
+}
 
-                // First pane: ALL borders
-                // Others: TOP + BOTTOM + RIGHT (no LEFT to avoid double border)
-                let borders = if idx == 0 {
-                    Borders::ALL
-                } else {
-                    Borders::TOP | Borders::BOTTOM | Borders::RIGHT
-                };
+/// Pane labels of positions 1-4; sub-pane labels come from the arrangement.
+const PANE_LABELS: [&str; 4] = ["110", "120", "210", "220"];
 
-                let block = Block::default().borders(borders).border_style(border_style);
+impl Widget for CockpitWidget<'_> {
+    #[allow(clippy::too_many_lines, clippy::cast_possible_truncation)]
+    fn render(self, _area: Rect, buf: &mut Buffer) {
+        // Infer which positions are expanded from sub_pane_areas
+        let default_arrangement;
+        let arrangement = if let Some(arrangement) = self.arrangement {
+            arrangement
+        } else {
+            default_arrangement = SubPaneArrangement::default();
+            &default_arrangement
+        };
+        let (expanded_positions, horizontal_expanded) = self.expansion.unwrap_or_else(|| {
+            (
+                self.infer_expanded_positions(arrangement),
+                self.infer_horizontal_expanded(arrangement),
+            )
+        });
+
+        // Panes in sub-pane slots are drawn with the sub-panes
+        let main_areas = || {
+            self.panes
+                .areas()
+                .filter(|(id, _)| !self.sub_pane_slots.contains(&Some(*id)))
+        };
+        // Position of a pane, counting left to right
+        let position = |pane_id: PaneId, area: Rect| {
+            main_areas()
+                .filter(|(id, other)| (other.x, id.0) < (area.x, pane_id.0))
+                .count()
+        };
+        let mut pane_areas = [Rect::default(); 4];
+        let mut positions = 0;
 
-                let widget = PaneWidget::new(handle)
-                    .focused(is_focused)
-                    .block(block)
-                    .focus_style(self.focus_style);
+        // Render each pane in its area
+        for (pane_id, pane_area) in main_areas() {
+            let idx = position(pane_id, pane_area);
+            if let Some(slot) = pane_areas.get_mut(idx) {
+                *slot = pane_area;
+            }
+            positions += 1;
+            if let Some(handle) = self.panes.handle(pane_id) {
+                let is_focused = self.focused == Some(pane_id);
 
-                widget.render(*pane_area, buf);
+                let borders = slot_borders(idx == 0);
 
-                // Render up arrow on expanded panes
-                if idx < 4 && expanded_positions[idx] {
-                    let arrow_style = Style::default().fg(Color::White);
-                    let base_y = pane_area.y + pane_area.height - 1 - ARROW_HEIGHT;
-                    let base_x = if is_left_arrow_position(idx) {
-                        pane_area.x + 1
-                    } else {
-                        pane_area.x + pane_area.width - 1 - ARROW_WIDTH
-                    };
+                let block = pane_block(&self.borders, handle, is_focused).borders(borders);
 
-                    for (row, line) in UP_ARROW.iter().enumerate() {
-                        let y = base_y + row as u16;
-                        if y >= buf.area.y + buf.area.height {
-                            continue;
-                        }
-                        for (col, &ch) in line.iter().enumerate() {
-                            let x = base_x + col as u16;
-                            if x < buf.area.x || x >= buf.area.x + buf.area.width {
-                                continue;
-                            }
-                            if ch != ' ' {
-                                let cell = &mut buf[(x, y)];
-                                cell.set_char(ch);
-                                cell.set_style(arrow_style);
-                            }
-                        }
-                    }
-                }
+                self.pane_widget(handle, is_focused, block)
+                    .render(pane_area, buf);
+                self.render_border(handle, pane_area, buf);
 
                 // Show PID or label as centered content
                 if self.show_numbers {
-                    let inner = Block::default().borders(borders).inner(*pane_area);
+                    let inner = Block::default().borders(borders).inner(pane_area);
                     let label = PANE_LABELS.get(idx).unwrap_or(&"");
                     let display_text = match self.pane_pids.get(label) {
-                        Some(pid) => format!("{}", pid),
+                        Some(pid) => pid.to_string(),
                         None => label.to_string(),
                     };
                     let paragraph = Paragraph::new(display_text)
@@ -711,17 +2057,9 @@ impl Widget for CockpitWidget<'_> {
 
         // Render empty pane areas
         for (pane_number, empty_area) in self.empty_pane_areas {
-            // First pane: ALL borders
-            // Others: TOP + BOTTOM + RIGHT (no LEFT to avoid double border)
-            let borders = if *pane_number == 1 {
-                Borders::ALL
-            } else {
-                Borders::TOP | Borders::BOTTOM | Borders::RIGHT
-            };
+            let borders = slot_borders(*pane_number == 1);
 
-            let block = Block::default()
-                .borders(borders)
-                .border_style(self.unfocus_style);
+            let block = self.borders.block(false).borders(borders);
             let inner = block.inner(*empty_area);
             block.render(*empty_area, buf);
 
@@ -730,7 +2068,7 @@ impl Widget for CockpitWidget<'_> {
                 let idx = pane_number - 1;
                 let label = PANE_LABELS.get(idx).unwrap_or(&"");
                 let display_text = match self.pane_pids.get(label) {
-                    Some(pid) => format!("{}", pid),
+                    Some(pid) => pid.to_string(),
                     None => label.to_string(),
                 };
                 let paragraph = Paragraph::new(display_text)
@@ -753,32 +2091,44 @@ impl Widget for CockpitWidget<'_> {
                 continue;
             }
 
-            // First visible sub-pane gets LEFT border, others don't
-            // Find if this is the first non-empty sub-pane
+            // Only the first visible sub-pane gets a left border
             let is_first_visible = self
                 .sub_pane_areas
                 .iter()
                 .take(idx)
                 .all(|r| r.width == 0 || r.height == 0);
-
-            let borders = if is_first_visible {
-                Borders::ALL
+            let borders = slot_borders(is_first_visible);
+
+            let hosted = self
+                .sub_pane_slots
+                .get(idx)
+                .copied()
+                .flatten()
+                .and_then(|id| self.panes.handle(id).map(|handle| (id, handle)));
+            let inner = if let Some((pane_id, handle)) = hosted {
+                let is_focused = self.focused == Some(pane_id);
+                let block = pane_block(&self.borders, handle, is_focused).borders(borders);
+                let inner = block.inner(*sub_area);
+                self.pane_widget(handle, is_focused, block)
+                    .render(*sub_area, buf);
+                self.render_border(handle, *sub_area, buf);
+                inner
             } else {
-                Borders::TOP | Borders::BOTTOM | Borders::RIGHT
+                let block = self.borders.block(false).borders(borders);
+                let inner = block.inner(*sub_area);
+                block.render(*sub_area, buf);
+                inner
             };
 
-            let block = Block::default()
-                .borders(borders)
-                .border_style(self.unfocus_style);
-            let inner = block.inner(*sub_area);
-            block.render(*sub_area, buf);
-
             // Show PID or label as centered content
             if self.show_numbers {
-                let label = SUB_PANE_LABELS.get(idx).unwrap_or(&"");
-                let display_text = match self.pane_pids.get(label) {
-                    Some(pid) => format!("{}", pid),
-                    None => label.to_string(),
+                let label = arrangement
+                    .label(idx)
+                    .map(|label| label.to_string())
+                    .unwrap_or_default();
+                let display_text = match self.pane_pids.get(label.as_str()) {
+                    Some(pid) => pid.to_string(),
+                    None => label,
                 };
                 let paragraph = Paragraph::new(display_text)
                     .alignment(Alignment::Center)
@@ -791,90 +2141,29 @@ impl Widget for CockpitWidget<'_> {
                 };
                 paragraph.render(centered_area, buf);
             }
+        }
 
-            // Render down arrows for overlay navigation
-            // 111 (idx 0): bottom-left, 122 (idx 3): bottom-right
-            // 211 (idx 4): bottom-left, 222 (idx 7): bottom-right
-            let arrow_style = Style::default().fg(Color::White);
-            let base_y = sub_area.y + sub_area.height - 1 - ARROW_HEIGHT;
-
-            let base_x = match idx {
-                0 | 4 => Some(sub_area.x + 1), // Bottom-left
-                3 | 7 => Some(sub_area.x + sub_area.width - 1 - ARROW_WIDTH), // Bottom-right
-                _ => None,
-            };
-
-            if let Some(base_x) = base_x {
-                for (row, line) in DOWN_ARROW.iter().enumerate() {
-                    let y = base_y + row as u16;
-                    if y >= buf.area.y + buf.area.height {
-                        continue;
-                    }
-                    for (col, &ch) in line.iter().enumerate() {
-                        let x = base_x + col as u16;
-                        if x < buf.area.x || x >= buf.area.x + buf.area.width {
-                            continue;
-                        }
-                        if ch != ' ' {
-                            let cell = &mut buf[(x, y)];
-                            cell.set_char(ch);
-                            cell.set_style(arrow_style);
-                        }
-                    }
-                }
-            }
-
-            // Render horizontal arrows for left/right navigation
-            // Arrow direction depends on horizontal expansion state:
-            // - Normal: 112/212 show RIGHT, 121/221 show LEFT
-            // - Left expanded: 112/212 show LEFT (to collapse)
-            // - Right expanded: 121/221 show RIGHT (to collapse)
-            let row = idx / 4; // Row 0 = indices 0-3, Row 1 = indices 4-7
-            let h_exp = horizontal_expanded[row];
-
-            let horizontal_arrow: Option<(&[[char; 5]; 3], u16)> = match idx {
-                1 | 5 => {
-                    // Sub-pane 112 or 212 (inner-left, normally shows RIGHT arrow)
-                    let arrow = if h_exp == Some(true) {
-                        &LEFT_ARROW // When left expanded, show LEFT to collapse
-                    } else {
-                        &RIGHT_ARROW
-                    };
-                    Some((
-                        arrow,
-                        sub_area.x + sub_area.width.saturating_sub(1 + ARROW_WIDTH),
-                    ))
-                }
-                2 | 6 => {
-                    // Sub-pane 121 or 221 (inner-right, normally shows LEFT arrow)
-                    let arrow = if h_exp == Some(false) {
-                        &RIGHT_ARROW // When right expanded, show RIGHT to collapse
-                    } else {
-                        &LEFT_ARROW
-                    };
-                    Some((arrow, sub_area.x + 1))
-                }
-                _ => None,
-            };
-
-            if let Some((arrow, h_base_x)) = horizontal_arrow {
-                for (row, line) in arrow.iter().enumerate() {
-                    let y = base_y + row as u16;
-                    if y >= buf.area.y + buf.area.height {
-                        continue;
-                    }
-                    for (col, &ch) in line.iter().enumerate() {
-                        let x = h_base_x + col as u16;
-                        if x < buf.area.x || x >= buf.area.x + buf.area.width {
-                            continue;
-                        }
-                        if ch != ' ' {
-                            let cell = &mut buf[(x, y)];
-                            cell.set_char(ch);
-                            cell.set_style(arrow_style);
-                        }
-                    }
-                }
+        // Render navigation arrows on top of panes and sub-panes
+        let arrows = self.arrows.unwrap_or(&default_theme().arrows);
+        let navigation = NavigationLayout {
+            pane_areas: &pane_areas[..positions.min(pane_areas.len())],
+            expanded_positions,
+            horizontal_expanded,
+            sub_pane_areas: self.sub_pane_areas,
+            arrangement,
+        };
+        render_regions(&navigation.regions(arrows), arrows, buf);
+
+        // Render the popup above everything else
+        if let Some((pane_id, area)) = self.popup {
+            if let Some(handle) = self.panes.handle(pane_id) {
+                let is_focused = self.focused == Some(pane_id);
+                Clear.render(area, buf);
+                // The manager sizes popups without padding
+                let block = pane_block(&self.borders, handle, is_focused).padding(Padding::ZERO);
+                self.pane_widget(handle, is_focused, block)
+                    .render(area, buf);
+                self.render_border(handle, area, buf);
             }
         }
     }
@@ -898,3 +2187,142 @@ fn convert_screen_color(color: ScreenColor) -> Color {
         ScreenColor::Rgb(r, g, b) => Color::Rgb(r, g, b),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+    fn key(code: KeyCode) -> KeyEvent {
+        KeyEvent::new(code, KeyModifiers::NONE)
+    }
+
+    #[test]
+    fn test_exit_message() {
+        assert_eq!(format_duration(Duration::from_secs(42)), "42s");
+        assert_eq!(format_duration(Duration::from_secs(192)), "3m 12s");
+        assert_eq!(format_duration(Duration::from_secs(3930)), "1h 5m");
+
+        let exited = PaneState::Exited { code: 1 };
+        let killed = PaneState::Crashed {
+            signal: Some(9),
+            error: None,
+        };
+        let runtime = Duration::from_secs(5);
+        assert_eq!(
+            exit_message(&exited, runtime).as_deref(),
+            Some("Exited with code 1 after 5s")
+        );
+        assert_eq!(
+            exit_message(&killed, runtime).as_deref(),
+            Some("Killed by signal 9 after 5s")
+        );
+        assert_eq!(exit_message(&PaneState::Running, runtime), None);
+
+        // A crash is shown in red across the pane
+        let area = Rect::new(0, 0, 30, 3);
+        let mut buf = Buffer::empty(area);
+        ExitOverlay::default().render(&killed, runtime, area, &mut buf);
+        let row: String = (0..30).map(|x| buf[(x, 1)].symbol()).collect();
+        assert_eq!(row, "  Killed by signal 9 after 5s ");
+        assert_eq!(buf[(0, 1)].bg, Color::Red);
+        assert_eq!(buf[(29, 1)].bg, Color::Red);
+        assert!(buf[(0, 0)].modifier.contains(Modifier::DIM));
+    }
+
+    #[test]
+    fn test_pane_title() {
+        let area = Rect::new(0, 0, 20, 1);
+        let render = |title: PaneTitle, text: &str, reserved| -> String {
+            let mut buf = Buffer::empty(area);
+            buf.set_string(0, 0, format!("┌{}┐", "─".repeat(18)), Style::default());
+            title.render(text, Style::default(), area, reserved, &mut buf);
+            (0..20).map(|x| buf[(x, 0)].symbol()).collect()
+        };
+
+        let left = PaneTitle::default();
+        assert_eq!(render(left, "vim", 0), "┌ vim ─────────────┐");
+        assert_eq!(render(left, "", 0), "┌──────────────────┐");
+        let right = PaneTitle {
+            alignment: Alignment::Right,
+            ..left
+        };
+        assert_eq!(render(right, "vim", 3), "┌────────── vim ───┐");
+        let centered = PaneTitle {
+            alignment: Alignment::Center,
+            ..left
+        };
+        assert_eq!(render(centered, "vim", 0), "┌────── vim ───────┐");
+
+        // Cut to the border, or to the configured width
+        assert_eq!(
+            render(left, "cargo watch -x test", 0),
+            "┌ cargo watch -x… ─┐"
+        );
+        let short = PaneTitle {
+            max_width: Some(6),
+            ..left
+        };
+        assert_eq!(render(short, "cargo watch", 0), "┌ cargo… ──────────┐");
+    }
+
+    #[test]
+    fn test_yes_no_dialog() {
+        let mut state = DialogState::new();
+        state.show();
+        assert_eq!(state.selected, usize::from(DialogButton::Yes));
+        state.next();
+        assert_eq!(state.handle_key(key(KeyCode::Enter)), Some(false));
+        state.show();
+        assert_eq!(state.handle_key(key(KeyCode::Char('Y'))), Some(true));
+        state.show();
+        assert_eq!(state.handle_key(key(KeyCode::Esc)), Some(false));
+
+        // Buttons sit where they always have
+        let area = DialogState::calculate_area(Rect::new(0, 0, 80, 24));
+        state.show();
+        assert_eq!(
+            state.handle_mouse(area.x + 12, area.y + 4, area),
+            Some(true)
+        );
+        state.show();
+        assert_eq!(
+            state.handle_mouse(area.x + 22, area.y + 4, area),
+            Some(false)
+        );
+    }
+
+    #[test]
+    fn test_multi_button_dialog() {
+        let mut state = DialogState::with_buttons(vec![
+            DialogChoice::new("Save").key('s').default(),
+            DialogChoice::new("Discard").key('d').danger(),
+            DialogChoice::new("Cancel").cancel(),
+        ]);
+        state.show();
+        assert_eq!(state.handle_button_key(key(KeyCode::Char('D'))), Some(1));
+        assert!(!state.visible);
+
+        state.show();
+        state.prev();
+        assert_eq!(state.selected, 2);
+        assert_eq!(state.handle_button_key(key(KeyCode::Char('x'))), None);
+        assert_eq!(state.handle_button_key(key(KeyCode::Esc)), Some(2));
+
+        let area = Rect::new(0, 0, 40, 7);
+        let mut buf = Buffer::empty(area);
+        state.show();
+        state.next();
+        ConfirmDialog::new(" Unsaved ", "Save changes?")
+            .buttons(state.buttons())
+            .selected(state.selected)
+            .render(area, &mut buf);
+        let row = |y| -> String { (0..40).map(|x| buf[(x, y)].symbol()).collect() };
+        assert_eq!(row(4), "│   Save     [ Discard ]     Cancel    │");
+        assert_eq!(row(5), "│       s/d • Enter • ←→ • Click       │");
+        // The selected danger button is drawn on red
+        assert_eq!(buf[(14, 4)].bg, Color::Red);
+
+        assert_eq!(state.handle_button_mouse(30, 4, area), Some(2));
+    }
+}